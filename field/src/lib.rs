@@ -11,7 +11,13 @@
 
 pub(crate) mod arch;
 pub mod batch_util;
+pub mod bls12_381_base;
+pub mod bls12_381_scalar;
+pub mod bn254_base;
+pub mod bn254_scalar;
 pub mod cosets;
+pub mod curve25519_base;
+pub mod curve25519_scalar;
 pub mod extension_field;
 pub mod fft;
 pub mod field_types;
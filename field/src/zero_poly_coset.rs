@@ -1,4 +1,5 @@
 use crate::field_types::Field;
+use crate::packed_field::PackedField;
 
 /// Precomputations of the evaluation of `Z_H(X) = X^n - 1` on a coset `gK` with `H <= K`.
 pub struct ZeroPolyOnCoset<F: Field> {
@@ -39,6 +40,17 @@ impl<F: Field> ZeroPolyOnCoset<F> {
         self.inverses[i % self.rate]
     }
 
+    /// Returns `1 / Z_H(g * w^i)` for `i` in `i_start..i_start + P::WIDTH`, packed.
+    pub fn eval_inverse_packed<P: PackedField<Scalar = F>>(&self, i_start: usize) -> P {
+        let mut packed = P::ZEROS;
+        packed
+            .as_slice_mut()
+            .iter_mut()
+            .enumerate()
+            .for_each(|(j, packed_j)| *packed_j = self.eval_inverse(i_start + j));
+        packed
+    }
+
     /// Returns `L_1(x) = Z_H(x)/(n * (x - 1))` with `x = w^i`.
     pub fn eval_l1(&self, i: usize, x: F) -> F {
         // Could also precompute the inverses using Montgomery.
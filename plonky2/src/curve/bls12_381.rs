@@ -0,0 +1,68 @@
+use plonky2_field::bls12_381_base::Bls12381Base;
+use plonky2_field::bls12_381_scalar::Bls12381Scalar;
+use plonky2_field::field_types::Field;
+use serde::{Deserialize, Serialize};
+
+use crate::curve::curve_types::{AffinePoint, Curve};
+
+/// The G1 group of the BLS12-381 pairing-friendly curve, `y^2 = x^3 + 4`. The curve has a
+/// cofactor, so `GENERATOR_AFFINE` generates the prime-order subgroup rather than the whole group.
+#[derive(Debug, Copy, Clone, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Bls12381;
+
+impl Curve for Bls12381 {
+    type BaseField = Bls12381Base;
+    type ScalarField = Bls12381Scalar;
+
+    const A: Bls12381Base = Bls12381Base::ZERO;
+    const B: Bls12381Base = Bls12381Base([4, 0, 0, 0, 0, 0]);
+    const GENERATOR_AFFINE: AffinePoint<Self> = AffinePoint {
+        x: BLS12_381_GENERATOR_X,
+        y: BLS12_381_GENERATOR_Y,
+        zero: false,
+    };
+}
+
+/// 3685416753713387016781088315183077757961620795782546409894578378688607592378376318836054947676345821548104185464507
+const BLS12_381_GENERATOR_X: Bls12381Base = Bls12381Base([
+    0xFB3AF00ADB22C6BB,
+    0x6C55E83FF97A1AEF,
+    0xA14E3A3F171BAC58,
+    0xC3688C4F9774B905,
+    0x2695638C4FA9AC0F,
+    0x17F1D3A73197D794,
+]);
+
+/// 1339506544944476473020471379941921221584933875938349620426543736416511423956333506472724655353366534992391756441569
+const BLS12_381_GENERATOR_Y: Bls12381Base = Bls12381Base([
+    0x0CAA232946C5E7E1,
+    0xD03CC744A2888AE4,
+    0x00DB18CB2C04B3ED,
+    0xFCF5E095D5D00AF6,
+    0xA09E30ED741D8AE4,
+    0x08B3F481E3AAA0F1,
+]);
+
+#[cfg(test)]
+mod tests {
+    use plonky2_field::bls12_381_scalar::Bls12381Scalar;
+    use plonky2_field::field_types::Field;
+
+    use crate::curve::bls12_381::Bls12381;
+    use crate::curve::curve_types::{Curve, CurveScalar, ProjectivePoint};
+
+    #[test]
+    fn test_generator() {
+        let g = Bls12381::GENERATOR_AFFINE;
+        assert!(g.is_valid());
+        assert!((-g).is_valid());
+    }
+
+    #[test]
+    fn test_generator_order() {
+        let g = Bls12381::GENERATOR_PROJECTIVE;
+        let order_minus_one = CurveScalar(Bls12381Scalar::NEG_ONE);
+        assert_eq!((order_minus_one * g).to_affine(), -g.to_affine());
+        assert_eq!(order_minus_one * g + g, ProjectivePoint::ZERO);
+    }
+}
@@ -0,0 +1,48 @@
+use plonky2_field::bn254_base::Bn254Base;
+use plonky2_field::bn254_scalar::Bn254Scalar;
+use plonky2_field::field_types::Field;
+use serde::{Deserialize, Serialize};
+
+use crate::curve::curve_types::{AffinePoint, Curve};
+
+/// The G1 group of the BN254 (alt_bn128) pairing-friendly curve, `y^2 = x^3 + 3`, as used by the
+/// Ethereum precompiles.
+#[derive(Debug, Copy, Clone, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Bn254;
+
+impl Curve for Bn254 {
+    type BaseField = Bn254Base;
+    type ScalarField = Bn254Scalar;
+
+    const A: Bn254Base = Bn254Base::ZERO;
+    const B: Bn254Base = Bn254Base([3, 0, 0, 0]);
+    const GENERATOR_AFFINE: AffinePoint<Self> = AffinePoint {
+        x: Bn254Base::ONE,
+        y: Bn254Base::TWO,
+        zero: false,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2_field::bn254_scalar::Bn254Scalar;
+    use plonky2_field::field_types::Field;
+
+    use crate::curve::bn254::Bn254;
+    use crate::curve::curve_types::{Curve, CurveScalar, ProjectivePoint};
+
+    #[test]
+    fn test_generator() {
+        let g = Bn254::GENERATOR_AFFINE;
+        assert!(g.is_valid());
+        assert!((-g).is_valid());
+    }
+
+    #[test]
+    fn test_generator_order() {
+        let g = Bn254::GENERATOR_PROJECTIVE;
+        let order_minus_one = CurveScalar(Bn254Scalar::NEG_ONE);
+        assert_eq!((order_minus_one * g).to_affine(), -g.to_affine());
+        assert_eq!(order_minus_one * g + g, ProjectivePoint::ZERO);
+    }
+}
@@ -2,6 +2,7 @@ use std::fmt::Debug;
 use std::hash::Hash;
 use std::ops::Neg;
 
+use num::{BigUint, Integer};
 use plonky2_field::field_types::{Field, PrimeField};
 use plonky2_field::ops::Square;
 use serde::{Deserialize, Serialize};
@@ -78,6 +79,30 @@ impl<C: Curve> AffinePoint<C> {
         affine_points.iter().map(Self::to_projective).collect()
     }
 
+    /// Returns the point with x-coordinate `x` whose y-coordinate has the given parity, if one
+    /// exists. Square roots are computed as `rhs^((p + 1) / 4)`, so this requires the order of the
+    /// base field to be 3 mod 4.
+    pub fn lift_x(x: C::BaseField, y_odd: bool) -> Option<Self> {
+        let p = C::BaseField::order();
+        assert_eq!(
+            &p % 4u32,
+            BigUint::from(3u32),
+            "lift_x requires a base field of order 3 mod 4"
+        );
+
+        let rhs = x.cube() + C::A * x + C::B;
+        let y = rhs.exp_biguint(&((p + 1u32) / 4u32));
+        if y.square() != rhs {
+            return None;
+        }
+        let y = if y.to_canonical_biguint().is_odd() == y_odd {
+            y
+        } else {
+            -y
+        };
+        Some(Self::nonzero(x, y))
+    }
+
     pub fn double(&self) -> Self {
         let AffinePoint { x: x1, y: y1, zero } = *self;
 
@@ -1,8 +1,11 @@
+use keccak_hash::keccak;
+use num::Integer;
+use plonky2_util::ceil_div_usize;
 use serde::{Deserialize, Serialize};
 
 use crate::curve::curve_msm::msm_parallel;
-use crate::curve::curve_types::{base_to_scalar, AffinePoint, Curve, CurveScalar};
-use crate::field::field_types::Field;
+use crate::curve::curve_types::{base_to_scalar, scalar_to_base, AffinePoint, Curve, CurveScalar};
+use crate::field::field_types::{Field, PrimeField};
 
 #[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct ECDSASignature<C: Curve> {
@@ -59,9 +62,88 @@ pub fn verify_message<C: Curve>(
     r == x
 }
 
+/// Like `sign_message`, but returns a low-s signature, i.e. one with `s <= (n - 1) / 2`, along
+/// with the recovery ID `v`, which is the parity of the y-coordinate of `k G`. Together they let
+/// `recover_public_key` recover the signer's public key.
+pub fn sign_message_recoverable<C: Curve>(
+    msg: C::ScalarField,
+    sk: ECDSASecretKey<C>,
+) -> (ECDSASignature<C>, bool) {
+    let (k, rr) = loop {
+        let k = C::ScalarField::rand();
+        let rr = (CurveScalar(k) * C::GENERATOR_PROJECTIVE).to_affine();
+        // The x-coordinate must be a canonical scalar, so that `rr` can be recovered from `r`.
+        if rr.x != C::BaseField::ZERO && rr.x.to_canonical_biguint() < C::ScalarField::order() {
+            break (k, rr);
+        }
+    };
+    let r = base_to_scalar::<C>(rr.x);
+    let s = k.inverse() * (msg + r * sk.0);
+    let v = rr.y.to_canonical_biguint().is_odd();
+
+    // `(r, -s)` is also valid, for the point `-k G`, whose y-coordinate has the opposite parity.
+    if is_low_s(s) {
+        (ECDSASignature { r, s }, v)
+    } else {
+        (ECDSASignature { r, s: -s }, !v)
+    }
+}
+
+/// Returns whether `s <= (n - 1) / 2`. Requiring this makes signatures non-malleable.
+pub fn is_low_s<FF: PrimeField>(s: FF) -> bool {
+    s.to_canonical_biguint() <= (FF::order() - 1u32) / 2u32
+}
+
+/// Recovers the public key which produced `sig` on `msg`, given the recovery ID `v`. Returns `None`
+/// if no such key exists.
+pub fn recover_public_key<C: Curve>(
+    msg: C::ScalarField,
+    sig: ECDSASignature<C>,
+    v: bool,
+) -> Option<ECDSAPublicKey<C>> {
+    let ECDSASignature { r, s } = sig;
+    if r == C::ScalarField::ZERO || s == C::ScalarField::ZERO {
+        return None;
+    }
+
+    // The public key is `r^-1 (s R - msg G)`, where `R = k G`.
+    let rr = AffinePoint::<C>::lift_x(scalar_to_base::<C>(r), v)?;
+    let r_inv = r.inverse();
+    let u1 = -msg * r_inv;
+    let u2 = s * r_inv;
+
+    let g = C::GENERATOR_PROJECTIVE;
+    let w = 5; // Experimentally fastest
+    let pk = msm_parallel(&[u1, u2], &[g, rr.to_projective()], w).to_affine();
+    if pk.zero {
+        None
+    } else {
+        Some(ECDSAPublicKey(pk))
+    }
+}
+
+/// Returns the Ethereum address of `pk`, i.e. the last 20 bytes of the Keccak-256 hash of its
+/// big-endian coordinates.
+pub fn public_key_to_address<C: Curve>(pk: ECDSAPublicKey<C>) -> [u8; 20] {
+    let num_bytes = ceil_div_usize(C::BaseField::BITS, 8);
+    let mut bytes = Vec::with_capacity(2 * num_bytes);
+    for coord in [pk.0.x, pk.0.y] {
+        let coord_bytes = coord.to_canonical_biguint().to_bytes_be();
+        bytes.resize(bytes.len() + num_bytes - coord_bytes.len(), 0);
+        bytes.extend(coord_bytes);
+    }
+
+    let mut address = [0; 20];
+    address.copy_from_slice(&keccak(bytes).0[12..]);
+    address
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::curve::ecdsa::{secret_to_public, sign_message, verify_message, ECDSASecretKey};
+    use crate::curve::ecdsa::{
+        is_low_s, public_key_to_address, recover_public_key, secret_to_public, sign_message,
+        sign_message_recoverable, verify_message, ECDSASecretKey,
+    };
     use crate::curve::secp256k1::Secp256K1;
     use crate::field::field_types::Field;
     use crate::field::secp256k1_scalar::Secp256K1Scalar;
@@ -78,4 +160,33 @@ mod tests {
         let result = verify_message(msg, sig, pk);
         assert!(result);
     }
+
+    #[test]
+    fn test_ecdsa_recovery_native() {
+        type C = Secp256K1;
+
+        let msg = Secp256K1Scalar::rand();
+        let sk = ECDSASecretKey::<C>(Secp256K1Scalar::rand());
+        let pk = secret_to_public(sk);
+
+        let (sig, v) = sign_message_recoverable(msg, sk);
+        assert!(is_low_s(sig.s));
+        assert!(verify_message(msg, sig, pk));
+        assert_eq!(recover_public_key(msg, sig, v), Some(pk));
+        assert_ne!(recover_public_key(msg, sig, !v), Some(pk));
+    }
+
+    #[test]
+    fn test_public_key_to_address() {
+        type C = Secp256K1;
+
+        // The address of the secret key 1, i.e. of the generator.
+        let pk = secret_to_public(ECDSASecretKey::<C>(Secp256K1Scalar::ONE));
+        let expected = "7e5f4552091a69125d5dfcb7b8c2659029395bdf";
+        let address: String = public_key_to_address(pk)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        assert_eq!(address, expected);
+    }
 }
@@ -0,0 +1,131 @@
+use num::{BigUint, Integer};
+use plonky2_field::curve25519_base::Curve25519Base;
+use plonky2_field::curve25519_scalar::Curve25519Scalar;
+use plonky2_field::field_types::{Field, PrimeField};
+use plonky2_field::ops::Square;
+use serde::{Deserialize, Serialize};
+
+use crate::curve::twisted_edwards::{EdwardsPoint, TwistedEdwardsCurve};
+
+/// The twisted Edwards curve `-x^2 + y^2 = 1 - (121665 / 121666) x^2 y^2` used by Ed25519.
+#[derive(Debug, Copy, Clone, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Ed25519;
+
+impl TwistedEdwardsCurve for Ed25519 {
+    type BaseField = Curve25519Base;
+    type ScalarField = Curve25519Scalar;
+
+    const A: Curve25519Base = Curve25519Base::NEG_ONE;
+    const D: Curve25519Base = ED25519_D;
+    const GENERATOR: EdwardsPoint<Self> = EdwardsPoint {
+        x: ED25519_GENERATOR_X,
+        y: ED25519_GENERATOR_Y,
+    };
+}
+
+/// -121665 / 121666
+/// = 37095705934669439343138083508754565189542113879843219016388785533085940283555
+const ED25519_D: Curve25519Base = Curve25519Base([
+    0x75EB4DCA135978A3,
+    0x00700A4D4141D8AB,
+    0x8CC740797779E898,
+    0x52036CEE2B6FFE73,
+]);
+
+/// 15112221349535400772501151409588531511454012693041857206046113283949847762202
+const ED25519_GENERATOR_X: Curve25519Base = Curve25519Base([
+    0xC9562D608F25D51A,
+    0x692CC7609525A7B2,
+    0xC0A4E231FDD6DC5C,
+    0x216936D3CD6E53FE,
+]);
+
+/// 46316835694926478169428394003475163141307993866256225615783033603165251855960
+const ED25519_GENERATOR_Y: Curve25519Base = Curve25519Base([
+    0x6666666666666658,
+    0x6666666666666666,
+    0x6666666666666666,
+    0x6666666666666666,
+]);
+
+/// A square root of -1, used when computing square roots, since `p = 5 mod 8`.
+const SQRT_M1: Curve25519Base = Curve25519Base::POWER_OF_TWO_GENERATOR;
+
+/// Encodes a point as in RFC 8032: the little-endian encoding of `y`, with the top bit set to the
+/// parity of `x`.
+pub fn compress_point(p: EdwardsPoint<Ed25519>) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    let y_bytes = p.y.to_canonical_biguint().to_bytes_le();
+    bytes[..y_bytes.len()].copy_from_slice(&y_bytes);
+    if p.x.to_canonical_biguint().is_odd() {
+        bytes[31] |= 0x80;
+    }
+    bytes
+}
+
+/// Decodes a point encoded with `compress_point`, or returns `None` if the encoding is invalid.
+pub fn decompress_point(bytes: [u8; 32]) -> Option<EdwardsPoint<Ed25519>> {
+    let x_odd = bytes[31] & 0x80 != 0;
+    let mut y_bytes = bytes;
+    y_bytes[31] &= 0x7F;
+    let y_biguint = BigUint::from_bytes_le(&y_bytes);
+    if y_biguint >= Curve25519Base::order() {
+        return None;
+    }
+    let y = Curve25519Base::from_biguint(y_biguint);
+
+    // x^2 = (y^2 - 1) / (d y^2 + 1)
+    let yy = y.square();
+    let xx = (yy - Curve25519Base::ONE) / (Ed25519::D * yy + Curve25519Base::ONE);
+    let candidate = xx.exp_biguint(&((Curve25519Base::order() + 3u32) / 8u32));
+    let x = if candidate.square() == xx {
+        candidate
+    } else if candidate.square() == -xx {
+        candidate * SQRT_M1
+    } else {
+        return None;
+    };
+
+    if x == Curve25519Base::ZERO && x_odd {
+        return None;
+    }
+    let x = if x.to_canonical_biguint().is_odd() == x_odd {
+        x
+    } else {
+        -x
+    };
+    Some(EdwardsPoint { x, y })
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2_field::curve25519_scalar::Curve25519Scalar;
+    use plonky2_field::field_types::Field;
+
+    use crate::curve::ed25519::{compress_point, decompress_point, Ed25519};
+    use crate::curve::twisted_edwards::{EdwardsPoint, TwistedEdwardsCurve};
+
+    #[test]
+    fn test_generator() {
+        let g = Ed25519::GENERATOR;
+        assert!(g.is_valid());
+        assert!((-g).is_valid());
+        assert_eq!(g + -g, EdwardsPoint::ZERO);
+    }
+
+    #[test]
+    fn test_generator_order() {
+        let g = Ed25519::GENERATOR;
+        let order_minus_one = Curve25519Scalar::NEG_ONE;
+        assert_eq!(g.mul(order_minus_one), -g);
+    }
+
+    #[test]
+    fn test_compression() {
+        for _ in 0..10 {
+            let p = Ed25519::GENERATOR.mul(Curve25519Scalar::rand());
+            assert_eq!(decompress_point(compress_point(p)), Some(p));
+            assert_eq!(decompress_point(compress_point(-p)), Some(-p));
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use num::BigUint;
+use plonky2_field::curve25519_scalar::Curve25519Scalar;
+use plonky2_field::field_types::{Field, PrimeField};
+use sha2::{Digest, Sha512};
+
+use crate::curve::ed25519::{compress_point, decompress_point, Ed25519};
+use crate::curve::twisted_edwards::{EdwardsPoint, TwistedEdwardsCurve};
+
+/// An Ed25519 signature, as in RFC 8032.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct EdDSASignature {
+    pub r: EdwardsPoint<Ed25519>,
+    pub s: Curve25519Scalar,
+}
+
+/// An Ed25519 secret key, i.e. the 32-byte seed from which the signing scalar is derived.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct EdDSASecretKey(pub [u8; 32]);
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct EdDSAPublicKey(pub EdwardsPoint<Ed25519>);
+
+impl EdDSASignature {
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0; 64];
+        bytes[..32].copy_from_slice(&compress_point(self.r));
+        let s_bytes = self.s.to_canonical_biguint().to_bytes_le();
+        bytes[32..32 + s_bytes.len()].copy_from_slice(&s_bytes);
+        bytes
+    }
+
+    /// Decodes a signature, or returns `None` if `R` is not a valid point or `S` is not reduced.
+    pub fn from_bytes(bytes: [u8; 64]) -> Option<Self> {
+        let r = decompress_point(bytes[..32].try_into().unwrap())?;
+        let s = BigUint::from_bytes_le(&bytes[32..]);
+        if s >= Curve25519Scalar::order() {
+            return None;
+        }
+        Some(Self {
+            r,
+            s: Curve25519Scalar::from_biguint(s),
+        })
+    }
+}
+
+impl EdDSAPublicKey {
+    pub fn to_bytes(&self) -> [u8; 32] {
+        compress_point(self.0)
+    }
+
+    pub fn from_bytes(bytes: [u8; 32]) -> Option<Self> {
+        decompress_point(bytes).map(Self)
+    }
+}
+
+/// Returns the signing scalar and the nonce prefix derived from a secret key.
+fn expand_secret_key(sk: EdDSASecretKey) -> (Curve25519Scalar, [u8; 32]) {
+    let h = Sha512::digest(sk.0);
+    let mut scalar_bytes: [u8; 32] = h[..32].try_into().unwrap();
+    scalar_bytes[0] &= 0xF8;
+    scalar_bytes[31] &= 0x7F;
+    scalar_bytes[31] |= 0x40;
+    let scalar = Curve25519Scalar::from_biguint(
+        BigUint::from_bytes_le(&scalar_bytes) % Curve25519Scalar::order(),
+    );
+    (scalar, h[32..].try_into().unwrap())
+}
+
+/// Hashes the concatenation of `parts` with SHA-512, and interprets the result as a little-endian
+/// integer modulo the group order.
+fn hash_to_scalar(parts: &[&[u8]]) -> Curve25519Scalar {
+    let mut hasher = Sha512::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    let h = BigUint::from_bytes_le(&hasher.finalize());
+    Curve25519Scalar::from_biguint(h % Curve25519Scalar::order())
+}
+
+pub fn secret_to_public(sk: EdDSASecretKey) -> EdDSAPublicKey {
+    let (a, _) = expand_secret_key(sk);
+    EdDSAPublicKey(Ed25519::GENERATOR.mul(a))
+}
+
+pub fn sign_message(msg: &[u8], sk: EdDSASecretKey) -> EdDSASignature {
+    let (a, prefix) = expand_secret_key(sk);
+    let pk = EdDSAPublicKey(Ed25519::GENERATOR.mul(a));
+
+    let r_scalar = hash_to_scalar(&[&prefix, msg]);
+    let r = Ed25519::GENERATOR.mul(r_scalar);
+    let k = hash_to_scalar(&[&compress_point(r), &pk.to_bytes(), msg]);
+    let s = r_scalar + k * a;
+
+    EdDSASignature { r, s }
+}
+
+/// Checks that `S B = R + k A`, where `k = SHA-512(R || A || msg)`.
+pub fn verify_message(msg: &[u8], sig: EdDSASignature, pk: EdDSAPublicKey) -> bool {
+    if !sig.r.is_valid() || !pk.0.is_valid() {
+        return false;
+    }
+
+    let k = hash_to_scalar(&[&compress_point(sig.r), &pk.to_bytes(), msg]);
+    Ed25519::GENERATOR.mul(sig.s) == sig.r + pk.0.mul(k)
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2_field::curve25519_scalar::Curve25519Scalar;
+    use plonky2_field::field_types::Field;
+
+    use crate::curve::eddsa::{
+        secret_to_public, sign_message, verify_message, EdDSAPublicKey, EdDSASecretKey,
+        EdDSASignature,
+    };
+
+    fn from_hex<const N: usize>(s: &str) -> [u8; N] {
+        let bytes: Vec<u8> = (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect();
+        bytes.try_into().unwrap()
+    }
+
+    /// Test vectors 1 and 2 from RFC 8032, section 7.1.
+    #[test]
+    fn test_rfc_8032_vectors() {
+        let vectors: [(&str, &str, &[u8], &str); 2] = [
+            (
+                "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+                "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+                &[],
+                "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e06522490155\
+                 5fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+            ),
+            (
+                "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+                "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+                &[0x72],
+                "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da\
+                 085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+            ),
+        ];
+
+        for (sk, pk, msg, sig) in vectors {
+            let sk = EdDSASecretKey(from_hex(sk));
+            let pk_bytes: [u8; 32] = from_hex(pk);
+            let sig_bytes: [u8; 64] = from_hex(sig);
+
+            let pk = secret_to_public(sk);
+            assert_eq!(pk.to_bytes(), pk_bytes);
+            assert_eq!(EdDSAPublicKey::from_bytes(pk_bytes), Some(pk));
+
+            let sig = sign_message(msg, sk);
+            assert_eq!(sig.to_bytes(), sig_bytes);
+            assert_eq!(EdDSASignature::from_bytes(sig_bytes), Some(sig));
+
+            assert!(verify_message(msg, sig, pk));
+            assert!(!verify_message(b"wrong message", sig, pk));
+        }
+    }
+
+    #[test]
+    fn test_eddsa_native() {
+        let sk = EdDSASecretKey(rand::random());
+        let pk = secret_to_public(sk);
+        let msg = b"hello";
+
+        let sig = sign_message(msg, sk);
+        assert!(verify_message(msg, sig, pk));
+
+        let bad_sig = EdDSASignature {
+            s: sig.s + Curve25519Scalar::ONE,
+            ..sig
+        };
+        assert!(!verify_message(msg, bad_sig, pk));
+    }
+}
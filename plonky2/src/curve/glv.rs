@@ -0,0 +1,127 @@
+//! The GLV endomorphism of secp256k1, which maps `(x, y)` to `(beta x, y)`. This is equivalent to
+//! multiplying by `lambda`, so a scalar `k` can be decomposed as `k = k1 + lambda k2`, where `k1`
+//! and `k2` have about 128 bits, and `k P` computed as `k1 P + k2 (lambda P)` with half as many
+//! doublings.
+
+use num::BigUint;
+use plonky2_field::field_types::{Field, PrimeField};
+use plonky2_field::secp256k1_base::Secp256K1Base;
+use plonky2_field::secp256k1_scalar::Secp256K1Scalar;
+
+use crate::curve::curve_msm::msm_parallel;
+use crate::curve::curve_types::{AffinePoint, ProjectivePoint};
+use crate::curve::secp256k1::Secp256K1;
+
+/// A cube root of unity in the base field, such that `(beta x, y) = lambda (x, y)`.
+pub const GLV_BETA: Secp256K1Base = Secp256K1Base([
+    13923278643952681454,
+    11308619431505398165,
+    7954561588662645993,
+    8856726876819556112,
+]);
+
+/// A cube root of unity in the scalar field, such that `lambda (x, y) = (beta x, y)`.
+pub const GLV_LAMBDA: Secp256K1Scalar = Secp256K1Scalar([
+    16069571880186789234,
+    1310022930574435960,
+    11900229862571533402,
+    6008836872998760672,
+]);
+
+// A short basis `(a1, b1), (a2, b2)` of the lattice of `(x, y)` with `x + lambda y = 0`.
+const A1: Secp256K1Scalar = Secp256K1Scalar([16747920425669159701, 3496713202691238861, 0, 0]);
+const MINUS_B1: Secp256K1Scalar =
+    Secp256K1Scalar([8022177200260244675, 16448129721693014056, 0, 0]);
+const A2: Secp256K1Scalar = Secp256K1Scalar([6323353552219852760, 1498098850674701302, 1, 0]);
+const B2: Secp256K1Scalar = Secp256K1Scalar([16747920425669159701, 3496713202691238861, 0, 0]);
+
+/// Decomposes `k` into `(k1, k2, k1_neg, k2_neg)`, where `k1, k2 < 2^128`, such that
+/// `k = (-1)^k1_neg k1 + lambda (-1)^k2_neg k2`.
+pub fn decompose_secp256k1_scalar(
+    k: Secp256K1Scalar,
+) -> (Secp256K1Scalar, Secp256K1Scalar, bool, bool) {
+    let p = Secp256K1Scalar::order();
+    let round_div = |x: BigUint| (x * 2u32 + &p) / (&p * 2u32);
+    let k_biguint = k.to_canonical_biguint();
+    let c1 = Secp256K1Scalar::from_biguint(round_div(B2.to_canonical_biguint() * &k_biguint));
+    let c2 = Secp256K1Scalar::from_biguint(round_div(MINUS_B1.to_canonical_biguint() * &k_biguint));
+
+    let k1_raw = k - c1 * A1 - c2 * A2;
+    let k2_raw = c1 * MINUS_B1 - c2 * B2;
+    debug_assert!(k1_raw + GLV_LAMBDA * k2_raw == k);
+
+    let half_p = p / 2u32;
+    let k1_neg = k1_raw.to_canonical_biguint() > half_p;
+    let k1 = if k1_neg { -k1_raw } else { k1_raw };
+    let k2_neg = k2_raw.to_canonical_biguint() > half_p;
+    let k2 = if k2_neg { -k2_raw } else { k2_raw };
+
+    (k1, k2, k1_neg, k2_neg)
+}
+
+/// Computes `k p` using the GLV endomorphism.
+pub fn glv_mul(p: ProjectivePoint<Secp256K1>, k: Secp256K1Scalar) -> ProjectivePoint<Secp256K1> {
+    let (k1, k2, k1_neg, k2_neg) = decompose_secp256k1_scalar(k);
+
+    let p_affine = p.to_affine();
+    let sp = AffinePoint::<Secp256K1> {
+        x: p_affine.x * GLV_BETA,
+        y: p_affine.y,
+        zero: p_affine.zero,
+    };
+
+    let first = if k1_neg { p.neg() } else { p };
+    let second = if k2_neg {
+        sp.to_projective().neg()
+    } else {
+        sp.to_projective()
+    };
+
+    msm_parallel(&[k1, k2], &[first, second], 5)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use plonky2_field::field_types::{Field, PrimeField};
+    use plonky2_field::secp256k1_scalar::Secp256K1Scalar;
+
+    use crate::curve::curve_types::{Curve, CurveScalar};
+    use crate::curve::glv::{decompose_secp256k1_scalar, glv_mul, GLV_BETA, GLV_LAMBDA};
+    use crate::curve::secp256k1::Secp256K1;
+
+    #[test]
+    fn test_glv_endomorphism() {
+        let g = Secp256K1::GENERATOR_AFFINE;
+        let lambda_g = (CurveScalar(GLV_LAMBDA) * g.to_projective()).to_affine();
+        assert_eq!(lambda_g.x, g.x * GLV_BETA);
+        assert_eq!(lambda_g.y, g.y);
+    }
+
+    #[test]
+    fn test_glv_decompose() -> Result<()> {
+        for _ in 0..100 {
+            let k = Secp256K1Scalar::rand();
+            let (k1, k2, k1_neg, k2_neg) = decompose_secp256k1_scalar(k);
+            assert!(k1.to_canonical_biguint().bits() <= 128);
+            assert!(k2.to_canonical_biguint().bits() <= 128);
+
+            let k1 = if k1_neg { -k1 } else { k1 };
+            let k2 = if k2_neg { -k2 } else { k2 };
+            assert_eq!(k1 + GLV_LAMBDA * k2, k);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_glv_mul() -> Result<()> {
+        for _ in 0..20 {
+            let k = Secp256K1Scalar::rand();
+            let p = CurveScalar(Secp256K1Scalar::rand()) * Secp256K1::GENERATOR_PROJECTIVE;
+            assert_eq!(glv_mul(p, k), CurveScalar(k) * p);
+        }
+
+        Ok(())
+    }
+}
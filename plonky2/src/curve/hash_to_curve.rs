@@ -0,0 +1,46 @@
+use keccak_hash::keccak;
+use num::BigUint;
+use plonky2_field::field_types::PrimeField;
+
+use crate::curve::curve_types::{AffinePoint, Curve};
+
+/// Deterministically maps `label` to a point on `C`, using try-and-increment: we hash the label
+/// with a counter until the hash is the x-coordinate of a point. Nobody knows the discrete log of
+/// the result with respect to the generator, which makes it suitable as a nothing-up-my-sleeve
+/// point. This is not constant-time, so it should only be used with public labels.
+pub fn hash_to_curve<C: Curve>(label: &[u8]) -> AffinePoint<C> {
+    for counter in 0u32.. {
+        let x = hash_to_field::<C::BaseField>(label, counter);
+        if let Some(point) = AffinePoint::lift_x(x, false) {
+            return point;
+        }
+    }
+    unreachable!()
+}
+
+/// Hashes `label` and `counter` to a field element. We use 512 bits of hash output, so the bias of
+/// the reduction is negligible for fields of up to 384 bits.
+fn hash_to_field<FF: PrimeField>(label: &[u8], counter: u32) -> FF {
+    let mut bytes = Vec::with_capacity(64);
+    for i in 0u8..2 {
+        let mut input = label.to_vec();
+        input.extend(counter.to_le_bytes());
+        input.push(i);
+        bytes.extend(keccak(input).0);
+    }
+    FF::from_biguint(BigUint::from_bytes_le(&bytes) % FF::order())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::curve::hash_to_curve::hash_to_curve;
+    use crate::curve::secp256k1::Secp256K1;
+
+    #[test]
+    fn test_hash_to_curve() {
+        let p = hash_to_curve::<Secp256K1>(b"test label");
+        assert!(p.is_valid());
+        assert_eq!(p, hash_to_curve::<Secp256K1>(b"test label"));
+        assert_ne!(p, hash_to_curve::<Secp256K1>(b"other label"));
+    }
+}
@@ -0,0 +1,91 @@
+use std::fmt::Debug;
+use std::ops::Neg;
+
+use plonky2_field::field_types::{Field, PrimeField};
+use plonky2_field::ops::Square;
+use serde::{Deserialize, Serialize};
+
+/// A twisted Edwards curve, `a x^2 + y^2 = 1 + d x^2 y^2`.
+pub trait TwistedEdwardsCurve: 'static + Sync + Sized + Copy + Debug {
+    type BaseField: PrimeField;
+    type ScalarField: PrimeField;
+
+    const A: Self::BaseField;
+    const D: Self::BaseField;
+
+    /// A generator of the prime-order subgroup, whose order is that of `ScalarField`.
+    const GENERATOR: EdwardsPoint<Self>;
+}
+
+/// A point on a twisted Edwards curve, represented in affine coordinates. Unlike short Weierstrass
+/// curves, the identity `(0, 1)` is an ordinary point, and we assume `a` is a square and `d` is not,
+/// so the addition law is complete.
+#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+pub struct EdwardsPoint<C: TwistedEdwardsCurve> {
+    pub x: C::BaseField,
+    pub y: C::BaseField,
+}
+
+impl<C: TwistedEdwardsCurve> EdwardsPoint<C> {
+    pub const ZERO: Self = Self {
+        x: C::BaseField::ZERO,
+        y: C::BaseField::ONE,
+    };
+
+    pub fn is_valid(&self) -> bool {
+        let Self { x, y } = *self;
+        let xx = x.square();
+        let yy = y.square();
+        C::A * xx + yy == C::BaseField::ONE + C::D * xx * yy
+    }
+
+    pub fn double(&self) -> Self {
+        *self + *self
+    }
+
+    /// Computes `k self` with double-and-add.
+    pub fn mul(&self, k: C::ScalarField) -> Self {
+        let k = k.to_canonical_biguint();
+        let mut result = Self::ZERO;
+        for i in (0..k.bits()).rev() {
+            result = result.double();
+            if k.bit(i) {
+                result = result + *self;
+            }
+        }
+        result
+    }
+}
+
+impl<C: TwistedEdwardsCurve> std::ops::Add for EdwardsPoint<C> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let Self { x: x1, y: y1 } = self;
+        let Self { x: x2, y: y2 } = rhs;
+
+        let dxxyy = C::D * x1 * x2 * y1 * y2;
+        let x3 = (x1 * y2 + y1 * x2) / (C::BaseField::ONE + dxxyy);
+        let y3 = (y1 * y2 - C::A * x1 * x2) / (C::BaseField::ONE - dxxyy);
+        Self { x: x3, y: y3 }
+    }
+}
+
+impl<C: TwistedEdwardsCurve> Neg for EdwardsPoint<C> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            x: -self.x,
+            y: self.y,
+        }
+    }
+}
+
+impl<C: TwistedEdwardsCurve> PartialEq for EdwardsPoint<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.x == other.x && self.y == other.y
+    }
+}
+
+impl<C: TwistedEdwardsCurve> Eq for EdwardsPoint<C> {}
@@ -323,6 +323,20 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         let res = self.sub(one, b.target);
         BoolTarget::new_unsafe(res)
     }
+
+    pub fn xor(&mut self, a: BoolTarget, b: BoolTarget) -> BoolTarget {
+        // a ^ b = a + b - 2ab
+        let sum = self.add(a.target, b.target);
+        let res = self.arithmetic(-F::TWO, F::ONE, a.target, b.target, sum);
+        BoolTarget::new_unsafe(res)
+    }
+
+    /// Computes `!a & b`.
+    pub fn and_not(&mut self, a: BoolTarget, b: BoolTarget) -> BoolTarget {
+        // (1 - a) b = b - ab
+        let res = self.arithmetic(F::NEG_ONE, F::ONE, a.target, b.target, b.target);
+        BoolTarget::new_unsafe(res)
+    }
 }
 
 /// Represents a base arithmetic operation in the circuit. Used to memoize results.
@@ -0,0 +1,305 @@
+use plonky2_field::extension_field::Extendable;
+
+use crate::gadgets::arithmetic_u32::U32Target;
+use crate::gadgets::bigint::BigIntTarget;
+use crate::gadgets::biguint::BigUintTarget;
+use crate::hash::hash_types::RichField;
+use crate::iop::target::BoolTarget;
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+/// A signed 32-bit integer in two's complement form, i.e. stored as the `u32` with the same bits.
+/// Arithmetic on these wraps around on overflow, like Rust's `wrapping_*` methods.
+#[derive(Clone, Copy, Debug)]
+pub struct I32Target(pub U32Target);
+
+/// A signed 64-bit integer in two's complement form, stored as two little-endian `u32` limbs.
+/// Arithmetic on these wraps around on overflow, like Rust's `wrapping_*` methods.
+#[derive(Clone, Copy, Debug)]
+pub struct I64Target(pub [U32Target; 2]);
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    pub fn add_virtual_i32_target(&mut self) -> I32Target {
+        I32Target(self.add_virtual_u32_target())
+    }
+
+    pub fn constant_i32(&mut self, c: i32) -> I32Target {
+        I32Target(self.constant_u32(c as u32))
+    }
+
+    pub fn connect_i32(&mut self, x: I32Target, y: I32Target) {
+        self.connect_u32(x.0, y.0)
+    }
+
+    pub fn neg_i32(&mut self, x: I32Target) -> I32Target {
+        I32Target(self.neg_twos_complement(&[x.0])[0])
+    }
+
+    pub fn add_i32(&mut self, a: I32Target, b: I32Target) -> I32Target {
+        I32Target(self.add_twos_complement(&[a.0], &[b.0])[0])
+    }
+
+    pub fn sub_i32(&mut self, a: I32Target, b: I32Target) -> I32Target {
+        I32Target(self.sub_twos_complement(&[a.0], &[b.0])[0])
+    }
+
+    pub fn mul_i32(&mut self, a: I32Target, b: I32Target) -> I32Target {
+        I32Target(self.mul_twos_complement(&[a.0], &[b.0])[0])
+    }
+
+    /// Returns the quotient and remainder of `a / b`, rounding the quotient towards zero.
+    pub fn div_rem_i32(&mut self, a: I32Target, b: I32Target) -> (I32Target, I32Target) {
+        let a = self.i32_to_bigint(a);
+        let b = self.i32_to_bigint(b);
+        let (div, rem) = self.div_rem_bigint(&a, &b);
+        (self.bigint_to_i32(&div), self.bigint_to_i32(&rem))
+    }
+
+    /// Returns whether `a <= b`.
+    pub fn cmp_i32(&mut self, a: I32Target, b: I32Target) -> BoolTarget {
+        self.cmp_twos_complement(&[a.0], &[b.0])
+    }
+
+    pub fn i32_to_bigint(&mut self, x: I32Target) -> BigIntTarget {
+        self.twos_complement_to_bigint(&[x.0])
+    }
+
+    /// Returns `x` modulo `2^32`, like an `as` cast.
+    pub fn bigint_to_i32(&mut self, x: &BigIntTarget) -> I32Target {
+        I32Target(self.bigint_to_twos_complement(x, 1)[0])
+    }
+
+    pub fn add_virtual_i64_target(&mut self) -> I64Target {
+        I64Target([self.add_virtual_u32_target(), self.add_virtual_u32_target()])
+    }
+
+    pub fn constant_i64(&mut self, c: i64) -> I64Target {
+        let c = c as u64;
+        I64Target([
+            self.constant_u32(c as u32),
+            self.constant_u32((c >> 32) as u32),
+        ])
+    }
+
+    pub fn connect_i64(&mut self, x: I64Target, y: I64Target) {
+        self.connect_u32(x.0[0], y.0[0]);
+        self.connect_u32(x.0[1], y.0[1]);
+    }
+
+    pub fn neg_i64(&mut self, x: I64Target) -> I64Target {
+        I64Target(self.neg_twos_complement(&x.0).try_into().unwrap())
+    }
+
+    pub fn add_i64(&mut self, a: I64Target, b: I64Target) -> I64Target {
+        I64Target(self.add_twos_complement(&a.0, &b.0).try_into().unwrap())
+    }
+
+    pub fn sub_i64(&mut self, a: I64Target, b: I64Target) -> I64Target {
+        I64Target(self.sub_twos_complement(&a.0, &b.0).try_into().unwrap())
+    }
+
+    pub fn mul_i64(&mut self, a: I64Target, b: I64Target) -> I64Target {
+        I64Target(self.mul_twos_complement(&a.0, &b.0).try_into().unwrap())
+    }
+
+    /// Returns the quotient and remainder of `a / b`, rounding the quotient towards zero.
+    pub fn div_rem_i64(&mut self, a: I64Target, b: I64Target) -> (I64Target, I64Target) {
+        let a = self.i64_to_bigint(a);
+        let b = self.i64_to_bigint(b);
+        let (div, rem) = self.div_rem_bigint(&a, &b);
+        (self.bigint_to_i64(&div), self.bigint_to_i64(&rem))
+    }
+
+    /// Returns whether `a <= b`.
+    pub fn cmp_i64(&mut self, a: I64Target, b: I64Target) -> BoolTarget {
+        self.cmp_twos_complement(&a.0, &b.0)
+    }
+
+    pub fn i64_to_bigint(&mut self, x: I64Target) -> BigIntTarget {
+        self.twos_complement_to_bigint(&x.0)
+    }
+
+    /// Returns `x` modulo `2^64`, like an `as` cast.
+    pub fn bigint_to_i64(&mut self, x: &BigIntTarget) -> I64Target {
+        I64Target(self.bigint_to_twos_complement(x, 2).try_into().unwrap())
+    }
+
+    fn neg_twos_complement(&mut self, x: &[U32Target]) -> Vec<U32Target> {
+        let zero = vec![self.zero_u32(); x.len()];
+        self.sub_twos_complement(&zero, x)
+    }
+
+    fn add_twos_complement(&mut self, a: &[U32Target], b: &[U32Target]) -> Vec<U32Target> {
+        let a_biguint = BigUintTarget { limbs: a.to_vec() };
+        let b_biguint = BigUintTarget { limbs: b.to_vec() };
+        let mut sum = self.add_biguint(&a_biguint, &b_biguint).limbs;
+        sum.truncate(a.len());
+        sum
+    }
+
+    fn sub_twos_complement(&mut self, a: &[U32Target], b: &[U32Target]) -> Vec<U32Target> {
+        let mut result = Vec::with_capacity(a.len());
+        let mut borrow = self.zero_u32();
+        for (&a_limb, &b_limb) in a.iter().zip(b) {
+            let (limb, new_borrow) = self.sub_u32(a_limb, b_limb, borrow);
+            result.push(limb);
+            borrow = new_borrow;
+        }
+        // The final borrow is discarded, so the result wraps around.
+        result
+    }
+
+    fn mul_twos_complement(&mut self, a: &[U32Target], b: &[U32Target]) -> Vec<U32Target> {
+        // The low limbs of the unsigned product are those of the signed product.
+        let a_biguint = BigUintTarget { limbs: a.to_vec() };
+        let b_biguint = BigUintTarget { limbs: b.to_vec() };
+        let mut product = self.mul_biguint(&a_biguint, &b_biguint).limbs;
+        product.truncate(a.len());
+        product
+    }
+
+    fn cmp_twos_complement(&mut self, a: &[U32Target], b: &[U32Target]) -> BoolTarget {
+        // Flipping the sign bits maps the signed order onto the unsigned order.
+        let sign_bit = self.constant_u32(1 << 31);
+        let mut a = a.to_vec();
+        let mut b = b.to_vec();
+        let top = a.len() - 1;
+        a[top] = self.add_u32(a[top], sign_bit).0;
+        b[top] = self.add_u32(b[top], sign_bit).0;
+
+        self.list_le_u32(a, b)
+    }
+
+    fn twos_complement_to_bigint(&mut self, x: &[U32Target]) -> BigIntTarget {
+        let top_bits = self.split_le(x[x.len() - 1].0, 32);
+        let sign = top_bits[31];
+
+        let abs = BigUintTarget { limbs: x.to_vec() };
+        let neg_abs = BigUintTarget {
+            limbs: self.neg_twos_complement(x),
+        };
+        BigIntTarget {
+            abs: self.select_biguint(sign, &neg_abs, &abs),
+            sign,
+        }
+    }
+
+    fn bigint_to_twos_complement(&mut self, x: &BigIntTarget, num_limbs: usize) -> Vec<U32Target> {
+        let abs_limbs: Vec<_> = (0..num_limbs)
+            .map(|i| {
+                if i < x.abs.num_limbs() {
+                    x.abs.get_limb(i)
+                } else {
+                    self.zero_u32()
+                }
+            })
+            .collect();
+
+        let abs = BigUintTarget {
+            limbs: abs_limbs.clone(),
+        };
+        let neg_abs = BigUintTarget {
+            limbs: self.neg_twos_complement(&abs_limbs),
+        };
+        self.select_biguint(x.sign, &neg_abs, &abs).limbs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use rand::Rng;
+
+    use crate::iop::witness::{PartialWitness, Witness};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    #[test]
+    fn test_i32_arithmetic() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let mut rng = rand::thread_rng();
+
+        let x_value: i32 = rng.gen();
+        let y_value: i32 = rng.gen();
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.add_virtual_i32_target();
+        let y = builder.add_virtual_i32_target();
+
+        let expected = [
+            (builder.add_i32(x, y), x_value.wrapping_add(y_value)),
+            (builder.sub_i32(x, y), x_value.wrapping_sub(y_value)),
+            (builder.mul_i32(x, y), x_value.wrapping_mul(y_value)),
+            (builder.neg_i32(x), x_value.wrapping_neg()),
+        ];
+        let (div, rem) = builder.div_rem_i32(x, y);
+        for (actual, expected_value) in expected
+            .into_iter()
+            .chain([(div, x_value / y_value), (rem, x_value % y_value)])
+        {
+            let expected = builder.constant_i32(expected_value);
+            builder.connect_i32(actual, expected);
+        }
+
+        let cmp = builder.cmp_i32(x, y);
+        let expected_cmp = builder.constant_bool(x_value <= y_value);
+        builder.connect(cmp.target, expected_cmp.target);
+
+        pw.set_i32_target(x, x_value);
+        pw.set_i32_target(y, y_value);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_i64_arithmetic() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let mut rng = rand::thread_rng();
+
+        let x_value: i64 = rng.gen();
+        let y_value: i64 = rng.gen();
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.add_virtual_i64_target();
+        let y = builder.add_virtual_i64_target();
+
+        let expected = [
+            (builder.add_i64(x, y), x_value.wrapping_add(y_value)),
+            (builder.sub_i64(x, y), x_value.wrapping_sub(y_value)),
+            (builder.mul_i64(x, y), x_value.wrapping_mul(y_value)),
+            (builder.neg_i64(x), x_value.wrapping_neg()),
+        ];
+        let (div, rem) = builder.div_rem_i64(x, y);
+        for (actual, expected_value) in expected
+            .into_iter()
+            .chain([(div, x_value / y_value), (rem, x_value % y_value)])
+        {
+            let expected = builder.constant_i64(expected_value);
+            builder.connect_i64(actual, expected);
+        }
+
+        let cmp = builder.cmp_i64(x, y);
+        let expected_cmp = builder.constant_bool(x_value <= y_value);
+        builder.connect(cmp.target, expected_cmp.target);
+
+        pw.set_i64_target(x, x_value);
+        pw.set_i64_target(y, y_value);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        verify(proof, &data.verifier_only, &data.common)
+    }
+}
@@ -0,0 +1,275 @@
+use num::bigint::Sign;
+use num::BigInt;
+use plonky2_field::extension_field::Extendable;
+
+use crate::gadgets::biguint::BigUintTarget;
+use crate::hash::hash_types::RichField;
+use crate::iop::target::BoolTarget;
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+/// A signed integer in sign-magnitude form. Zero may be represented with either sign; the gadgets
+/// below treat both representations as equal.
+#[derive(Clone, Debug)]
+pub struct BigIntTarget {
+    pub abs: BigUintTarget,
+    /// Whether the integer is negative.
+    pub sign: BoolTarget,
+}
+
+impl BigIntTarget {
+    pub fn num_limbs(&self) -> usize {
+        self.abs.num_limbs()
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    pub fn constant_bigint(&mut self, value: &BigInt) -> BigIntTarget {
+        BigIntTarget {
+            abs: self.constant_biguint(value.magnitude()),
+            sign: self.constant_bool(value.sign() == Sign::Minus),
+        }
+    }
+
+    pub fn zero_bigint(&mut self) -> BigIntTarget {
+        let abs = self.zero_biguint();
+        BigIntTarget {
+            abs,
+            sign: self._false(),
+        }
+    }
+
+    pub fn add_virtual_bigint_target(&mut self, num_limbs: usize) -> BigIntTarget {
+        BigIntTarget {
+            abs: self.add_virtual_biguint_target(num_limbs),
+            sign: self.add_virtual_bool_target_safe(),
+        }
+    }
+
+    pub fn connect_bigint(&mut self, lhs: &BigIntTarget, rhs: &BigIntTarget) {
+        self.connect_biguint(&lhs.abs, &rhs.abs);
+
+        // The signs must agree unless both sides are zero.
+        let sign_diff = self.sub(lhs.sign.target, rhs.sign.target);
+        for limb in &lhs.abs.limbs {
+            let product = self.mul(sign_diff, limb.0);
+            self.assert_zero(product);
+        }
+    }
+
+    /// Returns `x` as a signed integer.
+    pub fn biguint_to_bigint(&mut self, x: &BigUintTarget) -> BigIntTarget {
+        BigIntTarget {
+            abs: x.clone(),
+            sign: self._false(),
+        }
+    }
+
+    /// Returns the absolute value of `x`, asserting that `x` is non-negative.
+    pub fn bigint_to_biguint(&mut self, x: &BigIntTarget) -> BigUintTarget {
+        for limb in &x.abs.limbs {
+            let product = self.mul(x.sign.target, limb.0);
+            self.assert_zero(product);
+        }
+        x.abs.clone()
+    }
+
+    pub fn neg_bigint(&mut self, x: &BigIntTarget) -> BigIntTarget {
+        BigIntTarget {
+            abs: x.abs.clone(),
+            sign: self.not(x.sign),
+        }
+    }
+
+    /// Returns whether `a <= b`.
+    pub fn cmp_bigint(&mut self, a: &BigIntTarget, b: &BigIntTarget) -> BoolTarget {
+        let abs_a_le_abs_b = self.cmp_biguint(&a.abs, &b.abs);
+        let abs_b_le_abs_a = self.cmp_biguint(&b.abs, &a.abs);
+        // If `a` is non-negative and `b` is negative, `a <= b` only if both are zero.
+        let abs_sum = self.add_biguint(&a.abs, &b.abs);
+        let zero = self.zero_biguint();
+        let both_zero = self.cmp_biguint(&abs_sum, &zero);
+
+        let one = self.one();
+        let if_same_sign = self.select(a.sign, abs_b_le_abs_a.target, abs_a_le_abs_b.target);
+        let if_different_sign = self.select(a.sign, one, both_zero.target);
+        let signs_differ = self.xor(a.sign, b.sign);
+        BoolTarget::new_unsafe(self.select(signs_differ, if_different_sign, if_same_sign))
+    }
+
+    pub fn add_bigint(&mut self, a: &BigIntTarget, b: &BigIntTarget) -> BigIntTarget {
+        // The result has the sign of the operand with the larger absolute value, and its absolute
+        // value is either the sum or the difference of the operands' absolute values.
+        let abs_a_le_abs_b = self.cmp_biguint(&a.abs, &b.abs);
+        let larger = self.select_biguint(abs_a_le_abs_b, &b.abs, &a.abs);
+        let smaller = self.select_biguint(abs_a_le_abs_b, &a.abs, &b.abs);
+
+        let sum = self.add_biguint(&larger, &smaller);
+        let difference = self.sub_biguint(&larger, &smaller);
+        let signs_differ = self.xor(a.sign, b.sign);
+        let abs = self.select_biguint(signs_differ, &difference, &sum);
+        let sign =
+            BoolTarget::new_unsafe(self.select(abs_a_le_abs_b, b.sign.target, a.sign.target));
+
+        BigIntTarget { abs, sign }
+    }
+
+    pub fn sub_bigint(&mut self, a: &BigIntTarget, b: &BigIntTarget) -> BigIntTarget {
+        let neg_b = self.neg_bigint(b);
+        self.add_bigint(a, &neg_b)
+    }
+
+    pub fn mul_bigint(&mut self, a: &BigIntTarget, b: &BigIntTarget) -> BigIntTarget {
+        BigIntTarget {
+            abs: self.mul_biguint(&a.abs, &b.abs),
+            sign: self.xor(a.sign, b.sign),
+        }
+    }
+
+    /// Returns the quotient and remainder of `a / b`, rounding the quotient towards zero, so that
+    /// the remainder has the sign of `a`, as with Rust's `/` and `%`.
+    pub fn div_rem_bigint(
+        &mut self,
+        a: &BigIntTarget,
+        b: &BigIntTarget,
+    ) -> (BigIntTarget, BigIntTarget) {
+        let (div_abs, rem_abs) = self.div_rem_biguint(&a.abs, &b.abs);
+        let div = BigIntTarget {
+            abs: div_abs,
+            sign: self.xor(a.sign, b.sign),
+        };
+        let rem = BigIntTarget {
+            abs: rem_abs,
+            sign: a.sign,
+        };
+
+        (div, rem)
+    }
+
+    pub fn div_bigint(&mut self, a: &BigIntTarget, b: &BigIntTarget) -> BigIntTarget {
+        let (div, _rem) = self.div_rem_bigint(a, b);
+        div
+    }
+
+    pub fn rem_bigint(&mut self, a: &BigIntTarget, b: &BigIntTarget) -> BigIntTarget {
+        let (_div, rem) = self.div_rem_bigint(a, b);
+        rem
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use num::{BigInt, FromPrimitive, Integer};
+    use rand::Rng;
+
+    use crate::iop::witness::{PartialWitness, Witness};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    #[test]
+    fn test_bigint_add_sub_mul() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let mut rng = rand::thread_rng();
+
+        let x_value = BigInt::from_i128(rng.gen()).unwrap();
+        let y_value = BigInt::from_i128(rng.gen()).unwrap();
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.add_virtual_bigint_target(x_value.magnitude().to_u32_digits().len());
+        let y = builder.add_virtual_bigint_target(y_value.magnitude().to_u32_digits().len());
+
+        let sum = builder.add_bigint(&x, &y);
+        let expected_sum = builder.constant_bigint(&(&x_value + &y_value));
+        builder.connect_bigint(&sum, &expected_sum);
+
+        let difference = builder.sub_bigint(&x, &y);
+        let expected_difference = builder.constant_bigint(&(&x_value - &y_value));
+        builder.connect_bigint(&difference, &expected_difference);
+
+        let product = builder.mul_bigint(&x, &y);
+        let expected_product = builder.constant_bigint(&(&x_value * &y_value));
+        builder.connect_bigint(&product, &expected_product);
+
+        // Subtracting a value from itself may give a negative zero, which must still equal zero.
+        let zero = builder.sub_bigint(&x, &x);
+        let expected_zero = builder.zero_bigint();
+        builder.connect_bigint(&zero, &expected_zero);
+
+        pw.set_bigint_target(&x, &x_value);
+        pw.set_bigint_target(&y, &y_value);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_bigint_div_rem() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let mut rng = rand::thread_rng();
+
+        let x_value = BigInt::from_i128(rng.gen()).unwrap();
+        let y_value = BigInt::from_i64(rng.gen()).unwrap();
+        // `num` also rounds the quotient towards zero.
+        let (expected_div_value, expected_rem_value) = x_value.div_rem(&y_value);
+
+        let config = CircuitConfig::standard_recursion_config();
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.constant_bigint(&x_value);
+        let y = builder.constant_bigint(&y_value);
+        let (div, rem) = builder.div_rem_bigint(&x, &y);
+
+        let expected_div = builder.constant_bigint(&expected_div_value);
+        let expected_rem = builder.constant_bigint(&expected_rem_value);
+        builder.connect_bigint(&div, &expected_div);
+        builder.connect_bigint(&rem, &expected_rem);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_bigint_cmp() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let mut rng = rand::thread_rng();
+
+        let x_value = BigInt::from_i128(rng.gen()).unwrap();
+        let y_value = BigInt::from_i128(rng.gen()).unwrap();
+
+        let config = CircuitConfig::standard_recursion_config();
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.constant_bigint(&x_value);
+        let y = builder.constant_bigint(&y_value);
+        let cmp = builder.cmp_bigint(&x, &y);
+        let expected_cmp = builder.constant_bool(x_value <= y_value);
+        builder.connect(cmp.target, expected_cmp.target);
+
+        // Zero and negative zero compare as equal.
+        let zero = builder.sub_bigint(&x, &x);
+        let neg_zero = builder.neg_bigint(&zero);
+        let cmp_zero = builder.cmp_bigint(&zero, &neg_zero);
+        builder.assert_one(cmp_zero.target);
+        let cmp_neg_zero = builder.cmp_bigint(&neg_zero, &zero);
+        builder.assert_one(cmp_neg_zero.target);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        verify(proof, &data.verifier_only, &data.common)
+    }
+}
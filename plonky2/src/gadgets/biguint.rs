@@ -1,9 +1,10 @@
 use std::marker::PhantomData;
 
-use num::{BigUint, Integer, Zero};
+use num::{BigUint, Integer, One, Zero};
 use plonky2_field::extension_field::Extendable;
 
 use crate::gadgets::arithmetic_u32::U32Target;
+use crate::gates::add_many_u32::MAX_NUM_ADDENDS;
 use crate::hash::hash_types::RichField;
 use crate::iop::generator::{GeneratedValues, SimpleGenerator};
 use crate::iop::target::{BoolTarget, Target};
@@ -110,7 +111,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         }
     }
 
-    // Subtract two `BigUintTarget`s. We assume that the first is larger than the second.
+    // Subtract two `BigUintTarget`s, asserting that the first is at least the second.
     pub fn sub_biguint(&mut self, a: &BigUintTarget, b: &BigUintTarget) -> BigUintTarget {
         let (a, b) = self.pad_biguints(a, b);
         let num_limbs = a.limbs.len();
@@ -123,7 +124,8 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             result_limbs.push(result);
             borrow = new_borrow;
         }
-        // Borrow should be zero here.
+        // A final borrow would mean that `a < b`, and that the result had wrapped around.
+        self.assert_zero(borrow.0);
 
         BigUintTarget {
             limbs: result_limbs,
@@ -131,52 +133,93 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     }
 
     pub fn mul_biguint(&mut self, a: &BigUintTarget, b: &BigUintTarget) -> BigUintTarget {
-        let total_limbs = a.limbs.len() + b.limbs.len();
-
-        let mut to_add = vec![vec![]; total_limbs];
-        for i in 0..a.limbs.len() {
-            for j in 0..b.limbs.len() {
-                let (product, carry) = self.mul_u32(a.limbs[i], b.limbs[j]);
-                to_add[i + j].push(product);
-                to_add[i + j + 1].push(carry);
-            }
-        }
+        let zero = self.zero_biguint();
+        self.mul_add_biguint(a, b, &zero)
+    }
 
-        let mut combined_limbs = vec![];
-        let mut carry = self.zero_u32();
-        for summands in &mut to_add {
-            let (new_result, new_carry) = self.add_u32s_with_carry(summands, carry);
-            combined_limbs.push(new_result);
-            carry = new_carry;
-        }
-        combined_limbs.push(carry);
+    pub fn mul_biguint_by_bool(&mut self, a: &BigUintTarget, b: BoolTarget) -> BigUintTarget {
+        let t = b.target;
 
         BigUintTarget {
-            limbs: combined_limbs,
+            limbs: a
+                .limbs
+                .iter()
+                .map(|&l| U32Target(self.mul(l.0, t)))
+                .collect(),
         }
     }
 
-    pub fn mul_biguint_by_bool(&mut self, a: &BigUintTarget, b: BoolTarget) -> BigUintTarget {
-        let t = b.target;
+    /// Returns `a` if `b` is true, and `c` otherwise.
+    pub fn select_biguint(
+        &mut self,
+        b: BoolTarget,
+        a: &BigUintTarget,
+        c: &BigUintTarget,
+    ) -> BigUintTarget {
+        let (a, c) = self.pad_biguints(a, c);
 
         BigUintTarget {
             limbs: a
                 .limbs
                 .iter()
-                .map(|&l| U32Target(self.mul(l.0, t)))
+                .zip(&c.limbs)
+                .map(|(&a_limb, &c_limb)| U32Target(self.select(b, a_limb.0, c_limb.0)))
                 .collect(),
         }
     }
 
-    // Returns x * y + z. This is no more efficient than mul-then-add; it's purely for convenience (only need to call one CircuitBuilder function).
+    // Returns x * y + z. The limbs of z are summed along with the partial products, so this is
+    // cheaper than mul-then-add.
     pub fn mul_add_biguint(
         &mut self,
         x: &BigUintTarget,
         y: &BigUintTarget,
         z: &BigUintTarget,
     ) -> BigUintTarget {
-        let prod = self.mul_biguint(x, y);
-        self.add_biguint(&prod, z)
+        let total_limbs = (x.limbs.len() + y.limbs.len()).max(z.limbs.len());
+
+        let mut to_add = vec![vec![]; total_limbs];
+        for i in 0..x.limbs.len() {
+            for j in 0..y.limbs.len() {
+                let (product, carry) = self.mul_u32(x.limbs[i], y.limbs[j]);
+                to_add[i + j].push(product);
+                to_add[i + j + 1].push(carry);
+            }
+        }
+        for (i, &limb) in z.limbs.iter().enumerate() {
+            to_add[i].push(limb);
+        }
+
+        let mut combined_limbs = vec![];
+        let mut carry = self.zero_u32();
+        let mut i = 0;
+        while i < to_add.len() {
+            // A `U32AddManyGate` has a limited number of addends, so large columns are summed in
+            // chunks, with the chunks' carries deferred to the next column.
+            while to_add[i].len() > MAX_NUM_ADDENDS {
+                let chunk: Vec<_> = to_add[i].drain(..MAX_NUM_ADDENDS).collect();
+                let (chunk_sum, chunk_carry) = self.add_many_u32(&chunk);
+                to_add[i].push(chunk_sum);
+                if i + 1 == to_add.len() {
+                    to_add.push(vec![]);
+                }
+                to_add[i + 1].push(chunk_carry);
+            }
+
+            let (new_result, new_carry) = if to_add[i].is_empty() {
+                (carry, self.zero_u32())
+            } else {
+                self.add_u32s_with_carry(&to_add[i], carry)
+            };
+            combined_limbs.push(new_result);
+            carry = new_carry;
+            i += 1;
+        }
+        combined_limbs.push(carry);
+
+        BigUintTarget {
+            limbs: combined_limbs,
+        }
     }
 
     pub fn div_rem_biguint(
@@ -202,12 +245,15 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             _phantom: PhantomData,
         });
 
-        let div_b = self.mul_biguint(&div, b);
-        let div_b_plus_rem = self.add_biguint(&div_b, &rem);
+        self.range_check_u32(div.limbs.clone());
+        self.range_check_u32(rem.limbs.clone());
+
+        let div_b_plus_rem = self.mul_add_biguint(&div, b, &rem);
         self.connect_biguint(a, &div_b_plus_rem);
 
-        let cmp_rem_b = self.cmp_biguint(&rem, b);
-        self.assert_one(cmp_rem_b.target);
+        // Check that `rem < b`, so that the quotient and remainder are unique.
+        let cmp_b_rem = self.cmp_biguint(b, &rem);
+        self.assert_zero(cmp_b_rem.target);
 
         (div, rem)
     }
@@ -221,6 +267,92 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         let (_div, rem) = self.div_rem_biguint(a, b);
         rem
     }
+
+    /// Returns `a b mod m`.
+    ///
+    /// The prover supplies the quotient `q` and remainder `r`, and the circuit only checks that
+    /// `a b = q m + r` with `r < m`. For `n`-limb operands this costs two schoolbook products, i.e.
+    /// `2 n^2` `mul_u32` operations, which is about 2,700 `U32ArithmeticGate`s for 2048-bit operands
+    /// with three operations per gate, plus range checks on `q` and `r`. Barrett and Montgomery
+    /// reduction would cost more, since they compute `q` rather than check it: besides `a b` and
+    /// `q m`, Barrett needs a product with the precomputed reciprocal of `m`, and Montgomery needs
+    /// `(a b mod R) m'` and conversions in and out of Montgomery form, i.e. at least `2.5 n^2`
+    /// operations either way.
+    pub fn mul_mod_biguint(
+        &mut self,
+        a: &BigUintTarget,
+        b: &BigUintTarget,
+        m: &BigUintTarget,
+    ) -> BigUintTarget {
+        let product = self.mul_biguint(a, b);
+        self.rem_biguint(&product, m)
+    }
+
+    /// Returns `base^exponent mod modulus`, by square-and-multiply. If the exponent is a constant,
+    /// as RSA public exponents usually are, this defers to `mod_exp_biguint_const_exponent`.
+    pub fn mod_exp_biguint(
+        &mut self,
+        base: &BigUintTarget,
+        exponent: &BigUintTarget,
+        modulus: &BigUintTarget,
+    ) -> BigUintTarget {
+        let exponent_limbs: Option<Vec<u32>> = exponent
+            .limbs
+            .iter()
+            .map(|l| {
+                self.target_as_constant(l.0)
+                    .map(|c| c.to_canonical_u64() as u32)
+            })
+            .collect();
+        if let Some(exponent_limbs) = exponent_limbs {
+            let exponent = BigUint::new(exponent_limbs);
+            return self.mod_exp_biguint_const_exponent(base, &exponent, modulus);
+        }
+
+        let base = self.rem_biguint(base, modulus);
+        let exponent_bits: Vec<_> = exponent
+            .limbs
+            .iter()
+            .flat_map(|l| self.split_le(l.0, 32))
+            .collect();
+
+        let mut result = self.constant_biguint(&BigUint::one());
+        for &bit in exponent_bits.iter().rev() {
+            result = self.mul_mod_biguint(&result, &result, modulus);
+            let result_times_base = self.mul_mod_biguint(&result, &base, modulus);
+            result = self.select_biguint(bit, &result_times_base, &result);
+        }
+
+        result
+    }
+
+    /// Returns `base^exponent mod modulus` for a fixed exponent. Only the squarings and the
+    /// multiplications for the exponent's nonzero bits are performed, so a small exponent such as
+    /// 65537 costs 17 modular multiplications.
+    pub fn mod_exp_biguint_const_exponent(
+        &mut self,
+        base: &BigUintTarget,
+        exponent: &BigUint,
+        modulus: &BigUintTarget,
+    ) -> BigUintTarget {
+        let base = self.rem_biguint(base, modulus);
+        if exponent.is_zero() {
+            let one = self.constant_biguint(&BigUint::one());
+            return self.rem_biguint(&one, modulus);
+        }
+
+        // The most significant bit is always set, so we start from `base` rather than squaring 1.
+        let exponent_bits = exponent.to_radix_le(2);
+        let mut result = base.clone();
+        for &bit in exponent_bits.iter().rev().skip(1) {
+            result = self.mul_mod_biguint(&result, &result, modulus);
+            if bit == 1 {
+                result = self.mul_mod_biguint(&result, &base, modulus);
+            }
+        }
+
+        result
+    }
 }
 
 #[derive(Debug)]
@@ -327,6 +459,25 @@ mod tests {
         verify(proof, &data.verifier_only, &data.common)
     }
 
+    #[test]
+    #[should_panic]
+    fn test_biguint_sub_underflow() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.constant_biguint(&BigUint::from_u64(1).unwrap());
+        let y = builder.constant_biguint(&BigUint::from_u64(2).unwrap());
+        builder.sub_biguint(&x, &y);
+
+        let data = builder.build::<C>();
+        data.prove(pw).unwrap();
+    }
+
     #[test]
     fn test_biguint_mul() -> Result<()> {
         const D: usize = 2;
@@ -415,4 +566,66 @@ mod tests {
         let proof = data.prove(pw).unwrap();
         verify(proof, &data.verifier_only, &data.common)
     }
+
+    #[test]
+    fn test_biguint_mul_many_limbs() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let mut rng = rand::thread_rng();
+
+        // With 16 limbs, some columns of the product have more summands than a `U32AddManyGate`
+        // can take.
+        let x_value = BigUint::new((0..16).map(|_| rng.gen()).collect());
+        let y_value = BigUint::new((0..16).map(|_| rng.gen()).collect());
+        let expected_z_value = &x_value * &y_value;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.constant_biguint(&x_value);
+        let y = builder.constant_biguint(&y_value);
+        let z = builder.mul_biguint(&x, &y);
+        let expected_z = builder.constant_biguint(&expected_z_value);
+        builder.connect_biguint(&z, &expected_z);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_biguint_mod_exp() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let mut rng = rand::thread_rng();
+
+        let base_value = BigUint::from_u128(rng.gen()).unwrap();
+        let modulus_value = BigUint::from_u128(rng.gen()).unwrap();
+        let exponent_value = BigUint::from_u32(rng.gen()).unwrap();
+        let expected_value = base_value.modpow(&exponent_value, &modulus_value);
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let base = builder.constant_biguint(&base_value);
+        let modulus = builder.constant_biguint(&modulus_value);
+        let expected = builder.constant_biguint(&expected_value);
+
+        let exponent = builder.add_virtual_biguint_target(1);
+        pw.set_biguint_target(&exponent, &exponent_value);
+        let result = builder.mod_exp_biguint(&base, &exponent, &modulus);
+        builder.connect_biguint(&result, &expected);
+
+        let const_exponent = builder.constant_biguint(&exponent_value);
+        let const_result = builder.mod_exp_biguint(&base, &const_exponent, &modulus);
+        builder.connect_biguint(&const_result, &expected);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        verify(proof, &data.verifier_only, &data.common)
+    }
 }
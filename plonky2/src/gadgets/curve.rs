@@ -1,7 +1,7 @@
 use plonky2_field::extension_field::Extendable;
-use plonky2_field::field_types::Field;
 
-use crate::curve::curve_types::{AffinePoint, Curve, CurveScalar};
+use crate::curve::curve_types::{AffinePoint, Curve};
+use crate::curve::hash_to_curve::hash_to_curve;
 use crate::gadgets::nonnative::NonNativeTarget;
 use crate::hash::hash_types::RichField;
 use crate::plonk::circuit_builder::CircuitBuilder;
@@ -124,7 +124,8 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     ) -> AffinePointTarget<C> {
         let bits = self.split_nonnative_to_bits(n);
 
-        let rando = (CurveScalar(C::ScalarField::rand()) * C::GENERATOR_PROJECTIVE).to_affine();
+        // A fixed offset with no known discrete log, so that building a circuit is deterministic.
+        let rando = hash_to_curve::<C>(b"plonky2 curve_scalar_mul offset");
         let randot = self.constant_affine_point(rando);
         // Result starts at `rando`, which is later subtracted, because we don't support arithmetic with the zero point.
         let mut result = self.add_virtual_affine_point_target();
@@ -0,0 +1,143 @@
+use plonky2_field::extension_field::Extendable;
+
+use crate::curve::curve_types::Curve;
+use crate::gadgets::curve::AffinePointTarget;
+use crate::gadgets::nonnative::NonNativeTarget;
+use crate::hash::hash_types::RichField;
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Computes `sum_i scalars[i] points[i]` with Straus' method: each point gets a table of its
+    /// small multiples, and all points share the same doublings, so the cost of doubling is paid
+    /// once rather than once per point.
+    ///
+    /// The additions in `curve_windowed_linear_combination` use incomplete affine formulas, which
+    /// can't add a point to itself or to its negation. Tables and the running sum start from fixed
+    /// offsets, so zero scalars and repeated points are fine, but if the points are chosen such
+    /// that a running sum collides with a table entry, e.g. if a point is the negation of an
+    /// offset, proving fails. Such collisions can't be used to prove a wrong result, since each
+    /// addition's slope comes from `inv_nonnative`, which range-checks both the inverse and the
+    /// quotient in `x inv = q p + 1`, so a difference of zero has no valid inverse.
+    pub fn curve_msm<C: Curve>(
+        &mut self,
+        points: &[AffinePointTarget<C>],
+        scalars: &[NonNativeTarget<C::ScalarField>],
+    ) -> AffinePointTarget<C> {
+        assert_eq!(points.len(), scalars.len());
+        assert!(!points.is_empty(), "MSM requires at least one point");
+
+        let terms: Vec<_> = points
+            .iter()
+            .zip(scalars)
+            .map(|(p, n)| (p.clone(), self.split_nonnative_to_4_bit_limbs(n)))
+            .collect();
+        self.curve_windowed_linear_combination(&terms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use plonky2_field::field_types::Field;
+    use plonky2_field::secp256k1_scalar::Secp256K1Scalar;
+
+    use crate::curve::curve_msm::msm_parallel;
+    use crate::curve::curve_types::{Curve, CurveScalar};
+    use crate::curve::secp256k1::Secp256K1;
+    use crate::iop::witness::{PartialWitness, Witness};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    #[test]
+    #[ignore]
+    fn test_curve_msm() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        const NUM_POINTS: usize = 3;
+
+        let config = CircuitConfig::standard_ecc_config();
+
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let points: Vec<_> = (0..NUM_POINTS)
+            .map(|_| CurveScalar(Secp256K1Scalar::rand()) * Secp256K1::GENERATOR_PROJECTIVE)
+            .collect();
+        let scalars = Secp256K1Scalar::rand_vec(NUM_POINTS);
+        let expected = msm_parallel(&scalars, &points, 5).to_affine();
+
+        let point_targets: Vec<_> = points
+            .iter()
+            .map(|p| builder.constant_affine_point(p.to_affine()))
+            .collect();
+        let scalar_targets: Vec<_> = scalars
+            .iter()
+            .map(|&n| builder.constant_nonnative(n))
+            .collect();
+        let actual = builder.curve_msm(&point_targets, &scalar_targets);
+        builder.curve_assert_valid(&actual);
+
+        let expected_target = builder.constant_affine_point(expected);
+        builder.connect_affine_point(&expected_target, &actual);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    #[ignore]
+    fn test_curve_msm_witness() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        const NUM_POINTS: usize = 3;
+
+        let config = CircuitConfig::standard_ecc_config();
+
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let points: Vec<_> = (0..NUM_POINTS)
+            .map(|_| CurveScalar(Secp256K1Scalar::rand()) * Secp256K1::GENERATOR_PROJECTIVE)
+            .collect();
+        let mut scalars = Secp256K1Scalar::rand_vec(NUM_POINTS);
+        scalars[1] = Secp256K1Scalar::ZERO;
+        let expected = msm_parallel(&scalars, &points, 5).to_affine();
+        let expected_single = msm_parallel(&scalars[..1], &points[..1], 5).to_affine();
+
+        let point_targets: Vec<_> = (0..NUM_POINTS)
+            .map(|_| builder.add_virtual_affine_point_target::<Secp256K1>())
+            .collect();
+        let scalar_targets: Vec<_> = (0..NUM_POINTS)
+            .map(|_| builder.add_virtual_nonnative_target::<Secp256K1Scalar>())
+            .collect();
+        let actual = builder.curve_msm(&point_targets, &scalar_targets);
+        let actual_single = builder.curve_msm(&point_targets[..1], &scalar_targets[..1]);
+
+        let expected_target = builder.constant_affine_point(expected);
+        builder.connect_affine_point(&expected_target, &actual);
+        let expected_single_target = builder.constant_affine_point(expected_single);
+        builder.connect_affine_point(&expected_single_target, &actual_single);
+
+        for (target, point) in point_targets.iter().zip(&points) {
+            let point = point.to_affine();
+            pw.set_nonnative_target(&target.x, point.x);
+            pw.set_nonnative_target(&target.y, point.y);
+        }
+        for (target, &scalar) in scalar_targets.iter().zip(&scalars) {
+            pw.set_nonnative_target(target, scalar);
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+}
@@ -0,0 +1,410 @@
+use std::marker::PhantomData;
+
+use plonky2_field::extension_field::Extendable;
+use plonky2_field::field_types::{Field, PrimeField};
+
+use crate::curve::curve_types::{Curve, ProjectivePoint};
+use crate::gadgets::curve::AffinePointTarget;
+use crate::gadgets::nonnative::NonNativeTarget;
+use crate::hash::hash_types::RichField;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator};
+use crate::iop::target::{BoolTarget, Target};
+use crate::iop::witness::{PartitionWitness, Witness};
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+/// A Target representing a point on the curve `C` in homogeneous projective coordinates, so that
+/// `(x : y : z)` represents `(x / z, y / z)`, and `(0 : 1 : 0)` represents the point at infinity.
+/// Unlike `AffinePointTarget`, arithmetic on these points is complete, i.e. it has no exceptional
+/// cases, so it is safe to use with adversarial inputs.
+#[derive(Clone, Debug)]
+pub struct ProjectivePointTarget<C: Curve> {
+    pub x: NonNativeTarget<C::BaseField>,
+    pub y: NonNativeTarget<C::BaseField>,
+    pub z: NonNativeTarget<C::BaseField>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    pub fn constant_projective_point<C: Curve>(
+        &mut self,
+        point: ProjectivePoint<C>,
+    ) -> ProjectivePointTarget<C> {
+        ProjectivePointTarget {
+            x: self.constant_nonnative(point.x),
+            y: self.constant_nonnative(point.y),
+            z: self.constant_nonnative(point.z),
+        }
+    }
+
+    pub fn projective_point_zero<C: Curve>(&mut self) -> ProjectivePointTarget<C> {
+        self.constant_projective_point(ProjectivePoint::ZERO)
+    }
+
+    /// Asserts that `p` and `q` represent the same point, i.e. that their coordinates are
+    /// proportional. Both points must be valid, since `(0 : 0 : 0)` is proportional to every point.
+    pub fn connect_projective_point<C: Curve>(
+        &mut self,
+        p: &ProjectivePointTarget<C>,
+        q: &ProjectivePointTarget<C>,
+    ) {
+        let p_x_q_z = self.mul_nonnative(&p.x, &q.z);
+        let q_x_p_z = self.mul_nonnative(&q.x, &p.z);
+        self.connect_nonnative(&p_x_q_z, &q_x_p_z);
+
+        let p_y_q_z = self.mul_nonnative(&p.y, &q.z);
+        let q_y_p_z = self.mul_nonnative(&q.y, &p.z);
+        self.connect_nonnative(&p_y_q_z, &q_y_p_z);
+
+        // The checks above are trivial if both points have `z = 0`, so we also compare `x / y`.
+        let p_x_q_y = self.mul_nonnative(&p.x, &q.y);
+        let q_x_p_y = self.mul_nonnative(&q.x, &p.y);
+        self.connect_nonnative(&p_x_q_y, &q_x_p_y);
+    }
+
+    /// Asserts that `p` satisfies the projective curve equation `y^2 z = x^3 + a x z^2 + b z^3`,
+    /// and that it isn't `(0 : 0 : 0)`, which isn't a point.
+    pub fn curve_projective_assert_valid<C: Curve>(&mut self, p: &ProjectivePointTarget<C>) {
+        let a = self.constant_nonnative(C::A);
+        let b = self.constant_nonnative(C::B);
+
+        let y_squared = self.mul_nonnative(&p.y, &p.y);
+        let lhs = self.mul_nonnative(&y_squared, &p.z);
+
+        let x_squared = self.mul_nonnative(&p.x, &p.x);
+        let x_cubed = self.mul_nonnative(&x_squared, &p.x);
+        let z_squared = self.mul_nonnative(&p.z, &p.z);
+        let z_cubed = self.mul_nonnative(&z_squared, &p.z);
+        let x_z_squared = self.mul_nonnative(&p.x, &z_squared);
+        let a_x_z_squared = self.mul_nonnative(&a, &x_z_squared);
+        let b_z_cubed = self.mul_nonnative(&b, &z_cubed);
+        let rhs = self.add_many_nonnative(&[x_cubed, a_x_z_squared, b_z_cubed]);
+
+        self.connect_nonnative(&lhs, &rhs);
+
+        // If `z = 0`, the equation forces `x = 0`, so it only remains to check that `y != 0`. We do
+        // so by asserting that `z + y` is invertible if `z = 0`, and that `z` is invertible otherwise.
+        let z_is_zero = self.add_virtual_bool_target_safe();
+        self.add_simple_generator(NonNativeIsZeroGenerator::<F, D, C::BaseField> {
+            x: p.z.clone(),
+            is_zero: z_is_zero,
+            _phantom: PhantomData,
+        });
+        let z_if_zero = self.mul_nonnative_by_bool(&p.z, z_is_zero);
+        let zero = self.zero_nonnative();
+        self.connect_nonnative(&z_if_zero, &zero);
+        let y_if_z_zero = self.mul_nonnative_by_bool(&p.y, z_is_zero);
+        let z_or_y = self.add_nonnative(&p.z, &y_if_z_zero);
+        self.inv_nonnative(&z_or_y);
+    }
+
+    pub fn affine_to_projective_point<C: Curve>(
+        &mut self,
+        p: &AffinePointTarget<C>,
+    ) -> ProjectivePointTarget<C> {
+        ProjectivePointTarget {
+            x: p.x.clone(),
+            y: p.y.clone(),
+            z: self.constant_nonnative(C::BaseField::ONE),
+        }
+    }
+
+    /// Converts `p` to affine coordinates. Since `AffinePointTarget` can't represent the point at
+    /// infinity, this also returns whether `p` is the point at infinity, in which case the affine
+    /// point is meaningless.
+    pub fn projective_to_affine_point<C: Curve>(
+        &mut self,
+        p: &ProjectivePointTarget<C>,
+    ) -> (AffinePointTarget<C>, BoolTarget) {
+        let is_zero = self.add_virtual_bool_target_safe();
+        self.add_simple_generator(NonNativeIsZeroGenerator::<F, D, C::BaseField> {
+            x: p.z.clone(),
+            is_zero,
+            _phantom: PhantomData,
+        });
+
+        // If `is_zero` is set, `z` must be zero; otherwise `z` must be invertible.
+        let z_if_zero = self.mul_nonnative_by_bool(&p.z, is_zero);
+        let zero = self.zero_nonnative();
+        self.connect_nonnative(&z_if_zero, &zero);
+        let is_zero_nonnative = self.bool_to_nonnative(&is_zero);
+        let z_or_one = self.add_nonnative(&p.z, &is_zero_nonnative);
+        let z_inv = self.inv_nonnative(&z_or_one);
+
+        let x = self.mul_nonnative(&p.x, &z_inv);
+        let y = self.mul_nonnative(&p.y, &z_inv);
+        (AffinePointTarget { x, y }, is_zero)
+    }
+
+    pub fn curve_projective_neg<C: Curve>(
+        &mut self,
+        p: &ProjectivePointTarget<C>,
+    ) -> ProjectivePointTarget<C> {
+        ProjectivePointTarget {
+            x: p.x.clone(),
+            y: self.neg_nonnative(&p.y),
+            z: p.z.clone(),
+        }
+    }
+
+    /// Adds two points with the complete formulas of Renes, Costello and Batina (Algorithm 7 of
+    /// https://eprint.iacr.org/2015/1060.pdf). These require `a = 0`, and have no exceptional cases
+    /// as long as the curve has no points of order two, which holds for odd-order curves such as
+    /// secp256k1, BN254 and BLS12-381.
+    pub fn curve_projective_add<C: Curve>(
+        &mut self,
+        p1: &ProjectivePointTarget<C>,
+        p2: &ProjectivePointTarget<C>,
+    ) -> ProjectivePointTarget<C> {
+        assert!(
+            C::A.is_zero(),
+            "Complete addition is only implemented for a = 0"
+        );
+        let ProjectivePointTarget {
+            x: x1,
+            y: y1,
+            z: z1,
+        } = p1;
+        let ProjectivePointTarget {
+            x: x2,
+            y: y2,
+            z: z2,
+        } = p2;
+        let b3 = self.constant_nonnative(C::B.triple());
+
+        let t0 = self.mul_nonnative(x1, x2);
+        let t1 = self.mul_nonnative(y1, y2);
+        let t2 = self.mul_nonnative(z1, z2);
+
+        // t3 = (x1 + y1) (x2 + y2) - x1 x2 - y1 y2
+        let x1_plus_y1 = self.add_nonnative(x1, y1);
+        let x2_plus_y2 = self.add_nonnative(x2, y2);
+        let t3 = self.mul_nonnative(&x1_plus_y1, &x2_plus_y2);
+        let t0_plus_t1 = self.add_nonnative(&t0, &t1);
+        let t3 = self.sub_nonnative(&t3, &t0_plus_t1);
+
+        // t4 = (y1 + z1) (y2 + z2) - y1 y2 - z1 z2
+        let y1_plus_z1 = self.add_nonnative(y1, z1);
+        let y2_plus_z2 = self.add_nonnative(y2, z2);
+        let t4 = self.mul_nonnative(&y1_plus_z1, &y2_plus_z2);
+        let t1_plus_t2 = self.add_nonnative(&t1, &t2);
+        let t4 = self.sub_nonnative(&t4, &t1_plus_t2);
+
+        // y3 = (x1 + z1) (x2 + z2) - x1 x2 - z1 z2
+        let x1_plus_z1 = self.add_nonnative(x1, z1);
+        let x2_plus_z2 = self.add_nonnative(x2, z2);
+        let y3 = self.mul_nonnative(&x1_plus_z1, &x2_plus_z2);
+        let t0_plus_t2 = self.add_nonnative(&t0, &t2);
+        let y3 = self.sub_nonnative(&y3, &t0_plus_t2);
+
+        let t0 = self.add_many_nonnative(&[t0.clone(), t0.clone(), t0]);
+        let t2 = self.mul_nonnative(&b3, &t2);
+        let z3 = self.add_nonnative(&t1, &t2);
+        let t1 = self.sub_nonnative(&t1, &t2);
+        let y3 = self.mul_nonnative(&b3, &y3);
+
+        // x3 = t3 t1 - t4 y3
+        let t4_y3 = self.mul_nonnative(&t4, &y3);
+        let t3_t1 = self.mul_nonnative(&t3, &t1);
+        let x3 = self.sub_nonnative(&t3_t1, &t4_y3);
+
+        // y3 = t1 z3 + y3 t0
+        let y3_t0 = self.mul_nonnative(&y3, &t0);
+        let t1_z3 = self.mul_nonnative(&t1, &z3);
+        let y3 = self.add_nonnative(&t1_z3, &y3_t0);
+
+        // z3 = z3 t4 + t0 t3
+        let t0_t3 = self.mul_nonnative(&t0, &t3);
+        let z3_t4 = self.mul_nonnative(&z3, &t4);
+        let z3 = self.add_nonnative(&z3_t4, &t0_t3);
+
+        ProjectivePointTarget {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    /// Doubles a point with the complete formulas of Renes, Costello and Batina (Algorithm 9 of
+    /// https://eprint.iacr.org/2015/1060.pdf), which are cheaper than `curve_projective_add`.
+    pub fn curve_projective_double<C: Curve>(
+        &mut self,
+        p: &ProjectivePointTarget<C>,
+    ) -> ProjectivePointTarget<C> {
+        assert!(
+            C::A.is_zero(),
+            "Complete doubling is only implemented for a = 0"
+        );
+        let ProjectivePointTarget { x, y, z } = p;
+        let b3 = self.constant_nonnative(C::B.triple());
+
+        let t0 = self.mul_nonnative(y, y);
+        let t0_times_8 = {
+            let t0_times_2 = self.add_nonnative(&t0, &t0);
+            let t0_times_4 = self.add_nonnative(&t0_times_2, &t0_times_2);
+            self.add_nonnative(&t0_times_4, &t0_times_4)
+        };
+        let t1 = self.mul_nonnative(y, z);
+        let t2 = self.mul_nonnative(z, z);
+        let t2 = self.mul_nonnative(&b3, &t2);
+
+        let x3 = self.mul_nonnative(&t2, &t0_times_8);
+        let y3 = self.add_nonnative(&t0, &t2);
+        let z3 = self.mul_nonnative(&t1, &t0_times_8);
+
+        // t0 = y^2 - 3 t2
+        let t2_times_3 = self.add_many_nonnative(&[t2.clone(), t2.clone(), t2]);
+        let t0 = self.sub_nonnative(&t0, &t2_times_3);
+
+        let t0_y3 = self.mul_nonnative(&t0, &y3);
+        let y3 = self.add_nonnative(&x3, &t0_y3);
+
+        let t1 = self.mul_nonnative(x, y);
+        let t0_t1 = self.mul_nonnative(&t0, &t1);
+        let x3 = self.add_nonnative(&t0_t1, &t0_t1);
+
+        ProjectivePointTarget {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct NonNativeIsZeroGenerator<F: RichField + Extendable<D>, const D: usize, FF: PrimeField> {
+    x: NonNativeTarget<FF>,
+    is_zero: BoolTarget,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize, FF: PrimeField> SimpleGenerator<F>
+    for NonNativeIsZeroGenerator<F, D, FF>
+{
+    fn dependencies(&self) -> Vec<Target> {
+        self.x.value.limbs.iter().map(|&l| l.0).collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let x = witness.get_nonnative_target(self.x.clone());
+        out_buffer.set_bool_target(self.is_zero, x.is_zero());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use plonky2_field::field_types::Field;
+    use plonky2_field::secp256k1_base::Secp256K1Base;
+    use plonky2_field::secp256k1_scalar::Secp256K1Scalar;
+
+    use crate::curve::curve_types::{Curve, CurveScalar, ProjectivePoint};
+    use crate::curve::secp256k1::Secp256K1;
+    use crate::iop::witness::PartialWitness;
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    #[test]
+    fn test_curve_projective_add_exceptional_cases() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_ecc_config();
+
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let p = CurveScalar(Secp256K1Scalar::rand()) * Secp256K1::GENERATOR_PROJECTIVE;
+        let p_target = builder.constant_projective_point(p);
+        let neg_p_target = builder.curve_projective_neg(&p_target);
+        let zero_target = builder.projective_point_zero();
+        builder.curve_projective_assert_valid(&p_target);
+        builder.curve_projective_assert_valid(&zero_target);
+
+        // P + P, which incomplete addition can't handle.
+        let double_p_expected = builder.constant_projective_point(p.double());
+        let double_p = builder.curve_projective_add(&p_target, &p_target);
+        builder.curve_projective_assert_valid(&double_p);
+        builder.connect_projective_point(&double_p, &double_p_expected);
+        let double_p = builder.curve_projective_double(&p_target);
+        builder.connect_projective_point(&double_p, &double_p_expected);
+
+        // P + -P, and sums involving the point at infinity.
+        let sum = builder.curve_projective_add(&p_target, &neg_p_target);
+        builder.connect_projective_point(&sum, &zero_target);
+        let sum = builder.curve_projective_add(&zero_target, &p_target);
+        builder.connect_projective_point(&sum, &p_target);
+        let sum = builder.curve_projective_add(&zero_target, &zero_target);
+        builder.connect_projective_point(&sum, &zero_target);
+        let double_zero = builder.curve_projective_double(&zero_target);
+        builder.connect_projective_point(&double_zero, &zero_target);
+
+        // Conversions to and from affine coordinates.
+        let (p_affine, p_is_zero) = builder.projective_to_affine_point(&p_target);
+        let p_affine_expected = builder.constant_affine_point(p.to_affine());
+        builder.connect_affine_point(&p_affine, &p_affine_expected);
+        builder.assert_zero(p_is_zero.target);
+        let p_projective = builder.affine_to_projective_point(&p_affine);
+        builder.connect_projective_point(&p_projective, &p_target);
+
+        let (_, zero_is_zero) = builder.projective_to_affine_point(&zero_target);
+        builder.assert_one(zero_is_zero.target);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_curve_projective_all_zero_is_not_valid() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_ecc_config();
+
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        // `(0 : 0 : 0)` satisfies the curve equation, but doesn't represent any point.
+        let all_zero = ProjectivePoint::<Secp256K1> {
+            x: Secp256K1Base::ZERO,
+            y: Secp256K1Base::ZERO,
+            z: Secp256K1Base::ZERO,
+        };
+        let all_zero_target = builder.constant_projective_point(all_zero);
+        builder.curve_projective_assert_valid(&all_zero_target);
+
+        let data = builder.build::<C>();
+        data.prove(pw).unwrap();
+    }
+
+    #[test]
+    fn test_curve_projective_add() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_ecc_config();
+
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let p = CurveScalar(Secp256K1Scalar::rand()) * Secp256K1::GENERATOR_PROJECTIVE;
+        let q = CurveScalar(Secp256K1Scalar::rand()) * Secp256K1::GENERATOR_PROJECTIVE;
+        let p_target = builder.constant_projective_point(p);
+        let q_target = builder.constant_projective_point(q);
+
+        let sum = builder.curve_projective_add(&p_target, &q_target);
+        let sum_expected = builder.constant_projective_point(p + q);
+        builder.connect_projective_point(&sum, &sum_expected);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+}
@@ -0,0 +1,204 @@
+use std::marker::PhantomData;
+
+use plonky2_field::extension_field::Extendable;
+use plonky2_field::field_types::Field;
+
+use crate::curve::curve_types::{AffinePoint, Curve, CurveScalar, ProjectivePoint};
+use crate::curve::hash_to_curve::hash_to_curve;
+use crate::gadgets::curve::AffinePointTarget;
+use crate::gadgets::nonnative::NonNativeTarget;
+use crate::hash::hash_types::RichField;
+use crate::iop::target::Target;
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+/// The number of scalar bits handled by each table lookup.
+pub const WINDOW_SIZE: usize = 4;
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Returns the table `[offset, offset + p, ..., offset + (2^WINDOW_SIZE - 1) p]`. Starting from a
+    /// nonzero `offset` lets us avoid the zero point, which our incomplete arithmetic doesn't support.
+    pub fn precompute_window<C: Curve>(
+        &mut self,
+        p: &AffinePointTarget<C>,
+        offset: AffinePoint<C>,
+    ) -> Vec<AffinePointTarget<C>> {
+        let mut multiples = vec![self.constant_affine_point(offset)];
+        for i in 1..1 << WINDOW_SIZE {
+            let next = self.curve_add(p, &multiples[i - 1]);
+            multiples.push(next);
+        }
+        multiples
+    }
+
+    /// Returns `v[access_index]`, using one random access per limb of each coordinate.
+    /// Note: `access_index` is not range-checked.
+    pub fn random_access_curve_points<C: Curve>(
+        &mut self,
+        access_index: Target,
+        v: Vec<AffinePointTarget<C>>,
+    ) -> AffinePointTarget<C> {
+        let xs = v.iter().map(|p| p.x.clone()).collect();
+        let ys = v.iter().map(|p| p.y.clone()).collect();
+        let x = self.random_access_nonnative(access_index, xs);
+        let y = self.random_access_nonnative(access_index, ys);
+
+        AffinePointTarget { x, y }
+    }
+
+    fn random_access_nonnative<FF: Field>(
+        &mut self,
+        access_index: Target,
+        v: Vec<NonNativeTarget<FF>>,
+    ) -> NonNativeTarget<FF> {
+        let num_limbs = v.iter().map(|x| x.value.num_limbs()).max().unwrap();
+        let zero = self.zero_u32();
+        // The selected value equals one of the entries, so it needs no range check of its own.
+        let selected = NonNativeTarget {
+            value: self.add_virtual_biguint_target(num_limbs),
+            max_value: v.iter().map(|x| x.max_value.clone()).max().unwrap(),
+            _phantom: PhantomData,
+        };
+        for i in 0..num_limbs {
+            // Constants may have fewer limbs than the other entries, so we pad them with zeros.
+            let limbs = v
+                .iter()
+                .map(|x| {
+                    if i < x.value.num_limbs() {
+                        x.value.get_limb(i).0
+                    } else {
+                        zero.0
+                    }
+                })
+                .collect();
+            self.random_access(access_index, selected.value.get_limb(i).0, limbs);
+        }
+        selected
+    }
+
+    /// Splits a nonnative field element into little-endian `WINDOW_SIZE`-bit limbs.
+    pub fn split_nonnative_to_4_bit_limbs<FF: Field>(
+        &mut self,
+        x: &NonNativeTarget<FF>,
+    ) -> Vec<Target> {
+        let mut result = Vec::with_capacity(x.value.num_limbs() * 8);
+        for i in 0..x.value.num_limbs() {
+            let limb = x.value.get_limb(i);
+            result.extend(self.split_le_base::<{ 1 << WINDOW_SIZE }>(limb.0, 8));
+        }
+        result
+    }
+
+    /// Computes `sum_i n_i p_i`, where each scalar `n_i` is given by its little-endian
+    /// `WINDOW_SIZE`-bit limbs. The doublings are shared between all terms.
+    pub(crate) fn curve_windowed_linear_combination<C: Curve>(
+        &mut self,
+        terms: &[(AffinePointTarget<C>, Vec<Target>)],
+    ) -> AffinePointTarget<C> {
+        let num_windows = terms.iter().map(|(_, limbs)| limbs.len()).max().unwrap();
+        let zero = self.zero();
+
+        // Each table starts at its own fixed offset, so that entries of different tables are (with
+        // high probability) distinct and can be added with incomplete arithmetic.
+        let offsets: Vec<AffinePoint<C>> = (0..terms.len())
+            .map(|i| hash_to_curve(format!("plonky2 windowed offset {}", i).as_bytes()))
+            .collect();
+        let tables: Vec<_> = terms
+            .iter()
+            .zip(&offsets)
+            .map(|((p, _), &offset)| self.precompute_window(p, offset))
+            .collect();
+
+        let mut result: Option<AffinePointTarget<C>> = None;
+        for j in (0..num_windows).rev() {
+            if let Some(r) = result.as_mut() {
+                for _ in 0..WINDOW_SIZE {
+                    *r = self.curve_double(r);
+                }
+            }
+            for ((_, limbs), table) in terms.iter().zip(&tables) {
+                let limb = limbs.get(j).copied().unwrap_or(zero);
+                let entry = self.random_access_curve_points(limb, table.clone());
+                result = Some(match result {
+                    Some(r) => self.curve_add(&r, &entry),
+                    None => entry,
+                });
+            }
+        }
+
+        // Each window added every offset once, so the total offset is
+        // `(sum_i offset_i) * sum_j 2^(WINDOW_SIZE j)`.
+        let offset_sum = offsets
+            .iter()
+            .fold(ProjectivePoint::ZERO, |acc, o| acc + o.to_projective());
+        let window_base = C::ScalarField::from_canonical_usize(1 << WINDOW_SIZE);
+        let multiplier = (0..num_windows).fold(C::ScalarField::ZERO, |acc, _| {
+            acc * window_base + C::ScalarField::ONE
+        });
+        let total_offset = (CurveScalar(multiplier) * offset_sum).to_affine();
+        let neg_total_offset = self.constant_affine_point(-total_offset);
+
+        self.curve_add(&result.unwrap(), &neg_total_offset)
+    }
+
+    /// Computes `n p` with a fixed-window method, which uses one table lookup and one addition per
+    /// `WINDOW_SIZE` bits of `n`, rather than one addition per bit.
+    pub fn curve_scalar_mul_windowed<C: Curve>(
+        &mut self,
+        p: &AffinePointTarget<C>,
+        n: &NonNativeTarget<C::ScalarField>,
+    ) -> AffinePointTarget<C> {
+        let limbs = self.split_nonnative_to_4_bit_limbs(n);
+        self.curve_windowed_linear_combination(&[(p.clone(), limbs)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Neg;
+
+    use anyhow::Result;
+    use plonky2_field::field_types::Field;
+    use plonky2_field::secp256k1_scalar::Secp256K1Scalar;
+
+    use crate::curve::curve_types::{Curve, CurveScalar};
+    use crate::curve::secp256k1::Secp256K1;
+    use crate::iop::witness::PartialWitness;
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    #[test]
+    #[ignore]
+    fn test_curve_mul_windowed() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_ecc_config();
+
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let g =
+            (CurveScalar(Secp256K1Scalar::rand()) * Secp256K1::GENERATOR_PROJECTIVE).to_affine();
+        let five = Secp256K1Scalar::from_canonical_usize(5);
+        let neg_five = five.neg();
+        let neg_five_scalar = CurveScalar::<Secp256K1>(neg_five);
+        let neg_five_g = (neg_five_scalar * g.to_projective()).to_affine();
+        let neg_five_g_expected = builder.constant_affine_point(neg_five_g);
+        builder.curve_assert_valid(&neg_five_g_expected);
+
+        let g_target = builder.constant_affine_point(g);
+        let neg_five_target = builder.constant_nonnative(neg_five);
+        let neg_five_g_actual = builder.curve_scalar_mul_windowed(&g_target, &neg_five_target);
+        builder.curve_assert_valid(&neg_five_g_actual);
+
+        builder.connect_affine_point(&neg_five_g_expected, &neg_five_g_actual);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+}
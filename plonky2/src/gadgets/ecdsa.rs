@@ -1,10 +1,17 @@
 use std::marker::PhantomData;
 
-use crate::curve::curve_types::Curve;
+use crate::curve::curve_types::{AffinePoint, Curve};
+use crate::curve::secp256k1::Secp256K1;
 use crate::field::extension_field::Extendable;
+use crate::field::field_types::{Field, PrimeField};
+use crate::field::secp256k1_base::Secp256K1Base;
+use crate::field::secp256k1_scalar::Secp256K1Scalar;
 use crate::gadgets::curve::AffinePointTarget;
 use crate::gadgets::nonnative::NonNativeTarget;
 use crate::hash::hash_types::RichField;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator};
+use crate::iop::target::{BoolTarget, Target};
+use crate::iop::witness::{PartitionWitness, Witness};
 use crate::plonk::circuit_builder::CircuitBuilder;
 
 #[derive(Clone, Debug)]
@@ -39,12 +46,111 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         let point2 = self.curve_scalar_mul(&pk.0, &u2);
         let point = self.curve_add(&point1, &point2);
 
+        // Reduce `x` in the base field before reinterpreting it as a scalar, which `connect_nonnative`
+        // then reduces modulo the group order.
+        let point_x = self.reduce_nonnative_if_needed(&point.x);
         let x = NonNativeTarget::<C::ScalarField> {
-            value: point.x.value,
+            value: point_x.value,
+            max_value: point_x.max_value,
             _phantom: PhantomData,
         };
         self.connect_nonnative(&r, &x);
     }
+
+    /// Recovers the secp256k1 public key which signed `msg`, like Ethereum's `ecrecover`, given the
+    /// recovery ID `v`, i.e. the parity of the y-coordinate of `k G`. The signature must be low-s.
+    /// Returns the public key along with its 20-byte Ethereum address.
+    pub fn ecrecover(
+        &mut self,
+        msg: NonNativeTarget<Secp256K1Scalar>,
+        sig: ECDSASignatureTarget<Secp256K1>,
+        v: BoolTarget,
+    ) -> (ECDSAPublicKeyTarget<Secp256K1>, Vec<Target>) {
+        let ECDSASignatureTarget { r, s } = sig;
+        let one = self.one();
+
+        // Check that `s <= (n - 1) / 2`, so that signatures aren't malleable.
+        let half_order = self.constant_biguint(&((Secp256K1Scalar::order() - 1u32) / 2u32));
+        let s_is_low = self.cmp_biguint(&s.value, &half_order);
+        self.connect(s_is_low.target, one);
+        // Neither `r` nor `s` has an inverse if it is zero.
+        let r_inv = self.inv_nonnative(&r);
+        self.inv_nonnative(&s);
+
+        // Recover `R = k G`, whose x-coordinate is `r`.
+        let rr_x = NonNativeTarget::<Secp256K1Base> {
+            value: r.value.clone(),
+            max_value: r.max_value.clone(),
+            _phantom: PhantomData,
+        };
+        let rr_y = self.add_virtual_nonnative_target::<Secp256K1Base>();
+        self.add_simple_generator(RecoveryPointGenerator::<F, D> {
+            x: rr_x.clone(),
+            v,
+            y: rr_y.clone(),
+            _phantom: PhantomData,
+        });
+        let y_bits = self.split_le(rr_y.value.get_limb(0).0, 32);
+        self.connect(y_bits[0].target, v.target);
+        let rr = AffinePointTarget { x: rr_x, y: rr_y };
+        self.curve_assert_valid(&rr);
+
+        // The public key is `r^-1 (s R - msg G)`.
+        let msg_r_inv = self.mul_nonnative(&msg, &r_inv);
+        let u1 = self.neg_nonnative(&msg_r_inv);
+        let u2 = self.mul_nonnative(&s, &r_inv);
+        let g = self.constant_affine_point(Secp256K1::GENERATOR_AFFINE);
+        let pk = self.curve_msm(&[g, rr], &[u1, u2]);
+
+        // The address is the last 20 bytes of the Keccak-256 hash of the big-endian coordinates.
+        let mut pk_bits = self.nonnative_to_be_byte_bits(&pk.x);
+        pk_bits.extend(self.nonnative_to_be_byte_bits(&pk.y));
+        let hash = self.keccak256(&pk_bits);
+        let address = hash[12 * 8..]
+            .chunks(8)
+            .map(|byte_bits| self.le_sum(byte_bits.iter()))
+            .collect();
+
+        (ECDSAPublicKeyTarget(pk), address)
+    }
+
+    /// Returns the big-endian byte encoding of `x`, with each byte given as 8 little-endian bits,
+    /// as `keccak256` expects.
+    fn nonnative_to_be_byte_bits<FF: PrimeField>(
+        &mut self,
+        x: &NonNativeTarget<FF>,
+    ) -> Vec<BoolTarget> {
+        let le_bits = self.nonnative_to_canonical_le_bits(x);
+        le_bits.chunks(8).rev().flatten().copied().collect()
+    }
+}
+
+#[derive(Debug)]
+struct RecoveryPointGenerator<F: RichField + Extendable<D>, const D: usize> {
+    x: NonNativeTarget<Secp256K1Base>,
+    v: BoolTarget,
+    y: NonNativeTarget<Secp256K1Base>,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F>
+    for RecoveryPointGenerator<F, D>
+{
+    fn dependencies(&self) -> Vec<Target> {
+        let mut deps: Vec<_> = self.x.value.limbs.iter().map(|l| l.0).collect();
+        deps.push(self.v.target);
+        deps
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let x = witness.get_nonnative_target(self.x.clone());
+        let v = witness.get_bool_target(self.v);
+        // If `x` isn't the x-coordinate of a point, no witness satisfies the constraints, so we
+        // just fill in zero.
+        let y = AffinePoint::<Secp256K1>::lift_x(x, v).map_or(Secp256K1Base::ZERO, |p| p.y);
+
+        out_buffer.set_biguint_target(self.y.value.clone(), y.to_canonical_biguint());
+    }
 }
 
 #[cfg(test)]
@@ -52,7 +158,10 @@ mod tests {
     use anyhow::Result;
 
     use crate::curve::curve_types::{Curve, CurveScalar};
-    use crate::curve::ecdsa::{sign_message, ECDSAPublicKey, ECDSASecretKey, ECDSASignature};
+    use crate::curve::ecdsa::{
+        public_key_to_address, secret_to_public, sign_message, sign_message_recoverable,
+        ECDSAPublicKey, ECDSASecretKey, ECDSASignature,
+    };
     use crate::curve::secp256k1::Secp256K1;
     use crate::field::field_types::Field;
     use crate::field::secp256k1_scalar::Secp256K1Scalar;
@@ -101,4 +210,79 @@ mod tests {
         let proof = data.prove(pw).unwrap();
         verify(proof, &data.verifier_only, &data.common)
     }
+
+    #[test]
+    #[ignore]
+    fn test_ecrecover() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        type Curve = Secp256K1;
+
+        let config = CircuitConfig::standard_ecc_config();
+
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let msg = Secp256K1Scalar::rand();
+        let sk = ECDSASecretKey::<Curve>(Secp256K1Scalar::rand());
+        let pk = secret_to_public(sk);
+        let address = public_key_to_address(pk);
+        let (ECDSASignature { r, s }, v) = sign_message_recoverable(msg, sk);
+
+        let msg_target = builder.constant_nonnative(msg);
+        let sig_target = ECDSASignatureTarget {
+            r: builder.constant_nonnative(r),
+            s: builder.constant_nonnative(s),
+        };
+        let v_target = builder.constant_bool(v);
+
+        let (pk_target, address_target) = builder.ecrecover(msg_target, sig_target, v_target);
+
+        let pk_expected = builder.constant_affine_point(pk.0);
+        builder.connect_affine_point(&pk_expected, &pk_target.0);
+        for (&byte_target, &byte) in address_target.iter().zip(&address) {
+            let byte_expected = builder.constant(F::from_canonical_u64(byte as u64));
+            builder.connect(byte_target, byte_expected);
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    #[ignore]
+    fn test_ecdsa_circuit_is_deterministic() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        type Curve = Secp256K1;
+
+        let build = || {
+            let config = CircuitConfig::standard_ecc_config();
+            let mut builder = CircuitBuilder::<F, D>::new(config);
+
+            let msg_target = builder.add_virtual_nonnative_target();
+            let pk_target =
+                ECDSAPublicKeyTarget(builder.add_virtual_affine_point_target::<Curve>());
+            let sig_target = ECDSASignatureTarget {
+                r: builder.add_virtual_nonnative_target(),
+                s: builder.add_virtual_nonnative_target(),
+            };
+            builder.verify_message(msg_target, sig_target, pk_target);
+
+            builder.build::<C>()
+        };
+
+        let data_1 = build();
+        let data_2 = build();
+        assert_eq!(data_1.common.circuit_digest, data_2.common.circuit_digest);
+        assert_eq!(
+            data_1.verifier_only.constants_sigmas_cap,
+            data_2.verifier_only.constants_sigmas_cap
+        );
+    }
 }
@@ -0,0 +1,138 @@
+use plonky2_field::curve25519_base::Curve25519Base;
+use plonky2_field::curve25519_scalar::Curve25519Scalar;
+use plonky2_field::extension_field::Extendable;
+
+use crate::curve::ed25519::Ed25519;
+use crate::curve::twisted_edwards::TwistedEdwardsCurve;
+use crate::field::field_types::Field;
+use crate::gadgets::arithmetic_u32::U32Target;
+use crate::gadgets::biguint::BigUintTarget;
+use crate::gadgets::nonnative::NonNativeTarget;
+use crate::gadgets::twisted_edwards::EdwardsPointTarget;
+use crate::hash::hash_types::RichField;
+use crate::iop::target::BoolTarget;
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+#[derive(Clone, Debug)]
+pub struct EdDSAPublicKeyTarget(pub EdwardsPointTarget<Ed25519>);
+
+#[derive(Clone, Debug)]
+pub struct EdDSASignatureTarget {
+    pub r: EdwardsPointTarget<Ed25519>,
+    pub s: NonNativeTarget<Curve25519Scalar>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Verifies an Ed25519 signature on `msg`, a sequence of bytes each given as 8 little-endian
+    /// bits, by checking that `S B = R + k A`, where `k = SHA-512(R || A || msg)`.
+    pub fn verify_eddsa(
+        &mut self,
+        msg: &[BoolTarget],
+        sig: EdDSASignatureTarget,
+        pk: EdDSAPublicKeyTarget,
+    ) {
+        let EdDSASignatureTarget { r, s } = sig;
+
+        self.edwards_assert_valid(&r);
+        self.edwards_assert_valid(&pk.0);
+
+        let mut hash_input = self.compress_edwards_point(&r);
+        hash_input.extend(self.compress_edwards_point(&pk.0));
+        hash_input.extend_from_slice(msg);
+        let hash = self.sha512(&hash_input);
+
+        // The hash is interpreted as a little-endian integer, so its bits are already in order.
+        let limbs = hash
+            .chunks(32)
+            .map(|limb_bits| U32Target(self.le_sum(limb_bits.iter())))
+            .collect();
+        let hash_biguint = BigUintTarget { limbs };
+        let hash_nonnative = self.biguint_to_nonnative(&hash_biguint);
+        let k = self.reduce_nonnative(&hash_nonnative);
+
+        // Splitting `S` into bits also checks that `S < l`, as RFC 8032 requires, so that signatures
+        // aren't malleable.
+        let s_bits = self.nonnative_to_canonical_le_bits(&s);
+        let k_bits = self.nonnative_to_canonical_le_bits(&k);
+
+        // Compute `S B - k A` with Shamir's trick, sharing the doublings between both terms.
+        let zero = self.edwards_zero();
+        let b = self.constant_edwards_point(Ed25519::GENERATOR);
+        let neg_a = self.edwards_neg(&pk.0);
+        let b_minus_a = self.edwards_add(&b, &neg_a);
+
+        let mut result = zero.clone();
+        for (&s_bit, &k_bit) in s_bits.iter().zip(&k_bits).rev() {
+            result = self.edwards_double(&result);
+            let if_s_bit = self.edwards_select(k_bit, &b_minus_a, &b);
+            let if_not_s_bit = self.edwards_select(k_bit, &neg_a, &zero);
+            let summand = self.edwards_select(s_bit, &if_s_bit, &if_not_s_bit);
+            result = self.edwards_add(&result, &summand);
+        }
+
+        self.connect_edwards_point(&result, &r);
+    }
+
+    /// Returns the RFC 8032 encoding of `p`, i.e. the 255-bit little-endian encoding of `y`
+    /// followed by the parity of `x`, as 32 bytes of 8 little-endian bits each.
+    pub fn compress_edwards_point(&mut self, p: &EdwardsPointTarget<Ed25519>) -> Vec<BoolTarget> {
+        let mut bits = self.nonnative_to_canonical_le_bits(&p.y);
+        let x_bits = self.nonnative_to_canonical_le_bits(&p.x);
+        bits[Curve25519Base::BITS] = x_bits[0];
+        bits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use rand::{thread_rng, Rng};
+
+    use crate::curve::eddsa::{secret_to_public, sign_message, EdDSASecretKey, EdDSASignature};
+    use crate::gadgets::eddsa::{EdDSAPublicKeyTarget, EdDSASignatureTarget};
+    use crate::iop::witness::{PartialWitness, Witness};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    #[test]
+    #[ignore]
+    fn test_eddsa_circuit() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_ecc_config();
+
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let mut rng = thread_rng();
+        let msg: Vec<u8> = (0..100).map(|_| rng.gen()).collect();
+        let sk = EdDSASecretKey(rng.gen());
+        let pk = secret_to_public(sk);
+        let EdDSASignature { r, s } = sign_message(&msg, sk);
+
+        let msg_targets: Vec<_> = (0..8 * msg.len())
+            .map(|_| builder.add_virtual_bool_target_safe())
+            .collect();
+        for (byte_targets, byte) in msg_targets.chunks(8).zip(&msg) {
+            for (i, &t) in byte_targets.iter().enumerate() {
+                pw.set_bool_target(t, (byte >> i) & 1 != 0);
+            }
+        }
+
+        let pk_target = EdDSAPublicKeyTarget(builder.constant_edwards_point(pk.0));
+        let sig_target = EdDSASignatureTarget {
+            r: builder.constant_edwards_point(r),
+            s: builder.constant_nonnative(s),
+        };
+
+        builder.verify_eddsa(&msg_targets, sig_target, pk_target);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        verify(proof, &data.verifier_only, &data.common)
+    }
+}
@@ -0,0 +1,202 @@
+use std::marker::PhantomData;
+
+use plonky2_field::extension_field::Extendable;
+use plonky2_field::field_types::PrimeField;
+use plonky2_field::secp256k1_base::Secp256K1Base;
+use plonky2_field::secp256k1_scalar::Secp256K1Scalar;
+
+use crate::curve::glv::{decompose_secp256k1_scalar, GLV_BETA, GLV_LAMBDA};
+use crate::curve::secp256k1::Secp256K1;
+use crate::gadgets::curve::AffinePointTarget;
+use crate::gadgets::nonnative::NonNativeTarget;
+use crate::hash::hash_types::RichField;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator};
+use crate::iop::target::{BoolTarget, Target};
+use crate::iop::witness::{PartitionWitness, Witness};
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    pub fn secp256k1_glv_beta(&mut self) -> NonNativeTarget<Secp256K1Base> {
+        self.constant_nonnative(GLV_BETA)
+    }
+
+    /// Decomposes `k` into `(k1, k2, k1_neg, k2_neg)`, where `k1, k2 < 2^128`, such that
+    /// `k = (-1)^k1_neg k1 + lambda (-1)^k2_neg k2`.
+    pub fn decompose_secp256k1_scalar(
+        &mut self,
+        k: &NonNativeTarget<Secp256K1Scalar>,
+    ) -> (
+        NonNativeTarget<Secp256K1Scalar>,
+        NonNativeTarget<Secp256K1Scalar>,
+        BoolTarget,
+        BoolTarget,
+    ) {
+        let k1 = self.add_virtual_nonnative_target_sized::<Secp256K1Scalar>(4);
+        let k2 = self.add_virtual_nonnative_target_sized::<Secp256K1Scalar>(4);
+        let k1_neg = self.add_virtual_bool_target_safe();
+        let k2_neg = self.add_virtual_bool_target_safe();
+
+        self.add_simple_generator(GLVDecompositionGenerator::<F, D> {
+            k: k.clone(),
+            k1: k1.clone(),
+            k2: k2.clone(),
+            k1_neg,
+            k2_neg,
+            _phantom: PhantomData,
+        });
+
+        // Check that `(-1)^k1_neg k1 + lambda (-1)^k2_neg k2 == k`.
+        let k1_signed = self.nonnative_conditional_neg(&k1, k1_neg);
+        let k2_signed = self.nonnative_conditional_neg(&k2, k2_neg);
+        let lambda = self.constant_nonnative(GLV_LAMBDA);
+        let lambda_k2 = self.mul_nonnative(&lambda, &k2_signed);
+        let k_expected = self.add_nonnative(&k1_signed, &lambda_k2);
+        self.connect_nonnative(&k_expected, k);
+
+        (k1, k2, k1_neg, k2_neg)
+    }
+
+    /// Computes `k p` on secp256k1, by splitting `k` into two 128-bit halves with the GLV
+    /// endomorphism, so that the two halves share half as many doublings as a full scalar needs.
+    pub fn glv_mul(
+        &mut self,
+        p: &AffinePointTarget<Secp256K1>,
+        k: &NonNativeTarget<Secp256K1Scalar>,
+    ) -> AffinePointTarget<Secp256K1> {
+        let (k1, k2, k1_neg, k2_neg) = self.decompose_secp256k1_scalar(k);
+
+        let beta = self.secp256k1_glv_beta();
+        let beta_px = self.mul_nonnative(&p.x, &beta);
+        let sp = AffinePointTarget::<Secp256K1> {
+            x: beta_px,
+            y: p.y.clone(),
+        };
+
+        let first = AffinePointTarget::<Secp256K1> {
+            x: p.x.clone(),
+            y: self.nonnative_conditional_neg(&p.y, k1_neg),
+        };
+        let second = AffinePointTarget::<Secp256K1> {
+            x: sp.x.clone(),
+            y: self.nonnative_conditional_neg(&sp.y, k2_neg),
+        };
+
+        let k1_limbs = self.split_nonnative_to_4_bit_limbs(&k1);
+        let k2_limbs = self.split_nonnative_to_4_bit_limbs(&k2);
+        self.curve_windowed_linear_combination(&[(first, k1_limbs), (second, k2_limbs)])
+    }
+}
+
+#[derive(Debug)]
+struct GLVDecompositionGenerator<F: RichField + Extendable<D>, const D: usize> {
+    k: NonNativeTarget<Secp256K1Scalar>,
+    k1: NonNativeTarget<Secp256K1Scalar>,
+    k2: NonNativeTarget<Secp256K1Scalar>,
+    k1_neg: BoolTarget,
+    k2_neg: BoolTarget,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F>
+    for GLVDecompositionGenerator<F, D>
+{
+    fn dependencies(&self) -> Vec<Target> {
+        self.k.value.limbs.iter().map(|l| l.0).collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let k = witness.get_nonnative_target(self.k.clone());
+        let (k1, k2, k1_neg, k2_neg) = decompose_secp256k1_scalar(k);
+
+        out_buffer.set_biguint_target(self.k1.value.clone(), k1.to_canonical_biguint());
+        out_buffer.set_biguint_target(self.k2.value.clone(), k2.to_canonical_biguint());
+        out_buffer.set_bool_target(self.k1_neg, k1_neg);
+        out_buffer.set_bool_target(self.k2_neg, k2_neg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use plonky2_field::field_types::Field;
+    use plonky2_field::secp256k1_scalar::Secp256K1Scalar;
+
+    use crate::curve::curve_types::{Curve, CurveScalar};
+    use crate::curve::glv::glv_mul;
+    use crate::curve::secp256k1::Secp256K1;
+    use crate::iop::witness::PartialWitness;
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    #[test]
+    #[ignore]
+    fn test_glv_gadget() -> Result<()> {
+        let config = CircuitConfig::standard_ecc_config();
+
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let scalar = Secp256K1Scalar::rand();
+        let point_rand = Secp256K1Scalar::rand();
+        let point = (CurveScalar(point_rand) * Secp256K1::GENERATOR_PROJECTIVE).to_affine();
+
+        let point_target = builder.constant_affine_point(point);
+        let scalar_target = builder.constant_nonnative(scalar);
+
+        let rando = glv_mul(point.to_projective(), scalar).to_affine();
+        let expected = builder.constant_affine_point(rando);
+        let actual = builder.glv_mul(&point_target, &scalar_target);
+        builder.connect_affine_point(&expected, &actual);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    /// Checks that the windowed and GLV scalar multiplications use fewer gates than the naive one,
+    /// for a variable point and scalar. The circuits are built but not proven.
+    #[test]
+    fn test_curve_mul_gate_counts() {
+        let num_gates = |mul: &dyn Fn(&mut CircuitBuilder<F, D>)| {
+            let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_ecc_config());
+            mul(&mut builder);
+            builder.num_gates()
+        };
+
+        let naive = num_gates(&|builder: &mut CircuitBuilder<F, D>| {
+            let p = builder.add_virtual_affine_point_target::<Secp256K1>();
+            let k = builder.add_virtual_nonnative_target();
+            builder.curve_scalar_mul(&p, &k);
+        });
+        let windowed = num_gates(&|builder: &mut CircuitBuilder<F, D>| {
+            let p = builder.add_virtual_affine_point_target::<Secp256K1>();
+            let k = builder.add_virtual_nonnative_target();
+            builder.curve_scalar_mul_windowed(&p, &k);
+        });
+        let glv = num_gates(&|builder: &mut CircuitBuilder<F, D>| {
+            let p = builder.add_virtual_affine_point_target::<Secp256K1>();
+            let k = builder.add_virtual_nonnative_target();
+            builder.glv_mul(&p, &k);
+        });
+
+        assert!(
+            windowed < naive,
+            "Windowed multiplication uses {} gates, naive {}",
+            windowed,
+            naive
+        );
+        assert!(
+            glv < windowed,
+            "GLV multiplication uses {} gates, windowed {}",
+            glv,
+            windowed
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use plonky2_field::extension_field::Extendable;
+
+use crate::hash::hash_types::RichField;
+use crate::iop::target::BoolTarget;
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+const KECCAK_WIDTH: usize = 1600;
+/// The rate of Keccak-256, in bits.
+const KECCAK_256_RATE: usize = 1088;
+const KECCAK_ROUNDS: usize = 24;
+
+/// Rotation offsets of the rho step, indexed by `[x][y]`.
+const ROTATIONS: [[usize; 5]; 5] = [
+    [0, 36, 3, 41, 18],
+    [1, 44, 10, 45, 2],
+    [62, 6, 43, 15, 61],
+    [28, 55, 25, 21, 56],
+    [27, 20, 39, 8, 14],
+];
+
+const ROUND_CONSTANTS: [u64; KECCAK_ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808A,
+    0x8000000080008000,
+    0x000000000000808B,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008A,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000A,
+    0x000000008000808B,
+    0x800000000000008B,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800A,
+    0x800000008000000A,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// The index of bit `z` of lane `(x, y)` in the Keccak state.
+fn state_index(x: usize, y: usize, z: usize) -> usize {
+    64 * (x % 5 + 5 * (y % 5)) + z % 64
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Computes the Keccak-256 hash of `input`, as used by Ethereum (i.e. with the original Keccak
+    /// padding, rather than that of SHA-3). The input is a sequence of bytes, each given as 8
+    /// little-endian bits, and the 32-byte output is given in the same format.
+    pub fn keccak256(&mut self, input: &[BoolTarget]) -> Vec<BoolTarget> {
+        assert_eq!(input.len() % 8, 0, "Input must be a whole number of bytes");
+        let _false = self._false();
+        let _true = self._true();
+
+        // Apply pad10*1.
+        let mut padded = input.to_vec();
+        padded.push(_true);
+        while (padded.len() + 1) % KECCAK_256_RATE != 0 {
+            padded.push(_false);
+        }
+        padded.push(_true);
+
+        let mut state = vec![_false; KECCAK_WIDTH];
+        for block in padded.chunks(KECCAK_256_RATE) {
+            for (s, &b) in state.iter_mut().zip(block) {
+                *s = self.xor(*s, b);
+            }
+            self.keccak_f(&mut state);
+        }
+
+        state.truncate(256);
+        state
+    }
+
+    /// Applies the Keccak-f[1600] permutation to `state`.
+    fn keccak_f(&mut self, state: &mut [BoolTarget]) {
+        for round_constant in ROUND_CONSTANTS {
+            // Theta.
+            let mut c = vec![vec![]; 5];
+            for (x, c_x) in c.iter_mut().enumerate() {
+                for z in 0..64 {
+                    let mut parity = state[state_index(x, 0, z)];
+                    for y in 1..5 {
+                        parity = self.xor(parity, state[state_index(x, y, z)]);
+                    }
+                    c_x.push(parity);
+                }
+            }
+            for x in 0..5 {
+                for z in 0..64 {
+                    let d = self.xor(c[(x + 4) % 5][z], c[(x + 1) % 5][(z + 63) % 64]);
+                    for y in 0..5 {
+                        let i = state_index(x, y, z);
+                        state[i] = self.xor(state[i], d);
+                    }
+                }
+            }
+
+            // Rho and pi.
+            let mut b = state.to_vec();
+            for (x, rotations) in ROTATIONS.iter().enumerate() {
+                for (y, &rotation) in rotations.iter().enumerate() {
+                    for z in 0..64 {
+                        b[state_index(y, 2 * x + 3 * y, z)] =
+                            state[state_index(x, y, z + 64 - rotation)];
+                    }
+                }
+            }
+
+            // Chi.
+            for x in 0..5 {
+                for y in 0..5 {
+                    for z in 0..64 {
+                        let t =
+                            self.and_not(b[state_index(x + 1, y, z)], b[state_index(x + 2, y, z)]);
+                        state[state_index(x, y, z)] = self.xor(b[state_index(x, y, z)], t);
+                    }
+                }
+            }
+
+            // Iota.
+            for z in 0..64 {
+                if (round_constant >> z) & 1 != 0 {
+                    let i = state_index(0, 0, z);
+                    state[i] = self.not(state[i]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use keccak_hash::keccak;
+    use rand::{thread_rng, Rng};
+
+    use crate::iop::witness::{PartialWitness, Witness};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    fn to_bits(bytes: &[u8]) -> Vec<bool> {
+        bytes
+            .iter()
+            .flat_map(|&b| (0..8).map(move |i| (b >> i) & 1 != 0))
+            .collect()
+    }
+
+    #[test]
+    #[ignore]
+    fn test_keccak256() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_ecc_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        // An empty input, an input which exactly fills a block once padded, and one spanning two blocks.
+        let mut rng = thread_rng();
+        for len in [0, 135, 200] {
+            let msg: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            let expected = to_bits(&keccak(&msg).0);
+
+            let msg_targets: Vec<_> = (0..8 * len)
+                .map(|_| builder.add_virtual_bool_target_safe())
+                .collect();
+            for (&t, b) in msg_targets.iter().zip(to_bits(&msg)) {
+                pw.set_bool_target(t, b);
+            }
+
+            let hash = builder.keccak256(&msg_targets);
+            for (h, e) in hash.into_iter().zip(expected) {
+                let e = builder.constant_bool(e);
+                builder.connect(h.target, e.target);
+            }
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+}
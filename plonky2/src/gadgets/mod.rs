@@ -1,16 +1,27 @@
 pub mod arithmetic;
 pub mod arithmetic_extension;
+pub mod arithmetic_signed;
 pub mod arithmetic_u32;
+pub mod bigint;
 pub mod biguint;
 pub mod curve;
+pub mod curve_msm;
+pub mod curve_projective;
+pub mod curve_windowed_mul;
 pub mod ecdsa;
+pub mod eddsa;
+pub mod glv;
 pub mod hash;
 pub mod interpolation;
+pub mod keccak;
 pub mod multiple_comparison;
 pub mod nonnative;
 pub mod polynomial;
 pub mod random_access;
 pub mod range_check;
+pub mod rsa;
 pub mod select;
+pub mod sha512;
 pub mod split_base;
 pub(crate) mod split_join;
+pub mod twisted_edwards;
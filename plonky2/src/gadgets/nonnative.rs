@@ -1,6 +1,6 @@
 use std::marker::PhantomData;
 
-use num::{BigUint, Integer, One, Zero};
+use num::{BigUint, Integer, One};
 use plonky2_field::field_types::PrimeField;
 use plonky2_field::{extension_field::Extendable, field_types::Field};
 use plonky2_util::ceil_div_usize;
@@ -13,20 +13,69 @@ use crate::iop::target::{BoolTarget, Target};
 use crate::iop::witness::{PartitionWitness, Witness};
 use crate::plonk::circuit_builder::CircuitBuilder;
 
+/// The number of limbs, beyond those of a canonical element, which an unreduced value may occupy
+/// before we reduce it.
+const MAX_EXTRA_LIMBS: usize = 1;
+
+/// An element of the field `FF`, represented by an integer `value` which is congruent to it, and
+/// which may not be reduced modulo the order of `FF`. We track an upper bound on `value`, so that
+/// arithmetic can skip reductions until a comparison, a connection or the growth of the bound
+/// requires one.
 #[derive(Clone, Debug)]
 pub struct NonNativeTarget<FF: Field> {
     pub(crate) value: BigUintTarget,
+    /// An upper bound on `value`, which is at least the order of `FF` when `value` may be
+    /// unreduced.
+    pub(crate) max_value: BigUint,
     pub(crate) _phantom: PhantomData<FF>,
 }
 
+impl<FF: Field> NonNativeTarget<FF> {
+    /// Returns whether `value` is known to be less than the order of `FF`.
+    pub fn is_reduced(&self) -> bool {
+        self.max_value < FF::order()
+    }
+}
+
+/// Returns the largest integer which fits in `num_limbs` 32-bit limbs.
+fn max_value_for_limbs(num_limbs: usize) -> BigUint {
+    (BigUint::one() << (32 * num_limbs)) - 1u32
+}
+
 impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     fn num_nonnative_limbs<FF: Field>() -> usize {
         ceil_div_usize(FF::BITS, 32)
     }
 
+    /// Wraps `value`, which is known to be at most `max_value`, and reduces it if `max_value` has
+    /// grown too large.
+    fn bounded_nonnative<FF: Field>(
+        &mut self,
+        mut value: BigUintTarget,
+        max_value: BigUint,
+    ) -> NonNativeTarget<FF> {
+        // Limbs above the bound must be zero, so we assert that they are rather than keep them.
+        let num_limbs = ceil_div_usize(max_value.bits() as usize, 32);
+        for limb in value.limbs.drain(num_limbs.min(value.limbs.len())..) {
+            self.assert_zero(limb.0);
+        }
+
+        let x = NonNativeTarget {
+            value,
+            max_value,
+            _phantom: PhantomData,
+        };
+        if num_limbs > Self::num_nonnative_limbs::<FF>() + MAX_EXTRA_LIMBS {
+            self.reduce_nonnative(&x)
+        } else {
+            x
+        }
+    }
+
     pub fn biguint_to_nonnative<FF: Field>(&mut self, x: &BigUintTarget) -> NonNativeTarget<FF> {
         NonNativeTarget {
             value: x.clone(),
+            max_value: max_value_for_limbs(x.num_limbs()),
             _phantom: PhantomData,
         }
     }
@@ -36,63 +85,89 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     }
 
     pub fn constant_nonnative<FF: PrimeField>(&mut self, x: FF) -> NonNativeTarget<FF> {
-        let x_biguint = self.constant_biguint(&x.to_canonical_biguint());
-        self.biguint_to_nonnative(&x_biguint)
+        let x_biguint = x.to_canonical_biguint();
+        NonNativeTarget {
+            value: self.constant_biguint(&x_biguint),
+            max_value: x_biguint,
+            _phantom: PhantomData,
+        }
     }
 
     pub fn zero_nonnative<FF: PrimeField>(&mut self) -> NonNativeTarget<FF> {
         self.constant_nonnative(FF::ZERO)
     }
 
-    // Assert that two NonNativeTarget's, both assumed to be in reduced form, are equal.
+    // Assert that two NonNativeTarget's are equal, reducing them first if they may be unreduced.
     pub fn connect_nonnative<FF: Field>(
         &mut self,
         lhs: &NonNativeTarget<FF>,
         rhs: &NonNativeTarget<FF>,
     ) {
+        let lhs = self.reduce_nonnative_if_needed(lhs);
+        let rhs = self.reduce_nonnative_if_needed(rhs);
         self.connect_biguint(&lhs.value, &rhs.value);
     }
 
-    pub fn add_virtual_nonnative_target<FF: Field>(&mut self) -> NonNativeTarget<FF> {
+    /// Adds a virtual target for a field element, and asserts that its value is canonical.
+    pub fn add_virtual_nonnative_target<FF: PrimeField>(&mut self) -> NonNativeTarget<FF> {
         let num_limbs = Self::num_nonnative_limbs::<FF>();
         let value = self.add_virtual_biguint_target(num_limbs);
 
-        NonNativeTarget {
+        let x = NonNativeTarget {
             value,
+            max_value: FF::order() - 1u32,
             _phantom: PhantomData,
-        }
+        };
+        self.assert_nonnative_canonical(&x);
+        x
     }
 
-    pub fn add_nonnative<FF: PrimeField>(
+    /// Like `add_virtual_nonnative_target`, but with the given number of range-checked limbs. The
+    /// value is only reduced if these limbs can't hold the modulus.
+    pub fn add_virtual_nonnative_target_sized<FF: Field>(
         &mut self,
-        a: &NonNativeTarget<FF>,
-        b: &NonNativeTarget<FF>,
+        num_limbs: usize,
     ) -> NonNativeTarget<FF> {
-        let sum = self.add_virtual_nonnative_target::<FF>();
-        let overflow = self.add_virtual_bool_target();
+        let value = self.add_virtual_biguint_target(num_limbs);
+        self.range_check_u32(value.limbs.clone());
 
-        self.add_simple_generator(NonNativeAdditionGenerator::<F, D, FF> {
-            a: a.clone(),
-            b: b.clone(),
-            sum: sum.clone(),
-            overflow,
+        NonNativeTarget {
+            value,
+            max_value: max_value_for_limbs(num_limbs),
             _phantom: PhantomData,
-        });
-
-        let sum_expected = self.add_biguint(&a.value, &b.value);
-
-        let modulus = self.constant_biguint(&FF::order());
-        let mod_times_overflow = self.mul_biguint_by_bool(&modulus, overflow);
-        let sum_actual = self.add_biguint(&sum.value, &mod_times_overflow);
-        self.connect_biguint(&sum_expected, &sum_actual);
+        }
+    }
 
-        // Range-check result.
-        // TODO: can potentially leave unreduced until necessary (e.g. when connecting values).
-        let cmp = self.cmp_biguint(&sum.value, &modulus);
+    /// Asserts that `x` is less than the field order, and that its limbs are 32 bits.
+    pub fn assert_nonnative_canonical<FF: PrimeField>(&mut self, x: &NonNativeTarget<FF>) {
+        self.range_check_u32(x.value.limbs.clone());
+        let max = self.constant_biguint(&(FF::order() - 1u32));
+        let is_canonical = self.cmp_biguint(&x.value, &max);
         let one = self.one();
-        self.connect(cmp.target, one);
+        self.connect(is_canonical.target, one);
+    }
 
-        sum
+    /// Returns the canonical representative of `x`, i.e. the one less than the field order, with
+    /// range-checked limbs.
+    pub fn canonical_nonnative<FF: PrimeField>(
+        &mut self,
+        x: &NonNativeTarget<FF>,
+    ) -> NonNativeTarget<FF> {
+        if x.is_reduced() {
+            self.assert_nonnative_canonical(x);
+            x.clone()
+        } else {
+            self.reduce_nonnative(x)
+        }
+    }
+
+    pub fn add_nonnative<FF: PrimeField>(
+        &mut self,
+        a: &NonNativeTarget<FF>,
+        b: &NonNativeTarget<FF>,
+    ) -> NonNativeTarget<FF> {
+        let sum = self.add_biguint(&a.value, &b.value);
+        self.bounded_nonnative(sum, &a.max_value + &b.max_value)
     }
 
     pub fn mul_nonnative_by_bool<FF: Field>(
@@ -102,6 +177,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     ) -> NonNativeTarget<FF> {
         NonNativeTarget {
             value: self.mul_biguint_by_bool(&a.value, b),
+            max_value: a.max_value.clone(),
             _phantom: PhantomData,
         }
     }
@@ -110,42 +186,10 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         &mut self,
         to_add: &[NonNativeTarget<FF>],
     ) -> NonNativeTarget<FF> {
-        if to_add.len() == 1 {
-            return to_add[0].clone();
+        let mut sum = to_add[0].clone();
+        for x in &to_add[1..] {
+            sum = self.add_nonnative(&sum, x);
         }
-
-        let sum = self.add_virtual_nonnative_target::<FF>();
-        let overflow = self.add_virtual_u32_target();
-        let summands = to_add.to_vec();
-
-        self.add_simple_generator(NonNativeMultipleAddsGenerator::<F, D, FF> {
-            summands: summands.clone(),
-            sum: sum.clone(),
-            overflow,
-            _phantom: PhantomData,
-        });
-
-        self.range_check_u32(sum.value.limbs.clone());
-        self.range_check_u32(vec![overflow]);
-
-        let sum_expected = summands
-            .iter()
-            .fold(self.zero_biguint(), |a, b| self.add_biguint(&a, &b.value));
-
-        let modulus = self.constant_biguint(&FF::order());
-        let overflow_biguint = BigUintTarget {
-            limbs: vec![overflow],
-        };
-        let mod_times_overflow = self.mul_biguint(&modulus, &overflow_biguint);
-        let sum_actual = self.add_biguint(&sum.value, &mod_times_overflow);
-        self.connect_biguint(&sum_expected, &sum_actual);
-
-        // Range-check result.
-        // TODO: can potentially leave unreduced until necessary (e.g. when connecting values).
-        let cmp = self.cmp_biguint(&sum.value, &modulus);
-        let one = self.one();
-        self.connect(cmp.target, one);
-
         sum
     }
 
@@ -155,27 +199,15 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         a: &NonNativeTarget<FF>,
         b: &NonNativeTarget<FF>,
     ) -> NonNativeTarget<FF> {
-        let diff = self.add_virtual_nonnative_target::<FF>();
-        let overflow = self.add_virtual_bool_target();
-
-        self.add_simple_generator(NonNativeSubtractionGenerator::<F, D, FF> {
-            a: a.clone(),
-            b: b.clone(),
-            diff: diff.clone(),
-            overflow,
-            _phantom: PhantomData,
-        });
-
-        self.range_check_u32(diff.value.limbs.clone());
-        self.assert_bool(overflow);
-
-        let diff_plus_b = self.add_biguint(&diff.value, &b.value);
-        let modulus = self.constant_biguint(&FF::order());
-        let mod_times_overflow = self.mul_biguint_by_bool(&modulus, overflow);
-        let diff_plus_b_reduced = self.sub_biguint(&diff_plus_b, &mod_times_overflow);
-        self.connect_biguint(&a.value, &diff_plus_b_reduced);
+        // Compute `a + k p - b`, where `k p` is the smallest multiple of the modulus which is at
+        // least `b`'s bound, so that the difference can't underflow.
+        let modulus = FF::order();
+        let mod_multiple = b.max_value.div_ceil(&modulus) * &modulus;
+        let mod_multiple_target = self.constant_biguint(&mod_multiple);
+        let a_plus_mod_multiple = self.add_biguint(&a.value, &mod_multiple_target);
+        let diff = self.sub_biguint(&a_plus_mod_multiple, &b.value);
 
-        diff
+        self.bounded_nonnative(diff, &a.max_value + mod_multiple)
     }
 
     pub fn mul_nonnative<FF: PrimeField>(
@@ -183,11 +215,12 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         a: &NonNativeTarget<FF>,
         b: &NonNativeTarget<FF>,
     ) -> NonNativeTarget<FF> {
-        let prod = self.add_virtual_nonnative_target::<FF>();
+        let num_limbs = Self::num_nonnative_limbs::<FF>();
+        let prod = self.add_virtual_biguint_target(num_limbs);
         let modulus = self.constant_biguint(&FF::order());
-        let overflow = self.add_virtual_biguint_target(
-            a.value.num_limbs() + b.value.num_limbs() - modulus.num_limbs(),
-        );
+        let max_overflow = (&a.max_value * &b.max_value) / FF::order();
+        let overflow =
+            self.add_virtual_biguint_target(ceil_div_usize(max_overflow.bits() as usize, 32));
 
         self.add_simple_generator(NonNativeMultiplicationGenerator::<F, D, FF> {
             a: a.clone(),
@@ -197,16 +230,21 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             _phantom: PhantomData,
         });
 
-        self.range_check_u32(prod.value.limbs.clone());
+        self.range_check_u32(prod.limbs.clone());
         self.range_check_u32(overflow.limbs.clone());
 
         let prod_expected = self.mul_biguint(&a.value, &b.value);
 
         let mod_times_overflow = self.mul_biguint(&modulus, &overflow);
-        let prod_actual = self.add_biguint(&prod.value, &mod_times_overflow);
+        let prod_actual = self.add_biguint(&prod, &mod_times_overflow);
         self.connect_biguint(&prod_expected, &prod_actual);
 
-        prod
+        // The product is only range-checked to `num_limbs` limbs, so it may be unreduced.
+        NonNativeTarget {
+            value: prod,
+            max_value: max_value_for_limbs(num_limbs),
+            _phantom: PhantomData,
+        }
     }
 
     pub fn mul_many_nonnative<FF: PrimeField>(
@@ -228,19 +266,31 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         &mut self,
         x: &NonNativeTarget<FF>,
     ) -> NonNativeTarget<FF> {
-        let zero_target = self.constant_biguint(&BigUint::zero());
-        let zero_ff = self.biguint_to_nonnative(&zero_target);
+        let zero_ff = self.zero_nonnative();
 
         self.sub_nonnative(&zero_ff, x)
     }
 
+    /// Returns `-x` if `b` is true, and `x` otherwise.
+    pub fn nonnative_conditional_neg<FF: PrimeField>(
+        &mut self,
+        x: &NonNativeTarget<FF>,
+        b: BoolTarget,
+    ) -> NonNativeTarget<FF> {
+        let not_b = self.not(b);
+        let neg = self.neg_nonnative(x);
+        let neg_if_b = self.mul_nonnative_by_bool(&neg, b);
+        let x_if_not_b = self.mul_nonnative_by_bool(x, not_b);
+        self.add_nonnative(&neg_if_b, &x_if_not_b)
+    }
+
     pub fn inv_nonnative<FF: PrimeField>(
         &mut self,
         x: &NonNativeTarget<FF>,
     ) -> NonNativeTarget<FF> {
-        let num_limbs = x.value.num_limbs();
+        let num_limbs = Self::num_nonnative_limbs::<FF>();
         let inv_biguint = self.add_virtual_biguint_target(num_limbs);
-        let div = self.add_virtual_biguint_target(num_limbs);
+        let div = self.add_virtual_biguint_target(x.value.num_limbs());
 
         self.add_simple_generator(NonNativeInverseGenerator::<F, D, FF> {
             x: x.clone(),
@@ -249,6 +299,9 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             _phantom: PhantomData,
         });
 
+        self.range_check_u32(inv_biguint.limbs.clone());
+        self.range_check_u32(div.limbs.clone());
+
         let product = self.mul_biguint(&x.value, &inv_biguint);
 
         let modulus = self.constant_biguint(&FF::order());
@@ -259,6 +312,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
 
         NonNativeTarget::<FF> {
             value: inv_biguint,
+            max_value: max_value_for_limbs(num_limbs),
             _phantom: PhantomData,
         }
     }
@@ -271,6 +325,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
 
         NonNativeTarget {
             value,
+            max_value: modulus - 1u32,
             _phantom: PhantomData,
         }
     }
@@ -280,17 +335,31 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         self.reduce(&x_biguint)
     }
 
+    /// Like `reduce_nonnative`, but returns `x` itself if it is already known to be reduced.
+    pub fn reduce_nonnative_if_needed<FF: Field>(
+        &mut self,
+        x: &NonNativeTarget<FF>,
+    ) -> NonNativeTarget<FF> {
+        if x.is_reduced() {
+            x.clone()
+        } else {
+            self.reduce_nonnative(x)
+        }
+    }
+
     pub fn bool_to_nonnative<FF: Field>(&mut self, b: &BoolTarget) -> NonNativeTarget<FF> {
         let limbs = vec![U32Target(b.target)];
         let value = BigUintTarget { limbs };
 
         NonNativeTarget {
             value,
+            max_value: BigUint::one(),
             _phantom: PhantomData,
         }
     }
 
-    // Split a nonnative field element to bits.
+    // Split a nonnative field element to bits. If `x` may be unreduced, these are the bits of its
+    // unreduced representative.
     pub fn split_nonnative_to_bits<FF: Field>(
         &mut self,
         x: &NonNativeTarget<FF>,
@@ -311,130 +380,26 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
 
         result
     }
-}
-
-#[derive(Debug)]
-struct NonNativeAdditionGenerator<F: RichField + Extendable<D>, const D: usize, FF: PrimeField> {
-    a: NonNativeTarget<FF>,
-    b: NonNativeTarget<FF>,
-    sum: NonNativeTarget<FF>,
-    overflow: BoolTarget,
-    _phantom: PhantomData<F>,
-}
-
-impl<F: RichField + Extendable<D>, const D: usize, FF: PrimeField> SimpleGenerator<F>
-    for NonNativeAdditionGenerator<F, D, FF>
-{
-    fn dependencies(&self) -> Vec<Target> {
-        self.a
-            .value
-            .limbs
-            .iter()
-            .cloned()
-            .chain(self.b.value.limbs.clone())
-            .map(|l| l.0)
-            .collect()
-    }
-
-    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
-        let a = witness.get_nonnative_target(self.a.clone());
-        let b = witness.get_nonnative_target(self.b.clone());
-        let a_biguint = a.to_canonical_biguint();
-        let b_biguint = b.to_canonical_biguint();
-        let sum_biguint = a_biguint + b_biguint;
-        let modulus = FF::order();
-        let (overflow, sum_reduced) = if sum_biguint > modulus {
-            (true, sum_biguint - modulus)
-        } else {
-            (false, sum_biguint)
-        };
-
-        out_buffer.set_biguint_target(self.sum.value.clone(), sum_reduced);
-        out_buffer.set_bool_target(self.overflow, overflow);
-    }
-}
-
-#[derive(Debug)]
-struct NonNativeMultipleAddsGenerator<F: RichField + Extendable<D>, const D: usize, FF: PrimeField>
-{
-    summands: Vec<NonNativeTarget<FF>>,
-    sum: NonNativeTarget<FF>,
-    overflow: U32Target,
-    _phantom: PhantomData<F>,
-}
-
-impl<F: RichField + Extendable<D>, const D: usize, FF: PrimeField> SimpleGenerator<F>
-    for NonNativeMultipleAddsGenerator<F, D, FF>
-{
-    fn dependencies(&self) -> Vec<Target> {
-        self.summands
-            .iter()
-            .flat_map(|summand| summand.value.limbs.iter().map(|limb| limb.0))
-            .collect()
-    }
 
-    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
-        let summands: Vec<_> = self
-            .summands
-            .iter()
-            .map(|summand| witness.get_nonnative_target(summand.clone()))
-            .collect();
-        let summand_biguints: Vec<_> = summands
-            .iter()
-            .map(|summand| summand.to_canonical_biguint())
-            .collect();
-
-        let sum_biguint = summand_biguints
-            .iter()
-            .fold(BigUint::zero(), |a, b| a + b.clone());
-
-        let modulus = FF::order();
-        let (overflow_biguint, sum_reduced) = sum_biguint.div_rem(&modulus);
-        let overflow = overflow_biguint.to_u64_digits()[0] as u32;
-
-        out_buffer.set_biguint_target(self.sum.value.clone(), sum_reduced);
-        out_buffer.set_u32_target(self.overflow, overflow);
-    }
-}
-
-#[derive(Debug)]
-struct NonNativeSubtractionGenerator<F: RichField + Extendable<D>, const D: usize, FF: Field> {
-    a: NonNativeTarget<FF>,
-    b: NonNativeTarget<FF>,
-    diff: NonNativeTarget<FF>,
-    overflow: BoolTarget,
-    _phantom: PhantomData<F>,
-}
-
-impl<F: RichField + Extendable<D>, const D: usize, FF: PrimeField> SimpleGenerator<F>
-    for NonNativeSubtractionGenerator<F, D, FF>
-{
-    fn dependencies(&self) -> Vec<Target> {
-        self.a
-            .value
-            .limbs
-            .iter()
-            .cloned()
-            .chain(self.b.value.limbs.clone())
-            .map(|l| l.0)
-            .collect()
-    }
-
-    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
-        let a = witness.get_nonnative_target(self.a.clone());
-        let b = witness.get_nonnative_target(self.b.clone());
-        let a_biguint = a.to_canonical_biguint();
-        let b_biguint = b.to_canonical_biguint();
-
-        let modulus = FF::order();
-        let (diff_biguint, overflow) = if a_biguint > b_biguint {
-            (a_biguint - b_biguint, false)
-        } else {
-            (modulus + a_biguint - b_biguint, true)
-        };
-
-        out_buffer.set_biguint_target(self.diff.value.clone(), diff_biguint);
-        out_buffer.set_bool_target(self.overflow, overflow);
+    /// Returns the little-endian bits of the canonical representative of `x`, padded to a whole
+    /// number of limbs, so that the encoding of each element of `FF` is unique.
+    pub fn nonnative_to_canonical_le_bits<FF: PrimeField>(
+        &mut self,
+        x: &NonNativeTarget<FF>,
+    ) -> Vec<BoolTarget> {
+        let x = &self.canonical_nonnative(x);
+        let num_limbs = ceil_div_usize(FF::BITS, 32);
+        let zero = self.zero();
+        let mut bits = Vec::with_capacity(num_limbs * 32);
+        for i in 0..num_limbs {
+            let limb = if i < x.value.num_limbs() {
+                x.value.get_limb(i).0
+            } else {
+                zero
+            };
+            bits.extend(self.split_le(limb, 32));
+        }
+        bits
     }
 }
 
@@ -442,7 +407,7 @@ impl<F: RichField + Extendable<D>, const D: usize, FF: PrimeField> SimpleGenerat
 struct NonNativeMultiplicationGenerator<F: RichField + Extendable<D>, const D: usize, FF: Field> {
     a: NonNativeTarget<FF>,
     b: NonNativeTarget<FF>,
-    prod: NonNativeTarget<FF>,
+    prod: BigUintTarget,
     overflow: BigUintTarget,
     _phantom: PhantomData<F>,
 }
@@ -462,17 +427,17 @@ impl<F: RichField + Extendable<D>, const D: usize, FF: PrimeField> SimpleGenerat
     }
 
     fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
-        let a = witness.get_nonnative_target(self.a.clone());
-        let b = witness.get_nonnative_target(self.b.clone());
-        let a_biguint = a.to_canonical_biguint();
-        let b_biguint = b.to_canonical_biguint();
+        // The inputs may be unreduced, so we work with their integer values rather than their
+        // residues.
+        let a_biguint = witness.get_biguint_target(self.a.value.clone());
+        let b_biguint = witness.get_biguint_target(self.b.value.clone());
 
         let prod_biguint = a_biguint * b_biguint;
 
         let modulus = FF::order();
         let (overflow_biguint, prod_reduced) = prod_biguint.div_rem(&modulus);
 
-        out_buffer.set_biguint_target(self.prod.value.clone(), prod_reduced);
+        out_buffer.set_biguint_target(self.prod.clone(), prod_reduced);
         out_buffer.set_biguint_target(self.overflow.clone(), overflow_biguint);
     }
 }
@@ -496,7 +461,7 @@ impl<F: RichField + Extendable<D>, const D: usize, FF: PrimeField> SimpleGenerat
         let x = witness.get_nonnative_target(self.x.clone());
         let inv = x.inverse();
 
-        let x_biguint = x.to_canonical_biguint();
+        let x_biguint = witness.get_biguint_target(self.x.value.clone());
         let inv_biguint = inv.to_canonical_biguint();
         let prod = x_biguint * &inv_biguint;
         let modulus = FF::order();
@@ -510,10 +475,15 @@ impl<F: RichField + Extendable<D>, const D: usize, FF: PrimeField> SimpleGenerat
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
+    use plonky2_field::bls12_381_base::Bls12381Base;
+    use plonky2_field::bls12_381_scalar::Bls12381Scalar;
+    use plonky2_field::bn254_base::Bn254Base;
+    use plonky2_field::bn254_scalar::Bn254Scalar;
     use plonky2_field::field_types::{Field, PrimeField};
+    use plonky2_field::ops::Square;
     use plonky2_field::secp256k1_base::Secp256K1Base;
 
-    use crate::iop::witness::PartialWitness;
+    use crate::iop::witness::{PartialWitness, Witness};
     use crate::plonk::circuit_builder::CircuitBuilder;
     use crate::plonk::circuit_data::CircuitConfig;
     use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
@@ -689,4 +659,136 @@ mod tests {
         let proof = data.prove(pw).unwrap();
         verify(proof, &data.verifier_only, &data.common)
     }
+
+    #[test]
+    #[should_panic]
+    fn test_nonnative_inv_forged_witness() {
+        type FF = Secp256K1Base;
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let x_ff = FF::rand();
+        let forged_inv_x_ff = x_ff.inverse() + FF::ONE;
+
+        let config = CircuitConfig::standard_ecc_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.add_virtual_nonnative_target();
+        let inv_x = builder.inv_nonnative(&x);
+        pw.set_nonnative_target(&x, x_ff);
+        pw.set_nonnative_target(&inv_x, forged_inv_x_ff);
+
+        let data = builder.build::<C>();
+        data.prove(pw).unwrap();
+    }
+
+    #[test]
+    fn test_nonnative_lazy_reduction() -> Result<()> {
+        type FF = Secp256K1Base;
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let x_ff = FF::NEG_ONE;
+        let y_ff = FF::rand();
+        let z_ff = FF::rand();
+
+        let config = CircuitConfig::standard_ecc_config();
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.constant_nonnative(x_ff);
+        let y = builder.constant_nonnative(y_ff);
+        let z = builder.constant_nonnative(z_ff);
+
+        // `x + x` exceeds the modulus, so it is left unreduced, as is the difference.
+        let sum = builder.add_nonnative(&x, &x);
+        assert!(!sum.is_reduced());
+        let diff = builder.sub_nonnative(&sum, &y);
+        assert!(!diff.is_reduced());
+        let product = builder.mul_nonnative(&diff, &z);
+        let product_expected = builder.constant_nonnative((x_ff + x_ff - y_ff) * z_ff);
+        builder.connect_nonnative(&product, &product_expected);
+
+        // Repeated doubling eventually grows the bound enough to force a reduction.
+        let mut doubled = x;
+        for _ in 0..64 {
+            doubled = builder.add_nonnative(&doubled, &doubled);
+            assert!(doubled.value.num_limbs() <= 9);
+        }
+        let doubled_expected =
+            builder.constant_nonnative(x_ff * FF::from_canonical_u64(1 << 32).square());
+        builder.connect_nonnative(&doubled, &doubled_expected);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_nonnative_non_canonical_witness() {
+        type FF = Secp256K1Base;
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_ecc_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        // The modulus is congruent to zero, but isn't a canonical encoding of it.
+        let x = builder.add_virtual_nonnative_target::<FF>();
+        pw.set_biguint_target(&x.value, &FF::order());
+
+        let data = builder.build::<C>();
+        data.prove(pw).unwrap();
+    }
+
+    /// Checks addition, subtraction, multiplication and inversion in `FF` against native arithmetic.
+    fn test_nonnative_arithmetic<FF: PrimeField>() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let x_ff = FF::rand();
+        let y_ff = FF::rand();
+
+        let config = CircuitConfig::standard_ecc_config();
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.constant_nonnative(x_ff);
+        let y = builder.constant_nonnative(y_ff);
+
+        let sum = builder.add_nonnative(&x, &y);
+        let sum_expected = builder.constant_nonnative(x_ff + y_ff);
+        builder.connect_nonnative(&sum, &sum_expected);
+
+        let diff = builder.sub_nonnative(&sum, &y);
+        builder.connect_nonnative(&diff, &x);
+
+        let product = builder.mul_nonnative(&x, &y);
+        let product_expected = builder.constant_nonnative(x_ff * y_ff);
+        builder.connect_nonnative(&product, &product_expected);
+
+        let inv_x = builder.inv_nonnative(&x);
+        let inv_x_expected = builder.constant_nonnative(x_ff.inverse());
+        builder.connect_nonnative(&inv_x, &inv_x_expected);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_nonnative_bn254() -> Result<()> {
+        test_nonnative_arithmetic::<Bn254Base>()?;
+        test_nonnative_arithmetic::<Bn254Scalar>()
+    }
+
+    #[test]
+    fn test_nonnative_bls12_381() -> Result<()> {
+        test_nonnative_arithmetic::<Bls12381Base>()?;
+        test_nonnative_arithmetic::<Bls12381Scalar>()
+    }
 }
@@ -1,6 +1,7 @@
 use plonky2_field::extension_field::Extendable;
 
 use crate::gadgets::arithmetic_u32::U32Target;
+use crate::gates::gate::Gate;
 use crate::gates::range_check_u32::U32RangeCheckGate;
 use crate::hash::hash_types::RichField;
 use crate::iop::generator::{GeneratedValues, SimpleGenerator};
@@ -45,15 +46,21 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     }
 
     pub fn range_check_u32(&mut self, vals: Vec<U32Target>) {
-        let num_input_limbs = vals.len();
-        let gate = U32RangeCheckGate::<F, D>::new(num_input_limbs);
-        let gate_index = self.add_gate(gate, vec![]);
-
-        for i in 0..num_input_limbs {
-            self.connect(
-                Target::wire(gate_index, gate.wire_ith_input_limb(i)),
-                vals[i].0,
-            );
+        // Large inputs, such as RSA moduli, are split across as many gates as needed.
+        let wires_per_limb = U32RangeCheckGate::<F, D>::new(1).num_wires();
+        let max_limbs_per_gate = self.config.num_wires / wires_per_limb;
+
+        for chunk in vals.chunks(max_limbs_per_gate) {
+            let num_input_limbs = chunk.len();
+            let gate = U32RangeCheckGate::<F, D>::new(num_input_limbs);
+            let gate_index = self.add_gate(gate, vec![]);
+
+            for i in 0..num_input_limbs {
+                self.connect(
+                    Target::wire(gate_index, gate.wire_ith_input_limb(i)),
+                    chunk[i].0,
+                );
+            }
         }
     }
 
@@ -0,0 +1,266 @@
+use num::BigUint;
+use plonky2_field::extension_field::Extendable;
+
+use crate::gadgets::arithmetic_u32::U32Target;
+use crate::gadgets::biguint::BigUintTarget;
+use crate::hash::hash_types::RichField;
+use crate::iop::target::BoolTarget;
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+/// The DER encoding of the `DigestInfo` prefix for SHA-256, from RFC 8017, section 9.2.
+pub const SHA256_DIGEST_INFO_PREFIX: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+
+/// The DER encoding of the `DigestInfo` prefix for SHA-512, from RFC 8017, section 9.2.
+pub const SHA512_DIGEST_INFO_PREFIX: [u8; 19] = [
+    0x30, 0x51, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03, 0x05,
+    0x00, 0x04, 0x40,
+];
+
+#[derive(Clone, Debug)]
+pub struct RSAPublicKeyTarget {
+    pub n: BigUintTarget,
+    pub e: BigUintTarget,
+}
+
+#[derive(Clone, Debug)]
+pub struct RSASignatureTarget(pub BigUintTarget);
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Verifies an RSASSA-PKCS1-v1_5 signature with SHA-512 on `msg`, a sequence of bytes each
+    /// given as 8 little-endian bits. Public exponents are usually constants, such as 65537, in
+    /// which case the exponentiation only needs a few modular multiplications.
+    pub fn verify_rsa_pkcs1v15(
+        &mut self,
+        msg: &[BoolTarget],
+        sig: &RSASignatureTarget,
+        pk: &RSAPublicKeyTarget,
+    ) {
+        let digest = self.sha512(msg);
+        self.verify_rsa_pkcs1v15_digest(&digest, &SHA512_DIGEST_INFO_PREFIX, sig, pk);
+    }
+
+    /// Verifies an RSASSA-PKCS1-v1_5 signature on a message with the given digest, for a hash
+    /// function identified by `digest_info_prefix`, such as `SHA256_DIGEST_INFO_PREFIX`. The digest
+    /// is given as bytes of 8 little-endian bits. The modulus may have any number of limbs, as long
+    /// as its top bit is set and it is long enough to hold the encoded message.
+    pub fn verify_rsa_pkcs1v15_digest(
+        &mut self,
+        digest: &[BoolTarget],
+        digest_info_prefix: &[u8],
+        sig: &RSASignatureTarget,
+        pk: &RSAPublicKeyTarget,
+    ) {
+        let num_limbs = pk.n.num_limbs();
+        assert_eq!(sig.0.num_limbs(), num_limbs);
+
+        // The modulus must fill its limbs exactly, as the encoded message's length depends on it.
+        let top_limb_bits = self.split_le(pk.n.get_limb(num_limbs - 1).0, 32);
+        self.assert_one(top_limb_bits[31].target);
+
+        // RFC 8017 requires the signature to be less than `n`. It suffices to check `s <= n`, since
+        // `s = n` gives `s^e mod n = 0`, which is not a valid encoded message.
+        let s_le_n = self.cmp_biguint(&sig.0, &pk.n);
+        self.assert_one(s_le_n.target);
+
+        let m = self.mod_exp_biguint(&sig.0, &pk.e, &pk.n);
+        let encoded_message = self.pkcs1v15_encode(digest, digest_info_prefix, num_limbs * 4);
+        self.connect_biguint(&m, &encoded_message);
+    }
+
+    /// Returns the PKCS #1 v1.5 encoding `0x00 || 0x01 || 0xff..ff || 0x00 || DigestInfo` of a
+    /// digest, given as bytes of 8 little-endian bits, interpreted as a big-endian integer of
+    /// `modulus_bytes` bytes.
+    fn pkcs1v15_encode(
+        &mut self,
+        digest: &[BoolTarget],
+        digest_info_prefix: &[u8],
+        modulus_bytes: usize,
+    ) -> BigUintTarget {
+        assert_eq!(digest.len() % 32, 0, "Digests must fill whole limbs");
+        let digest_bytes = digest.len() / 8;
+        // RFC 8017 requires at least 8 bytes of `0xff` padding.
+        assert!(
+            modulus_bytes >= digest_bytes + digest_info_prefix.len() + 11,
+            "The modulus is too short for this hash function"
+        );
+
+        // Everything but the digest itself is constant.
+        let mut constant_bytes = vec![0xff; modulus_bytes];
+        constant_bytes[0] = 0x00;
+        constant_bytes[1] = 0x01;
+        let prefix_start = modulus_bytes - digest_bytes - digest_info_prefix.len();
+        constant_bytes[prefix_start - 1] = 0x00;
+        constant_bytes[prefix_start..modulus_bytes - digest_bytes]
+            .copy_from_slice(digest_info_prefix);
+        constant_bytes[modulus_bytes - digest_bytes..].fill(0x00);
+        let constant_part = self.constant_biguint(&BigUint::from_bytes_be(&constant_bytes));
+
+        // The digest occupies the least significant bytes, in big-endian order, so reversing its
+        // bytes gives the little-endian bits of the low limbs.
+        let digest_num_limbs = digest_bytes / 4;
+        let reversed_bytes: Vec<&[BoolTarget]> = digest.chunks(8).rev().collect();
+        let mut limbs: Vec<U32Target> = reversed_bytes
+            .chunks(4)
+            .map(|limb_bytes| U32Target(self.le_sum(limb_bytes.iter().flat_map(|b| b.iter()))))
+            .collect();
+        limbs.extend_from_slice(&constant_part.limbs[digest_num_limbs..]);
+
+        BigUintTarget { limbs }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use num::{BigUint, FromPrimitive, Num};
+    use rand::{thread_rng, Rng};
+    use sha2::{Digest, Sha256, Sha512};
+
+    use crate::gadgets::rsa::{
+        RSAPublicKeyTarget, RSASignatureTarget, SHA256_DIGEST_INFO_PREFIX,
+        SHA512_DIGEST_INFO_PREFIX,
+    };
+    use crate::iop::target::BoolTarget;
+    use crate::iop::witness::{PartialWitness, Witness};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    // A 2048-bit key with public exponent 65537, generated for this test.
+    const MODULUS_HEX: &str = "b9f54d9080a36ca8024dbae7d23e4716c5da56d9eab616bf00120ec6da6f1587a9e306a4c31cb224524a1398deae51e8ef310591c6c0381d2649e1e2b8b4f2ec93ed2121d8bd9e802a2e49c7106dce69a2240eefe2e20629e3b80dc1f1d81878c02a03e1fe4157aa73e444c3c47616171b75be0e5ac6a4f83498f13ff4d610b9e400194da05a86617815fa22e546efff05636edc0b02b49f9c502929a92b2f1898a23cf32010ec9a193c9369e294e546bfbfea1bcdc68d70f3db1791b00ebf3e7ab1f2d524637ef4301e0a68eb85c2aee313b24e130aaee9312287ae6ae098e3ed5b2dae90ed6954c95f27829a542da7f4f8bedb7c3c9872a6288fdbb4c3d89b";
+    const PRIVATE_EXPONENT_HEX: &str = "8df191c05080ee4a9c5f8ae0b359f85788b4ee00af2948d9888b401e47d3ed223dea5e42dbf00686b50d784203101ad3ebe88670ccbe22d71547e615729a24a7b30e9970c5898ff812ba7c7467b4f98f2645d1e5085131153e8e5a6a0559c6ec3cfa95362726e76ce3c3853dcdb3b98eefd60339dfceab540e8a03f4a6c5d3c353d6b775048ba4a276fae1e178148dc683315b72cc1d1972e0af3d1f7413d5715afca39f33714b7abbcbe87aed4b0f92a400a9afeaec533845f9c420e9ac35d90090493cacfb0ea78e61a8c0ab6525f945a64958b2a946afe01b46391e1de4c5289cf8f03e4c588dff8c5925358043b10347953dafa00e97f4ca6f37a426c541";
+
+    // A 512-bit key with public exponent 65537, generated for the cheaper tests. It is just long
+    // enough for SHA-256 signatures.
+    const SMALL_MODULUS_HEX: &str = "ac2d46064459fc8f3bb131d870be1cb4b13339f364fbe3b9f287a2cb6a7d4739702e885928fb29f7cae25da893847d6797073dcf71a52d6025f5ceb79e089c71";
+    const SMALL_PRIVATE_EXPONENT_HEX: &str = "79eb492590eb039b4d52891051ad5d585a0e032025c6f417acf47cc37bf1a02793695b1775ea2f99d7e8d99ccff4f6fa5cf4ad7db6eeef361e0b49bbdaad8aad";
+
+    fn sign(digest_info_prefix: &[u8], digest: &[u8], n: &BigUint, d: &BigUint) -> BigUint {
+        let mut t = digest_info_prefix.to_vec();
+        t.extend(digest);
+
+        let modulus_bytes = (n.bits() as usize + 7) / 8;
+        let mut em = vec![0x00, 0x01];
+        em.resize(modulus_bytes - t.len() - 1, 0xff);
+        em.push(0x00);
+        em.extend(t);
+
+        BigUint::from_bytes_be(&em).modpow(d, n)
+    }
+
+    /// Adds virtual targets for `bytes`, as 8 little-endian bits each, and sets them in `pw`.
+    fn add_byte_targets(
+        builder: &mut CircuitBuilder<F, D>,
+        pw: &mut PartialWitness<F>,
+        bytes: &[u8],
+    ) -> Vec<BoolTarget> {
+        let targets: Vec<_> = (0..8 * bytes.len())
+            .map(|_| builder.add_virtual_bool_target_safe())
+            .collect();
+        for (byte_targets, byte) in targets.chunks(8).zip(bytes) {
+            for (i, &t) in byte_targets.iter().enumerate() {
+                pw.set_bool_target(t, (byte >> i) & 1 != 0);
+            }
+        }
+        targets
+    }
+
+    /// Proves that `sig` is a valid signature on the SHA-256 digest of `msg` under the small key.
+    fn prove_small_key_sha256(msg: &[u8], sig: &BigUint) -> Result<()> {
+        let config = CircuitConfig::standard_ecc_config();
+
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let n = BigUint::from_str_radix(SMALL_MODULUS_HEX, 16).unwrap();
+        let digest_targets = add_byte_targets(&mut builder, &mut pw, &Sha256::digest(msg));
+
+        let pk_target = RSAPublicKeyTarget {
+            n: builder.constant_biguint(&n),
+            e: builder.constant_biguint(&BigUint::from_u32(65537).unwrap()),
+        };
+        let sig_target =
+            RSASignatureTarget(builder.add_virtual_biguint_target(n.to_u32_digits().len()));
+        pw.set_biguint_target(&sig_target.0, sig);
+
+        builder.verify_rsa_pkcs1v15_digest(
+            &digest_targets,
+            &SHA256_DIGEST_INFO_PREFIX,
+            &sig_target,
+            &pk_target,
+        );
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_rsa_pkcs1v15_sha256_small_key() -> Result<()> {
+        let mut rng = thread_rng();
+        let msg: Vec<u8> = (0..100).map(|_| rng.gen()).collect();
+        let n = BigUint::from_str_radix(SMALL_MODULUS_HEX, 16).unwrap();
+        let d = BigUint::from_str_radix(SMALL_PRIVATE_EXPONENT_HEX, 16).unwrap();
+        let s = sign(&SHA256_DIGEST_INFO_PREFIX, &Sha256::digest(&msg), &n, &d);
+
+        prove_small_key_sha256(&msg, &s)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rsa_pkcs1v15_wrong_message() {
+        let mut rng = thread_rng();
+        let msg: Vec<u8> = (0..100).map(|_| rng.gen()).collect();
+        let mut other_msg = msg.clone();
+        other_msg[0] ^= 1;
+        let n = BigUint::from_str_radix(SMALL_MODULUS_HEX, 16).unwrap();
+        let d = BigUint::from_str_radix(SMALL_PRIVATE_EXPONENT_HEX, 16).unwrap();
+        let s = sign(
+            &SHA256_DIGEST_INFO_PREFIX,
+            &Sha256::digest(&other_msg),
+            &n,
+            &d,
+        );
+
+        prove_small_key_sha256(&msg, &s).unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rsa_pkcs1v15_circuit() -> Result<()> {
+        let config = CircuitConfig::standard_ecc_config();
+
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let mut rng = thread_rng();
+        let msg: Vec<u8> = (0..100).map(|_| rng.gen()).collect();
+        let n = BigUint::from_str_radix(MODULUS_HEX, 16).unwrap();
+        let d = BigUint::from_str_radix(PRIVATE_EXPONENT_HEX, 16).unwrap();
+        let s = sign(&SHA512_DIGEST_INFO_PREFIX, &Sha512::digest(&msg), &n, &d);
+
+        let msg_targets = add_byte_targets(&mut builder, &mut pw, &msg);
+
+        let pk_target = RSAPublicKeyTarget {
+            n: builder.constant_biguint(&n),
+            e: builder.constant_biguint(&BigUint::from_u32(65537).unwrap()),
+        };
+        let sig_target =
+            RSASignatureTarget(builder.add_virtual_biguint_target(n.to_u32_digits().len()));
+        pw.set_biguint_target(&sig_target.0, &s);
+
+        builder.verify_rsa_pkcs1v15(&msg_targets, &sig_target, &pk_target);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        verify(proof, &data.verifier_only, &data.common)
+    }
+}
@@ -0,0 +1,332 @@
+use plonky2_field::extension_field::Extendable;
+use plonky2_field::field_types::Field;
+
+use crate::hash::hash_types::RichField;
+use crate::iop::target::BoolTarget;
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+/// The SHA-512 block size, in bytes.
+const BLOCK_BYTES: usize = 128;
+
+const INITIAL_HASH: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const ROUND_CONSTANTS: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+/// A 64-bit word, as 64 little-endian bits.
+type WordTarget = Vec<BoolTarget>;
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Computes the SHA-512 hash of `input`. As with `keccak256`, the input is a sequence of bytes,
+    /// each given as 8 little-endian bits, and the 64-byte output is given in the same format.
+    pub fn sha512(&mut self, input: &[BoolTarget]) -> Vec<BoolTarget> {
+        assert_eq!(input.len() % 8, 0, "Input must be a whole number of bytes");
+        let input_bytes = input.len() / 8;
+
+        // Append a 1 bit, zeros, and the 128-bit big-endian length in bits.
+        let mut padding = vec![0x80u8];
+        padding.resize(
+            1 + (2 * BLOCK_BYTES - 17 - input_bytes % BLOCK_BYTES) % BLOCK_BYTES,
+            0,
+        );
+        padding.extend((8 * input_bytes as u128).to_be_bytes());
+        let mut padded = input.to_vec();
+        for byte in padding {
+            for i in 0..8 {
+                padded.push(self.constant_bool((byte >> i) & 1 != 0));
+            }
+        }
+
+        let mut state: Vec<WordTarget> = INITIAL_HASH
+            .iter()
+            .map(|&h| self.constant_word(h))
+            .collect();
+        for block in padded.chunks(8 * BLOCK_BYTES) {
+            self.sha512_compress(&mut state, block);
+        }
+
+        // Serialize each word in big-endian order.
+        state
+            .iter()
+            .flat_map(|word| word.chunks(8).rev().flatten().copied().collect::<Vec<_>>())
+            .collect()
+    }
+
+    fn sha512_compress(&mut self, state: &mut [WordTarget], block: &[BoolTarget]) {
+        let _false = self._false();
+
+        // Each word is read from 8 bytes in big-endian order.
+        let mut w: Vec<WordTarget> = block
+            .chunks(64)
+            .map(|word_bytes| word_bytes.chunks(8).rev().flatten().copied().collect())
+            .collect();
+        for t in 16..80 {
+            let s0 = self.xor_words(&[
+                rotate_right(&w[t - 15], 1),
+                rotate_right(&w[t - 15], 8),
+                shift_right(&w[t - 15], 7, _false),
+            ]);
+            let s1 = self.xor_words(&[
+                rotate_right(&w[t - 2], 19),
+                rotate_right(&w[t - 2], 61),
+                shift_right(&w[t - 2], 6, _false),
+            ]);
+            let w_t = self.add_words(&[s1, w[t - 7].clone(), s0, w[t - 16].clone()], 0);
+            w.push(w_t);
+        }
+
+        let mut vars = state.to_vec();
+        for (w_t, &k_t) in w.iter().zip(&ROUND_CONSTANTS) {
+            let [a, b, c, d, e, f, g, h]: [WordTarget; 8] = vars.try_into().unwrap();
+
+            let big_s1 = self.xor_words(&[
+                rotate_right(&e, 14),
+                rotate_right(&e, 18),
+                rotate_right(&e, 41),
+            ]);
+            // ch = (e & f) ^ (!e & g) = g + e (f - g)
+            let ch: WordTarget = (0..64)
+                .map(|i| {
+                    let f_minus_g = self.sub(f[i].target, g[i].target);
+                    let res = self.mul_add(e[i].target, f_minus_g, g[i].target);
+                    BoolTarget::new_unsafe(res)
+                })
+                .collect();
+            let t1 = self.add_words(&[h, big_s1, ch, w_t.clone()], k_t);
+
+            let big_s0 = self.xor_words(&[
+                rotate_right(&a, 28),
+                rotate_right(&a, 34),
+                rotate_right(&a, 39),
+            ]);
+            // maj = (a & b) ^ (a & c) ^ (b & c) = ab + c (a ^ b), since `ab` and `a ^ b` are never
+            // both set.
+            let maj: WordTarget = (0..64)
+                .map(|i| {
+                    let ab = self.mul(a[i].target, b[i].target);
+                    let a_xor_b = self.xor(a[i], b[i]);
+                    let res = self.mul_add(c[i].target, a_xor_b.target, ab);
+                    BoolTarget::new_unsafe(res)
+                })
+                .collect();
+            let t2 = self.add_words(&[big_s0, maj], 0);
+
+            let new_e = self.add_words(&[d, t1.clone()], 0);
+            let new_a = self.add_words(&[t1, t2], 0);
+            vars = vec![new_a, a, b, c, new_e, e, f, g];
+        }
+
+        for (h, v) in state.iter_mut().zip(vars) {
+            *h = self.add_words(&[h.clone(), v], 0);
+        }
+    }
+
+    fn constant_word(&mut self, x: u64) -> WordTarget {
+        (0..64)
+            .map(|i| self.constant_bool((x >> i) & 1 != 0))
+            .collect()
+    }
+
+    fn xor_words(&mut self, words: &[WordTarget]) -> WordTarget {
+        (0..64)
+            .map(|i| {
+                let mut acc = words[0][i];
+                for word in &words[1..] {
+                    acc = self.xor(acc, word[i]);
+                }
+                acc
+            })
+            .collect()
+    }
+
+    /// Adds `words` and `constant` modulo `2^64`. Each word is split into 32-bit halves, which are
+    /// summed as field elements, and the sums are split into bits again to discard overflow.
+    fn add_words(&mut self, words: &[WordTarget], constant: u64) -> WordTarget {
+        // The sum of each half, including the constant and the carry from the low half, must fit
+        // in `32 + CARRY_BITS` bits.
+        const CARRY_BITS: usize = 3;
+        assert!(words.len() + 2 <= 1 << CARRY_BITS);
+
+        let (lo_halves, hi_halves): (Vec<_>, Vec<_>) = words
+            .iter()
+            .map(|w| (self.le_sum(w[..32].iter()), self.le_sum(w[32..].iter())))
+            .unzip();
+
+        let lo_constant = self.constant(F::from_canonical_u64(constant & 0xFFFFFFFF));
+        let lo_sum = lo_halves
+            .into_iter()
+            .fold(lo_constant, |acc, x| self.add(acc, x));
+        let lo_bits = self.split_le(lo_sum, 32 + CARRY_BITS);
+        let carry = self.le_sum(lo_bits[32..].iter());
+
+        let hi_constant = self.constant(F::from_canonical_u64(constant >> 32));
+        let hi_sum = hi_halves
+            .into_iter()
+            .fold(hi_constant, |acc, x| self.add(acc, x));
+        let hi_sum = self.add(hi_sum, carry);
+        let hi_bits = self.split_le(hi_sum, 32 + CARRY_BITS);
+
+        lo_bits[..32]
+            .iter()
+            .chain(&hi_bits[..32])
+            .copied()
+            .collect()
+    }
+}
+
+fn rotate_right(x: &[BoolTarget], n: usize) -> WordTarget {
+    (0..64).map(|i| x[(i + n) % 64]).collect()
+}
+
+fn shift_right(x: &[BoolTarget], n: usize, _false: BoolTarget) -> WordTarget {
+    (0..64)
+        .map(|i| if i + n < 64 { x[i + n] } else { _false })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use rand::{thread_rng, Rng};
+    use sha2::{Digest, Sha512};
+
+    use crate::iop::witness::{PartialWitness, Witness};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    fn to_bits(bytes: &[u8]) -> Vec<bool> {
+        bytes
+            .iter()
+            .flat_map(|&b| (0..8).map(move |i| (b >> i) & 1 != 0))
+            .collect()
+    }
+
+    #[test]
+    #[ignore]
+    fn test_sha512() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_ecc_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        // An empty input, an input which exactly fills a block once padded, and one whose padding
+        // spills into a second block.
+        let mut rng = thread_rng();
+        for len in [0, 111, 112] {
+            let msg: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            let expected = to_bits(&Sha512::digest(&msg));
+
+            let msg_targets: Vec<_> = (0..8 * len)
+                .map(|_| builder.add_virtual_bool_target_safe())
+                .collect();
+            for (&t, b) in msg_targets.iter().zip(to_bits(&msg)) {
+                pw.set_bool_target(t, b);
+            }
+
+            let hash = builder.sha512(&msg_targets);
+            for (h, e) in hash.into_iter().zip(expected) {
+                let e = builder.constant_bool(e);
+                builder.connect(h.target, e.target);
+            }
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+}
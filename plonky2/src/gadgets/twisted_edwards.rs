@@ -0,0 +1,270 @@
+use plonky2_field::extension_field::Extendable;
+use plonky2_field::field_types::Field;
+
+use crate::curve::twisted_edwards::{EdwardsPoint, TwistedEdwardsCurve};
+use crate::gadgets::nonnative::NonNativeTarget;
+use crate::hash::hash_types::RichField;
+use crate::iop::target::BoolTarget;
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+/// A Target representing an affine point on the twisted Edwards curve `C`. Since the addition law is
+/// complete, any valid point, including the identity, may be used in arithmetic.
+#[derive(Clone, Debug)]
+pub struct EdwardsPointTarget<C: TwistedEdwardsCurve> {
+    pub x: NonNativeTarget<C::BaseField>,
+    pub y: NonNativeTarget<C::BaseField>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    pub fn constant_edwards_point<C: TwistedEdwardsCurve>(
+        &mut self,
+        point: EdwardsPoint<C>,
+    ) -> EdwardsPointTarget<C> {
+        EdwardsPointTarget {
+            x: self.constant_nonnative(point.x),
+            y: self.constant_nonnative(point.y),
+        }
+    }
+
+    pub fn connect_edwards_point<C: TwistedEdwardsCurve>(
+        &mut self,
+        lhs: &EdwardsPointTarget<C>,
+        rhs: &EdwardsPointTarget<C>,
+    ) {
+        self.connect_nonnative(&lhs.x, &rhs.x);
+        self.connect_nonnative(&lhs.y, &rhs.y);
+    }
+
+    pub fn add_virtual_edwards_point_target<C: TwistedEdwardsCurve>(
+        &mut self,
+    ) -> EdwardsPointTarget<C> {
+        let x = self.add_virtual_nonnative_target();
+        let y = self.add_virtual_nonnative_target();
+
+        EdwardsPointTarget { x, y }
+    }
+
+    pub fn edwards_assert_valid<C: TwistedEdwardsCurve>(&mut self, p: &EdwardsPointTarget<C>) {
+        let a = self.constant_nonnative(C::A);
+        let d = self.constant_nonnative(C::D);
+        let one = self.constant_nonnative(C::BaseField::ONE);
+
+        let x_squared = self.mul_nonnative(&p.x, &p.x);
+        let y_squared = self.mul_nonnative(&p.y, &p.y);
+        let a_x_squared = self.mul_nonnative(&a, &x_squared);
+        let lhs = self.add_nonnative(&a_x_squared, &y_squared);
+
+        let x_squared_y_squared = self.mul_nonnative(&x_squared, &y_squared);
+        let d_x_squared_y_squared = self.mul_nonnative(&d, &x_squared_y_squared);
+        let rhs = self.add_nonnative(&one, &d_x_squared_y_squared);
+
+        self.connect_nonnative(&lhs, &rhs);
+    }
+
+    pub fn edwards_neg<C: TwistedEdwardsCurve>(
+        &mut self,
+        p: &EdwardsPointTarget<C>,
+    ) -> EdwardsPointTarget<C> {
+        let neg_x = self.neg_nonnative(&p.x);
+        EdwardsPointTarget {
+            x: neg_x,
+            y: p.y.clone(),
+        }
+    }
+
+    /// Adds two points with the complete twisted Edwards addition law.
+    pub fn edwards_add<C: TwistedEdwardsCurve>(
+        &mut self,
+        p1: &EdwardsPointTarget<C>,
+        p2: &EdwardsPointTarget<C>,
+    ) -> EdwardsPointTarget<C> {
+        let EdwardsPointTarget { x: x1, y: y1 } = p1;
+        let EdwardsPointTarget { x: x2, y: y2 } = p2;
+
+        let a = self.constant_nonnative(C::A);
+        let d = self.constant_nonnative(C::D);
+        let one = self.constant_nonnative(C::BaseField::ONE);
+
+        let x1_y2 = self.mul_nonnative(x1, y2);
+        let y1_x2 = self.mul_nonnative(y1, x2);
+        let x1_x2 = self.mul_nonnative(x1, x2);
+        let y1_y2 = self.mul_nonnative(y1, y2);
+        let x1_x2_y1_y2 = self.mul_nonnative(&x1_x2, &y1_y2);
+        let d_x1_x2_y1_y2 = self.mul_nonnative(&d, &x1_x2_y1_y2);
+
+        // x3 = (x1 y2 + y1 x2) / (1 + d x1 x2 y1 y2)
+        let x3_numerator = self.add_nonnative(&x1_y2, &y1_x2);
+        let x3_denominator = self.add_nonnative(&one, &d_x1_x2_y1_y2);
+        let x3_denominator_inv = self.inv_nonnative(&x3_denominator);
+        let x3 = self.mul_nonnative(&x3_numerator, &x3_denominator_inv);
+
+        // y3 = (y1 y2 - a x1 x2) / (1 - d x1 x2 y1 y2)
+        let a_x1_x2 = self.mul_nonnative(&a, &x1_x2);
+        let y3_numerator = self.sub_nonnative(&y1_y2, &a_x1_x2);
+        let y3_denominator = self.sub_nonnative(&one, &d_x1_x2_y1_y2);
+        let y3_denominator_inv = self.inv_nonnative(&y3_denominator);
+        let y3 = self.mul_nonnative(&y3_numerator, &y3_denominator_inv);
+
+        EdwardsPointTarget { x: x3, y: y3 }
+    }
+
+    /// Doubles a point, using the curve equation to simplify the denominators of `edwards_add`.
+    pub fn edwards_double<C: TwistedEdwardsCurve>(
+        &mut self,
+        p: &EdwardsPointTarget<C>,
+    ) -> EdwardsPointTarget<C> {
+        let EdwardsPointTarget { x, y } = p;
+
+        let a = self.constant_nonnative(C::A);
+        let two = self.constant_nonnative(C::BaseField::TWO);
+
+        let x_y = self.mul_nonnative(x, y);
+        let x_squared = self.mul_nonnative(x, x);
+        let y_squared = self.mul_nonnative(y, y);
+        let a_x_squared = self.mul_nonnative(&a, &x_squared);
+
+        // x3 = 2 x y / (a x^2 + y^2)
+        let x3_numerator = self.add_nonnative(&x_y, &x_y);
+        let x3_denominator = self.add_nonnative(&a_x_squared, &y_squared);
+        let x3_denominator_inv = self.inv_nonnative(&x3_denominator);
+        let x3 = self.mul_nonnative(&x3_numerator, &x3_denominator_inv);
+
+        // y3 = (y^2 - a x^2) / (2 - a x^2 - y^2)
+        let y3_numerator = self.sub_nonnative(&y_squared, &a_x_squared);
+        let y3_denominator = self.sub_nonnative(&two, &x3_denominator);
+        let y3_denominator_inv = self.inv_nonnative(&y3_denominator);
+        let y3 = self.mul_nonnative(&y3_numerator, &y3_denominator_inv);
+
+        EdwardsPointTarget { x: x3, y: y3 }
+    }
+
+    /// Returns `p` if `b` is true, and `q` otherwise.
+    pub fn edwards_select<C: TwistedEdwardsCurve>(
+        &mut self,
+        b: BoolTarget,
+        p: &EdwardsPointTarget<C>,
+        q: &EdwardsPointTarget<C>,
+    ) -> EdwardsPointTarget<C> {
+        let not_b = self.not(b);
+
+        let x_if_b = self.mul_nonnative_by_bool(&p.x, b);
+        let x_if_not_b = self.mul_nonnative_by_bool(&q.x, not_b);
+        let y_if_b = self.mul_nonnative_by_bool(&p.y, b);
+        let y_if_not_b = self.mul_nonnative_by_bool(&q.y, not_b);
+
+        EdwardsPointTarget {
+            x: self.add_nonnative(&x_if_b, &x_if_not_b),
+            y: self.add_nonnative(&y_if_b, &y_if_not_b),
+        }
+    }
+
+    /// Returns the identity `(0, 1)`. Its coordinates are given the usual number of limbs, rather
+    /// than those of the constants, so that it can be doubled.
+    pub fn edwards_zero<C: TwistedEdwardsCurve>(&mut self) -> EdwardsPointTarget<C> {
+        let zero = self.constant_edwards_point(EdwardsPoint::<C>::ZERO);
+        let result = self.add_virtual_edwards_point_target();
+        self.connect_edwards_point(&zero, &result);
+        result
+    }
+
+    /// Computes `n p` with double-and-add, starting from the most significant bit.
+    pub fn edwards_scalar_mul<C: TwistedEdwardsCurve>(
+        &mut self,
+        p: &EdwardsPointTarget<C>,
+        n: &NonNativeTarget<C::ScalarField>,
+    ) -> EdwardsPointTarget<C> {
+        let bits = self.split_nonnative_to_bits(n);
+
+        let mut result = self.edwards_zero();
+        for &bit in bits.iter().rev() {
+            result = self.edwards_double(&result);
+            let result_plus_p = self.edwards_add(&result, p);
+            result = self.edwards_select(bit, &result_plus_p, &result);
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use plonky2_field::curve25519_scalar::Curve25519Scalar;
+    use plonky2_field::field_types::Field;
+
+    use crate::curve::ed25519::Ed25519;
+    use crate::curve::twisted_edwards::{EdwardsPoint, TwistedEdwardsCurve};
+    use crate::iop::witness::PartialWitness;
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    #[test]
+    fn test_edwards_add_and_double() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_ecc_config();
+
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let g = Ed25519::GENERATOR;
+        let p = g.mul(Curve25519Scalar::rand());
+        let g_target = builder.constant_edwards_point(g);
+        let p_target = builder.constant_edwards_point(p);
+        builder.edwards_assert_valid(&g_target);
+        builder.edwards_assert_valid(&p_target);
+
+        let sum = builder.edwards_add(&g_target, &p_target);
+        let expected_sum = builder.constant_edwards_point(g + p);
+        builder.connect_edwards_point(&sum, &expected_sum);
+
+        // Adding a point to its negation gives the identity.
+        let neg_p_target = builder.edwards_neg(&p_target);
+        let zero = builder.edwards_add(&p_target, &neg_p_target);
+        let expected_zero = builder.edwards_zero();
+        builder.connect_edwards_point(&zero, &expected_zero);
+
+        let double = builder.edwards_double(&p_target);
+        let expected_double = builder.constant_edwards_point(p.double());
+        builder.connect_edwards_point(&double, &expected_double);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    #[ignore]
+    fn test_edwards_scalar_mul() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_ecc_config();
+
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let g = Ed25519::GENERATOR;
+        let n = Curve25519Scalar::rand();
+        let expected = g.mul(n);
+        assert_ne!(expected, EdwardsPoint::ZERO);
+
+        let g_target = builder.constant_edwards_point(g);
+        let n_target = builder.constant_nonnative(n);
+        let actual = builder.edwards_scalar_mul(&g_target, &n_target);
+
+        let expected_target = builder.constant_edwards_point(expected);
+        builder.connect_edwards_point(&actual, &expected_target);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+}
@@ -1,13 +1,16 @@
 use std::collections::HashMap;
 
 use itertools::Itertools;
-use num::{BigUint, FromPrimitive, Zero};
+use num::bigint::Sign;
+use num::{BigInt, BigUint, FromPrimitive, Zero};
 use plonky2_field::extension_field::{Extendable, FieldExtension};
 use plonky2_field::field_types::{Field, PrimeField};
 
 use crate::fri::structure::{FriOpenings, FriOpeningsTarget};
 use crate::fri::witness_util::set_fri_proof_target;
+use crate::gadgets::arithmetic_signed::{I32Target, I64Target};
 use crate::gadgets::arithmetic_u32::U32Target;
+use crate::gadgets::bigint::BigIntTarget;
 use crate::gadgets::biguint::BigUintTarget;
 use crate::gadgets::nonnative::NonNativeTarget;
 use crate::hash::hash_types::HashOutTarget;
@@ -79,12 +82,25 @@ pub trait Witness<F: Field> {
         result
     }
 
+    fn get_bigint_target(&self, target: BigIntTarget) -> BigInt
+    where
+        F: PrimeField,
+    {
+        let abs = self.get_biguint_target(target.abs);
+        if self.get_bool_target(target.sign) {
+            -BigInt::from(abs)
+        } else {
+            BigInt::from(abs)
+        }
+    }
+
     fn get_nonnative_target<FF: PrimeField>(&self, target: NonNativeTarget<FF>) -> FF
     where
         F: PrimeField,
     {
+        // The target may hold an unreduced representative.
         let val = self.get_biguint_target(target.value);
-        FF::from_biguint(val)
+        FF::from_biguint(val % FF::order())
     }
 
     fn get_hash_target(&self, ht: HashOutTarget) -> HashOut<F> {
@@ -160,11 +176,33 @@ pub trait Witness<F: Field> {
     }
 
     fn set_biguint_target(&mut self, target: &BigUintTarget, value: &BigUint) {
-        for (&lt, &l) in target.limbs.iter().zip(&value.to_u32_digits()) {
+        let mut limbs = value.to_u32_digits();
+        assert!(target.num_limbs() >= limbs.len());
+        limbs.resize(target.num_limbs(), 0);
+        for (&lt, &l) in target.limbs.iter().zip(&limbs) {
             self.set_u32_target(lt, l);
         }
     }
 
+    fn set_nonnative_target<FF: PrimeField>(&mut self, target: &NonNativeTarget<FF>, value: FF) {
+        self.set_biguint_target(&target.value, &value.to_canonical_biguint())
+    }
+
+    fn set_i32_target(&mut self, target: I32Target, value: i32) {
+        self.set_u32_target(target.0, value as u32)
+    }
+
+    fn set_i64_target(&mut self, target: I64Target, value: i64) {
+        let value = value as u64;
+        self.set_u32_target(target.0[0], value as u32);
+        self.set_u32_target(target.0[1], (value >> 32) as u32);
+    }
+
+    fn set_bigint_target(&mut self, target: &BigIntTarget, value: &BigInt) {
+        self.set_biguint_target(&target.abs, value.magnitude());
+        self.set_bool_target(target.sign, value.sign() == Sign::Minus);
+    }
+
     /// Set the targets in a `ProofWithPublicInputsTarget` to their corresponding values in a
     /// `ProofWithPublicInputs`.
     fn set_proof_with_pis_target<C: GenericConfig<D, F = F>, const D: usize>(
@@ -23,8 +23,9 @@ use crate::gates::gate::{CurrentSlot, Gate, GateInstance, GateRef, PrefixedGate}
 use crate::gates::gate_tree::Tree;
 use crate::gates::noop::NoopGate;
 use crate::gates::public_input::PublicInputGate;
-use crate::hash::hash_types::{HashOutTarget, MerkleCapTarget, RichField};
+use crate::hash::hash_types::{HashOut, HashOutTarget, MerkleCapTarget, RichField};
 use crate::hash::merkle_proofs::MerkleProofTarget;
+use crate::hash::merkle_tree::MerkleCap;
 use crate::iop::ext_target::ExtensionTarget;
 use crate::iop::generator::{
     CopyGenerator, RandomValueGenerator, SimpleGenerator, WitnessGenerator,
@@ -339,6 +340,22 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         constants.iter().map(|&c| self.constant(c)).collect()
     }
 
+    /// Returns a `HashOutTarget` whose elements are routable targets with the given constant values.
+    pub fn constant_hash(&mut self, h: HashOut<F>) -> HashOutTarget {
+        HashOutTarget {
+            elements: h.elements.map(|x| self.constant(x)),
+        }
+    }
+
+    /// Returns a `MerkleCapTarget` whose hashes are routable targets with the given constant
+    /// values.
+    pub fn constant_merkle_cap<H: Hasher<F, Hash = HashOut<F>>>(
+        &mut self,
+        cap: &MerkleCap<F, H>,
+    ) -> MerkleCapTarget {
+        MerkleCapTarget(cap.0.iter().map(|&h| self.constant_hash(h)).collect())
+    }
+
     pub fn constant_bool(&mut self, b: bool) -> BoolTarget {
         if b {
             self._true()
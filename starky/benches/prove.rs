@@ -0,0 +1,43 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use plonky2::field::field_types::Field;
+use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+use plonky2::util::timing::TimingTree;
+use starky::config::StarkConfig;
+use starky::fibonacci_stark::FibonacciStark;
+use starky::prover::prove;
+
+const D: usize = 2;
+type C = PoseidonGoldilocksConfig;
+type F = <C as GenericConfig<D>>::F;
+type S = FibonacciStark<F, D>;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("prove-fibonacci");
+    group.sample_size(10);
+
+    let config = StarkConfig::standard_fast_config();
+    for num_rows_log in [12, 14, 16] {
+        let num_rows = 1 << num_rows_log;
+        group.bench_with_input(BenchmarkId::from_parameter(num_rows), &num_rows, |b, _| {
+            let stark = S::new(num_rows);
+            let trace = stark.generate_trace(F::ZERO, F::ONE);
+            let public_inputs = [F::ZERO, F::ONE, trace[1].values[num_rows - 1]];
+            b.iter(|| {
+                prove::<F, C, S, D>(
+                    stark,
+                    &config,
+                    trace.clone(),
+                    public_inputs,
+                    &mut TimingTree::default(),
+                )
+                .unwrap()
+            });
+        });
+    }
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,114 @@
+//! Tools for locating constraint violations in a trace, to help debug trace generation.
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use plonky2::field::extension_field::Extendable;
+use plonky2::field::field_types::Field;
+use plonky2::field::polynomial::PolynomialValues;
+use plonky2::hash::hash_types::RichField;
+use plonky2_util::log2_strict;
+use rayon::prelude::*;
+
+use crate::constraint_consumer::{ConstraintConsumer, ConstraintKind};
+use crate::stark::Stark;
+use crate::vars::StarkEvaluationVars;
+
+/// A constraint which doesn't hold on some row of a trace.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct ConstraintViolation<F: Field> {
+    pub row: usize,
+    /// The index of the constraint, in the order in which constraints are emitted by
+    /// `eval_packed_base`.
+    pub index: usize,
+    pub kind: ConstraintKind,
+    /// The (nonzero) value of the constraint on this row, after filtering, i.e. multiplied by the
+    /// first-row, last-row or transition selector according to `kind`.
+    pub value: F,
+}
+
+impl<F: Field> Display for ConstraintViolation<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Constraint {} ({:?}) is violated on row {}, with value {}.",
+            self.index, self.kind, self.row, self.value
+        )
+    }
+}
+
+/// Evaluates the constraints of `stark` on every row of `trace`, and returns every constraint
+/// which doesn't hold, ordered by row and then by constraint index. Permutation arguments are not
+/// checked.
+pub fn check_constraints<F, S, const D: usize>(
+    stark: &S,
+    trace: &[PolynomialValues<F>],
+    public_inputs: [F; S::PUBLIC_INPUTS],
+) -> Vec<ConstraintViolation<F>>
+where
+    F: RichField + Extendable<D>,
+    S: Stark<F, D>,
+    [(); S::COLUMNS]:,
+    [(); S::PUBLIC_INPUTS]:,
+{
+    assert_eq!(
+        trace.len(),
+        S::COLUMNS,
+        "Trace width doesn't match with Stark."
+    );
+    let degree = trace[0].len();
+    let degree_bits = log2_strict(degree);
+    let preprocessed = stark.preprocessed_columns(degree_bits);
+    let row_offsets = stark.row_offsets();
+
+    let get_row = |i: isize| -> [F; S::COLUMNS] {
+        let i = i.rem_euclid(degree as isize) as usize;
+        trace
+            .iter()
+            .map(|col| col.values[i])
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    };
+    let get_preprocessed_row =
+        |i: usize| -> Vec<F> { preprocessed.iter().map(|col| col.values[i]).collect() };
+
+    let g = F::primitive_root_of_unity(degree_bits);
+    let last = g.inverse();
+    (0..degree)
+        .into_par_iter()
+        .flat_map_iter(|i| {
+            let window_values = row_offsets
+                .iter()
+                .map(|&offset| get_row(i as isize + offset))
+                .collect::<Vec<_>>();
+            let vars = StarkEvaluationVars {
+                local_values: &get_row(i as isize),
+                next_values: &get_row(i as isize + 1),
+                window_values: &window_values,
+                local_preprocessed_values: &get_preprocessed_row(i),
+                next_preprocessed_values: &get_preprocessed_row((i + 1) % degree),
+                public_inputs: &public_inputs,
+            };
+            // The accumulated value isn't used, so we don't need any alphas.
+            let mut consumer = ConstraintConsumer::new_recording(
+                vec![],
+                g.exp_u64(i as u64) - last,
+                F::from_bool(i == 0),
+                F::from_bool(i == degree - 1),
+            );
+            stark.eval_packed_base(vars, &mut consumer);
+            consumer
+                .recorded_constraints()
+                .into_iter()
+                .enumerate()
+                .filter(|(_, (_, value))| value.is_nonzero())
+                .map(move |(index, (kind, value))| ConstraintViolation {
+                    row: i,
+                    index,
+                    kind,
+                    value,
+                })
+        })
+        .collect()
+}
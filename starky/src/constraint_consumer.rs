@@ -7,6 +7,16 @@ use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::iop::target::Target;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 
+/// Describes which rows a constraint applies to.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ConstraintKind {
+    AllRows,
+    /// All rows except the last.
+    Transition,
+    FirstRow,
+    LastRow,
+}
+
 pub struct ConstraintConsumer<P: PackedField> {
     /// Random values used to combine multiple constraints into one.
     alphas: Vec<P::Scalar>,
@@ -26,6 +36,10 @@ pub struct ConstraintConsumer<P: PackedField> {
     /// The evaluation of the Lagrange basis polynomial which is nonzero at the point associated
     /// with the last trace row, and zero at other points in the subgroup.
     lagrange_basis_last: P,
+
+    /// If present, every emitted constraint is also recorded here, after filtering, along with its
+    /// kind. This is only used for debugging.
+    recorded_constraints: Option<Vec<(ConstraintKind, P)>>,
 }
 
 impl<P: PackedField> ConstraintConsumer<P> {
@@ -41,9 +55,31 @@ impl<P: PackedField> ConstraintConsumer<P> {
             z_last,
             lagrange_basis_first,
             lagrange_basis_last,
+            recorded_constraints: None,
         }
     }
 
+    /// Like `new`, but also records each emitted constraint, so that violated constraints can be
+    /// identified.
+    pub fn new_recording(
+        alphas: Vec<P::Scalar>,
+        z_last: P,
+        lagrange_basis_first: P,
+        lagrange_basis_last: P,
+    ) -> Self {
+        Self {
+            recorded_constraints: Some(Vec::new()),
+            ..Self::new(alphas, z_last, lagrange_basis_first, lagrange_basis_last)
+        }
+    }
+
+    /// Returns the filtered evaluations of all emitted constraints, in the order they were
+    /// emitted. Panics if this consumer was not created with `new_recording`.
+    pub fn recorded_constraints(self) -> Vec<(ConstraintKind, P)> {
+        self.recorded_constraints
+            .expect("Constraints were not recorded.")
+    }
+
     // TODO: Do this correctly.
     pub fn accumulators(self) -> Vec<P::Scalar> {
         self.constraint_accs
@@ -52,29 +88,49 @@ impl<P: PackedField> ConstraintConsumer<P> {
             .collect()
     }
 
+    /// Returns the accumulated constraints at every point of the packing, unlike `accumulators`,
+    /// which only returns those at the first point.
+    pub fn accumulators_packed(self) -> Vec<P> {
+        self.constraint_accs
+    }
+
     /// Add one constraint valid on all rows except the last.
     pub fn constraint_transition(&mut self, constraint: P) {
-        self.constraint(constraint * self.z_last);
+        self.add_constraint(ConstraintKind::Transition, constraint * self.z_last);
     }
 
     /// Add one constraint on all rows.
     pub fn constraint(&mut self, constraint: P) {
-        for (&alpha, acc) in self.alphas.iter().zip(&mut self.constraint_accs) {
-            *acc *= alpha;
-            *acc += constraint;
-        }
+        self.add_constraint(ConstraintKind::AllRows, constraint);
     }
 
     /// Add one constraint, but first multiply it by a filter such that it will only apply to the
     /// first row of the trace.
     pub fn constraint_first_row(&mut self, constraint: P) {
-        self.constraint(constraint * self.lagrange_basis_first);
+        self.add_constraint(
+            ConstraintKind::FirstRow,
+            constraint * self.lagrange_basis_first,
+        );
     }
 
     /// Add one constraint, but first multiply it by a filter such that it will only apply to the
     /// last row of the trace.
     pub fn constraint_last_row(&mut self, constraint: P) {
-        self.constraint(constraint * self.lagrange_basis_last);
+        self.add_constraint(
+            ConstraintKind::LastRow,
+            constraint * self.lagrange_basis_last,
+        );
+    }
+
+    /// Add one constraint which has already been filtered.
+    fn add_constraint(&mut self, kind: ConstraintKind, filtered_constraint: P) {
+        for (&alpha, acc) in self.alphas.iter().zip(&mut self.constraint_accs) {
+            *acc *= alpha;
+            *acc += filtered_constraint;
+        }
+        if let Some(recorded_constraints) = &mut self.recorded_constraints {
+            recorded_constraints.push((kind, filtered_constraint));
+        }
     }
 }
 
@@ -9,15 +9,17 @@ use plonky2::plonk::circuit_builder::CircuitBuilder;
 use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
 use crate::permutation::PermutationPair;
 use crate::stark::Stark;
-use crate::util::trace_rows_to_poly_values;
+use crate::trace::TraceBuilder;
 use crate::vars::{StarkEvaluationTargets, StarkEvaluationVars};
 
 /// Toy STARK system used for testing.
 /// Computes a Fibonacci sequence with state `[x0, x1, i, j]` using the state transition
 /// `x0' <- x1, x1' <- x0 + x1, i' <- i+1, j' <- j+1`.
-/// Note: The `i, j` columns are only used to test the permutation argument.
+/// Note: The `i, j` columns are only used to test the permutation argument. The `i` column is also
+/// checked against a preprocessed column containing the row indices, to test preprocessed columns.
+/// The redundant constraint `x0' = x0 + x0_prev` is only used to test multi-row windows.
 #[derive(Copy, Clone)]
-struct FibonacciStark<F: RichField + Extendable<D>, const D: usize> {
+pub struct FibonacciStark<F: RichField + Extendable<D>, const D: usize> {
     num_rows: usize,
     _phantom: PhantomData<F>,
 }
@@ -31,7 +33,7 @@ impl<F: RichField + Extendable<D>, const D: usize> FibonacciStark<F, D> {
     // `num_rows`-th Fibonacci number.
     const PI_INDEX_RES: usize = 2;
 
-    fn new(num_rows: usize) -> Self {
+    pub fn new(num_rows: usize) -> Self {
         Self {
             num_rows,
             _phantom: PhantomData,
@@ -39,25 +41,26 @@ impl<F: RichField + Extendable<D>, const D: usize> FibonacciStark<F, D> {
     }
 
     /// Generate the trace using `x0, x1, 0, 1` as initial state values.
-    fn generate_trace(&self, x0: F, x1: F) -> Vec<PolynomialValues<F>> {
-        let mut trace_rows = (0..self.num_rows)
-            .scan([x0, x1, F::ZERO, F::ONE], |acc, _| {
-                let tmp = *acc;
-                acc[0] = tmp[1];
-                acc[1] = tmp[0] + tmp[1];
-                acc[2] = tmp[2] + F::ONE;
-                acc[3] = tmp[3] + F::ONE;
-                Some(tmp)
-            })
-            .collect::<Vec<_>>();
-        trace_rows[self.num_rows - 1][3] = F::ZERO; // So that column 2 and 3 are permutation of one another.
-        trace_rows_to_poly_values(trace_rows)
+    pub fn generate_trace_builder(&self, x0: F, x1: F) -> TraceBuilder<F, 4> {
+        let mut builder = TraceBuilder::with_capacity(self.num_rows);
+        let mut row = [x0, x1, F::ZERO, F::ONE];
+        for _ in 0..self.num_rows {
+            builder.push_row(row);
+            row = [row[1], row[0] + row[1], row[2] + F::ONE, row[3] + F::ONE];
+        }
+        builder.set(self.num_rows - 1, 3, F::ZERO); // So that column 2 and 3 are permutation of one another.
+        builder
+    }
+
+    pub fn generate_trace(&self, x0: F, x1: F) -> Vec<PolynomialValues<F>> {
+        self.generate_trace_builder(x0, x1).into_poly_values()
     }
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for FibonacciStark<F, D> {
     const COLUMNS: usize = 4;
     const PUBLIC_INPUTS: usize = 3;
+    const PREPROCESSED_COLUMNS: usize = 1;
 
     fn eval_packed_generic<FE, P, const D2: usize>(
         &self,
@@ -81,6 +84,12 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for FibonacciStar
         yield_constr.constraint_transition(
             vars.next_values[1] - vars.local_values[0] - vars.local_values[1],
         );
+        // i = row index
+        let row_index = vars.local_preprocessed_values[0];
+        yield_constr.constraint(vars.local_values[2] - row_index);
+        // x0' <- x0 + x0_prev, except on the first row, where the previous row wraps around.
+        let [prev_x0, local_x0, next_x0] = [0, 1, 2].map(|row| vars.window_values[row][0]);
+        yield_constr.constraint_transition(row_index * (next_x0 - local_x0 - prev_x0));
     }
 
     fn eval_ext_recursively(
@@ -108,12 +117,33 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for FibonacciStar
             builder.sub_extension(tmp, vars.local_values[1])
         };
         yield_constr.constraint_transition(builder, second_col_constraint);
+        // i = row index
+        let row_index_constraint =
+            builder.sub_extension(vars.local_values[2], vars.local_preprocessed_values[0]);
+        yield_constr.constraint(builder, row_index_constraint);
+        // x0' <- x0 + x0_prev, except on the first row, where the previous row wraps around.
+        let [prev_x0, local_x0, next_x0] = [0, 1, 2].map(|row| vars.window_values[row][0]);
+        let window_constraint = {
+            let tmp = builder.sub_extension(next_x0, local_x0);
+            let tmp = builder.sub_extension(tmp, prev_x0);
+            builder.mul_extension(vars.local_preprocessed_values[0], tmp)
+        };
+        yield_constr.constraint_transition(builder, window_constraint);
     }
 
     fn constraint_degree(&self) -> usize {
         2
     }
 
+    fn row_offsets(&self) -> Vec<isize> {
+        vec![-1, 0, 1]
+    }
+
+    fn preprocessed_columns(&self, degree_bits: usize) -> Vec<PolynomialValues<F>> {
+        let row_indices = (0..1 << degree_bits).map(F::from_canonical_usize).collect();
+        vec![PolynomialValues::new(row_indices)]
+    }
+
     fn permutation_pairs(&self) -> Vec<PermutationPair> {
         vec![PermutationPair::singletons(2, 3)]
     }
@@ -134,6 +164,8 @@ mod tests {
     use plonky2::util::timing::TimingTree;
 
     use crate::config::StarkConfig;
+    use crate::constraint_checker::{check_constraints, ConstraintViolation};
+    use crate::constraint_consumer::ConstraintKind;
     use crate::fibonacci_stark::FibonacciStark;
     use crate::proof::StarkProofWithPublicInputs;
     use crate::prover::prove;
@@ -142,7 +174,7 @@ mod tests {
         set_stark_proof_with_pis_target,
     };
     use crate::stark::Stark;
-    use crate::stark_testing::test_stark_low_degree;
+    use crate::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
     use crate::verifier::verify_stark_proof;
 
     fn fibonacci<F: Field>(n: usize, x0: F, x1: F) -> F {
@@ -172,6 +204,76 @@ mod tests {
         verify_stark_proof(stark, proof, &config)
     }
 
+    #[test]
+    fn test_fibonacci_stark_trace_check() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type S = FibonacciStark<F, D>;
+
+        let num_rows = 1 << 5;
+        let public_inputs = [F::ZERO, F::ONE, fibonacci(num_rows - 1, F::ZERO, F::ONE)];
+        let stark = S::new(num_rows);
+
+        let builder = stark.generate_trace_builder(public_inputs[0], public_inputs[1]);
+        assert!(builder
+            .into_poly_values_checked(&stark, public_inputs)
+            .is_ok());
+
+        let mut builder = stark.generate_trace_builder(public_inputs[0], public_inputs[1]);
+        builder.set(7, 1, F::TWO);
+        let err = builder
+            .into_poly_values_checked(&stark, public_inputs)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Constraint 4 (Transition) is violated on row 6"));
+    }
+
+    #[test]
+    fn test_fibonacci_stark_check_constraints() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type S = FibonacciStark<F, D>;
+
+        let num_rows = 1 << 5;
+        let public_inputs = [F::ZERO, F::ONE, fibonacci(num_rows - 1, F::ZERO, F::ONE)];
+        let stark = S::new(num_rows);
+
+        let trace = stark.generate_trace(public_inputs[0], public_inputs[1]);
+        assert!(check_constraints(&stark, &trace, public_inputs).is_empty());
+
+        let mut trace = stark.generate_trace(public_inputs[0], public_inputs[1]);
+        trace[1].values[7] += F::ONE;
+        let violations = check_constraints(&stark, &trace, public_inputs);
+        let g = F::primitive_root_of_unity(5);
+        let z_last = |row: u64| g.exp_u64(row) - g.inverse();
+        assert_eq!(
+            violations,
+            vec![
+                ConstraintViolation {
+                    row: 6,
+                    index: 4,
+                    kind: ConstraintKind::Transition,
+                    value: z_last(6),
+                },
+                ConstraintViolation {
+                    row: 7,
+                    index: 3,
+                    kind: ConstraintKind::Transition,
+                    value: -z_last(7),
+                },
+                ConstraintViolation {
+                    row: 7,
+                    index: 4,
+                    kind: ConstraintKind::Transition,
+                    value: -z_last(7),
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_fibonacci_stark_degree() -> Result<()> {
         const D: usize = 2;
@@ -184,6 +286,18 @@ mod tests {
         test_stark_low_degree(stark)
     }
 
+    #[test]
+    fn test_fibonacci_stark_circuit() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type S = FibonacciStark<F, D>;
+
+        let num_rows = 1 << 5;
+        let stark = S::new(num_rows);
+        test_stark_circuit_constraints::<F, C, S, D>(stark)
+    }
+
     #[test]
     fn test_recursive_stark_verifier() -> Result<()> {
         init_logger();
@@ -226,6 +340,7 @@ mod tests {
         [(); S::COLUMNS]:,
         [(); S::PUBLIC_INPUTS]:,
         [(); C::Hasher::HASH_SIZE]:,
+        [(); InnerC::Hasher::HASH_SIZE]:,
     {
         let circuit_config = CircuitConfig::standard_recursion_config();
         let mut builder = CircuitBuilder::<F, D>::new(circuit_config);
@@ -18,6 +18,7 @@ use crate::stark::Stark;
 
 fn get_challenges<F, C, S, const D: usize>(
     stark: &S,
+    preprocessed_cap: Option<&MerkleCap<F, C::Hasher>>,
     trace_cap: &MerkleCap<F, C::Hasher>,
     permutation_zs_cap: Option<&MerkleCap<F, C::Hasher>>,
     quotient_polys_cap: &MerkleCap<F, C::Hasher>,
@@ -37,6 +38,9 @@ where
 
     let mut challenger = Challenger::<F, C::Hasher>::new();
 
+    if let Some(preprocessed_cap) = preprocessed_cap {
+        challenger.observe_cap(preprocessed_cap);
+    }
     challenger.observe_cap(trace_cap);
 
     let permutation_challenge_sets = permutation_zs_cap.map(|permutation_zs_cap| {
@@ -78,10 +82,11 @@ where
     pub(crate) fn fri_query_indices<S: Stark<F, D>>(
         &self,
         stark: &S,
+        preprocessed_cap: Option<&MerkleCap<F, C::Hasher>>,
         config: &StarkConfig,
         degree_bits: usize,
     ) -> Vec<usize> {
-        self.get_challenges(stark, config, degree_bits)
+        self.get_challenges(stark, preprocessed_cap, config, degree_bits)
             .fri_challenges
             .fri_query_indices
     }
@@ -90,6 +95,7 @@ where
     pub(crate) fn get_challenges<S: Stark<F, D>>(
         &self,
         stark: &S,
+        preprocessed_cap: Option<&MerkleCap<F, C::Hasher>>,
         config: &StarkConfig,
         degree_bits: usize,
     ) -> StarkProofChallenges<F, D> {
@@ -109,6 +115,7 @@ where
 
         get_challenges::<F, C, S, D>(
             stark,
+            preprocessed_cap,
             trace_cap,
             permutation_zs_cap.as_ref(),
             quotient_polys_cap,
@@ -131,6 +138,7 @@ pub(crate) fn get_challenges_target<
 >(
     builder: &mut CircuitBuilder<F, D>,
     stark: &S,
+    preprocessed_cap: Option<&MerkleCapTarget>,
     trace_cap: &MerkleCapTarget,
     permutation_zs_cap: Option<&MerkleCapTarget>,
     quotient_polys_cap: &MerkleCapTarget,
@@ -147,6 +155,9 @@ where
 
     let mut challenger = RecursiveChallenger::<F, C::Hasher, D>::new(builder);
 
+    if let Some(preprocessed_cap) = preprocessed_cap {
+        challenger.observe_cap(preprocessed_cap);
+    }
     challenger.observe_cap(trace_cap);
 
     let permutation_challenge_sets = permutation_zs_cap.map(|permutation_zs_cap| {
@@ -190,6 +201,7 @@ impl<const D: usize> StarkProofWithPublicInputsTarget<D> {
         &self,
         builder: &mut CircuitBuilder<F, D>,
         stark: &S,
+        preprocessed_cap: Option<&MerkleCapTarget>,
         config: &StarkConfig,
     ) -> StarkProofChallengesTarget<D>
     where
@@ -212,6 +224,7 @@ impl<const D: usize> StarkProofWithPublicInputsTarget<D> {
         get_challenges_target::<F, C, S, D>(
             builder,
             stark,
+            preprocessed_cap,
             trace_cap,
             permutation_zs_cap.as_ref(),
             quotient_polys_cap,
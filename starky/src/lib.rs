@@ -7,18 +7,20 @@
 #![feature(generic_const_exprs)]
 
 pub mod config;
+pub mod constraint_checker;
 pub mod constraint_consumer;
+#[cfg(any(test, feature = "testing"))]
+pub mod fibonacci_stark;
 mod get_challenges;
 pub mod permutation;
+pub mod preprocessed;
 pub mod proof;
 pub mod prover;
 pub mod recursive_verifier;
 pub mod stark;
 pub mod stark_testing;
+pub mod trace;
 pub mod util;
 pub mod vanishing_poly;
 pub mod vars;
 pub mod verifier;
-
-#[cfg(test)]
-pub mod fibonacci_stark;
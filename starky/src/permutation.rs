@@ -12,7 +12,7 @@ use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::iop::target::Target;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
-use plonky2::util::reducing::{ReducingFactor, ReducingFactorTarget};
+use plonky2::util::reducing::ReducingFactorTarget;
 use rayon::prelude::*;
 
 use crate::config::StarkConfig;
@@ -247,18 +247,23 @@ pub(crate) fn get_permutation_batches<'a, T: Copy>(
 }
 
 // TODO: Use slices.
-pub struct PermutationCheckVars<F: Field, FE: FieldExtension<D2, BaseField = F>, const D2: usize> {
-    pub(crate) local_zs: Vec<FE>,
-    pub(crate) next_zs: Vec<FE>,
+pub struct PermutationCheckVars<F, FE, P, const D2: usize>
+where
+    F: Field,
+    FE: FieldExtension<D2, BaseField = F>,
+    P: PackedField<Scalar = FE>,
+{
+    pub(crate) local_zs: Vec<P>,
+    pub(crate) next_zs: Vec<P>,
     pub(crate) permutation_challenge_sets: Vec<PermutationChallengeSet<F>>,
 }
 
 pub(crate) fn eval_permutation_checks<F, FE, P, C, S, const D: usize, const D2: usize>(
     stark: &S,
     config: &StarkConfig,
-    vars: StarkEvaluationVars<FE, FE, { S::COLUMNS }, { S::PUBLIC_INPUTS }>,
-    permutation_data: PermutationCheckVars<F, FE, D2>,
-    consumer: &mut ConstraintConsumer<FE>,
+    vars: StarkEvaluationVars<FE, P, { S::COLUMNS }, { S::PUBLIC_INPUTS }>,
+    permutation_data: PermutationCheckVars<F, FE, P, D2>,
+    consumer: &mut ConstraintConsumer<P>,
 ) where
     F: RichField + Extendable<D>,
     FE: FieldExtension<D2, BaseField = F>,
@@ -291,26 +296,30 @@ pub(crate) fn eval_permutation_checks<F, FE, P, C, S, const D: usize, const D2:
     // Each zs value corresponds to a permutation batch.
     for (i, instances) in permutation_batches.iter().enumerate() {
         // Z(gx) * down = Z x  * up
-        let (reduced_lhs, reduced_rhs): (Vec<FE>, Vec<FE>) = instances
+        let (reduced_lhs, reduced_rhs): (Vec<P>, Vec<P>) = instances
             .iter()
             .map(|instance| {
                 let PermutationInstance {
                     pair: PermutationPair { column_pairs },
                     challenge: PermutationChallenge { beta, gamma },
                 } = instance;
-                let mut factor = ReducingFactor::new(*beta);
-                let (lhs, rhs): (Vec<_>, Vec<_>) = column_pairs
-                    .iter()
-                    .map(|&(i, j)| (vars.local_values[i], vars.local_values[j]))
-                    .unzip();
-                (
-                    factor.reduce_ext(lhs.into_iter()) + FE::from_basefield(*gamma),
-                    factor.reduce_ext(rhs.into_iter()) + FE::from_basefield(*gamma),
-                )
+                let beta = FE::from_basefield(*beta);
+                let gamma = FE::from_basefield(*gamma);
+                let (lhs, rhs) =
+                    column_pairs
+                        .iter()
+                        .rev()
+                        .fold((P::ZEROS, P::ZEROS), |(lhs, rhs), &(i, j)| {
+                            (
+                                lhs * beta + vars.local_values[i],
+                                rhs * beta + vars.local_values[j],
+                            )
+                        });
+                (lhs + gamma, rhs + gamma)
             })
             .unzip();
-        let constraint = next_zs[i] * reduced_rhs.into_iter().product()
-            - local_zs[i] * reduced_lhs.into_iter().product();
+        let constraint = next_zs[i] * reduced_rhs.into_iter().product::<P>()
+            - local_zs[i] * reduced_lhs.into_iter().product::<P>();
         consumer.constraint(constraint);
     }
 }
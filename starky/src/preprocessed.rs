@@ -0,0 +1,109 @@
+//! Commitments to preprocessed columns.
+//!
+//! Preprocessed columns depend only on the STARK and the trace length, so they can be committed to
+//! once, ahead of time. The prover keeps the full commitment, while the verifier only needs its
+//! Merkle cap.
+
+use anyhow::{ensure, Result};
+use plonky2::field::extension_field::Extendable;
+use plonky2::fri::oracle::PolynomialBatch;
+use plonky2::hash::hash_types::RichField;
+use plonky2::hash::merkle_tree::MerkleCap;
+use plonky2::plonk::config::{GenericConfig, Hasher};
+use plonky2::timed;
+use plonky2::util::timing::TimingTree;
+
+use crate::config::StarkConfig;
+use crate::stark::Stark;
+
+/// Data used by the prover which depends only on the STARK and the trace length.
+pub struct StarkProverKey<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
+{
+    pub degree_bits: usize,
+    /// Commitment to the LDEs of the preprocessed columns, if the STARK has any.
+    pub preprocessed_commitment: Option<PolynomialBatch<F, C, D>>,
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
+    StarkProverKey<F, C, D>
+{
+    pub fn new<S: Stark<F, D>>(
+        stark: &S,
+        config: &StarkConfig,
+        degree_bits: usize,
+        timing: &mut TimingTree,
+    ) -> Self
+    where
+        [(); C::Hasher::HASH_SIZE]:,
+    {
+        let preprocessed_commitment = stark.uses_preprocessed_columns().then(|| {
+            let preprocessed_values = stark.preprocessed_columns(degree_bits);
+            assert_eq!(preprocessed_values.len(), S::PREPROCESSED_COLUMNS);
+            assert!(
+                preprocessed_values
+                    .iter()
+                    .all(|col| col.len() == 1 << degree_bits),
+                "Preprocessed columns must have the same length as the trace."
+            );
+            timed!(
+                timing,
+                "compute preprocessed commitment",
+                PolynomialBatch::from_values(
+                    preprocessed_values,
+                    config.fri_config.rate_bits,
+                    false,
+                    config.fri_config.cap_height,
+                    timing,
+                    None,
+                )
+            )
+        });
+        Self {
+            degree_bits,
+            preprocessed_commitment,
+        }
+    }
+
+    pub fn verifier_key(&self) -> StarkVerifierKey<F, C, D> {
+        StarkVerifierKey {
+            degree_bits: self.degree_bits,
+            preprocessed_cap: self
+                .preprocessed_commitment
+                .as_ref()
+                .map(|commitment| commitment.merkle_tree.cap.clone()),
+        }
+    }
+}
+
+/// Data used by the verifier which depends only on the STARK and the trace length.
+#[derive(Debug, Clone)]
+pub struct StarkVerifierKey<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+> {
+    pub degree_bits: usize,
+    /// Merkle cap of LDEs of the preprocessed columns, if the STARK has any.
+    pub preprocessed_cap: Option<MerkleCap<F, C::Hasher>>,
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
+    StarkVerifierKey<F, C, D>
+{
+    pub fn new<S: Stark<F, D>>(stark: &S, config: &StarkConfig, degree_bits: usize) -> Self
+    where
+        [(); C::Hasher::HASH_SIZE]:,
+    {
+        StarkProverKey::<F, C, D>::new(stark, config, degree_bits, &mut TimingTree::default())
+            .verifier_key()
+    }
+
+    /// Checks that this key is consistent with the given STARK.
+    pub(crate) fn check<S: Stark<F, D>>(&self, stark: &S) -> Result<()> {
+        ensure!(
+            self.preprocessed_cap.is_some() == stark.uses_preprocessed_columns(),
+            "Preprocessed data doesn't match with Stark configuration."
+        );
+        Ok(())
+    }
+}
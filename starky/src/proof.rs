@@ -16,6 +16,7 @@ use rayon::prelude::*;
 
 use crate::config::StarkConfig;
 use crate::permutation::PermutationChallengeSet;
+use crate::util::row_shift;
 
 #[derive(Debug, Clone)]
 pub struct StarkProof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> {
@@ -33,7 +34,7 @@ pub struct StarkProof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>,
 
 impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> StarkProof<F, C, D> {
     /// Recover the length of the trace from a STARK proof and a STARK config.
-    pub(crate) fn recover_degree_bits(&self, config: &StarkConfig) -> usize {
+    pub fn recover_degree_bits(&self, config: &StarkConfig) -> usize {
         let initial_merkle_proof = &self.opening_proof.query_round_proofs[0]
             .initial_trees_proof
             .evals_proofs[0]
@@ -43,6 +44,7 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> S
     }
 }
 
+#[derive(Clone)]
 pub struct StarkProofTarget<const D: usize> {
     pub trace_cap: MerkleCapTarget,
     pub permutation_zs_cap: Option<MerkleCapTarget>,
@@ -74,6 +76,7 @@ pub struct StarkProofWithPublicInputs<
     pub public_inputs: Vec<F>,
 }
 
+#[derive(Clone)]
 pub struct StarkProofWithPublicInputsTarget<const D: usize> {
     pub proof: StarkProofTarget<D>,
     pub public_inputs: Vec<Target>,
@@ -126,6 +129,10 @@ pub(crate) struct StarkProofChallengesTarget<const D: usize> {
 pub struct StarkOpeningSet<F: RichField + Extendable<D>, const D: usize> {
     pub local_values: Vec<F::Extension>,
     pub next_values: Vec<F::Extension>,
+    /// Values of the trace at `g^k * zeta`, for each `k` in `Stark::extra_row_offsets`.
+    pub extra_row_values: Vec<Vec<F::Extension>>,
+    pub preprocessed_local_values: Option<Vec<F::Extension>>,
+    pub preprocessed_next_values: Option<Vec<F::Extension>>,
     pub permutation_zs: Option<Vec<F::Extension>>,
     pub permutation_zs_right: Option<Vec<F::Extension>>,
     pub quotient_polys: Vec<F::Extension>,
@@ -135,7 +142,9 @@ impl<F: RichField + Extendable<D>, const D: usize> StarkOpeningSet<F, D> {
     pub fn new<C: GenericConfig<D, F = F>>(
         zeta: F::Extension,
         g: F,
+        extra_row_offsets: &[isize],
         trace_commitment: &PolynomialBatch<F, C, D>,
+        preprocessed_commitment: Option<&PolynomialBatch<F, C, D>>,
         permutation_zs_commitment: Option<&PolynomialBatch<F, C, D>>,
         quotient_commitment: &PolynomialBatch<F, C, D>,
     ) -> Self {
@@ -149,6 +158,15 @@ impl<F: RichField + Extendable<D>, const D: usize> StarkOpeningSet<F, D> {
         Self {
             local_values: eval_commitment(zeta, trace_commitment),
             next_values: eval_commitment(zeta_right, trace_commitment),
+            extra_row_values: extra_row_offsets
+                .iter()
+                .map(|&offset| {
+                    eval_commitment(zeta.scalar_mul(row_shift(g, offset)), trace_commitment)
+                })
+                .collect(),
+            preprocessed_local_values: preprocessed_commitment.map(|c| eval_commitment(zeta, c)),
+            preprocessed_next_values: preprocessed_commitment
+                .map(|c| eval_commitment(zeta_right, c)),
             permutation_zs: permutation_zs_commitment.map(|c| eval_commitment(zeta, c)),
             permutation_zs_right: permutation_zs_commitment.map(|c| eval_commitment(zeta_right, c)),
             quotient_polys: eval_commitment(zeta, quotient_commitment),
@@ -160,6 +178,7 @@ impl<F: RichField + Extendable<D>, const D: usize> StarkOpeningSet<F, D> {
             values: self
                 .local_values
                 .iter()
+                .chain(self.preprocessed_local_values.iter().flatten())
                 .chain(self.permutation_zs.iter().flatten())
                 .chain(&self.quotient_polys)
                 .copied()
@@ -169,19 +188,44 @@ impl<F: RichField + Extendable<D>, const D: usize> StarkOpeningSet<F, D> {
             values: self
                 .next_values
                 .iter()
+                .chain(self.preprocessed_next_values.iter().flatten())
                 .chain(self.permutation_zs_right.iter().flatten())
                 .copied()
                 .collect_vec(),
         };
+        let extra_row_batches = self.extra_row_values.iter().map(|values| FriOpeningBatch {
+            values: values.clone(),
+        });
         FriOpenings {
-            batches: vec![zeta_batch, zeta_right_batch],
+            batches: [zeta_batch, zeta_right_batch]
+                .into_iter()
+                .chain(extra_row_batches)
+                .collect(),
         }
     }
+
+    /// Returns the trace values at each of the given row offsets, which must be the STARK's
+    /// `row_offsets`.
+    pub(crate) fn window_values(&self, row_offsets: &[isize]) -> Vec<Vec<F::Extension>> {
+        let mut extra_row_values = self.extra_row_values.iter();
+        row_offsets
+            .iter()
+            .map(|&offset| match offset {
+                0 => self.local_values.clone(),
+                1 => self.next_values.clone(),
+                _ => extra_row_values.next().unwrap().clone(),
+            })
+            .collect()
+    }
 }
 
+#[derive(Clone)]
 pub struct StarkOpeningSetTarget<const D: usize> {
     pub local_values: Vec<ExtensionTarget<D>>,
     pub next_values: Vec<ExtensionTarget<D>>,
+    pub extra_row_values: Vec<Vec<ExtensionTarget<D>>>,
+    pub preprocessed_local_values: Option<Vec<ExtensionTarget<D>>>,
+    pub preprocessed_next_values: Option<Vec<ExtensionTarget<D>>>,
     pub permutation_zs: Option<Vec<ExtensionTarget<D>>>,
     pub permutation_zs_right: Option<Vec<ExtensionTarget<D>>>,
     pub quotient_polys: Vec<ExtensionTarget<D>>,
@@ -193,6 +237,7 @@ impl<const D: usize> StarkOpeningSetTarget<D> {
             values: self
                 .local_values
                 .iter()
+                .chain(self.preprocessed_local_values.iter().flatten())
                 .chain(self.permutation_zs.iter().flatten())
                 .chain(&self.quotient_polys)
                 .copied()
@@ -202,12 +247,36 @@ impl<const D: usize> StarkOpeningSetTarget<D> {
             values: self
                 .next_values
                 .iter()
+                .chain(self.preprocessed_next_values.iter().flatten())
                 .chain(self.permutation_zs_right.iter().flatten())
                 .copied()
                 .collect_vec(),
         };
+        let extra_row_batches = self
+            .extra_row_values
+            .iter()
+            .map(|values| FriOpeningBatchTarget {
+                values: values.clone(),
+            });
         FriOpeningsTarget {
-            batches: vec![zeta_batch, zeta_right_batch],
+            batches: [zeta_batch, zeta_right_batch]
+                .into_iter()
+                .chain(extra_row_batches)
+                .collect(),
         }
     }
+
+    /// Returns the trace values at each of the given row offsets, which must be the STARK's
+    /// `row_offsets`.
+    pub(crate) fn window_values(&self, row_offsets: &[isize]) -> Vec<Vec<ExtensionTarget<D>>> {
+        let mut extra_row_values = self.extra_row_values.iter();
+        row_offsets
+            .iter()
+            .map(|&offset| match offset {
+                0 => self.local_values.clone(),
+                1 => self.next_values.clone(),
+                _ => extra_row_values.next().unwrap().clone(),
+            })
+            .collect()
+    }
 }
@@ -4,6 +4,8 @@ use anyhow::{ensure, Result};
 use itertools::Itertools;
 use plonky2::field::extension_field::Extendable;
 use plonky2::field::field_types::Field;
+use plonky2::field::packable::Packable;
+use plonky2::field::packed_field::PackedField;
 use plonky2::field::polynomial::{PolynomialCoeffs, PolynomialValues};
 use plonky2::field::zero_poly_coset::ZeroPolyOnCoset;
 use plonky2::fri::oracle::PolynomialBatch;
@@ -22,6 +24,7 @@ use crate::permutation::PermutationCheckVars;
 use crate::permutation::{
     compute_permutation_z_polys, get_n_permutation_challenge_sets, PermutationChallengeSet,
 };
+use crate::preprocessed::StarkProverKey;
 use crate::proof::{StarkOpeningSet, StarkProof, StarkProofWithPublicInputs};
 use crate::stark::Stark;
 use crate::vanishing_poly::eval_vanishing_poly;
@@ -34,6 +37,36 @@ pub fn prove<F, C, S, const D: usize>(
     public_inputs: [F; S::PUBLIC_INPUTS],
     timing: &mut TimingTree,
 ) -> Result<StarkProofWithPublicInputs<F, C, D>>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    S: Stark<F, D>,
+    [(); S::COLUMNS]:,
+    [(); S::PUBLIC_INPUTS]:,
+    [(); C::Hasher::HASH_SIZE]:,
+{
+    let degree_bits = log2_strict(trace_poly_values[0].len());
+    let prover_key = StarkProverKey::new(&stark, config, degree_bits, timing);
+    prove_with_key(
+        stark,
+        &prover_key,
+        config,
+        trace_poly_values,
+        public_inputs,
+        timing,
+    )
+}
+
+/// Like `prove`, but uses a prover key which has been computed ahead of time, so that any
+/// preprocessed columns aren't recommitted for every proof.
+pub fn prove_with_key<F, C, S, const D: usize>(
+    stark: S,
+    prover_key: &StarkProverKey<F, C, D>,
+    config: &StarkConfig,
+    trace_poly_values: Vec<PolynomialValues<F>>,
+    public_inputs: [F; S::PUBLIC_INPUTS],
+    timing: &mut TimingTree,
+) -> Result<StarkProofWithPublicInputs<F, C, D>>
 where
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
@@ -44,6 +77,11 @@ where
 {
     let degree = trace_poly_values[0].len();
     let degree_bits = log2_strict(degree);
+    ensure!(
+        prover_key.degree_bits == degree_bits,
+        "Prover key was computed for a different trace length."
+    );
+    let preprocessed_commitment = prover_key.preprocessed_commitment.as_ref();
     let fri_params = config.fri_params(degree_bits);
     let rate_bits = config.fri_config.rate_bits;
     let cap_height = config.fri_config.cap_height;
@@ -69,6 +107,9 @@ where
 
     let trace_cap = trace_commitment.merkle_tree.cap.clone();
     let mut challenger = Challenger::new();
+    if let Some(preprocessed_commitment) = preprocessed_commitment {
+        challenger.observe_cap(&preprocessed_commitment.merkle_tree.cap);
+    }
     challenger.observe_cap(&trace_cap);
 
     // Permutation arguments.
@@ -110,9 +151,10 @@ where
     }
 
     let alphas = challenger.get_n_challenges(config.num_challenges);
-    let quotient_polys = compute_quotient_polys::<F, C, S, D>(
+    let quotient_polys = compute_quotient_polys::<F, <F as Packable>::Packing, C, S, D>(
         &stark,
         &trace_commitment,
+        preprocessed_commitment,
         &permutation_zs_commitment_challenges,
         public_inputs,
         alphas,
@@ -156,13 +198,16 @@ where
     let openings = StarkOpeningSet::new(
         zeta,
         g,
+        &stark.extra_row_offsets(),
         &trace_commitment,
+        preprocessed_commitment,
         permutation_zs_commitment,
         &quotient_commitment,
     );
     challenger.observe_openings(&openings.to_fri_openings());
 
     let initial_merkle_trees = once(&trace_commitment)
+        .chain(preprocessed_commitment)
         .chain(permutation_zs_commitment)
         .chain(once(&quotient_commitment))
         .collect_vec();
@@ -194,9 +239,10 @@ where
 
 /// Computes the quotient polynomials `(sum alpha^i C_i(x)) / Z_H(x)` for `alpha` in `alphas`,
 /// where the `C_i`s are the Stark constraints.
-fn compute_quotient_polys<'a, F, C, S, const D: usize>(
+fn compute_quotient_polys<'a, F, P, C, S, const D: usize>(
     stark: &S,
     trace_commitment: &'a PolynomialBatch<F, C, D>,
+    preprocessed_commitment: Option<&'a PolynomialBatch<F, C, D>>,
     permutation_zs_commitment_challenges: &'a Option<(
         PolynomialBatch<F, C, D>,
         Vec<PermutationChallengeSet<F>>,
@@ -208,6 +254,7 @@ fn compute_quotient_polys<'a, F, C, S, const D: usize>(
 ) -> Vec<PolynomialCoeffs<F>>
 where
     F: RichField + Extendable<D>,
+    P: PackedField<Scalar = F>,
     C: GenericConfig<D, F = F>,
     S: Stark<F, D>,
     [(); S::COLUMNS]:,
@@ -232,11 +279,7 @@ where
         PolynomialValues::selector(degree, degree - 1).lde_onto_coset(quotient_degree_bits);
 
     let z_h_on_coset = ZeroPolyOnCoset::<F>::new(degree_bits, quotient_degree_bits);
-
-    // Retrieve the LDE values at index `i`.
-    let get_at_index =
-        |comm: &'a PolynomialBatch<F, C, D>, i: usize| -> &'a [F] { comm.get_lde_values(i * step) };
-    let get_trace_at_index = |i| get_at_index(trace_commitment, i).try_into().unwrap();
+    let row_offsets = stark.row_offsets();
 
     // Last element of the subgroup.
     let last = F::primitive_root_of_unity(degree_bits).inverse();
@@ -247,45 +290,92 @@ where
         size,
     );
 
+    // We evaluate the constraints on `P::WIDTH` consecutive points at a time.
+    assert!(
+        size >= P::WIDTH,
+        "The LDE is smaller than the packing width."
+    );
+    // Retrieve the LDE values at indices `i_start..i_start + P::WIDTH`, wrapping around, packed
+    // column-wise.
+    let get_packed_at_index = |comm: &PolynomialBatch<F, C, D>, i_start: usize| -> Vec<P> {
+        let rows = (0..P::WIDTH)
+            .map(|j| comm.get_lde_values(((i_start + j) % size) * step))
+            .collect::<Vec<_>>();
+        (0..rows[0].len())
+            .map(|col| {
+                let mut packed = P::ZEROS;
+                for (packed_j, row) in packed.as_slice_mut().iter_mut().zip(&rows) {
+                    *packed_j = row[col];
+                }
+                packed
+            })
+            .collect()
+    };
+    let get_trace_packed = |i_start| -> [P; S::COLUMNS] {
+        get_packed_at_index(trace_commitment, i_start)
+            .try_into()
+            .unwrap()
+    };
+    let get_preprocessed_packed =
+        |i_start| preprocessed_commitment.map_or(vec![], |comm| get_packed_at_index(comm, i_start));
+
     let quotient_values = (0..size)
         .into_par_iter()
-        .map(|i| {
-            // TODO: Set `P` to a genuine `PackedField` here.
-            let mut consumer = ConstraintConsumer::<F>::new(
+        .step_by(P::WIDTH)
+        .flat_map_iter(|i_start| {
+            let i_next_start = (i_start + next_step) % size;
+            let i_range = i_start..i_start + P::WIDTH;
+
+            let x = *P::from_slice(&coset[i_range.clone()]);
+            let mut consumer = ConstraintConsumer::new(
                 alphas.clone(),
-                coset[i] - last,
-                lagrange_first.values[i],
-                lagrange_last.values[i],
+                x - last,
+                *P::from_slice(&lagrange_first.values[i_range.clone()]),
+                *P::from_slice(&lagrange_last.values[i_range]),
             );
-            let vars = StarkEvaluationVars::<F, F, { S::COLUMNS }, { S::PUBLIC_INPUTS }> {
-                local_values: &get_trace_at_index(i),
-                next_values: &get_trace_at_index((i + next_step) % size),
+            let window_values = row_offsets
+                .iter()
+                .map(|&offset| {
+                    let shifted_i = i_start as isize + offset * next_step as isize;
+                    get_trace_packed(shifted_i.rem_euclid(size as isize) as usize)
+                })
+                .collect::<Vec<_>>();
+            let vars = StarkEvaluationVars::<F, P, { S::COLUMNS }, { S::PUBLIC_INPUTS }> {
+                local_values: &get_trace_packed(i_start),
+                next_values: &get_trace_packed(i_next_start),
+                window_values: &window_values,
+                local_preprocessed_values: &get_preprocessed_packed(i_start),
+                next_preprocessed_values: &get_preprocessed_packed(i_next_start),
                 public_inputs: &public_inputs,
             };
             let permutation_check_data = permutation_zs_commitment_challenges.as_ref().map(
                 |(permutation_zs_commitment, permutation_challenge_sets)| PermutationCheckVars {
-                    local_zs: get_at_index(permutation_zs_commitment, i).to_vec(),
-                    next_zs: get_at_index(permutation_zs_commitment, (i + next_step) % size)
-                        .to_vec(),
+                    local_zs: get_packed_at_index(permutation_zs_commitment, i_start),
+                    next_zs: get_packed_at_index(permutation_zs_commitment, i_next_start),
                     permutation_challenge_sets: permutation_challenge_sets.to_vec(),
                 },
             );
-            // TODO: Use packed field for F.
-            eval_vanishing_poly::<F, F, F, C, S, D, 1>(
+            eval_vanishing_poly::<F, F, P, C, S, D, 1>(
                 stark,
                 config,
                 vars,
                 permutation_check_data,
                 &mut consumer,
             );
-            // TODO: Fix this once we use a genuine `PackedField`.
-            let mut constraints_evals = consumer.accumulators();
+            let mut constraints_evals = consumer.accumulators_packed();
             // We divide the constraints evaluations by `Z_H(x)`.
-            let denominator_inv = z_h_on_coset.eval_inverse(i);
+            let denominator_inv: P = z_h_on_coset.eval_inverse_packed(i_start);
             for eval in &mut constraints_evals {
                 *eval *= denominator_inv;
             }
-            constraints_evals
+
+            // Unpack the evaluations, giving one `Vec` of evaluations per point.
+            (0..P::WIDTH).map(move |j| {
+                constraints_evals
+                    .iter()
+                    .map(|eval| eval.as_slice()[j])
+                    .collect::<Vec<_>>()
+            })
         })
         .collect::<Vec<_>>();
 
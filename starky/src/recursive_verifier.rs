@@ -5,16 +5,17 @@ use itertools::Itertools;
 use plonky2::field::extension_field::Extendable;
 use plonky2::field::field_types::Field;
 use plonky2::fri::witness_util::set_fri_proof_target;
-use plonky2::hash::hash_types::RichField;
+use plonky2::hash::hash_types::{MerkleCapTarget, RichField};
 use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::iop::witness::Witness;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
-use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
 use plonky2::util::reducing::ReducingFactorTarget;
 
 use crate::config::StarkConfig;
 use crate::constraint_consumer::RecursiveConstraintConsumer;
 use crate::permutation::PermutationCheckDataTarget;
+use crate::preprocessed::StarkVerifierKey;
 use crate::proof::{
     StarkOpeningSetTarget, StarkProof, StarkProofChallengesTarget, StarkProofTarget,
     StarkProofWithPublicInputs, StarkProofWithPublicInputsTarget,
@@ -37,14 +38,27 @@ pub fn recursively_verify_stark_proof<
     C::Hasher: AlgebraicHasher<F>,
     [(); S::COLUMNS]:,
     [(); S::PUBLIC_INPUTS]:,
+    [(); C::Hasher::HASH_SIZE]:,
 {
     assert_eq!(proof_with_pis.public_inputs.len(), S::PUBLIC_INPUTS);
     let degree_bits = proof_with_pis.proof.recover_degree_bits(inner_config);
-    let challenges = proof_with_pis.get_challenges::<F, C, S>(builder, &stark, inner_config);
+    // The preprocessed columns are fixed for a given trace length, so their cap is a constant of
+    // the circuit.
+    let verifier_key = StarkVerifierKey::<F, C, D>::new(&stark, inner_config, degree_bits);
+    let preprocessed_cap = verifier_key
+        .preprocessed_cap
+        .map(|cap| builder.constant_merkle_cap(&cap));
+    let challenges = proof_with_pis.get_challenges::<F, C, S>(
+        builder,
+        &stark,
+        preprocessed_cap.as_ref(),
+        inner_config,
+    );
 
     recursively_verify_stark_proof_with_challenges::<F, C, S, D>(
         builder,
         stark,
+        preprocessed_cap,
         proof_with_pis,
         challenges,
         inner_config,
@@ -61,6 +75,7 @@ fn recursively_verify_stark_proof_with_challenges<
 >(
     builder: &mut CircuitBuilder<F, D>,
     stark: S,
+    preprocessed_cap: Option<MerkleCapTarget>,
     proof_with_pis: StarkProofWithPublicInputsTarget<D>,
     challenges: StarkProofChallengesTarget<D>,
     inner_config: &StarkConfig,
@@ -80,13 +95,25 @@ fn recursively_verify_stark_proof_with_challenges<
     let StarkOpeningSetTarget {
         local_values,
         next_values,
+        extra_row_values: _,
+        preprocessed_local_values,
+        preprocessed_next_values,
         permutation_zs,
         permutation_zs_right,
         quotient_polys,
     } = &proof.openings;
+    let window_values = proof
+        .openings
+        .window_values(&stark.row_offsets())
+        .into_iter()
+        .map(|row| row.try_into().unwrap())
+        .collect::<Vec<_>>();
     let vars = StarkEvaluationTargets {
         local_values: &local_values.to_vec().try_into().unwrap(),
         next_values: &next_values.to_vec().try_into().unwrap(),
+        window_values: &window_values,
+        local_preprocessed_values: preprocessed_local_values.as_deref().unwrap_or_default(),
+        next_preprocessed_values: preprocessed_next_values.as_deref().unwrap_or_default(),
         public_inputs: &public_inputs
             .into_iter()
             .map(|t| builder.convert_to_ext(t))
@@ -138,6 +165,7 @@ fn recursively_verify_stark_proof_with_challenges<
     }
 
     let merkle_caps = once(proof.trace_cap)
+        .chain(preprocessed_cap)
         .chain(proof.permutation_zs_cap)
         .chain(once(proof.quotient_polys_cap))
         .collect_vec();
@@ -205,6 +233,11 @@ pub fn add_virtual_stark_proof<F: RichField + Extendable<D>, S: Stark<F, D>, con
     let cap_height = fri_params.config.cap_height;
 
     let num_leaves_per_oracle = once(S::COLUMNS)
+        .chain(
+            stark
+                .uses_preprocessed_columns()
+                .then(|| S::PREPROCESSED_COLUMNS),
+        )
         .chain(
             stark
                 .uses_permutation_args()
@@ -235,6 +268,17 @@ fn add_stark_opening_set<F: RichField + Extendable<D>, S: Stark<F, D>, const D:
     StarkOpeningSetTarget {
         local_values: builder.add_virtual_extension_targets(S::COLUMNS),
         next_values: builder.add_virtual_extension_targets(S::COLUMNS),
+        extra_row_values: stark
+            .extra_row_offsets()
+            .iter()
+            .map(|_| builder.add_virtual_extension_targets(S::COLUMNS))
+            .collect(),
+        preprocessed_local_values: stark
+            .uses_preprocessed_columns()
+            .then(|| builder.add_virtual_extension_targets(S::PREPROCESSED_COLUMNS)),
+        preprocessed_next_values: stark
+            .uses_preprocessed_columns()
+            .then(|| builder.add_virtual_extension_targets(S::PREPROCESSED_COLUMNS)),
         permutation_zs: stark
             .uses_permutation_args()
             .then(|| builder.add_virtual_extension_targets(stark.num_permutation_batches(config))),
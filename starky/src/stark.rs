@@ -1,5 +1,6 @@
 use plonky2::field::extension_field::{Extendable, FieldExtension};
 use plonky2::field::packed_field::PackedField;
+use plonky2::field::polynomial::PolynomialValues;
 use plonky2::fri::structure::{
     FriBatchInfo, FriBatchInfoTarget, FriInstanceInfo, FriInstanceInfoTarget, FriOracleInfo,
     FriPolynomialInfo,
@@ -12,6 +13,7 @@ use plonky2_util::ceil_div_usize;
 use crate::config::StarkConfig;
 use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
 use crate::permutation::PermutationPair;
+use crate::util::row_shift;
 use crate::vars::StarkEvaluationTargets;
 use crate::vars::StarkEvaluationVars;
 
@@ -21,6 +23,9 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
     const COLUMNS: usize;
     /// The number of public inputs.
     const PUBLIC_INPUTS: usize;
+    /// The number of preprocessed columns, i.e. columns which are fixed for a given trace length,
+    /// rather than supplied by the prover. None by default.
+    const PREPROCESSED_COLUMNS: usize = 0;
 
     /// Evaluate constraints at a vector of points.
     ///
@@ -89,6 +94,15 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         let trace_info =
             FriPolynomialInfo::from_range(oracle_indices.next().unwrap(), 0..Self::COLUMNS);
 
+        let preprocessed_info = if self.uses_preprocessed_columns() {
+            FriPolynomialInfo::from_range(
+                oracle_indices.next().unwrap(),
+                0..Self::PREPROCESSED_COLUMNS,
+            )
+        } else {
+            vec![]
+        };
+
         let permutation_zs_info = if self.uses_permutation_args() {
             FriPolynomialInfo::from_range(
                 oracle_indices.next().unwrap(),
@@ -107,6 +121,7 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
             point: zeta,
             polynomials: [
                 trace_info.clone(),
+                preprocessed_info.clone(),
                 permutation_zs_info.clone(),
                 quotient_info,
             ]
@@ -114,11 +129,21 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         };
         let zeta_right_batch = FriBatchInfo {
             point: zeta.scalar_mul(g),
-            polynomials: [trace_info, permutation_zs_info].concat(),
+            polynomials: [trace_info.clone(), preprocessed_info, permutation_zs_info].concat(),
         };
+        let extra_row_batches = self
+            .extra_row_offsets()
+            .into_iter()
+            .map(|offset| FriBatchInfo {
+                point: zeta.scalar_mul(row_shift(g, offset)),
+                polynomials: trace_info.clone(),
+            });
         FriInstanceInfo {
             oracles: vec![no_blinding_oracle; oracle_indices.next().unwrap()],
-            batches: vec![zeta_batch, zeta_right_batch],
+            batches: [zeta_batch, zeta_right_batch]
+                .into_iter()
+                .chain(extra_row_batches)
+                .collect(),
         }
     }
 
@@ -136,6 +161,15 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         let trace_info =
             FriPolynomialInfo::from_range(oracle_indices.next().unwrap(), 0..Self::COLUMNS);
 
+        let preprocessed_info = if self.uses_preprocessed_columns() {
+            FriPolynomialInfo::from_range(
+                oracle_indices.next().unwrap(),
+                0..Self::PREPROCESSED_COLUMNS,
+            )
+        } else {
+            vec![]
+        };
+
         let permutation_zs_info = if self.uses_permutation_args() {
             FriPolynomialInfo::from_range(
                 oracle_indices.next().unwrap(),
@@ -154,6 +188,7 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
             point: zeta,
             polynomials: [
                 trace_info.clone(),
+                preprocessed_info.clone(),
                 permutation_zs_info.clone(),
                 quotient_info,
             ]
@@ -162,14 +197,58 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         let zeta_right = builder.mul_const_extension(g, zeta);
         let zeta_right_batch = FriBatchInfoTarget {
             point: zeta_right,
-            polynomials: [trace_info, permutation_zs_info].concat(),
+            polynomials: [trace_info.clone(), preprocessed_info, permutation_zs_info].concat(),
         };
+        let extra_row_batches = self
+            .extra_row_offsets()
+            .into_iter()
+            .map(|offset| FriBatchInfoTarget {
+                point: builder.mul_const_extension(row_shift(g, offset), zeta),
+                polynomials: trace_info.clone(),
+            })
+            .collect::<Vec<_>>();
         FriInstanceInfoTarget {
             oracles: vec![no_blinding_oracle; oracle_indices.next().unwrap()],
-            batches: vec![zeta_batch, zeta_right_batch],
+            batches: [zeta_batch, zeta_right_batch]
+                .into_iter()
+                .chain(extra_row_batches)
+                .collect(),
         }
     }
 
+    /// Generates the values of the preprocessed columns for a trace of length `2^degree_bits`. These
+    /// are committed to once, in the verifier key, so they must not depend on the witness.
+    fn preprocessed_columns(&self, _degree_bits: usize) -> Vec<PolynomialValues<F>> {
+        vec![]
+    }
+
+    fn uses_preprocessed_columns(&self) -> bool {
+        Self::PREPROCESSED_COLUMNS > 0
+    }
+
+    /// The offsets of the trace rows, relative to the local row, which constraints can access
+    /// through `StarkEvaluationVars::window_values`, in the same order. They must include `0` and
+    /// `1`, i.e. the local and next rows. Rows wrap around, so for example the window of the last
+    /// row includes the first row at offset `1`; constraints involving other offsets should be
+    /// filtered accordingly.
+    fn row_offsets(&self) -> Vec<isize> {
+        vec![0, 1]
+    }
+
+    /// The row offsets other than `0` and `1`. The trace is opened at a shifted point for each of
+    /// these, in addition to `zeta` and `g * zeta`.
+    fn extra_row_offsets(&self) -> Vec<isize> {
+        let row_offsets = self.row_offsets();
+        assert!(
+            row_offsets.contains(&0) && row_offsets.contains(&1),
+            "Row offsets must include the local and next rows."
+        );
+        row_offsets
+            .into_iter()
+            .filter(|&offset| offset != 0 && offset != 1)
+            .collect()
+    }
+
     /// Pairs of lists of columns that should be permutations of one another. A permutation argument
     /// will be used for each such pair. Empty by default.
     fn permutation_pairs(&self) -> Vec<PermutationPair> {
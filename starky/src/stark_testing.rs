@@ -1,14 +1,18 @@
 use anyhow::{ensure, Result};
-use plonky2::field::extension_field::Extendable;
+use plonky2::field::extension_field::{Extendable, FieldExtension};
 use plonky2::field::field_types::Field;
 use plonky2::field::polynomial::{PolynomialCoeffs, PolynomialValues};
 use plonky2::hash::hash_types::RichField;
+use plonky2::iop::witness::{PartialWitness, Witness};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::CircuitConfig;
+use plonky2::plonk::config::{GenericConfig, Hasher};
 use plonky2::util::transpose;
 use plonky2_util::{log2_ceil, log2_strict};
 
-use crate::constraint_consumer::ConstraintConsumer;
+use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
 use crate::stark::Stark;
-use crate::vars::StarkEvaluationVars;
+use crate::vars::{StarkEvaluationTargets, StarkEvaluationVars};
 
 const WITNESS_SIZE: usize = 1 << 5;
 
@@ -25,6 +29,11 @@ where
 
     let trace_ldes = random_low_degree_matrix::<F>(S::COLUMNS, rate_bits);
     let size = trace_ldes.len();
+    let preprocessed_ldes = if stark.uses_preprocessed_columns() {
+        random_low_degree_matrix::<F>(S::PREPROCESSED_COLUMNS, rate_bits)
+    } else {
+        vec![vec![]; size]
+    };
     let public_inputs = F::rand_arr::<{ S::PUBLIC_INPUTS }>();
 
     let lagrange_first = PolynomialValues::selector(WITNESS_SIZE, 0).lde(rate_bits);
@@ -34,14 +43,28 @@ where
     let subgroup =
         F::cyclic_subgroup_known_order(F::primitive_root_of_unity(log2_strict(size)), size);
     let alpha = F::rand();
+    let row_offsets = stark.row_offsets();
     let constraint_evals = (0..size)
         .map(|i| {
+            let window_values = row_offsets
+                .iter()
+                .map(|&offset| {
+                    let shifted_i = i as isize + (offset << rate_bits);
+                    trace_ldes[shifted_i.rem_euclid(size as isize) as usize]
+                        .clone()
+                        .try_into()
+                        .unwrap()
+                })
+                .collect::<Vec<_>>();
             let vars = StarkEvaluationVars {
                 local_values: &trace_ldes[i].clone().try_into().unwrap(),
                 next_values: &trace_ldes[(i + (1 << rate_bits)) % size]
                     .clone()
                     .try_into()
                     .unwrap(),
+                window_values: &window_values,
+                local_preprocessed_values: &preprocessed_ldes[i],
+                next_preprocessed_values: &preprocessed_ldes[(i + (1 << rate_bits)) % size],
                 public_inputs: &public_inputs,
             };
 
@@ -71,6 +94,118 @@ where
     Ok(())
 }
 
+/// Tests that the circuit constraints imposed by the given STARK are coherent with the native
+/// constraints, by evaluating both at the same random point and checking, in a proven circuit, that
+/// they agree.
+pub fn test_stark_circuit_constraints<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    S: Stark<F, D>,
+    const D: usize,
+>(
+    stark: S,
+) -> Result<()>
+where
+    [(); S::COLUMNS]:,
+    [(); S::PUBLIC_INPUTS]:,
+    [(); C::Hasher::HASH_SIZE]:,
+{
+    // Compute the native constraint evaluation on random values. The window rows at offsets 0 and 1
+    // are the local and next rows.
+    let local_values = F::Extension::rand_arr::<{ S::COLUMNS }>();
+    let next_values = F::Extension::rand_arr::<{ S::COLUMNS }>();
+    let window_values = stark
+        .row_offsets()
+        .into_iter()
+        .map(|offset| match offset {
+            0 => local_values,
+            1 => next_values,
+            _ => F::Extension::rand_arr::<{ S::COLUMNS }>(),
+        })
+        .collect::<Vec<_>>();
+    let num_preprocessed = if stark.uses_preprocessed_columns() {
+        S::PREPROCESSED_COLUMNS
+    } else {
+        0
+    };
+    let local_preprocessed_values = F::Extension::rand_vec(num_preprocessed);
+    let next_preprocessed_values = F::Extension::rand_vec(num_preprocessed);
+    let public_inputs = F::rand_arr::<{ S::PUBLIC_INPUTS }>();
+    let alpha = F::rand();
+    let z_last = F::Extension::rand();
+    let lagrange_first = F::Extension::rand();
+    let lagrange_last = F::Extension::rand();
+
+    let vars = StarkEvaluationVars {
+        local_values: &local_values,
+        next_values: &next_values,
+        window_values: &window_values,
+        local_preprocessed_values: &local_preprocessed_values,
+        next_preprocessed_values: &next_preprocessed_values,
+        public_inputs: &public_inputs.map(F::Extension::from_basefield),
+    };
+    let mut consumer = ConstraintConsumer::<F::Extension>::new(
+        vec![F::Extension::from_basefield(alpha)],
+        z_last,
+        lagrange_first,
+        lagrange_last,
+    );
+    stark.eval_ext(vars, &mut consumer);
+    let native_eval = consumer.accumulators()[0];
+
+    // Compute the circuit constraint evaluation on the same values.
+    let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+    let mut pw = PartialWitness::<F>::new();
+    let mut add_values = |builder: &mut CircuitBuilder<F, D>, values: &[F::Extension]| {
+        let targets = builder.add_virtual_extension_targets(values.len());
+        pw.set_extension_targets(&targets, values);
+        targets
+    };
+
+    let local_values_t = add_values(&mut builder, &local_values);
+    let next_values_t = add_values(&mut builder, &next_values);
+    let window_values_t = window_values
+        .iter()
+        .map(|row| add_values(&mut builder, row).try_into().unwrap())
+        .collect::<Vec<_>>();
+    let local_preprocessed_values_t = add_values(&mut builder, &local_preprocessed_values);
+    let next_preprocessed_values_t = add_values(&mut builder, &next_preprocessed_values);
+    let public_inputs_t = add_values(
+        &mut builder,
+        &public_inputs.map(F::Extension::from_basefield),
+    );
+    let [z_last_t, lagrange_first_t, lagrange_last_t]: [_; 3] =
+        add_values(&mut builder, &[z_last, lagrange_first, lagrange_last])
+            .try_into()
+            .unwrap();
+    let alpha_t = builder.add_virtual_target();
+    pw.set_target(alpha_t, alpha);
+
+    let vars = StarkEvaluationTargets {
+        local_values: &local_values_t.try_into().unwrap(),
+        next_values: &next_values_t.try_into().unwrap(),
+        window_values: &window_values_t,
+        local_preprocessed_values: &local_preprocessed_values_t,
+        next_preprocessed_values: &next_preprocessed_values_t,
+        public_inputs: &public_inputs_t.try_into().unwrap(),
+    };
+    let mut consumer = RecursiveConstraintConsumer::<F, D>::new(
+        builder.zero_extension(),
+        vec![alpha_t],
+        z_last_t,
+        lagrange_first_t,
+        lagrange_last_t,
+    );
+    stark.eval_ext_recursively(&mut builder, vars, &mut consumer);
+    let circuit_eval = consumer.accumulators()[0];
+    let native_eval_t = builder.constant_extension(native_eval);
+    builder.connect_extension(circuit_eval, native_eval_t);
+
+    let data = builder.build::<C>();
+    let proof = data.prove(pw)?;
+    data.verify(proof)
+}
+
 fn random_low_degree_matrix<F: Field>(num_polys: usize, rate_bits: usize) -> Vec<Vec<F>> {
     let polys = (0..num_polys)
         .map(|_| random_low_degree_values(rate_bits))
@@ -0,0 +1,255 @@
+//! Utilities for generating STARK traces.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use plonky2::field::extension_field::Extendable;
+use plonky2::field::field_types::Field;
+use plonky2::field::polynomial::PolynomialValues;
+use plonky2::hash::hash_types::RichField;
+use rayon::prelude::*;
+
+use crate::constraint_checker::check_constraints;
+use crate::stark::Stark;
+
+/// Determines how a trace is padded to a power of two length.
+pub enum TracePadding<F: Field, const COLUMNS: usize> {
+    /// Append copies of the last row.
+    RepeatLast,
+    /// Append copies of the given row.
+    Constant([F; COLUMNS]),
+    /// Generate each padding row from the row preceding it.
+    Generated(Box<dyn Fn(&[F; COLUMNS]) -> [F; COLUMNS] + Send + Sync>),
+}
+
+/// A helper for generating a trace. Rows can be written one at a time or in parallel, and
+/// individual cells or whole columns can be written afterwards. When the trace is built, it is
+/// padded to a power of two length and converted to the column-wise form that `prove` expects.
+pub struct TraceBuilder<F: Field, const COLUMNS: usize> {
+    /// The trace, in row-major form.
+    rows: Vec<[F; COLUMNS]>,
+    /// Optional names of columns, which can be used to address them.
+    column_names: HashMap<&'static str, usize>,
+    padding: TracePadding<F, COLUMNS>,
+    /// The minimum number of rows in the padded trace.
+    min_rows: usize,
+}
+
+impl<F: Field, const COLUMNS: usize> TraceBuilder<F, COLUMNS> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(num_rows: usize) -> Self {
+        Self {
+            rows: Vec::with_capacity(num_rows),
+            column_names: HashMap::new(),
+            padding: TracePadding::RepeatLast,
+            min_rows: 1,
+        }
+    }
+
+    /// Sets how the trace will be padded. By default, the last row is repeated.
+    pub fn set_padding(&mut self, padding: TracePadding<F, COLUMNS>) {
+        self.padding = padding;
+    }
+
+    /// Sets the minimum number of rows in the padded trace. This must be a power of two.
+    pub fn set_min_rows(&mut self, min_rows: usize) {
+        assert!(
+            min_rows.is_power_of_two(),
+            "Minimum rows must be a power of two."
+        );
+        self.min_rows = min_rows;
+    }
+
+    /// Gives a name to a column, so that it can be addressed with `column_index`.
+    pub fn name_column(&mut self, col: usize, name: &'static str) {
+        assert!(col < COLUMNS, "Column {} is out of range.", col);
+        let old_col = self.column_names.insert(name, col);
+        assert!(old_col.is_none(), "Column name {} is already in use.", name);
+    }
+
+    /// Returns the index of the column with the given name.
+    pub fn column_index(&self, name: &str) -> usize {
+        *self
+            .column_names
+            .get(name)
+            .unwrap_or_else(|| panic!("Unknown column {}.", name))
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn push_row(&mut self, row: [F; COLUMNS]) {
+        self.rows.push(row);
+    }
+
+    /// Appends `num_rows` rows, generating them in parallel. `generator` is given the index of the
+    /// row being generated within the whole trace.
+    pub fn push_rows_par<G>(&mut self, num_rows: usize, generator: G)
+    where
+        G: Fn(usize) -> [F; COLUMNS] + Send + Sync,
+    {
+        let start = self.rows.len();
+        let new_rows = (start..start + num_rows)
+            .into_par_iter()
+            .map(generator)
+            .collect::<Vec<_>>();
+        self.rows.extend(new_rows);
+    }
+
+    pub fn row(&self, row: usize) -> &[F; COLUMNS] {
+        &self.rows[row]
+    }
+
+    pub fn row_mut(&mut self, row: usize) -> &mut [F; COLUMNS] {
+        &mut self.rows[row]
+    }
+
+    pub fn last_row(&self) -> Option<&[F; COLUMNS]> {
+        self.rows.last()
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> F {
+        self.rows[row][col]
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: F) {
+        self.row_mut(row)[col] = value;
+    }
+
+    pub fn get_named(&self, row: usize, name: &str) -> F {
+        self.get(row, self.column_index(name))
+    }
+
+    pub fn set_named(&mut self, row: usize, name: &str, value: F) {
+        self.set(row, self.column_index(name), value);
+    }
+
+    /// Returns a copy of the given column, as it currently stands.
+    pub fn column(&self, col: usize) -> Vec<F> {
+        self.rows.iter().map(|row| row[col]).collect()
+    }
+
+    /// Overwrites the given column. `values` must have one entry per row.
+    pub fn set_column(&mut self, col: usize, values: &[F]) {
+        assert_eq!(values.len(), self.rows.len(), "Column length mismatch.");
+        for (row, &value) in self.rows.iter_mut().zip(values) {
+            row[col] = value;
+        }
+    }
+
+    /// Pads the trace to the next power of two, or to `min_rows` if that is larger.
+    pub fn pad(&mut self) {
+        let padded_len = self.rows.len().next_power_of_two().max(self.min_rows);
+        while self.rows.len() < padded_len {
+            let padding_row = match &self.padding {
+                TracePadding::RepeatLast => *self.rows.last().expect("Cannot pad an empty trace."),
+                TracePadding::Constant(row) => *row,
+                TracePadding::Generated(generator) => {
+                    generator(self.rows.last().expect("Cannot pad an empty trace."))
+                }
+            };
+            self.rows.push(padding_row);
+        }
+    }
+
+    /// Pads the trace and transposes it into column-wise form.
+    pub fn into_columns(mut self) -> Vec<Vec<F>> {
+        self.pad();
+        let rows = self.rows;
+        (0..COLUMNS)
+            .into_par_iter()
+            .map(|col| rows.iter().map(|row| row[col]).collect())
+            .collect()
+    }
+
+    /// Pads the trace and puts it in the format that `prove` expects.
+    pub fn into_poly_values(self) -> Vec<PolynomialValues<F>> {
+        self.into_columns()
+            .into_iter()
+            .map(PolynomialValues::new)
+            .collect()
+    }
+
+    /// Like `into_poly_values`, but also checks that the constraints of `stark` hold on every row
+    /// of the padded trace, and reports the first violated constraint otherwise. This is intended
+    /// for debugging trace generation.
+    pub fn into_poly_values_checked<S: Stark<F, D>, const D: usize>(
+        self,
+        stark: &S,
+        public_inputs: [F; S::PUBLIC_INPUTS],
+    ) -> Result<Vec<PolynomialValues<F>>>
+    where
+        F: RichField + Extendable<D>,
+        [(); S::COLUMNS]:,
+        [(); S::PUBLIC_INPUTS]:,
+    {
+        assert_eq!(COLUMNS, S::COLUMNS, "Trace width doesn't match with Stark.");
+        let trace = self.into_poly_values();
+        if let Some(violation) = check_constraints(stark, &trace, public_inputs).first() {
+            return Err(anyhow!("{}", violation));
+        }
+        Ok(trace)
+    }
+}
+
+impl<F: Field, const COLUMNS: usize> Default for TraceBuilder<F, COLUMNS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::field_types::Field;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use crate::trace::{TraceBuilder, TracePadding};
+
+    type F = GoldilocksField;
+
+    #[test]
+    fn padding() {
+        let mut builder = TraceBuilder::<F, 2>::new();
+        builder.set_padding(TracePadding::Generated(Box::new(|row| {
+            [row[0] + F::ONE, row[1]]
+        })));
+        builder.push_rows_par(5, |i| [F::from_canonical_usize(i), F::TWO]);
+
+        let columns = builder.into_columns();
+        assert_eq!(
+            columns[0],
+            (0..8).map(F::from_canonical_usize).collect::<Vec<_>>()
+        );
+        assert_eq!(columns[1], vec![F::TWO; 8]);
+    }
+
+    #[test]
+    fn min_rows() {
+        let mut builder = TraceBuilder::<F, 1>::new();
+        builder.set_min_rows(16);
+        builder.push_row([F::ONE]);
+
+        let trace = builder.into_poly_values();
+        assert_eq!(trace[0].values, vec![F::ONE; 16]);
+    }
+
+    #[test]
+    fn named_columns() {
+        let mut builder = TraceBuilder::<F, 3>::new();
+        builder.name_column(1, "x");
+        builder.push_row([F::ZERO; 3]);
+        builder.push_row([F::ZERO; 3]);
+        builder.set_named(1, "x", F::TWO);
+        builder.set_column(2, &[F::ONE, F::TWO]);
+
+        assert_eq!(
+            builder.column(builder.column_index("x")),
+            vec![F::ZERO, F::TWO]
+        );
+        assert_eq!(builder.row(1), &[F::ZERO, F::TWO, F::TWO]);
+    }
+}
@@ -14,3 +14,13 @@ pub fn trace_rows_to_poly_values<F: Field, const COLUMNS: usize>(
         .map(|column| PolynomialValues::new(column))
         .collect()
 }
+
+/// Returns `g^offset`, where `offset` may be negative. Used to shift an opening point by a number of
+/// rows.
+pub(crate) fn row_shift<F: Field>(g: F, offset: isize) -> F {
+    if offset >= 0 {
+        g.exp_u64(offset as u64)
+    } else {
+        g.inverse().exp_u64(offset.unsigned_abs() as u64)
+    }
+}
@@ -10,6 +10,12 @@ where
 {
     pub local_values: &'a [P; COLUMNS],
     pub next_values: &'a [P; COLUMNS],
+    /// Values of the rows at each offset in `Stark::row_offsets`, in the same order.
+    pub window_values: &'a [[P; COLUMNS]],
+    /// Values of the preprocessed columns in the local row. Empty if the STARK has none.
+    pub local_preprocessed_values: &'a [P],
+    /// Values of the preprocessed columns in the next row. Empty if the STARK has none.
+    pub next_preprocessed_values: &'a [P],
     pub public_inputs: &'a [P::Scalar; PUBLIC_INPUTS],
 }
 
@@ -22,5 +28,8 @@ pub struct StarkEvaluationTargets<
 > {
     pub local_values: &'a [ExtensionTarget<D>; COLUMNS],
     pub next_values: &'a [ExtensionTarget<D>; COLUMNS],
+    pub window_values: &'a [[ExtensionTarget<D>; COLUMNS]],
+    pub local_preprocessed_values: &'a [ExtensionTarget<D>],
+    pub next_preprocessed_values: &'a [ExtensionTarget<D>],
     pub public_inputs: &'a [ExtensionTarget<D>; PUBLIC_INPUTS],
 }
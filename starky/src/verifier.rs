@@ -12,6 +12,7 @@ use plonky2::plonk::plonk_common::reduce_with_powers;
 use crate::config::StarkConfig;
 use crate::constraint_consumer::ConstraintConsumer;
 use crate::permutation::PermutationCheckVars;
+use crate::preprocessed::StarkVerifierKey;
 use crate::proof::{StarkOpeningSet, StarkProofChallenges, StarkProofWithPublicInputs};
 use crate::stark::Stark;
 use crate::vanishing_poly::eval_vanishing_poly;
@@ -27,15 +28,55 @@ pub fn verify_stark_proof<
     proof_with_pis: StarkProofWithPublicInputs<F, C, D>,
     config: &StarkConfig,
 ) -> Result<()>
+where
+    [(); S::COLUMNS]:,
+    [(); S::PUBLIC_INPUTS]:,
+    [(); C::Hasher::HASH_SIZE]:,
+{
+    let degree_bits = proof_with_pis.proof.recover_degree_bits(config);
+    let verifier_key = StarkVerifierKey::new(&stark, config, degree_bits);
+    verify_stark_proof_with_key(stark, &verifier_key, proof_with_pis, config)
+}
+
+/// Like `verify_stark_proof`, but uses a verifier key which has been computed ahead of time, so
+/// that any preprocessed columns aren't recommitted for every verification.
+pub fn verify_stark_proof_with_key<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    S: Stark<F, D>,
+    const D: usize,
+>(
+    stark: S,
+    verifier_key: &StarkVerifierKey<F, C, D>,
+    proof_with_pis: StarkProofWithPublicInputs<F, C, D>,
+    config: &StarkConfig,
+) -> Result<()>
 where
     [(); S::COLUMNS]:,
     [(); S::PUBLIC_INPUTS]:,
     [(); C::Hasher::HASH_SIZE]:,
 {
     ensure!(proof_with_pis.public_inputs.len() == S::PUBLIC_INPUTS);
+    verifier_key.check(&stark)?;
     let degree_bits = proof_with_pis.proof.recover_degree_bits(config);
-    let challenges = proof_with_pis.get_challenges(&stark, config, degree_bits);
-    verify_stark_proof_with_challenges(stark, proof_with_pis, challenges, degree_bits, config)
+    ensure!(
+        verifier_key.degree_bits == degree_bits,
+        "Verifier key was computed for a different trace length."
+    );
+    let challenges = proof_with_pis.get_challenges(
+        &stark,
+        verifier_key.preprocessed_cap.as_ref(),
+        config,
+        degree_bits,
+    );
+    verify_stark_proof_with_challenges(
+        stark,
+        verifier_key,
+        proof_with_pis,
+        challenges,
+        degree_bits,
+        config,
+    )
 }
 
 pub(crate) fn verify_stark_proof_with_challenges<
@@ -45,6 +86,7 @@ pub(crate) fn verify_stark_proof_with_challenges<
     const D: usize,
 >(
     stark: S,
+    verifier_key: &StarkVerifierKey<F, C, D>,
     proof_with_pis: StarkProofWithPublicInputs<F, C, D>,
     challenges: StarkProofChallenges<F, D>,
     degree_bits: usize,
@@ -63,13 +105,37 @@ where
     let StarkOpeningSet {
         local_values,
         next_values,
+        extra_row_values,
+        preprocessed_local_values,
+        preprocessed_next_values,
         permutation_zs,
         permutation_zs_right,
         quotient_polys,
     } = &proof.openings;
+    ensure!(
+        extra_row_values.len() == stark.extra_row_offsets().len(),
+        "Extra row openings don't match with Stark configuration."
+    );
+    let window_values = proof
+        .openings
+        .window_values(&stark.row_offsets())
+        .into_iter()
+        .map(|row| row.try_into().unwrap())
+        .collect::<Vec<_>>();
+    let num_preprocessed_columns = stark
+        .uses_preprocessed_columns()
+        .then(|| S::PREPROCESSED_COLUMNS);
+    ensure!(
+        preprocessed_local_values.as_ref().map(Vec::len) == num_preprocessed_columns
+            && preprocessed_next_values.as_ref().map(Vec::len) == num_preprocessed_columns,
+        "Preprocessed openings don't match with Stark configuration."
+    );
     let vars = StarkEvaluationVars {
         local_values: &local_values.to_vec().try_into().unwrap(),
         next_values: &next_values.to_vec().try_into().unwrap(),
+        window_values: &window_values,
+        local_preprocessed_values: preprocessed_local_values.as_deref().unwrap_or_default(),
+        next_preprocessed_values: preprocessed_next_values.as_deref().unwrap_or_default(),
         public_inputs: &public_inputs
             .into_iter()
             .map(F::Extension::from_basefield)
@@ -125,6 +191,7 @@ where
     }
 
     let merkle_caps = once(proof.trace_cap)
+        .chain(verifier_key.preprocessed_cap.clone())
         .chain(proof.permutation_zs_cap)
         .chain(once(proof.quotient_polys_cap))
         .collect_vec();
@@ -0,0 +1,39 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+use starky::config::StarkConfig;
+use system_zero::instruction::{Instruction, Opcode, NUM_REGISTERS};
+use system_zero::state::State;
+use system_zero::system_zero::SystemZero;
+
+const D: usize = 2;
+type C = PoseidonGoldilocksConfig;
+type F = <C as GenericConfig<D>>::F;
+type S = SystemZero<F, D>;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("prove-system-zero");
+    group.sample_size(10);
+
+    let system = S::default();
+    let config = StarkConfig::standard_fast_config();
+    // Counts down from 1000.
+    let program = [
+        Instruction::new(Opcode::LoadI, 0, 0, 0, 1000),
+        Instruction::new(Opcode::LoadI, 1, 0, 0, 1),
+        Instruction::new(Opcode::Sub, 0, 0, 1, 0),
+        Instruction::new(Opcode::JumpI, 0, 0, 0, 2),
+    ];
+    group.bench_function("prove", |b| {
+        b.iter(|| {
+            system
+                .prove_program::<C>(&program, [0; NUM_REGISTERS], &State::default(), &config)
+                .unwrap()
+        });
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
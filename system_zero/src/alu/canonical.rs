@@ -27,24 +27,26 @@ pub(crate) fn compute_canonical_inv<F: Field>(value_to_check: u64) -> F {
 
 /// Adds constraints to require that a list of four `u16`s, in little-endian order, represent a
 /// canonical field element, i.e. that their combined value is less than `|F|`. Returns their
-/// combined value.
+/// combined value. See `combine_u32s_check_canonical` for the role of `filter`.
 pub(crate) fn combine_u16s_check_canonical<F: Field, P: PackedField<Scalar = F>>(
     limb_0_u16: P,
     limb_1_u16: P,
     limb_2_u16: P,
     limb_3_u16: P,
     inverse: P,
+    filter: P,
     yield_constr: &mut ConstraintConsumer<P>,
 ) -> P {
     let base = F::from_canonical_u32(1 << 16);
     let limb_0_u32 = limb_0_u16 + limb_1_u16 * base;
     let limb_1_u32 = limb_2_u16 + limb_3_u16 * base;
-    combine_u32s_check_canonical(limb_0_u32, limb_1_u32, inverse, yield_constr)
+    combine_u32s_check_canonical(limb_0_u32, limb_1_u32, inverse, filter, yield_constr)
 }
 
 /// Adds constraints to require that a list of four `u16`s, in little-endian order, represent a
 /// canonical field element, i.e. that their combined value is less than `|F|`. Returns their
-/// combined value.
+/// combined value. See `combine_u32s_check_canonical` for the role of `filter`.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn combine_u16s_check_canonical_circuit<F: RichField + Extendable<D>, const D: usize>(
     builder: &mut CircuitBuilder<F, D>,
     limb_0_u16: ExtensionTarget<D>,
@@ -52,28 +54,41 @@ pub(crate) fn combine_u16s_check_canonical_circuit<F: RichField + Extendable<D>,
     limb_2_u16: ExtensionTarget<D>,
     limb_3_u16: ExtensionTarget<D>,
     inverse: ExtensionTarget<D>,
+    filter: ExtensionTarget<D>,
     yield_constr: &mut RecursiveConstraintConsumer<F, D>,
 ) -> ExtensionTarget<D> {
     let base = F::from_canonical_u32(1 << 16);
     let limb_0_u32 = builder.mul_const_add_extension(base, limb_1_u16, limb_0_u16);
     let limb_1_u32 = builder.mul_const_add_extension(base, limb_3_u16, limb_2_u16);
-    combine_u32s_check_canonical_circuit(builder, limb_0_u32, limb_1_u32, inverse, yield_constr)
+    combine_u32s_check_canonical_circuit(
+        builder,
+        limb_0_u32,
+        limb_1_u32,
+        inverse,
+        filter,
+        yield_constr,
+    )
 }
 
 /// Adds constraints to require that a pair of `u32`s, in little-endian order, represent a canonical
 /// field element, i.e. that their combined value is less than `|F|`. Returns their combined value.
+///
+/// The check only applies when `filter` is 1. When `filter` is 0, it is satisfied by setting
+/// `inverse` to 0; filtering this way avoids raising the constraint degree above 3.
 pub(crate) fn combine_u32s_check_canonical<F: Field, P: PackedField<Scalar = F>>(
     limb_0_u32: P,
     limb_1_u32: P,
     inverse: P,
+    filter: P,
     yield_constr: &mut ConstraintConsumer<P>,
 ) -> P {
     let u32_max = P::from(F::from_canonical_u32(u32::MAX));
 
     // This is zero if and only if the high limb is `u32::MAX`.
     let diff = u32_max - limb_1_u32;
-    // If this is zero, the diff is invertible, so the high limb is not `u32::MAX`.
-    let hi_not_max = inverse * diff - F::ONE;
+    // If this is zero (and the filter is 1), the diff is invertible, so the high limb is not
+    // `u32::MAX`.
+    let hi_not_max = inverse * diff - filter;
     // If this is zero, either the high limb is not `u32::MAX`, or the low limb is zero.
     let hi_not_max_or_lo_zero = hi_not_max * limb_0_u32;
 
@@ -85,20 +100,22 @@ pub(crate) fn combine_u32s_check_canonical<F: Field, P: PackedField<Scalar = F>>
 
 /// Adds constraints to require that a pair of `u32`s, in little-endian order, represent a canonical
 /// field element, i.e. that their combined value is less than `|F|`. Returns their combined value.
+/// See `combine_u32s_check_canonical` for the role of `filter`.
 pub(crate) fn combine_u32s_check_canonical_circuit<F: RichField + Extendable<D>, const D: usize>(
     builder: &mut CircuitBuilder<F, D>,
     limb_0_u32: ExtensionTarget<D>,
     limb_1_u32: ExtensionTarget<D>,
     inverse: ExtensionTarget<D>,
+    filter: ExtensionTarget<D>,
     yield_constr: &mut RecursiveConstraintConsumer<F, D>,
 ) -> ExtensionTarget<D> {
-    let one = builder.one_extension();
     let u32_max = builder.constant_extension(F::Extension::from_canonical_u32(u32::MAX));
 
     // This is zero if and only if the high limb is `u32::MAX`.
     let diff = builder.sub_extension(u32_max, limb_1_u32);
-    // If this is zero, the diff is invertible, so the high limb is not `u32::MAX`.
-    let hi_not_max = builder.mul_sub_extension(inverse, diff, one);
+    // If this is zero (and the filter is 1), the diff is invertible, so the high limb is not
+    // `u32::MAX`.
+    let hi_not_max = builder.mul_sub_extension(inverse, diff, filter);
     // If this is zero, either the high limb is not `u32::MAX`, or the low limb is zero.
     let hi_not_max_or_lo_zero = builder.mul_extension(hi_not_max, limb_0_u32);
 
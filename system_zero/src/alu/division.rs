@@ -1,3 +1,7 @@
+//! Unsigned u32 division, producing a quotient and a remainder.
+//!
+//! As in the EVM, dividing by zero yields a quotient and remainder of zero.
+
 use plonky2::field::extension_field::Extendable;
 use plonky2::field::field_types::{Field, PrimeField64};
 use plonky2::field::packed_field::PackedField;
@@ -10,15 +14,57 @@ use crate::registers::alu::*;
 use crate::registers::NUM_COLUMNS;
 
 pub(crate) fn generate_division<F: PrimeField64>(values: &mut [F; NUM_COLUMNS]) {
-    // TODO
+    let dividend = values[COL_DIV_INPUT_DIVIDEND].to_canonical_u64() as u32;
+    let divisor = values[COL_DIV_INPUT_DIVISOR].to_canonical_u64() as u32;
+
+    let (quo, rem, tmp) = if divisor == 0 {
+        values[COL_DIV_INVDIVISOR] = F::ZERO;
+        values[COL_DIV_DIVISOR_IS_ZERO] = F::ONE;
+        (0, 0, 0)
+    } else {
+        values[COL_DIV_INVDIVISOR] = F::from_canonical_u32(divisor).inverse();
+        values[COL_DIV_DIVISOR_IS_ZERO] = F::ZERO;
+        let rem = dividend % divisor;
+        (dividend / divisor, rem, divisor - rem - 1)
+    };
+
+    values[COL_DIV_OUTPUT_QUOT_0] = F::from_canonical_u16(quo as u16);
+    values[COL_DIV_OUTPUT_QUOT_1] = F::from_canonical_u16((quo >> 16) as u16);
+    values[COL_DIV_OUTPUT_REM_0] = F::from_canonical_u16(rem as u16);
+    values[COL_DIV_OUTPUT_REM_1] = F::from_canonical_u16((rem >> 16) as u16);
+    values[COL_DIV_RANGE_CHECKED_TMP_0] = F::from_canonical_u16(tmp as u16);
+    values[COL_DIV_RANGE_CHECKED_TMP_1] = F::from_canonical_u16((tmp >> 16) as u16);
 }
 
 pub(crate) fn eval_division<F: Field, P: PackedField<Scalar = F>>(
     local_values: &[P; NUM_COLUMNS],
     yield_constr: &mut ConstraintConsumer<P>,
 ) {
+    let base = F::from_canonical_u64(1 << 16);
+
     let is_div = local_values[IS_DIV];
-    // TODO
+    let dividend = local_values[COL_DIV_INPUT_DIVIDEND];
+    let divisor = local_values[COL_DIV_INPUT_DIVISOR];
+    let divisor_inv = local_values[COL_DIV_INVDIVISOR];
+    let divisor_is_zero = local_values[COL_DIV_DIVISOR_IS_ZERO];
+    let quo = local_values[COL_DIV_OUTPUT_QUOT_0] + local_values[COL_DIV_OUTPUT_QUOT_1] * base;
+    let rem = local_values[COL_DIV_OUTPUT_REM_0] + local_values[COL_DIV_OUTPUT_REM_1] * base;
+    let tmp = local_values[COL_DIV_RANGE_CHECKED_TMP_0]
+        + local_values[COL_DIV_RANGE_CHECKED_TMP_1] * base;
+
+    // `divisor_is_zero` must be 1 if the divisor is zero, and 0 otherwise. If the divisor is
+    // nonzero, the second constraint forces `divisor_is_zero = 0`. If it is zero, the first forces
+    // `divisor_is_zero = 1`.
+    yield_constr.constraint(is_div * (divisor * divisor_inv + divisor_is_zero - F::ONE));
+    yield_constr.constraint(is_div * divisor * divisor_is_zero);
+
+    // `dividend = quo * divisor + rem`, unless the divisor is zero. Since all values are u32s,
+    // `quo * divisor + rem < 2^64 - 2^32 + 1`, so this can't overflow.
+    yield_constr.constraint(is_div * (quo * divisor + rem + divisor_is_zero * dividend - dividend));
+    // If the divisor is zero, the quotient is zero, which then forces the remainder to be zero.
+    yield_constr.constraint(is_div * divisor_is_zero * quo);
+    // If the divisor is nonzero, `divisor - rem - 1` must be a u32, so that `rem < divisor`.
+    yield_constr.constraint(is_div * (P::ONES - divisor_is_zero) * (divisor - rem - F::ONE - tmp));
 }
 
 pub(crate) fn eval_division_recursively<F: RichField + Extendable<D>, const D: usize>(
@@ -26,6 +72,136 @@ pub(crate) fn eval_division_recursively<F: RichField + Extendable<D>, const D: u
     local_values: &[ExtensionTarget<D>; NUM_COLUMNS],
     yield_constr: &mut RecursiveConstraintConsumer<F, D>,
 ) {
+    let base = F::from_canonical_u64(1 << 16);
+    let one = builder.one_extension();
+
     let is_div = local_values[IS_DIV];
-    // TODO
+    let dividend = local_values[COL_DIV_INPUT_DIVIDEND];
+    let divisor = local_values[COL_DIV_INPUT_DIVISOR];
+    let divisor_inv = local_values[COL_DIV_INVDIVISOR];
+    let divisor_is_zero = local_values[COL_DIV_DIVISOR_IS_ZERO];
+    let quo = builder.mul_const_add_extension(
+        base,
+        local_values[COL_DIV_OUTPUT_QUOT_1],
+        local_values[COL_DIV_OUTPUT_QUOT_0],
+    );
+    let rem = builder.mul_const_add_extension(
+        base,
+        local_values[COL_DIV_OUTPUT_REM_1],
+        local_values[COL_DIV_OUTPUT_REM_0],
+    );
+    let tmp = builder.mul_const_add_extension(
+        base,
+        local_values[COL_DIV_RANGE_CHECKED_TMP_1],
+        local_values[COL_DIV_RANGE_CHECKED_TMP_0],
+    );
+
+    // divisor * divisor_inv + divisor_is_zero - 1 = 0
+    let constraint = builder.mul_add_extension(divisor, divisor_inv, divisor_is_zero);
+    let constraint = builder.sub_extension(constraint, one);
+    let constraint = builder.mul_extension(is_div, constraint);
+    yield_constr.constraint(builder, constraint);
+
+    // divisor * divisor_is_zero = 0
+    let constraint = builder.mul_many_extension(&[is_div, divisor, divisor_is_zero]);
+    yield_constr.constraint(builder, constraint);
+
+    // quo * divisor + rem + divisor_is_zero * dividend - dividend = 0
+    let constraint = builder.mul_add_extension(quo, divisor, rem);
+    let constraint = builder.mul_add_extension(divisor_is_zero, dividend, constraint);
+    let constraint = builder.sub_extension(constraint, dividend);
+    let constraint = builder.mul_extension(is_div, constraint);
+    yield_constr.constraint(builder, constraint);
+
+    // divisor_is_zero * quo = 0
+    let constraint = builder.mul_many_extension(&[is_div, divisor_is_zero, quo]);
+    yield_constr.constraint(builder, constraint);
+
+    // (1 - divisor_is_zero) * (divisor - rem - 1 - tmp) = 0
+    let divisor_is_nonzero = builder.sub_extension(one, divisor_is_zero);
+    let constraint = builder.sub_extension(divisor, rem);
+    let constraint = builder.sub_extension(constraint, one);
+    let constraint = builder.sub_extension(constraint, tmp);
+    let constraint = builder.mul_many_extension(&[is_div, divisor_is_nonzero, constraint]);
+    yield_constr.constraint(builder, constraint);
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::field_types::Field;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use crate::alu::division::{eval_division, generate_division};
+    use crate::registers::alu::*;
+    use crate::registers::NUM_COLUMNS;
+    use crate::system_zero::unit_testing::row_constraints_hold;
+
+    type F = GoldilocksField;
+
+    fn generate(dividend: u32, divisor: u32) -> [F; NUM_COLUMNS] {
+        let mut values = [F::ZERO; NUM_COLUMNS];
+        values[IS_DIV] = F::ONE;
+        values[COL_DIV_INPUT_DIVIDEND] = F::from_canonical_u32(dividend);
+        values[COL_DIV_INPUT_DIVISOR] = F::from_canonical_u32(divisor);
+        generate_division(&mut values);
+        values
+    }
+
+    fn constraints_hold(values: &[F; NUM_COLUMNS]) -> bool {
+        row_constraints_hold(values, |vars, consumer| {
+            eval_division(vars.local_values, consumer)
+        })
+    }
+
+    fn quotient(values: &[F; NUM_COLUMNS]) -> F {
+        values[COL_DIV_OUTPUT_QUOT_0]
+            + values[COL_DIV_OUTPUT_QUOT_1] * F::from_canonical_u32(1 << 16)
+    }
+
+    fn remainder(values: &[F; NUM_COLUMNS]) -> F {
+        values[COL_DIV_OUTPUT_REM_0] + values[COL_DIV_OUTPUT_REM_1] * F::from_canonical_u32(1 << 16)
+    }
+
+    #[test]
+    fn generate_eval_consistency() {
+        for (dividend, divisor) in [
+            (0, 1),
+            (17, 5),
+            (5, 17),
+            (u32::MAX, 1),
+            (u32::MAX, u32::MAX),
+            (u32::MAX, 0x10000),
+            (123456789, 0),
+            (0, 0),
+        ] {
+            let values = generate(dividend, divisor);
+            assert!(constraints_hold(&values));
+            let (expected_quo, expected_rem) = dividend
+                .checked_div(divisor)
+                .zip(dividend.checked_rem(divisor))
+                .unwrap_or((0, 0));
+            assert_eq!(quotient(&values), F::from_canonical_u32(expected_quo));
+            assert_eq!(remainder(&values), F::from_canonical_u32(expected_rem));
+        }
+    }
+
+    #[test]
+    fn wrong_quotient() {
+        // 17 = 2 * 5 + 7 satisfies the main equation, but the remainder is too large.
+        let mut values = generate(17, 5);
+        values[COL_DIV_OUTPUT_QUOT_0] = F::TWO;
+        values[COL_DIV_OUTPUT_REM_0] = F::from_canonical_u32(7);
+        assert!(!constraints_hold(&values));
+    }
+
+    #[test]
+    fn wrong_division_by_zero() {
+        let mut values = generate(17, 0);
+        values[COL_DIV_OUTPUT_REM_0] = F::from_canonical_u32(17);
+        assert!(!constraints_hold(&values));
+
+        let mut values = generate(17, 0);
+        values[COL_DIV_DIVISOR_IS_ZERO] = F::ZERO;
+        assert!(!constraints_hold(&values));
+    }
 }
@@ -18,7 +18,7 @@ use crate::registers::alu::*;
 use crate::registers::NUM_COLUMNS;
 
 mod addition;
-mod canonical;
+pub(crate) mod canonical;
 mod division;
 mod mul_add;
 mod subtraction;
@@ -47,13 +47,13 @@ pub(crate) fn eval_mul_add<F: Field, P: PackedField<Scalar = F>>(
     let result_canonical_inv = local_values[COL_MUL_ADD_RESULT_CANONICAL_INV];
 
     let computed_output = factor_0 * factor_1 + addend;
-    // TODO: Needs to be filtered by IS_MUL_ADD.
     let output = combine_u16s_check_canonical(
         output_1,
         output_2,
         output_3,
         output_4,
         result_canonical_inv,
+        is_mul,
         yield_constr,
     );
     yield_constr.constraint(is_mul * (computed_output - output));
@@ -75,7 +75,6 @@ pub(crate) fn eval_mul_add_recursively<F: RichField + Extendable<D>, const D: us
     let result_canonical_inv = local_values[COL_MUL_ADD_RESULT_CANONICAL_INV];
 
     let computed_output = builder.mul_add_extension(factor_0, factor_1, addend);
-    // TODO: Needs to be filtered by IS_MUL_ADD.
     let output = combine_u16s_check_canonical_circuit(
         builder,
         output_1,
@@ -83,9 +82,10 @@ pub(crate) fn eval_mul_add_recursively<F: RichField + Extendable<D>, const D: us
         output_3,
         output_4,
         result_canonical_inv,
+        is_mul,
         yield_constr,
     );
     let diff = builder.sub_extension(computed_output, output);
     let filtered_diff = builder.mul_extension(is_mul, diff);
-    yield_constr.constraint(builder, diff);
+    yield_constr.constraint(builder, filtered_diff);
 }
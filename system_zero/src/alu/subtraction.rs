@@ -63,8 +63,8 @@ pub(crate) fn eval_subtraction_recursively<F: RichField + Extendable<D>, const D
     let base = builder.constant_extension(F::Extension::from_canonical_u64(1 << 16));
     let base_sqr = builder.constant_extension(F::Extension::from_canonical_u64(1 << 32));
 
-    // lhs = (out_br + in_1) - in_2
-    let lhs = builder.add_extension(out_br, in_1);
+    // lhs = (out_br * base_sqr + in_1) - in_2
+    let lhs = builder.mul_add_extension(out_br, base_sqr, in_1);
     let lhs = builder.sub_extension(lhs, in_2);
 
     // rhs = out_1 + base * out_2
@@ -0,0 +1,128 @@
+//! Arithmetic on unsigned integers of any size, represented as u32 limbs in little-endian order.
+//! Used by the interpreter and by the 256-bit ALU's trace generation.
+
+use std::cmp::Ordering;
+
+/// Returns `a + b`.
+pub(crate) fn add(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for i in 0..a.len().max(b.len()) {
+        let limb = limb(a, i) as u64 + limb(b, i) as u64 + carry;
+        sum.push(limb as u32);
+        carry = limb >> 32;
+    }
+    sum.push(carry as u32);
+    sum
+}
+
+/// Returns `a - b`, which must not be negative.
+pub(crate) fn sub(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (diff, borrow) = overflowing_sub(a, b);
+    assert!(!borrow, "Subtraction underflowed.");
+    diff
+}
+
+/// Returns `a - b` modulo `2^(32 n)`, where `n` is the number of limbs of `a`, and whether it
+/// wrapped.
+pub(crate) fn overflowing_sub(a: &[u32], b: &[u32]) -> (Vec<u32>, bool) {
+    debug_assert!(b[a.len().min(b.len())..].iter().all(|&x| x == 0));
+    let mut diff = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &x) in a.iter().enumerate() {
+        let limb = x as i64 - limb(b, i) as i64 - borrow;
+        diff.push(limb as u32);
+        borrow = (limb < 0) as i64;
+    }
+    (diff, borrow != 0)
+}
+
+/// Returns `a * b`.
+pub(crate) fn mul(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let limb = product[i + j] as u64 + x as u64 * y as u64 + carry;
+            product[i + j] = limb as u32;
+            carry = limb >> 32;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+    product
+}
+
+/// Returns the quotient and remainder of `a / b`, where `b` must be nonzero. The quotient has as
+/// many limbs as `a`, and the remainder as many as `b`.
+pub(crate) fn div_rem(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    assert!(!is_zero(b), "Division by zero.");
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = vec![0u32; b.len() + 1];
+    for bit in (0..32 * a.len()).rev() {
+        // Shift the next bit of `a` into the remainder, which is less than `2 b`.
+        let mut carry = (a[bit / 32] >> (bit % 32)) & 1;
+        for limb in remainder.iter_mut() {
+            let shifted = *limb >> 31;
+            *limb = *limb << 1 | carry;
+            carry = shifted;
+        }
+        if cmp(&remainder, b) != Ordering::Less {
+            remainder = overflowing_sub(&remainder, b).0;
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    remainder.truncate(b.len());
+    (quotient, remainder)
+}
+
+/// Compares `a` and `b`.
+pub(crate) fn cmp(a: &[u32], b: &[u32]) -> Ordering {
+    (0..a.len().max(b.len()))
+        .rev()
+        .map(|i| limb(a, i).cmp(&limb(b, i)))
+        .find(|&ordering| ordering != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
+
+pub(crate) fn is_zero(a: &[u32]) -> bool {
+    a.iter().all(|&x| x == 0)
+}
+
+/// Returns `-a` modulo `2^(32 n)`, where `n` is the number of limbs of `a`.
+pub(crate) fn neg(a: &[u32]) -> Vec<u32> {
+    overflowing_sub(&vec![0; a.len()], a).0
+}
+
+/// Whether `a` is negative when treated as a two's complement integer.
+pub(crate) fn is_negative(a: &[u32]) -> bool {
+    a.last().map_or(false, |&top| top >> 31 != 0)
+}
+
+/// Returns the magnitude of `a`, treated as a two's complement integer.
+pub(crate) fn abs(a: &[u32]) -> Vec<u32> {
+    if is_negative(a) {
+        neg(a)
+    } else {
+        a.to_vec()
+    }
+}
+
+/// Compares `a` and `b`, treated as two's complement integers with the same number of limbs.
+pub(crate) fn signed_cmp(a: &[u32], b: &[u32]) -> Ordering {
+    debug_assert_eq!(a.len(), b.len());
+    is_negative(b).cmp(&is_negative(a)).then_with(|| cmp(a, b))
+}
+
+/// Returns `a` modulo `2^(32 N)`, as exactly `N` limbs.
+pub(crate) fn truncate<const N: usize>(a: &[u32]) -> [u32; N] {
+    let mut limbs = [0; N];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        *limb = self::limb(a, i);
+    }
+    limbs
+}
+
+/// The `i`th limb of `a`, or zero if it has no such limb.
+fn limb(a: &[u32], i: usize) -> u32 {
+    a.get(i).copied().unwrap_or(0)
+}
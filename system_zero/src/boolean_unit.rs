@@ -0,0 +1,36 @@
+use plonky2::field::extension_field::Extendable;
+use plonky2::field::field_types::Field;
+use plonky2::field::packed_field::PackedField;
+use plonky2::hash::hash_types::RichField;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::vars::StarkEvaluationTargets;
+use starky::vars::StarkEvaluationVars;
+
+use crate::public_input_layout::NUM_PUBLIC_INPUTS;
+use crate::registers::boolean::{col_bit, NUM_BITS};
+use crate::registers::NUM_COLUMNS;
+
+pub(crate) fn eval_boolean_unit<F: Field, P: PackedField<Scalar = F>>(
+    vars: StarkEvaluationVars<F, P, NUM_COLUMNS, NUM_PUBLIC_INPUTS>,
+    yield_constr: &mut ConstraintConsumer<P>,
+) {
+    // Check that every column of the boolean unit is binary.
+    for i in 0..NUM_BITS {
+        let bit = vars.local_values[col_bit(i)];
+        yield_constr.constraint(bit * bit - bit);
+    }
+}
+
+pub(crate) fn eval_boolean_unit_recursively<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    vars: StarkEvaluationTargets<D, NUM_COLUMNS, NUM_PUBLIC_INPUTS>,
+    yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    // Check that every column of the boolean unit is binary.
+    for i in 0..NUM_BITS {
+        let bit = vars.local_values[col_bit(i)];
+        let constraint = builder.mul_sub_extension(bit, bit, bit);
+        yield_constr.constraint(builder, constraint);
+    }
+}
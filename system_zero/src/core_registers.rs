@@ -2,21 +2,90 @@ use plonky2::field::extension_field::Extendable;
 use plonky2::field::field_types::{Field, PrimeField64};
 use plonky2::field::packed_field::PackedField;
 use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
 use starky::vars::StarkEvaluationTargets;
 use starky::vars::StarkEvaluationVars;
 
-use crate::public_input_layout::NUM_PUBLIC_INPUTS;
+use crate::alu::generate_alu;
+use crate::hash_unit::{generate_hash, generate_hash_absorb_row, generate_hash_output_row};
+use crate::instruction::{Instruction, Opcode, NUM_REGISTERS};
+use crate::logic_unit::generate_logic_unit;
+use crate::memory::{Segment, TransactionMemory};
+use crate::memory_unit::generate_dummy_memory_op;
+use crate::public_input_layout::{pi_input_register, pi_output_register, NUM_PUBLIC_INPUTS};
+use crate::registers::alu::*;
 use crate::registers::core::*;
+use crate::registers::hash::{
+    col_output_position, COL_HASH_INPUT_ADDRESS, COL_HASH_IS_LAST_WORD, COL_HASH_IS_NONEMPTY,
+    COL_HASH_OUTPUT_ADDRESS, COL_HASH_WORDS_LEFT, NUM_DIGEST_WORDS,
+};
+use crate::registers::logic::{COL_LOGIC_INPUT_0, COL_LOGIC_INPUT_1, COL_LOGIC_OUTPUT};
+use crate::registers::memory::*;
+use crate::registers::state::{
+    COL_PATH_INDEX, COL_STATE_NEW_VALUE, COL_STATE_OLD_VALUE, COL_STATE_ROOT_UPDATE,
+};
+use crate::registers::wide_alu::{
+    col_compute_position, col_write_position, COL_WIDE_ADDRESS_0, COL_WIDE_ADDRESS_1,
+    COL_WIDE_LAST_READ, COL_WIDE_MEMORY_ADDRESS, COL_WIDE_OUTPUT_ADDRESS, NUM_COMPUTE_ROWS,
+    NUM_WIDE_WORDS,
+};
 use crate::registers::NUM_COLUMNS;
+use crate::state::State;
+use crate::state_unit::{generate_state_access, generate_state_path_row};
+use crate::wide_alu::{
+    generate_wide_compute_row, generate_wide_op, generate_wide_read_row, generate_wide_write_row,
+    WIDE_OPS,
+};
 
-pub(crate) fn generate_first_row_core_registers<F: Field>(first_values: &mut [F; NUM_COLUMNS]) {
+/// The opcodes which are handled by the logic unit.
+const LOGIC_OPCODES: [Opcode; 6] = [
+    Opcode::And,
+    Opcode::Or,
+    Opcode::Xor,
+    Opcode::Not,
+    Opcode::Shl,
+    Opcode::Shr,
+];
+
+/// Maps a register index to a column of a register field's one-hot encoding.
+type RegisterFlagColumn = fn(usize) -> usize;
+
+/// The shift of each register field in an instruction word, with the columns of its one-hot
+/// encoding.
+const REGISTER_FIELDS: [(usize, RegisterFlagColumn); 3] =
+    [(8, col_rd_flag), (10, col_rs1_flag), (12, col_rs2_flag)];
+
+/// The flags of opcodes which aren't handled by other units, and so are checked to be binary here.
+const CORE_OPCODE_FLAGS: [usize; 13] = [
+    IS_HALT, IS_LOADI, IS_LOAD, IS_STORE, IS_PUSH, IS_POP, IS_JUMP, IS_JUMPI, IS_CALL, IS_RET,
+    IS_SLOAD, IS_SSTORE, IS_HASH,
+];
+
+pub(crate) fn generate_first_row_core_registers<F: Field>(
+    first_values: &mut [F; NUM_COLUMNS],
+    program: &[Instruction],
+    inputs: [u32; NUM_REGISTERS],
+) {
     first_values[COL_CLOCK] = F::ZERO;
-    first_values[COL_RANGE_16] = F::ZERO;
+    first_values[COL_RANGE_8] = F::ZERO;
     first_values[COL_INSTRUCTION_PTR] = F::ZERO;
     first_values[COL_FRAME_PTR] = F::ZERO;
     first_values[COL_STACK_PTR] = F::ZERO;
+    for (i, input) in inputs.into_iter().enumerate() {
+        first_values[col_register(i)] = F::from_canonical_u32(input);
+    }
+    first_values[IS_LOADING] = F::from_bool(!program.is_empty());
+    first_values[IS_FETCH] = F::from_bool(program.is_empty());
+    first_values[IS_HALTED] = F::ZERO;
+    first_values[IS_OLD_PATH] = F::ZERO;
+    first_values[IS_NEW_PATH] = F::ZERO;
+    first_values[IS_HASH_ABSORB] = F::ZERO;
+    first_values[IS_HASH_OUTPUT] = F::ZERO;
+    first_values[IS_WIDE_READ] = F::ZERO;
+    first_values[IS_WIDE_COMPUTE] = F::ZERO;
+    first_values[IS_WIDE_WRITE] = F::ZERO;
 }
 
 pub(crate) fn generate_next_row_core_registers<F: PrimeField64>(
@@ -26,17 +95,306 @@ pub(crate) fn generate_next_row_core_registers<F: PrimeField64>(
     // We increment the clock by 1.
     next_values[COL_CLOCK] = local_values[COL_CLOCK] + F::ONE;
 
-    // We increment the 16-bit table by 1, unless we've reached the max value of 2^16 - 1, in
-    // which case we repeat that value.
-    let prev_range_16 = local_values[COL_RANGE_16].to_canonical_u64();
-    let next_range_16 = (prev_range_16 + 1).min((1 << 16) - 1);
-    next_values[COL_RANGE_16] = F::from_canonical_u64(next_range_16);
+    // We increment the 8-bit table by 1, unless we've reached the max value of 2^8 - 1, in which
+    // case we repeat that value.
+    let prev_range_8 = local_values[COL_RANGE_8].to_canonical_u64();
+    let next_range_8 = (prev_range_8 + 1).min((1 << 8) - 1);
+    next_values[COL_RANGE_8] = F::from_canonical_u64(next_range_8);
+
+    // The following mirrors the transition constraints in `eval_core_registers`.
+    let is_loading = local_values[IS_LOADING];
+    let is_fetch = local_values[IS_FETCH];
+    let is_halted = local_values[IS_HALTED];
+    let is_old_path = local_values[IS_OLD_PATH];
+    let is_new_path = local_values[IS_NEW_PATH];
+    let is_hash_absorb = local_values[IS_HASH_ABSORB];
+    let is_hash_output = local_values[IS_HASH_OUTPUT];
+    let is_wide_read = local_values[IS_WIDE_READ];
+    let is_wide_compute = local_values[IS_WIDE_COMPUTE];
+    let is_wide_write = local_values[IS_WIDE_WRITE];
+    let is_execute = F::ONE
+        - is_loading
+        - is_fetch
+        - is_halted
+        - is_old_path
+        - is_new_path
+        - is_hash_absorb
+        - is_hash_output
+        - is_wide_read
+        - is_wide_compute
+        - is_wide_write;
+    let is_state_access = local_values[IS_SLOAD] + local_values[IS_SSTORE];
+    let root_update = local_values[COL_STATE_ROOT_UPDATE];
+    let is_hash = local_values[IS_HASH];
+    let is_hash_nonempty = local_values[COL_HASH_IS_NONEMPTY];
+    let is_last_word = local_values[COL_HASH_IS_LAST_WORD];
+    let hash_done = local_values[col_output_position(NUM_DIGEST_WORDS - 1)];
+    let is_wide = WIDE_OPS
+        .iter()
+        .map(|op| local_values[op.opcode.flag()])
+        .sum::<F>();
+    let last_read = local_values[COL_WIDE_LAST_READ];
+    let compute_done = local_values[col_compute_position(NUM_COMPUTE_ROWS - 1)];
+    let wide_done = local_values[col_write_position(NUM_WIDE_WORDS - 1)];
+
+    let imm = local_values[COL_IMMEDIATE];
+    let ip = local_values[COL_INSTRUCTION_PTR];
+    let fp = local_values[COL_FRAME_PTR];
+    let sp = local_values[COL_STACK_PTR];
+    let is_jumping = local_values[IS_JUMP] + local_values[IS_CALL] + local_values[COL_JUMPI_TAKEN];
+    let is_call = local_values[IS_CALL];
+    let is_ret = local_values[IS_RET];
+
+    next_values[COL_INSTRUCTION_PTR] = ip
+        + is_execute
+        + is_jumping * (imm - ip - F::ONE)
+        + is_ret * (local_values[COL_RET_INSTRUCTION_PTR] - ip - F::ONE);
+    next_values[COL_STACK_PTR] =
+        sp + local_values[IS_PUSH] + is_call - local_values[IS_POP] - is_ret * (sp - fp + F::ONE);
+    next_values[COL_FRAME_PTR] =
+        fp + is_call * (sp + F::ONE - fp) + is_ret * (local_values[COL_RET_FRAME_PTR] - fp);
+    // Frame records can only hold 16-bit instruction and frame pointers.
+    debug_assert!(next_values[COL_INSTRUCTION_PTR].to_canonical_u64() < 1 << 16);
+    debug_assert!(next_values[COL_FRAME_PTR].to_canonical_u64() < 1 << 16);
+
+    for i in 0..NUM_REGISTERS {
+        next_values[col_register(i)] = if local_values[col_register_write(i)].is_one() {
+            local_values[COL_RESULT]
+        } else {
+            local_values[col_register(i)]
+        };
+    }
 
-    // next_values[COL_INSTRUCTION_PTR] = todo!();
+    let next_is_loading = is_loading * (F::ONE - is_last_word);
+    next_values[IS_LOADING] = next_is_loading;
+    next_values[IS_FETCH] =
+        is_execute - local_values[IS_HALT] - is_state_access - is_hash - is_wide + is_loading
+            - next_is_loading
+            + root_update
+            + hash_done
+            + wide_done;
+    next_values[IS_HALTED] = is_halted + local_values[IS_HALT];
+    next_values[IS_OLD_PATH] = is_state_access + is_new_path - root_update;
+    next_values[IS_NEW_PATH] = is_old_path;
+    next_values[IS_HASH_ABSORB] =
+        is_hash * is_hash_nonempty + is_hash_absorb * (F::ONE - is_last_word);
+    next_values[IS_HASH_OUTPUT] =
+        is_hash * (F::ONE - is_hash_nonempty) + is_hash_absorb * is_last_word + is_hash_output
+            - hash_done;
+    next_values[IS_WIDE_READ] = is_wide + is_wide_read - last_read;
+    next_values[IS_WIDE_COMPUTE] = last_read + is_wide_compute - compute_done;
+    next_values[IS_WIDE_WRITE] = compute_done + is_wide_write - wide_done;
+    next_values[COL_INSTRUCTION_WORD] = is_fetch * local_values[COL_MEMORY_OP_VALUE];
+}
+
+/// Performs the memory operation of a loading or fetch row, hashes a level of a Merkle path,
+/// absorbs or writes a word of the input or digest of `HASH`, reads, computes or writes a 256-bit
+/// result, or decodes and executes the instruction of an execute row. Loading rows also absorb
+/// their word into the program's digest. Assumes the phase flags, pointers, registers and hash
+/// columns have already been generated.
+pub(crate) fn generate_core_row<F: RichField>(
+    values: &mut [F; NUM_COLUMNS],
+    program: &[Instruction],
+    memory: &mut TransactionMemory,
+    state: &mut State,
+) {
+    generate_dummy_memory_op(values);
+
+    let clock = values[COL_CLOCK];
+    let ip = values[COL_INSTRUCTION_PTR];
+    let fp = values[COL_FRAME_PTR];
+    let sp = values[COL_STACK_PTR];
+
+    if values[IS_LOADING].is_one() {
+        let word = program[clock.to_canonical_u64() as usize].encode();
+        let word = F::from_canonical_u32(word);
+        memory_op(values, memory, Segment::Code, clock, Some(word));
+        generate_hash_absorb_row(values, word);
+        return;
+    }
+    if values[IS_FETCH].is_one() {
+        memory_op(values, memory, Segment::Code, ip, None);
+        return;
+    }
+    if values[IS_OLD_PATH].is_one() || values[IS_NEW_PATH].is_one() {
+        generate_state_path_row(values, state);
+        return;
+    }
+    if values[IS_HASH_ABSORB].is_one() {
+        let address = values[COL_HASH_INPUT_ADDRESS];
+        let word = memory_op(values, memory, Segment::Main, address, None);
+        generate_hash_absorb_row(values, word);
+        return;
+    }
+    if values[IS_HASH_OUTPUT].is_one() {
+        let word = generate_hash_output_row(values);
+        let address = values[COL_HASH_OUTPUT_ADDRESS];
+        memory_op(values, memory, Segment::Main, address, Some(word));
+        return;
+    }
+    if values[IS_WIDE_READ].is_one() {
+        let address = generate_wide_read_row(values, memory);
+        memory_op(values, memory, Segment::Main, address, None);
+        return;
+    }
+    if values[IS_WIDE_COMPUTE].is_one() {
+        generate_wide_compute_row(values);
+        return;
+    }
+    if values[IS_WIDE_WRITE].is_one() {
+        let (address, word) = generate_wide_write_row(values);
+        memory_op(values, memory, Segment::Main, address, Some(word));
+        return;
+    }
+    if values[IS_HALTED].is_one() {
+        return;
+    }
 
-    // next_values[COL_FRAME_PTR] = todo!();
+    let word = values[COL_INSTRUCTION_WORD].to_canonical_u64() as u32;
+    let instruction = Instruction::decode(word)
+        .unwrap_or_else(|| panic!("Invalid instruction {:#x} at {}.", word, ip));
+    values[instruction.opcode.flag()] = F::ONE;
+    values[col_rd_flag(instruction.rd)] = F::ONE;
+    values[col_rs1_flag(instruction.rs1)] = F::ONE;
+    values[col_rs2_flag(instruction.rs2)] = F::ONE;
+
+    let imm = F::from_canonical_u16(instruction.imm);
+    let op_0 = values[col_register(instruction.rs1)];
+    let op_1 = values[col_register(instruction.rs2)];
+    values[COL_IMMEDIATE] = imm;
+    values[COL_OPERAND_0] = op_0;
+    values[COL_OPERAND_1] = op_1;
+
+    let base = F::from_canonical_u64(1 << 16);
+    let result = match instruction.opcode {
+        Opcode::Halt | Opcode::Jump => None,
+        Opcode::Add => {
+            values[COL_ADD_INPUT_0] = op_0;
+            values[COL_ADD_INPUT_1] = op_1;
+            values[COL_ADD_INPUT_2] = F::ZERO;
+            generate_alu(values);
+            Some(values[COL_ADD_OUTPUT_0] + values[COL_ADD_OUTPUT_1] * base)
+        }
+        Opcode::Sub => {
+            values[COL_SUB_INPUT_0] = op_0;
+            values[COL_SUB_INPUT_1] = op_1;
+            generate_alu(values);
+            Some(values[COL_SUB_OUTPUT_0] + values[COL_SUB_OUTPUT_1] * base)
+        }
+        Opcode::Mul => {
+            values[COL_MUL_ADD_FACTOR_0] = op_0;
+            values[COL_MUL_ADD_FACTOR_1] = op_1;
+            values[COL_MUL_ADD_ADDEND] = F::ZERO;
+            generate_alu(values);
+            Some(values[COL_MUL_ADD_OUTPUT_0] + values[COL_MUL_ADD_OUTPUT_1] * base)
+        }
+        Opcode::Div => {
+            values[COL_DIV_INPUT_DIVIDEND] = op_0;
+            values[COL_DIV_INPUT_DIVISOR] = op_1;
+            generate_alu(values);
+            Some(values[COL_DIV_OUTPUT_QUOT_0] + values[COL_DIV_OUTPUT_QUOT_1] * base)
+        }
+        Opcode::And | Opcode::Or | Opcode::Xor | Opcode::Not | Opcode::Shl | Opcode::Shr => {
+            values[COL_LOGIC_INPUT_0] = op_0;
+            values[COL_LOGIC_INPUT_1] = op_1;
+            generate_logic_unit(values);
+            Some(values[COL_LOGIC_OUTPUT])
+        }
+        Opcode::LoadI => Some(imm),
+        Opcode::Load => Some(memory_op(values, memory, Segment::Main, op_0 + imm, None)),
+        Opcode::Store => {
+            memory_op(values, memory, Segment::Main, op_0 + imm, Some(op_1));
+            None
+        }
+        Opcode::Push => {
+            memory_op(values, memory, Segment::Stack, sp, Some(op_0));
+            None
+        }
+        Opcode::Pop => Some(memory_op(values, memory, Segment::Stack, sp - F::ONE, None)),
+        Opcode::JumpI => {
+            values[COL_JUMPI_CONDITION_INV] = op_0.try_inverse().unwrap_or(F::ZERO);
+            values[COL_JUMPI_TAKEN] = F::from_bool(op_0.is_nonzero());
+            None
+        }
+        Opcode::Call => {
+            let record = ip + F::ONE + fp * base;
+            memory_op(values, memory, Segment::Stack, sp, Some(record));
+            None
+        }
+        Opcode::Ret => {
+            let record = memory_op(values, memory, Segment::Stack, fp - F::ONE, None);
+            let record = record.to_canonical_u64();
+            values[COL_RET_INSTRUCTION_PTR] = F::from_canonical_u64(record & 0xffff);
+            values[COL_RET_FRAME_PTR] = F::from_canonical_u64(record >> 16);
+            None
+        }
+        Opcode::SLoad => Some(generate_state_access(values, state, op_0 + imm, None)),
+        Opcode::SStore => {
+            generate_state_access(values, state, op_0 + imm, Some(op_1));
+            None
+        }
+        Opcode::Hash => {
+            let output_address = values[col_register(instruction.rd)];
+            generate_hash(values, op_0 + imm, op_1, output_address);
+            None
+        }
+        Opcode::Add256
+        | Opcode::Sub256
+        | Opcode::Mul256
+        | Opcode::Div256
+        | Opcode::Mod256
+        | Opcode::AddMod256
+        | Opcode::MulMod256
+        | Opcode::SDiv256
+        | Opcode::SMod256
+        | Opcode::Lt256
+        | Opcode::Gt256
+        | Opcode::SLt256
+        | Opcode::SGt256
+        | Opcode::Eq256
+        | Opcode::IsZero256 => {
+            let output_address = values[col_register(instruction.rd)];
+            generate_wide_op(values, op_0 + imm, op_1, output_address);
+            None
+        }
+    };
+
+    if let Some(result) = result {
+        values[COL_RESULT] = result;
+        values[col_register_write(instruction.rd)] = F::ONE;
+    }
+}
 
-    // next_values[COL_STACK_PTR] = todo!();
+/// Sets this row's memory operation to a read, if `value` is `None`, or otherwise a write of
+/// `value`. Returns the value read or written.
+fn memory_op<F: PrimeField64>(
+    values: &mut [F; NUM_COLUMNS],
+    memory: &mut TransactionMemory,
+    segment: Segment,
+    address: F,
+    value: Option<F>,
+) -> F {
+    let address_usize = address.to_canonical_u64() as usize;
+    let is_read = value.is_none();
+    let value = match value {
+        Some(value) => {
+            let value_u32 = value.to_canonical_u64().try_into();
+            memory.write(
+                0,
+                segment,
+                address_usize,
+                value_u32.expect("Value is not a u32."),
+            );
+            value
+        }
+        None => F::from_canonical_u32(memory.read(0, segment, address_usize)),
+    };
+    values[COL_MEMORY_OP_CONTEXT] = F::ZERO;
+    values[COL_MEMORY_OP_SEGMENT] = F::from_canonical_usize(segment as usize);
+    values[COL_MEMORY_OP_ADDRESS] = address;
+    values[COL_MEMORY_OP_VALUE] = value;
+    values[COL_MEMORY_OP_IS_READ] = F::from_bool(is_read);
+    value
 }
 
 #[inline]
@@ -44,22 +402,336 @@ pub(crate) fn eval_core_registers<F: Field, P: PackedField<Scalar = F>>(
     vars: StarkEvaluationVars<F, P, NUM_COLUMNS, NUM_PUBLIC_INPUTS>,
     yield_constr: &mut ConstraintConsumer<P>,
 ) {
+    let local_values = &vars.local_values;
+    let next_values = &vars.next_values;
+
     // The clock must start with 0, and increment by 1.
-    let local_clock = vars.local_values[COL_CLOCK];
-    let next_clock = vars.next_values[COL_CLOCK];
+    let local_clock = local_values[COL_CLOCK];
+    let next_clock = next_values[COL_CLOCK];
     let delta_clock = next_clock - local_clock;
     yield_constr.constraint_first_row(local_clock);
     yield_constr.constraint_transition(delta_clock - F::ONE);
 
-    // The 16-bit table must start with 0, end with 2^16 - 1, and increment by 0 or 1.
-    let local_range_16 = vars.local_values[COL_RANGE_16];
-    let next_range_16 = vars.next_values[COL_RANGE_16];
-    let delta_range_16 = next_range_16 - local_range_16;
-    yield_constr.constraint_first_row(local_range_16);
-    yield_constr.constraint_last_row(local_range_16 - F::from_canonical_u64((1 << 16) - 1));
-    yield_constr.constraint_transition(delta_range_16 * delta_range_16 - delta_range_16);
+    // The 8-bit table must start with 0, end with 2^8 - 1, and increment by 0 or 1.
+    let local_range_8 = local_values[COL_RANGE_8];
+    let next_range_8 = next_values[COL_RANGE_8];
+    let delta_range_8 = next_range_8 - local_range_8;
+    yield_constr.constraint_first_row(local_range_8);
+    yield_constr.constraint_last_row(local_range_8 - F::from_canonical_u64((1 << 8) - 1));
+    yield_constr.constraint_transition(delta_range_8 * delta_range_8 - delta_range_8);
+
+    let base = F::from_canonical_u64(1 << 16);
+    let ip = local_values[COL_INSTRUCTION_PTR];
+    let fp = local_values[COL_FRAME_PTR];
+    let sp = local_values[COL_STACK_PTR];
+    let imm = local_values[COL_IMMEDIATE];
+    let op_0 = local_values[COL_OPERAND_0];
+    let op_1 = local_values[COL_OPERAND_1];
+    let result = local_values[COL_RESULT];
+    let flag = |opcode: Opcode| local_values[opcode.flag()];
+
+    // Execution starts by loading the program, or fetching if it is empty, with all pointers zeroed
+    // and the registers holding the program's inputs. It must have halted by the last row, where
+    // the registers hold the program's outputs.
+    let is_loading = local_values[IS_LOADING];
+    let is_fetch = local_values[IS_FETCH];
+    let is_halted = local_values[IS_HALTED];
+    let is_old_path = local_values[IS_OLD_PATH];
+    let is_new_path = local_values[IS_NEW_PATH];
+    let is_hash_absorb = local_values[IS_HASH_ABSORB];
+    let is_hash_output = local_values[IS_HASH_OUTPUT];
+    let is_wide_read = local_values[IS_WIDE_READ];
+    let is_wide_compute = local_values[IS_WIDE_COMPUTE];
+    let is_wide_write = local_values[IS_WIDE_WRITE];
+    let phases = [
+        is_loading,
+        is_fetch,
+        is_halted,
+        is_old_path,
+        is_new_path,
+        is_hash_absorb,
+        is_hash_output,
+        is_wide_read,
+        is_wide_compute,
+        is_wide_write,
+    ];
+    let is_execute = phases.into_iter().fold(P::ONES, |acc, x| acc - x);
+    for phase in phases {
+        yield_constr.constraint(phase * phase - phase);
+    }
+    yield_constr.constraint_first_row(is_loading + is_fetch - F::ONE);
+    yield_constr.constraint_first_row(is_halted);
+    yield_constr.constraint_first_row(is_old_path);
+    yield_constr.constraint_first_row(is_new_path);
+    yield_constr.constraint_first_row(is_hash_absorb);
+    yield_constr.constraint_first_row(is_hash_output);
+    yield_constr.constraint_first_row(is_wide_read);
+    yield_constr.constraint_first_row(is_wide_compute);
+    yield_constr.constraint_first_row(is_wide_write);
+    yield_constr.constraint_first_row(ip);
+    yield_constr.constraint_first_row(fp);
+    yield_constr.constraint_first_row(sp);
+    for i in 0..NUM_REGISTERS {
+        let reg = local_values[col_register(i)];
+        yield_constr.constraint_first_row(reg - vars.public_inputs[pi_input_register(i)]);
+        yield_constr.constraint_last_row(reg - vars.public_inputs[pi_output_register(i)]);
+    }
+    yield_constr.constraint_last_row(is_halted - F::ONE);
+
+    // Loading continues until the last word of the program has been written, and is followed by a
+    // fetch, a fetch by an execute, and an execute by a fetch, unless it executes `HALT`, after
+    // which all rows are halted, a state access, after which old and new path rows alternate until
+    // the state root is updated, `HASH`, which is followed by its absorb rows and then its output
+    // rows, or a 256-bit instruction, which is followed by its read, compute and write rows.
+    let next_is_loading = next_values[IS_LOADING];
+    let is_state_access = flag(Opcode::SLoad) + flag(Opcode::SStore);
+    let root_update = local_values[COL_STATE_ROOT_UPDATE];
+    let is_hash = flag(Opcode::Hash);
+    let is_hash_nonempty = local_values[COL_HASH_IS_NONEMPTY];
+    let is_last_word = local_values[COL_HASH_IS_LAST_WORD];
+    let hash_done = local_values[col_output_position(NUM_DIGEST_WORDS - 1)];
+    let is_wide = WIDE_OPS
+        .into_iter()
+        .map(|op| flag(op.opcode))
+        .fold(P::ZEROS, |acc, x| acc + x);
+    let last_read = local_values[COL_WIDE_LAST_READ];
+    let compute_done = local_values[col_compute_position(NUM_COMPUTE_ROWS - 1)];
+    let wide_done = local_values[col_write_position(NUM_WIDE_WORDS - 1)];
+    yield_constr.constraint_transition(next_is_loading - is_loading * (P::ONES - is_last_word));
+    yield_constr.constraint_transition(
+        next_values[IS_FETCH]
+            - (is_execute - flag(Opcode::Halt) - is_state_access - is_hash - is_wide + is_loading
+                - next_is_loading
+                + root_update
+                + hash_done
+                + wide_done),
+    );
+    yield_constr.constraint_transition(next_values[IS_HALTED] - is_halted - flag(Opcode::Halt));
+    yield_constr.constraint_transition(
+        next_values[IS_OLD_PATH] - (is_state_access + is_new_path - root_update),
+    );
+    yield_constr.constraint_transition(next_values[IS_NEW_PATH] - is_old_path);
+    yield_constr.constraint_transition(
+        next_values[IS_HASH_ABSORB]
+            - is_hash * is_hash_nonempty
+            - is_hash_absorb * (P::ONES - is_last_word),
+    );
+    yield_constr.constraint_transition(
+        next_values[IS_HASH_OUTPUT]
+            - is_hash * (P::ONES - is_hash_nonempty)
+            - is_hash_absorb * is_last_word
+            - is_hash_output
+            + hash_done,
+    );
+    yield_constr
+        .constraint_transition(next_values[IS_WIDE_READ] - is_wide - is_wide_read + last_read);
+    yield_constr.constraint_transition(
+        next_values[IS_WIDE_COMPUTE] - last_read - is_wide_compute + compute_done,
+    );
+    yield_constr.constraint_transition(
+        next_values[IS_WIDE_WRITE] - compute_done - is_wide_write + wide_done,
+    );
+
+    // Exactly one opcode flag is set in execute rows, and none in other rows. Likewise for the
+    // register flags.
+    for col in CORE_OPCODE_FLAGS {
+        let val = local_values[col];
+        yield_constr.constraint(val * val - val);
+    }
+    let flag_sum = Opcode::ALL
+        .into_iter()
+        .map(flag)
+        .fold(P::ZEROS, |acc, x| acc + x);
+    yield_constr.constraint(flag_sum - is_execute);
+    let mut decoded_word = Opcode::ALL
+        .into_iter()
+        .map(|opcode| flag(opcode) * F::from_canonical_u16(opcode as u16))
+        .fold(P::ZEROS, |acc, x| acc + x);
+    for (shift, col_reg_flag) in REGISTER_FIELDS {
+        let mut reg_flag_sum = P::ZEROS;
+        for i in 0..NUM_REGISTERS {
+            let reg_flag = local_values[col_reg_flag(i)];
+            yield_constr.constraint(reg_flag * reg_flag - reg_flag);
+            reg_flag_sum += reg_flag;
+            decoded_word += reg_flag * F::from_canonical_u64((i as u64) << shift);
+        }
+        yield_constr.constraint(reg_flag_sum - is_execute);
+    }
+    decoded_word += imm * base;
+    yield_constr.constraint(local_values[COL_INSTRUCTION_WORD] - decoded_word);
+
+    // The operands are the values of registers `rs1` and `rs2`.
+    for (operand, col_reg_flag) in [
+        (op_0, col_rs1_flag as fn(usize) -> usize),
+        (op_1, col_rs2_flag),
+    ] {
+        let selected = (0..NUM_REGISTERS)
+            .map(|i| local_values[col_reg_flag(i)] * local_values[col_register(i)])
+            .fold(P::ZEROS, |acc, x| acc + x);
+        yield_constr.constraint(operand - selected);
+    }
+
+    // Registers are unchanged, except for `rd` if the instruction writes a result.
+    let writes_register = Opcode::ALL
+        .into_iter()
+        .filter(|opcode| opcode.writes_register())
+        .map(flag)
+        .fold(P::ZEROS, |acc, x| acc + x);
+    for i in 0..NUM_REGISTERS {
+        let write = local_values[col_register_write(i)];
+        let local_reg = local_values[col_register(i)];
+        let next_reg = next_values[col_register(i)];
+        yield_constr.constraint(write - local_values[col_rd_flag(i)] * writes_register);
+        yield_constr.constraint_transition(write * (next_reg - result));
+        yield_constr.constraint_transition((P::ONES - write) * (next_reg - local_reg));
+    }
+
+    // Arithmetic and logic instructions pass their operands to the ALU or logic unit, and take
+    // their result from it.
+    let is_add = flag(Opcode::Add);
+    yield_constr.constraint(is_add * (local_values[COL_ADD_INPUT_0] - op_0));
+    yield_constr.constraint(is_add * (local_values[COL_ADD_INPUT_1] - op_1));
+    yield_constr.constraint(is_add * local_values[COL_ADD_INPUT_2]);
+    let sum = local_values[COL_ADD_OUTPUT_0] + local_values[COL_ADD_OUTPUT_1] * base;
+    yield_constr.constraint(is_add * (result - sum));
+
+    let is_sub = flag(Opcode::Sub);
+    yield_constr.constraint(is_sub * (local_values[COL_SUB_INPUT_0] - op_0));
+    yield_constr.constraint(is_sub * (local_values[COL_SUB_INPUT_1] - op_1));
+    let diff = local_values[COL_SUB_OUTPUT_0] + local_values[COL_SUB_OUTPUT_1] * base;
+    yield_constr.constraint(is_sub * (result - diff));
+
+    let is_mul = flag(Opcode::Mul);
+    yield_constr.constraint(is_mul * (local_values[COL_MUL_ADD_FACTOR_0] - op_0));
+    yield_constr.constraint(is_mul * (local_values[COL_MUL_ADD_FACTOR_1] - op_1));
+    yield_constr.constraint(is_mul * local_values[COL_MUL_ADD_ADDEND]);
+    let product = local_values[COL_MUL_ADD_OUTPUT_0] + local_values[COL_MUL_ADD_OUTPUT_1] * base;
+    yield_constr.constraint(is_mul * (result - product));
+
+    let is_div = flag(Opcode::Div);
+    yield_constr.constraint(is_div * (local_values[COL_DIV_INPUT_DIVIDEND] - op_0));
+    yield_constr.constraint(is_div * (local_values[COL_DIV_INPUT_DIVISOR] - op_1));
+    let quotient = local_values[COL_DIV_OUTPUT_QUOT_0] + local_values[COL_DIV_OUTPUT_QUOT_1] * base;
+    yield_constr.constraint(is_div * (result - quotient));
+
+    let is_logic = LOGIC_OPCODES
+        .into_iter()
+        .map(flag)
+        .fold(P::ZEROS, |acc, x| acc + x);
+    yield_constr.constraint(is_logic * (local_values[COL_LOGIC_INPUT_0] - op_0));
+    yield_constr.constraint(is_logic * (local_values[COL_LOGIC_INPUT_1] - op_1));
+    yield_constr.constraint(is_logic * (result - local_values[COL_LOGIC_OUTPUT]));
+
+    yield_constr.constraint(flag(Opcode::LoadI) * (result - imm));
+
+    // State accesses pass the slot and the new value to the state unit, and take the old value from
+    // it. `SLOAD` writes back the old value.
+    let is_sload = flag(Opcode::SLoad);
+    let is_sstore = flag(Opcode::SStore);
+    let old_value = local_values[COL_STATE_OLD_VALUE];
+    let new_value = local_values[COL_STATE_NEW_VALUE];
+    yield_constr.constraint(is_state_access * (local_values[COL_PATH_INDEX] - op_0 - imm));
+    yield_constr.constraint(is_sload * (result - old_value));
+    yield_constr.constraint(is_sload * (new_value - old_value));
+    yield_constr.constraint(is_sstore * (new_value - op_1));
+
+    // `HASH` passes the input's address and length, and the value of register `rd` as the output's
+    // address, to the hash unit.
+    let rd_value = (0..NUM_REGISTERS)
+        .map(|i| local_values[col_rd_flag(i)] * local_values[col_register(i)])
+        .fold(P::ZEROS, |acc, x| acc + x);
+    let input_address = local_values[COL_HASH_INPUT_ADDRESS];
+    let output_address = local_values[COL_HASH_OUTPUT_ADDRESS];
+    yield_constr.constraint(is_hash * (input_address - op_0 - imm));
+    yield_constr.constraint(is_hash * (local_values[COL_HASH_WORDS_LEFT] - op_1));
+    yield_constr.constraint(is_hash * (output_address - rd_value));
+
+    // 256-bit instructions pass the addresses of their operands, and the value of register `rd` as
+    // the result's address, to the 256-bit ALU.
+    yield_constr.constraint(is_wide * (local_values[COL_WIDE_ADDRESS_0] - op_0 - imm));
+    yield_constr.constraint(is_wide * (local_values[COL_WIDE_ADDRESS_1] - op_1));
+    yield_constr.constraint(is_wide * (local_values[COL_WIDE_OUTPUT_ADDRESS] - rd_value));
+
+    // Each row performs one memory operation. Rows which don't need one perform a read, which has
+    // no effect.
+    let is_load = flag(Opcode::Load);
+    let is_store = flag(Opcode::Store);
+    let is_push = flag(Opcode::Push);
+    let is_pop = flag(Opcode::Pop);
+    let is_call = flag(Opcode::Call);
+    let is_ret = flag(Opcode::Ret);
+    let mem_context = local_values[COL_MEMORY_OP_CONTEXT];
+    let mem_segment = local_values[COL_MEMORY_OP_SEGMENT];
+    let mem_address = local_values[COL_MEMORY_OP_ADDRESS];
+    let mem_value = local_values[COL_MEMORY_OP_VALUE];
+    let mem_is_read = local_values[COL_MEMORY_OP_IS_READ];
+    let uses_code = is_loading + is_fetch;
+    let uses_main =
+        is_load + is_store + is_hash_absorb + is_hash_output + is_wide_read + is_wide_write;
+    let uses_stack = is_push + is_pop + is_call + is_ret;
+    let uses_memory = uses_code + uses_main + uses_stack;
+    yield_constr.constraint(uses_memory * mem_context);
+    yield_constr.constraint(
+        uses_memory * mem_segment
+            - uses_code * F::from_canonical_usize(Segment::Code as usize)
+            - uses_main * F::from_canonical_usize(Segment::Main as usize)
+            - uses_stack * F::from_canonical_usize(Segment::Stack as usize),
+    );
+    yield_constr.constraint(
+        uses_memory * mem_address
+            - is_loading * local_clock
+            - is_fetch * ip
+            - (is_load + is_store) * (op_0 + imm)
+            - is_hash_absorb * input_address
+            - is_hash_output * output_address
+            - (is_wide_read + is_wide_write) * local_values[COL_WIDE_MEMORY_ADDRESS]
+            - (is_push + is_call) * sp
+            - is_pop * (sp - F::ONE)
+            - is_ret * (fp - F::ONE),
+    );
+    yield_constr.constraint(
+        P::ONES
+            - mem_is_read
+            - is_loading
+            - is_store
+            - is_push
+            - is_call
+            - is_hash_output
+            - is_wide_write,
+    );
+    yield_constr.constraint(is_store * (mem_value - op_1));
+    yield_constr.constraint(is_push * (mem_value - op_0));
+    yield_constr.constraint((is_load + is_pop) * (result - mem_value));
+    yield_constr.constraint_transition(is_fetch * (next_values[COL_INSTRUCTION_WORD] - mem_value));
 
-    // TODO constraints for stack etc.
+    // The frame record of a call holds the return address and the caller's frame pointer.
+    let ret_ip = local_values[COL_RET_INSTRUCTION_PTR];
+    let ret_fp = local_values[COL_RET_FRAME_PTR];
+    yield_constr.constraint(is_call * (mem_value - ip - F::ONE - fp * base));
+    yield_constr.constraint(is_ret * (mem_value - ret_ip - ret_fp * base));
+
+    // `JUMPI` is taken iff its condition is nonzero.
+    let is_jumpi = flag(Opcode::JumpI);
+    let jumpi_taken = local_values[COL_JUMPI_TAKEN];
+    yield_constr.constraint(jumpi_taken - is_jumpi * op_0 * local_values[COL_JUMPI_CONDITION_INV]);
+    yield_constr.constraint(op_0 * (is_jumpi - jumpi_taken));
+
+    // Update the pointers. The instruction pointer advances after each execute row, unless the
+    // instruction jumps.
+    let is_jumping = flag(Opcode::Jump) + is_call + jumpi_taken;
+    yield_constr.constraint_transition(
+        next_values[COL_INSTRUCTION_PTR]
+            - ip
+            - is_execute
+            - is_jumping * (imm - ip - F::ONE)
+            - is_ret * (ret_ip - ip - F::ONE),
+    );
+    yield_constr.constraint_transition(
+        next_values[COL_STACK_PTR] - sp - is_push - is_call + is_pop + is_ret * (sp - fp + F::ONE),
+    );
+    yield_constr.constraint_transition(
+        next_values[COL_FRAME_PTR] - fp - is_call * (sp + F::ONE - fp) - is_ret * (ret_fp - fp),
+    );
 }
 
 pub(crate) fn eval_core_registers_recursively<F: RichField + Extendable<D>, const D: usize>(
@@ -67,27 +739,445 @@ pub(crate) fn eval_core_registers_recursively<F: RichField + Extendable<D>, cons
     vars: StarkEvaluationTargets<D, NUM_COLUMNS, NUM_PUBLIC_INPUTS>,
     yield_constr: &mut RecursiveConstraintConsumer<F, D>,
 ) {
+    let local_values = &vars.local_values;
+    let next_values = &vars.next_values;
+    let zero = builder.zero_extension();
     let one_ext = builder.one_extension();
-    let max_u16 = builder.constant(F::from_canonical_u64((1 << 16) - 1));
-    let max_u16_ext = builder.convert_to_ext(max_u16);
+    let max_u8 = builder.constant(F::from_canonical_u64((1 << 8) - 1));
+    let max_u8_ext = builder.convert_to_ext(max_u8);
 
     // The clock must start with 0, and increment by 1.
-    let local_clock = vars.local_values[COL_CLOCK];
-    let next_clock = vars.next_values[COL_CLOCK];
+    let local_clock = local_values[COL_CLOCK];
+    let next_clock = next_values[COL_CLOCK];
     let delta_clock = builder.sub_extension(next_clock, local_clock);
     yield_constr.constraint_first_row(builder, local_clock);
     let constraint = builder.sub_extension(delta_clock, one_ext);
     yield_constr.constraint_transition(builder, constraint);
 
-    // The 16-bit table must start with 0, end with 2^16 - 1, and increment by 0 or 1.
-    let local_range_16 = vars.local_values[COL_RANGE_16];
-    let next_range_16 = vars.next_values[COL_RANGE_16];
-    let delta_range_16 = builder.sub_extension(next_range_16, local_range_16);
-    yield_constr.constraint_first_row(builder, local_range_16);
-    let constraint = builder.sub_extension(local_range_16, max_u16_ext);
+    // The 8-bit table must start with 0, end with 2^8 - 1, and increment by 0 or 1.
+    let local_range_8 = local_values[COL_RANGE_8];
+    let next_range_8 = next_values[COL_RANGE_8];
+    let delta_range_8 = builder.sub_extension(next_range_8, local_range_8);
+    yield_constr.constraint_first_row(builder, local_range_8);
+    let constraint = builder.sub_extension(local_range_8, max_u8_ext);
     yield_constr.constraint_last_row(builder, constraint);
-    let constraint = builder.mul_add_extension(delta_range_16, delta_range_16, delta_range_16);
+    let constraint = builder.mul_sub_extension(delta_range_8, delta_range_8, delta_range_8);
+    yield_constr.constraint_transition(builder, constraint);
+
+    let base = F::from_canonical_u64(1 << 16);
+    let ip = local_values[COL_INSTRUCTION_PTR];
+    let fp = local_values[COL_FRAME_PTR];
+    let sp = local_values[COL_STACK_PTR];
+    let imm = local_values[COL_IMMEDIATE];
+    let op_0 = local_values[COL_OPERAND_0];
+    let op_1 = local_values[COL_OPERAND_1];
+    let result = local_values[COL_RESULT];
+    let flag = |opcode: Opcode| local_values[opcode.flag()];
+
+    // Execution starts by loading the program, or fetching if it is empty, with all pointers zeroed
+    // and the registers holding the program's inputs. It must have halted by the last row, where
+    // the registers hold the program's outputs.
+    let is_loading = local_values[IS_LOADING];
+    let is_fetch = local_values[IS_FETCH];
+    let is_halted = local_values[IS_HALTED];
+    let is_old_path = local_values[IS_OLD_PATH];
+    let is_new_path = local_values[IS_NEW_PATH];
+    let is_hash_absorb = local_values[IS_HASH_ABSORB];
+    let is_hash_output = local_values[IS_HASH_OUTPUT];
+    let is_wide_read = local_values[IS_WIDE_READ];
+    let is_wide_compute = local_values[IS_WIDE_COMPUTE];
+    let is_wide_write = local_values[IS_WIDE_WRITE];
+    let phases = [
+        is_loading,
+        is_fetch,
+        is_halted,
+        is_old_path,
+        is_new_path,
+        is_hash_absorb,
+        is_hash_output,
+        is_wide_read,
+        is_wide_compute,
+        is_wide_write,
+    ];
+    let is_not_execute = builder.add_many_extension(&phases);
+    let is_execute = builder.sub_extension(one_ext, is_not_execute);
+    for phase in phases {
+        let constraint = builder.mul_sub_extension(phase, phase, phase);
+        yield_constr.constraint(builder, constraint);
+    }
+    let constraint = builder.add_extension(is_loading, is_fetch);
+    let constraint = builder.sub_extension(constraint, one_ext);
+    yield_constr.constraint_first_row(builder, constraint);
+    yield_constr.constraint_first_row(builder, is_halted);
+    yield_constr.constraint_first_row(builder, is_old_path);
+    yield_constr.constraint_first_row(builder, is_new_path);
+    yield_constr.constraint_first_row(builder, is_hash_absorb);
+    yield_constr.constraint_first_row(builder, is_hash_output);
+    yield_constr.constraint_first_row(builder, is_wide_read);
+    yield_constr.constraint_first_row(builder, is_wide_compute);
+    yield_constr.constraint_first_row(builder, is_wide_write);
+    yield_constr.constraint_first_row(builder, ip);
+    yield_constr.constraint_first_row(builder, fp);
+    yield_constr.constraint_first_row(builder, sp);
+    for i in 0..NUM_REGISTERS {
+        let reg = local_values[col_register(i)];
+        let constraint = builder.sub_extension(reg, vars.public_inputs[pi_input_register(i)]);
+        yield_constr.constraint_first_row(builder, constraint);
+        let constraint = builder.sub_extension(reg, vars.public_inputs[pi_output_register(i)]);
+        yield_constr.constraint_last_row(builder, constraint);
+    }
+    let constraint = builder.sub_extension(is_halted, one_ext);
+    yield_constr.constraint_last_row(builder, constraint);
+
+    // Loading continues until the last word of the program has been written, and is followed by a
+    // fetch, a fetch by an execute, and an execute by a fetch, unless it executes `HALT`, after
+    // which all rows are halted, a state access, after which old and new path rows alternate until
+    // the state root is updated, `HASH`, which is followed by its absorb rows and then its output
+    // rows, or a 256-bit instruction, which is followed by its read, compute and write rows.
+    let next_is_loading = next_values[IS_LOADING];
+    let is_state_access = builder.add_extension(flag(Opcode::SLoad), flag(Opcode::SStore));
+    let root_update = local_values[COL_STATE_ROOT_UPDATE];
+    let is_hash = flag(Opcode::Hash);
+    let is_hash_nonempty = local_values[COL_HASH_IS_NONEMPTY];
+    let is_last_word = local_values[COL_HASH_IS_LAST_WORD];
+    let hash_done = local_values[col_output_position(NUM_DIGEST_WORDS - 1)];
+    let wide_flags = WIDE_OPS.map(|op| flag(op.opcode));
+    let is_wide = builder.add_many_extension(&wide_flags);
+    let last_read = local_values[COL_WIDE_LAST_READ];
+    let compute_done = local_values[col_compute_position(NUM_COMPUTE_ROWS - 1)];
+    let wide_done = local_values[col_write_position(NUM_WIDE_WORDS - 1)];
+    let loading_words_left = builder.mul_sub_extension(is_loading, is_last_word, is_loading);
+    let constraint = builder.add_extension(next_is_loading, loading_words_left);
+    yield_constr.constraint_transition(builder, constraint);
+    let expected =
+        builder.add_many_extension(&[is_execute, is_loading, root_update, hash_done, wide_done]);
+    let expected = builder.sub_extension(expected, flag(Opcode::Halt));
+    let expected = builder.sub_extension(expected, is_state_access);
+    let expected = builder.sub_extension(expected, is_hash);
+    let expected = builder.sub_extension(expected, is_wide);
+    let expected = builder.sub_extension(expected, next_is_loading);
+    let constraint = builder.sub_extension(next_values[IS_FETCH], expected);
+    yield_constr.constraint_transition(builder, constraint);
+    let expected = builder.add_extension(is_halted, flag(Opcode::Halt));
+    let constraint = builder.sub_extension(next_values[IS_HALTED], expected);
+    yield_constr.constraint_transition(builder, constraint);
+    let expected = builder.add_extension(is_state_access, is_new_path);
+    let expected = builder.sub_extension(expected, root_update);
+    let constraint = builder.sub_extension(next_values[IS_OLD_PATH], expected);
+    yield_constr.constraint_transition(builder, constraint);
+    let constraint = builder.sub_extension(next_values[IS_NEW_PATH], is_old_path);
+    yield_constr.constraint_transition(builder, constraint);
+    let absorbing_words_left =
+        builder.mul_sub_extension(is_hash_absorb, is_last_word, is_hash_absorb);
+    let expected = builder.mul_sub_extension(is_hash, is_hash_nonempty, absorbing_words_left);
+    let constraint = builder.sub_extension(next_values[IS_HASH_ABSORB], expected);
+    yield_constr.constraint_transition(builder, constraint);
+    let hash_empty = builder.mul_sub_extension(is_hash, is_hash_nonempty, is_hash);
+    let expected = builder.sub_extension(is_hash_output, hash_empty);
+    let expected = builder.mul_add_extension(is_hash_absorb, is_last_word, expected);
+    let expected = builder.sub_extension(expected, hash_done);
+    let constraint = builder.sub_extension(next_values[IS_HASH_OUTPUT], expected);
+    yield_constr.constraint_transition(builder, constraint);
+    for (phase, start, stay, end) in [
+        (IS_WIDE_READ, is_wide, is_wide_read, last_read),
+        (IS_WIDE_COMPUTE, last_read, is_wide_compute, compute_done),
+        (IS_WIDE_WRITE, compute_done, is_wide_write, wide_done),
+    ] {
+        let expected = builder.add_extension(start, stay);
+        let expected = builder.sub_extension(expected, end);
+        let constraint = builder.sub_extension(next_values[phase], expected);
+        yield_constr.constraint_transition(builder, constraint);
+    }
+
+    // Exactly one opcode flag is set in execute rows, and none in other rows. Likewise for the
+    // register flags.
+    for col in CORE_OPCODE_FLAGS {
+        let val = local_values[col];
+        let constraint = builder.mul_sub_extension(val, val, val);
+        yield_constr.constraint(builder, constraint);
+    }
+    let flags = Opcode::ALL.map(flag);
+    let flag_sum = builder.add_many_extension(&flags);
+    let constraint = builder.sub_extension(flag_sum, is_execute);
+    yield_constr.constraint(builder, constraint);
+    let mut decoded_word = builder.zero_extension();
+    for opcode in Opcode::ALL {
+        decoded_word = builder.mul_const_add_extension(
+            F::from_canonical_u16(opcode as u16),
+            flag(opcode),
+            decoded_word,
+        );
+    }
+    for (shift, col_reg_flag) in REGISTER_FIELDS {
+        let reg_flags = (0..NUM_REGISTERS)
+            .map(|i| local_values[col_reg_flag(i)])
+            .collect::<Vec<_>>();
+        for (i, &reg_flag) in reg_flags.iter().enumerate() {
+            let constraint = builder.mul_sub_extension(reg_flag, reg_flag, reg_flag);
+            yield_constr.constraint(builder, constraint);
+            decoded_word = builder.mul_const_add_extension(
+                F::from_canonical_u64((i as u64) << shift),
+                reg_flag,
+                decoded_word,
+            );
+        }
+        let reg_flag_sum = builder.add_many_extension(&reg_flags);
+        let constraint = builder.sub_extension(reg_flag_sum, is_execute);
+        yield_constr.constraint(builder, constraint);
+    }
+    let decoded_word = builder.mul_const_add_extension(base, imm, decoded_word);
+    let constraint = builder.sub_extension(local_values[COL_INSTRUCTION_WORD], decoded_word);
+    yield_constr.constraint(builder, constraint);
+
+    // The operands are the values of registers `rs1` and `rs2`.
+    for (operand, col_reg_flag) in [
+        (op_0, col_rs1_flag as fn(usize) -> usize),
+        (op_1, col_rs2_flag),
+    ] {
+        let pairs = (0..NUM_REGISTERS)
+            .map(|i| (local_values[col_reg_flag(i)], local_values[col_register(i)]))
+            .collect();
+        let selected = builder.inner_product_extension(F::ONE, zero, pairs);
+        let constraint = builder.sub_extension(operand, selected);
+        yield_constr.constraint(builder, constraint);
+    }
+
+    // Registers are unchanged, except for `rd` if the instruction writes a result.
+    let writing_flags = Opcode::ALL
+        .into_iter()
+        .filter(|opcode| opcode.writes_register())
+        .map(flag)
+        .collect::<Vec<_>>();
+    let writes_register = builder.add_many_extension(&writing_flags);
+    for i in 0..NUM_REGISTERS {
+        let write = local_values[col_register_write(i)];
+        let local_reg = local_values[col_register(i)];
+        let next_reg = next_values[col_register(i)];
+        let expected = builder.mul_extension(local_values[col_rd_flag(i)], writes_register);
+        let constraint = builder.sub_extension(write, expected);
+        yield_constr.constraint(builder, constraint);
+        let diff = builder.sub_extension(next_reg, result);
+        let constraint = builder.mul_extension(write, diff);
+        yield_constr.constraint_transition(builder, constraint);
+        let diff = builder.sub_extension(next_reg, local_reg);
+        let constraint = builder.arithmetic_extension(F::NEG_ONE, F::ONE, write, diff, diff);
+        yield_constr.constraint_transition(builder, constraint);
+    }
+
+    // Arithmetic and logic instructions pass their operands to the ALU or logic unit, and take
+    // their result from it. Likewise, state accesses pass the slot and the new value to the state
+    // unit, and take the old value from it. `SLOAD` writes back the old value. `HASH` passes the
+    // input's address and length, and the value of register `rd` as the output's address, to the
+    // hash unit, and 256-bit instructions pass the addresses of their operands and result to the
+    // 256-bit ALU.
+    let combine_u16s = |builder: &mut CircuitBuilder<F, D>, lo: usize, hi: usize| {
+        builder.mul_const_add_extension(base, local_values[hi], local_values[lo])
+    };
+    let sum = combine_u16s(builder, COL_ADD_OUTPUT_0, COL_ADD_OUTPUT_1);
+    let diff = combine_u16s(builder, COL_SUB_OUTPUT_0, COL_SUB_OUTPUT_1);
+    let product = combine_u16s(builder, COL_MUL_ADD_OUTPUT_0, COL_MUL_ADD_OUTPUT_1);
+    let quotient = combine_u16s(builder, COL_DIV_OUTPUT_QUOT_0, COL_DIV_OUTPUT_QUOT_1);
+    let logic_flags = LOGIC_OPCODES.map(flag);
+    let address = builder.add_extension(op_0, imm);
+    let is_logic = builder.add_many_extension(&logic_flags);
+    let rd_pairs = (0..NUM_REGISTERS)
+        .map(|i| (local_values[col_rd_flag(i)], local_values[col_register(i)]))
+        .collect();
+    let rd_value = builder.inner_product_extension(F::ONE, zero, rd_pairs);
+    let input_address = local_values[COL_HASH_INPUT_ADDRESS];
+    let output_address = local_values[COL_HASH_OUTPUT_ADDRESS];
+    let unit_checks = [
+        (flag(Opcode::Add), local_values[COL_ADD_INPUT_0], op_0),
+        (flag(Opcode::Add), local_values[COL_ADD_INPUT_1], op_1),
+        (flag(Opcode::Add), local_values[COL_ADD_INPUT_2], zero),
+        (flag(Opcode::Add), result, sum),
+        (flag(Opcode::Sub), local_values[COL_SUB_INPUT_0], op_0),
+        (flag(Opcode::Sub), local_values[COL_SUB_INPUT_1], op_1),
+        (flag(Opcode::Sub), result, diff),
+        (flag(Opcode::Mul), local_values[COL_MUL_ADD_FACTOR_0], op_0),
+        (flag(Opcode::Mul), local_values[COL_MUL_ADD_FACTOR_1], op_1),
+        (flag(Opcode::Mul), local_values[COL_MUL_ADD_ADDEND], zero),
+        (flag(Opcode::Mul), result, product),
+        (
+            flag(Opcode::Div),
+            local_values[COL_DIV_INPUT_DIVIDEND],
+            op_0,
+        ),
+        (flag(Opcode::Div), local_values[COL_DIV_INPUT_DIVISOR], op_1),
+        (flag(Opcode::Div), result, quotient),
+        (is_logic, local_values[COL_LOGIC_INPUT_0], op_0),
+        (is_logic, local_values[COL_LOGIC_INPUT_1], op_1),
+        (is_logic, result, local_values[COL_LOGIC_OUTPUT]),
+        (flag(Opcode::LoadI), result, imm),
+        (is_state_access, local_values[COL_PATH_INDEX], address),
+        (
+            flag(Opcode::SLoad),
+            result,
+            local_values[COL_STATE_OLD_VALUE],
+        ),
+        (
+            flag(Opcode::SLoad),
+            local_values[COL_STATE_NEW_VALUE],
+            local_values[COL_STATE_OLD_VALUE],
+        ),
+        (
+            flag(Opcode::SStore),
+            local_values[COL_STATE_NEW_VALUE],
+            op_1,
+        ),
+        (is_hash, input_address, address),
+        (is_hash, local_values[COL_HASH_WORDS_LEFT], op_1),
+        (is_hash, output_address, rd_value),
+        (is_wide, local_values[COL_WIDE_ADDRESS_0], address),
+        (is_wide, local_values[COL_WIDE_ADDRESS_1], op_1),
+        (is_wide, local_values[COL_WIDE_OUTPUT_ADDRESS], rd_value),
+    ];
+    for (filter, x, y) in unit_checks {
+        constraint_filtered_eq(builder, yield_constr, filter, x, y);
+    }
+
+    // Each row performs one memory operation. Rows which don't need one perform a read, which has
+    // no effect.
+    let is_load = flag(Opcode::Load);
+    let is_store = flag(Opcode::Store);
+    let is_push = flag(Opcode::Push);
+    let is_pop = flag(Opcode::Pop);
+    let is_call = flag(Opcode::Call);
+    let is_ret = flag(Opcode::Ret);
+    let mem_context = local_values[COL_MEMORY_OP_CONTEXT];
+    let mem_segment = local_values[COL_MEMORY_OP_SEGMENT];
+    let mem_address = local_values[COL_MEMORY_OP_ADDRESS];
+    let mem_value = local_values[COL_MEMORY_OP_VALUE];
+    let mem_is_read = local_values[COL_MEMORY_OP_IS_READ];
+    let uses_code = builder.add_extension(is_loading, is_fetch);
+    let uses_main = builder.add_many_extension(&[
+        is_load,
+        is_store,
+        is_hash_absorb,
+        is_hash_output,
+        is_wide_read,
+        is_wide_write,
+    ]);
+    let uses_stack = builder.add_many_extension(&[is_push, is_pop, is_call, is_ret]);
+    let uses_memory = builder.add_many_extension(&[uses_code, uses_main, uses_stack]);
+    let constraint = builder.mul_extension(uses_memory, mem_context);
+    yield_constr.constraint(builder, constraint);
+
+    let mut expected_segment = builder.zero_extension();
+    for (uses_segment, segment) in [
+        (uses_code, Segment::Code),
+        (uses_main, Segment::Main),
+        (uses_stack, Segment::Stack),
+    ] {
+        expected_segment = builder.mul_const_add_extension(
+            F::from_canonical_usize(segment as usize),
+            uses_segment,
+            expected_segment,
+        );
+    }
+    let constraint = builder.mul_sub_extension(uses_memory, mem_segment, expected_segment);
+    yield_constr.constraint(builder, constraint);
+
+    let is_load_or_store = builder.add_extension(is_load, is_store);
+    let sp_minus_one = builder.sub_extension(sp, one_ext);
+    let fp_minus_one = builder.sub_extension(fp, one_ext);
+    let is_push_or_call = builder.add_extension(is_push, is_call);
+    let is_wide_memory_op = builder.add_extension(is_wide_read, is_wide_write);
+    let expected_address = builder.inner_product_extension(
+        F::ONE,
+        zero,
+        vec![
+            (is_loading, local_clock),
+            (is_fetch, ip),
+            (is_load_or_store, address),
+            (is_hash_absorb, input_address),
+            (is_hash_output, output_address),
+            (is_wide_memory_op, local_values[COL_WIDE_MEMORY_ADDRESS]),
+            (is_push_or_call, sp),
+            (is_pop, sp_minus_one),
+            (is_ret, fp_minus_one),
+        ],
+    );
+    let constraint = builder.mul_sub_extension(uses_memory, mem_address, expected_address);
+    yield_constr.constraint(builder, constraint);
+
+    let is_write = builder.add_many_extension(&[
+        mem_is_read,
+        is_loading,
+        is_store,
+        is_push,
+        is_call,
+        is_hash_output,
+        is_wide_write,
+    ]);
+    let constraint = builder.sub_extension(one_ext, is_write);
+    yield_constr.constraint(builder, constraint);
+    let is_load_or_pop = builder.add_extension(is_load, is_pop);
+    constraint_filtered_eq(builder, yield_constr, is_store, mem_value, op_1);
+    constraint_filtered_eq(builder, yield_constr, is_push, mem_value, op_0);
+    constraint_filtered_eq(builder, yield_constr, is_load_or_pop, result, mem_value);
+    let diff = builder.sub_extension(next_values[COL_INSTRUCTION_WORD], mem_value);
+    let constraint = builder.mul_extension(is_fetch, diff);
+    yield_constr.constraint_transition(builder, constraint);
+
+    // The frame record of a call holds the return address and the caller's frame pointer.
+    let ret_ip = local_values[COL_RET_INSTRUCTION_PTR];
+    let ret_fp = local_values[COL_RET_FRAME_PTR];
+    let call_record = builder.mul_const_add_extension(base, fp, ip);
+    let call_record = builder.add_extension(call_record, one_ext);
+    constraint_filtered_eq(builder, yield_constr, is_call, mem_value, call_record);
+    let ret_record = builder.mul_const_add_extension(base, ret_fp, ret_ip);
+    constraint_filtered_eq(builder, yield_constr, is_ret, mem_value, ret_record);
+
+    // `JUMPI` is taken iff its condition is nonzero.
+    let is_jumpi = flag(Opcode::JumpI);
+    let jumpi_taken = local_values[COL_JUMPI_TAKEN];
+    let expected =
+        builder.mul_many_extension(&[is_jumpi, op_0, local_values[COL_JUMPI_CONDITION_INV]]);
+    let constraint = builder.sub_extension(jumpi_taken, expected);
+    yield_constr.constraint(builder, constraint);
+    let diff = builder.sub_extension(is_jumpi, jumpi_taken);
+    let constraint = builder.mul_extension(op_0, diff);
+    yield_constr.constraint(builder, constraint);
+
+    // Update the pointers. The instruction pointer advances after each execute row, unless the
+    // instruction jumps.
+    let is_jumping = builder.add_many_extension(&[flag(Opcode::Jump), is_call, jumpi_taken]);
+    let ip_plus_one = builder.add_extension(ip, one_ext);
+    let jump_offset = builder.sub_extension(imm, ip_plus_one);
+    let ret_offset = builder.sub_extension(ret_ip, ip_plus_one);
+    let expected_ip = builder.add_extension(ip, is_execute);
+    let expected_ip =
+        builder.wide_arithmetic_extension(is_jumping, jump_offset, is_ret, ret_offset, expected_ip);
+    let constraint = builder.sub_extension(next_values[COL_INSTRUCTION_PTR], expected_ip);
     yield_constr.constraint_transition(builder, constraint);
 
-    // TODO constraints for stack etc.
+    let frame_size = builder.sub_extension(sp, fp_minus_one);
+    let expected_sp = builder.add_many_extension(&[sp, is_push, is_call]);
+    let expected_sp = builder.sub_extension(expected_sp, is_pop);
+    let expected_sp =
+        builder.arithmetic_extension(F::NEG_ONE, F::ONE, is_ret, frame_size, expected_sp);
+    let constraint = builder.sub_extension(next_values[COL_STACK_PTR], expected_sp);
+    yield_constr.constraint_transition(builder, constraint);
+
+    let sp_plus_one = builder.add_extension(sp, one_ext);
+    let call_offset = builder.sub_extension(sp_plus_one, fp);
+    let ret_offset = builder.sub_extension(ret_fp, fp);
+    let expected_fp =
+        builder.wide_arithmetic_extension(is_call, call_offset, is_ret, ret_offset, fp);
+    let constraint = builder.sub_extension(next_values[COL_FRAME_PTR], expected_fp);
+    yield_constr.constraint_transition(builder, constraint);
+}
+
+/// Adds a constraint that `x = y` whenever `filter` is 1.
+fn constraint_filtered_eq<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    filter: ExtensionTarget<D>,
+    x: ExtensionTarget<D>,
+    y: ExtensionTarget<D>,
+) {
+    let diff = builder.sub_extension(x, y);
+    let constraint = builder.mul_extension(filter, diff);
+    yield_constr.constraint(builder, constraint);
 }
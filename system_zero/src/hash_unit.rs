@@ -0,0 +1,585 @@
+use plonky2::field::extension_field::Extendable;
+use plonky2::field::field_types::{Field, PrimeField64};
+use plonky2::field::packed_field::PackedField;
+use plonky2::hash::hash_types::RichField;
+use plonky2::hash::hashing::SPONGE_WIDTH;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::vars::StarkEvaluationTargets;
+use starky::vars::StarkEvaluationVars;
+
+use crate::alu::canonical::{
+    combine_u32s_check_canonical, combine_u32s_check_canonical_circuit, compute_canonical_inv,
+};
+use crate::public_input_layout::{pi_program_hash, NUM_PUBLIC_INPUTS, PI_PROGRAM_LENGTH};
+use crate::registers::core::{IS_HASH, IS_HASH_ABSORB, IS_HASH_OUTPUT, IS_LOADING};
+use crate::registers::hash::*;
+use crate::registers::memory::COL_MEMORY_OP_VALUE;
+use crate::registers::permutation::{col_input, col_output};
+use crate::registers::state::NUM_HASH_OUT_ELTS;
+use crate::registers::NUM_COLUMNS;
+
+/// Sets up the first row to absorb the program's words, if any, as it is loaded.
+pub(crate) fn generate_first_row_hash_unit<F: Field>(
+    first_values: &mut [F; NUM_COLUMNS],
+    program_length: usize,
+) {
+    let length = F::from_canonical_usize(program_length);
+    first_values[col_sponge_state(SPONGE_RATE)] = length;
+    first_values[COL_HASH_WORDS_LEFT] = length;
+    first_values[col_absorb_position(0)] = F::from_bool(program_length != 0);
+}
+
+/// Sets up the execute row of `HASH`, which hashes the `length` words at `input_address`, and
+/// writes the digest to `output_address`.
+pub(crate) fn generate_hash<F: Field>(
+    values: &mut [F; NUM_COLUMNS],
+    input_address: F,
+    length: F,
+    output_address: F,
+) {
+    for i in 0..SPONGE_WIDTH {
+        let initial = if i == SPONGE_RATE { length } else { F::ZERO };
+        values[col_sponge_state(i)] = initial;
+        values[col_sponge_next_state(i)] = initial;
+    }
+    values[COL_HASH_INPUT_ADDRESS] = input_address;
+    values[COL_HASH_WORDS_LEFT] = length;
+    values[COL_HASH_LENGTH_INV] = length.try_inverse().unwrap_or(F::ZERO);
+    values[COL_HASH_IS_NONEMPTY] = F::from_bool(length.is_nonzero());
+    values[COL_HASH_OUTPUT_ADDRESS] = output_address;
+}
+
+/// Generates an absorb row of `HASH`, or a loading row, which absorbs `word`, given the hash
+/// columns carried over from the previous row.
+pub(crate) fn generate_hash_absorb_row<F: RichField>(values: &mut [F; NUM_COLUMNS], word: F) {
+    let position = (0..SPONGE_RATE)
+        .position(|i| values[col_absorb_position(i)].is_one())
+        .expect("No absorb position is set.");
+    let mut state = [F::ZERO; SPONGE_WIDTH];
+    for i in 0..SPONGE_WIDTH {
+        state[i] = values[col_sponge_state(i)];
+    }
+    state[position] = word;
+
+    let words_left_minus_one = values[COL_HASH_WORDS_LEFT] - F::ONE;
+    let is_last = words_left_minus_one.is_zero();
+    let permute = is_last || position == SPONGE_RATE - 1;
+    values[COL_HASH_WORDS_LEFT_MINUS_ONE_INV] =
+        words_left_minus_one.try_inverse().unwrap_or(F::ZERO);
+    values[COL_HASH_IS_LAST_WORD] = F::from_bool(is_last);
+    values[COL_HASH_PERMUTE] = F::from_bool(permute);
+
+    for i in 0..SPONGE_WIDTH {
+        values[col_input(i)] = state[i];
+    }
+    if permute {
+        state = F::poseidon(state);
+    }
+    for i in 0..SPONGE_WIDTH {
+        values[col_sponge_next_state(i)] = state[i];
+    }
+}
+
+/// Generates an output row, given the hash columns carried over from the previous row, and returns
+/// the word of the digest which it writes.
+pub(crate) fn generate_hash_output_row<F: PrimeField64>(values: &mut [F; NUM_COLUMNS]) -> F {
+    let position = (0..NUM_DIGEST_WORDS)
+        .position(|i| values[col_output_position(i)].is_one())
+        .expect("No output position is set.");
+    for i in 0..SPONGE_WIDTH {
+        values[col_sponge_next_state(i)] = values[col_sponge_state(i)];
+    }
+
+    let element = values[col_sponge_state(position / 2)].to_canonical_u64();
+    for i in 0..4 {
+        values[col_digest_limb(i)] = F::from_canonical_u64((element >> (16 * i)) & 0xffff);
+    }
+    values[COL_HASH_CANONICAL_INV] = compute_canonical_inv(element);
+    F::from_canonical_u32((element >> (32 * (position % 2))) as u32)
+}
+
+/// Generates the hash columns of the next row, given that its phase flags have been generated.
+pub(crate) fn generate_next_row_hash_unit<F: Field>(
+    local_values: &[F; NUM_COLUMNS],
+    next_values: &mut [F; NUM_COLUMNS],
+) {
+    // The following mirrors the transition constraints in `eval_hash_unit`.
+    let is_hash = local_values[IS_HASH];
+    let is_hash_absorb = local_values[IS_HASH_ABSORB];
+    let is_absorb = is_hash_absorb + local_values[IS_LOADING];
+    let is_output = local_values[IS_HASH_OUTPUT];
+    let next_is_hash_absorb = next_values[IS_HASH_ABSORB];
+    let next_is_absorb = next_is_hash_absorb + next_values[IS_LOADING];
+    let next_is_output = next_values[IS_HASH_OUTPUT];
+
+    if (next_is_absorb + next_is_output).is_one() {
+        for i in 0..SPONGE_WIDTH {
+            next_values[col_sponge_state(i)] = local_values[col_sponge_next_state(i)];
+        }
+    }
+    if (next_is_hash_absorb + next_is_output).is_one() {
+        next_values[COL_HASH_OUTPUT_ADDRESS] = local_values[COL_HASH_OUTPUT_ADDRESS] + is_output;
+    }
+    if next_is_absorb.is_one() {
+        next_values[col_absorb_position(0)] =
+            local_values[col_absorb_position(SPONGE_RATE - 1)] + is_hash;
+        for i in 1..SPONGE_RATE {
+            next_values[col_absorb_position(i)] = local_values[col_absorb_position(i - 1)];
+        }
+        next_values[COL_HASH_WORDS_LEFT] = local_values[COL_HASH_WORDS_LEFT] - is_absorb;
+    }
+    if next_is_hash_absorb.is_one() {
+        next_values[COL_HASH_INPUT_ADDRESS] = local_values[COL_HASH_INPUT_ADDRESS] + is_hash_absorb;
+    }
+    next_values[col_output_position(0)] = is_hash * (F::ONE - local_values[COL_HASH_IS_NONEMPTY])
+        + is_hash_absorb * local_values[COL_HASH_IS_LAST_WORD];
+    for i in 1..NUM_DIGEST_WORDS {
+        next_values[col_output_position(i)] = local_values[col_output_position(i - 1)];
+    }
+}
+
+pub(crate) fn eval_hash_unit<F: Field, P: PackedField<Scalar = F>>(
+    vars: StarkEvaluationVars<F, P, NUM_COLUMNS, NUM_PUBLIC_INPUTS>,
+    yield_constr: &mut ConstraintConsumer<P>,
+) {
+    let local_values = &vars.local_values;
+    let next_values = &vars.next_values;
+
+    let is_hash = local_values[IS_HASH];
+    let is_hash_absorb = local_values[IS_HASH_ABSORB];
+    let is_loading = local_values[IS_LOADING];
+    let is_absorb = is_hash_absorb + is_loading;
+    let is_output = local_values[IS_HASH_OUTPUT];
+    let next_is_hash_absorb = next_values[IS_HASH_ABSORB];
+    let next_is_absorb = next_is_hash_absorb + next_values[IS_LOADING];
+    let next_is_output = next_values[IS_HASH_OUTPUT];
+    let words_left = local_values[COL_HASH_WORDS_LEFT];
+    let is_nonempty = local_values[COL_HASH_IS_NONEMPTY];
+    let is_last = local_values[COL_HASH_IS_LAST_WORD];
+    let permute = local_values[COL_HASH_PERMUTE];
+
+    // The execute row of `HASH` sets up the sponge state, which is zero except for the length of the
+    // input in the first capacity element, and checks whether the input is empty.
+    let state_minus_initial = (0..SPONGE_WIDTH)
+        .map(|i| {
+            let state = local_values[col_sponge_state(i)];
+            if i == SPONGE_RATE {
+                state - words_left
+            } else {
+                state
+            }
+        })
+        .collect::<Vec<_>>();
+    for &diff in &state_minus_initial {
+        yield_constr.constraint(is_hash * diff);
+    }
+    let length_inv = local_values[COL_HASH_LENGTH_INV];
+    yield_constr.constraint(is_hash * (words_left * length_inv - is_nonempty));
+    yield_constr.constraint(is_hash * words_left * (P::ONES - is_nonempty));
+
+    // Loading rows absorb the words of the program in the same way, with the sponge state set up in
+    // the first row, so the last one computes the program's digest, which must be the one in the
+    // public inputs. There must be as many of them as the public program length; an empty program
+    // has no loading rows, and a zero digest.
+    let program_length = vars.public_inputs[PI_PROGRAM_LENGTH];
+    yield_constr.constraint_first_row(words_left - program_length);
+    yield_constr.constraint_first_row((P::ONES - is_loading) * program_length);
+    yield_constr.constraint_first_row(is_loading * (local_values[col_absorb_position(0)] - F::ONE));
+    for &diff in &state_minus_initial {
+        yield_constr.constraint_first_row(is_loading * diff);
+    }
+    for i in 0..NUM_HASH_OUT_ELTS {
+        let program_hash = vars.public_inputs[pi_program_hash(i)];
+        let next_state = local_values[col_sponge_next_state(i)];
+        yield_constr.constraint(is_loading * is_last * (next_state - program_hash));
+        yield_constr.constraint_first_row((P::ONES - is_loading) * program_hash);
+    }
+
+    // Exactly one position flag is set in absorb and output rows, and none in other rows.
+    let mut absorb_position_sum = P::ZEROS;
+    for i in 0..SPONGE_RATE {
+        let flag = local_values[col_absorb_position(i)];
+        yield_constr.constraint(flag * flag - flag);
+        absorb_position_sum += flag;
+    }
+    yield_constr.constraint(absorb_position_sum - is_absorb);
+    let mut output_position_sum = P::ZEROS;
+    for i in 0..NUM_DIGEST_WORDS {
+        let flag = local_values[col_output_position(i)];
+        yield_constr.constraint(flag * flag - flag);
+        output_position_sum += flag;
+    }
+    yield_constr.constraint(output_position_sum - is_output);
+
+    // An absorb row writes its word to its position of the rate, leaving the rest of the sponge
+    // state unchanged, and passes the result to the permutation unit.
+    let word = local_values[COL_MEMORY_OP_VALUE];
+    for i in 0..SPONGE_WIDTH {
+        let input = local_values[col_input(i)];
+        let state = local_values[col_sponge_state(i)];
+        if i < SPONGE_RATE {
+            let flag = local_values[col_absorb_position(i)];
+            yield_constr.constraint(flag * (input - word));
+            yield_constr.constraint((is_absorb - flag) * (input - state));
+        } else {
+            yield_constr.constraint(is_absorb * (input - state));
+        }
+    }
+
+    // The last word is the one with one word left. The sponge state is permuted after the last word,
+    // or when the rate is full.
+    let words_left_minus_one = words_left - F::ONE;
+    let words_left_minus_one_inv = local_values[COL_HASH_WORDS_LEFT_MINUS_ONE_INV];
+    yield_constr.constraint(
+        is_absorb * (words_left_minus_one * words_left_minus_one_inv + is_last - F::ONE),
+    );
+    yield_constr.constraint(is_absorb * is_last * words_left_minus_one);
+    let is_rate_full = local_values[col_absorb_position(SPONGE_RATE - 1)];
+    yield_constr
+        .constraint(is_absorb * (permute - is_rate_full - is_last + is_rate_full * is_last));
+
+    // Each row of `HASH`, and each loading row, passes the sponge state on to the next one. Only
+    // absorb rows change it.
+    for i in 0..SPONGE_WIDTH {
+        let state = local_values[col_sponge_state(i)];
+        let next_state = local_values[col_sponge_next_state(i)];
+        let input = local_values[col_input(i)];
+        let output = local_values[col_output(i)];
+        yield_constr.constraint(is_absorb * (next_state - input - permute * (output - input)));
+        yield_constr.constraint((is_hash + is_output) * (next_state - state));
+        yield_constr.constraint_transition(
+            (next_is_absorb + next_is_output) * (next_values[col_sponge_state(i)] - next_state),
+        );
+    }
+
+    // Absorb rows write to consecutive positions of the rate, starting with the first, and those of
+    // `HASH` read consecutive words. Output rows write the words of the digest in order, to
+    // consecutive addresses, after the last absorb row, or after the execute row if the input is
+    // empty.
+    yield_constr.constraint_transition(
+        next_is_absorb
+            * (next_values[col_absorb_position(0)]
+                - local_values[col_absorb_position(SPONGE_RATE - 1)]
+                - is_hash),
+    );
+    for i in 1..SPONGE_RATE {
+        yield_constr.constraint_transition(
+            next_is_absorb
+                * (next_values[col_absorb_position(i)] - local_values[col_absorb_position(i - 1)]),
+        );
+    }
+    let input_address = local_values[COL_HASH_INPUT_ADDRESS];
+    let next_input_address = next_values[COL_HASH_INPUT_ADDRESS];
+    let next_words_left = next_values[COL_HASH_WORDS_LEFT];
+    yield_constr.constraint_transition(
+        next_is_hash_absorb * (next_input_address - input_address - is_hash_absorb),
+    );
+    yield_constr.constraint_transition(next_is_absorb * (next_words_left - words_left + is_absorb));
+
+    yield_constr.constraint_transition(
+        next_values[col_output_position(0)]
+            - is_hash * (P::ONES - is_nonempty)
+            - is_hash_absorb * is_last,
+    );
+    for i in 1..NUM_DIGEST_WORDS {
+        yield_constr.constraint_transition(
+            next_values[col_output_position(i)] - local_values[col_output_position(i - 1)],
+        );
+    }
+    let output_address = local_values[COL_HASH_OUTPUT_ADDRESS];
+    let next_output_address = next_values[COL_HASH_OUTPUT_ADDRESS];
+    yield_constr.constraint_transition(
+        (next_is_hash_absorb + next_is_output) * (next_output_address - output_address - is_output),
+    );
+
+    // An output row splits an element of the digest canonically into u32s, and writes the low one
+    // if its position is even, or the high one if it is odd.
+    let base = F::from_canonical_u32(1 << 16);
+    let lo = local_values[col_digest_limb(0)] + local_values[col_digest_limb(1)] * base;
+    let hi = local_values[col_digest_limb(2)] + local_values[col_digest_limb(3)] * base;
+    let inverse = local_values[COL_HASH_CANONICAL_INV];
+    let element = combine_u32s_check_canonical(lo, hi, inverse, is_output, yield_constr);
+    let mut is_even = P::ZEROS;
+    let mut is_odd = P::ZEROS;
+    for i in 0..NUM_HASH_OUT_ELTS {
+        let even_flag = local_values[col_output_position(2 * i)];
+        let odd_flag = local_values[col_output_position(2 * i + 1)];
+        let digest_element = local_values[col_sponge_state(i)];
+        yield_constr.constraint((even_flag + odd_flag) * (digest_element - element));
+        is_even += even_flag;
+        is_odd += odd_flag;
+    }
+    yield_constr.constraint(is_even * (word - lo));
+    yield_constr.constraint(is_odd * (word - hi));
+}
+
+pub(crate) fn eval_hash_unit_recursively<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    vars: StarkEvaluationTargets<D, NUM_COLUMNS, NUM_PUBLIC_INPUTS>,
+    yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    let local_values = &vars.local_values;
+    let next_values = &vars.next_values;
+    let one = builder.one_extension();
+
+    let is_hash = local_values[IS_HASH];
+    let is_hash_absorb = local_values[IS_HASH_ABSORB];
+    let is_loading = local_values[IS_LOADING];
+    let is_absorb = builder.add_extension(is_hash_absorb, is_loading);
+    let is_output = local_values[IS_HASH_OUTPUT];
+    let next_is_hash_absorb = next_values[IS_HASH_ABSORB];
+    let next_is_absorb = builder.add_extension(next_is_hash_absorb, next_values[IS_LOADING]);
+    let next_is_output = next_values[IS_HASH_OUTPUT];
+    let words_left = local_values[COL_HASH_WORDS_LEFT];
+    let is_nonempty = local_values[COL_HASH_IS_NONEMPTY];
+    let is_last = local_values[COL_HASH_IS_LAST_WORD];
+    let permute = local_values[COL_HASH_PERMUTE];
+
+    // Constrains `filter * (x - y)`.
+    let constraint_filtered_eq = |builder: &mut CircuitBuilder<F, D>,
+                                  yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+                                  filter: ExtensionTarget<D>,
+                                  x: ExtensionTarget<D>,
+                                  y: ExtensionTarget<D>| {
+        let diff = builder.sub_extension(x, y);
+        let constraint = builder.mul_extension(filter, diff);
+        yield_constr.constraint(builder, constraint);
+    };
+    // Constrains `filter * (next_values[col] - x)` over transitions.
+    let transition_filtered_eq = |builder: &mut CircuitBuilder<F, D>,
+                                  yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+                                  filter: ExtensionTarget<D>,
+                                  col: usize,
+                                  x: ExtensionTarget<D>| {
+        let diff = builder.sub_extension(next_values[col], x);
+        let constraint = builder.mul_extension(filter, diff);
+        yield_constr.constraint_transition(builder, constraint);
+    };
+
+    // The execute row of `HASH` sets up the sponge state, which is zero except for the length of the
+    // input in the first capacity element, and checks whether the input is empty.
+    let state_minus_initial = (0..SPONGE_WIDTH)
+        .map(|i| {
+            let state = local_values[col_sponge_state(i)];
+            if i == SPONGE_RATE {
+                builder.sub_extension(state, words_left)
+            } else {
+                state
+            }
+        })
+        .collect::<Vec<_>>();
+    for &diff in &state_minus_initial {
+        let constraint = builder.mul_extension(is_hash, diff);
+        yield_constr.constraint(builder, constraint);
+    }
+    let length_inv = local_values[COL_HASH_LENGTH_INV];
+    let product = builder.mul_extension(words_left, length_inv);
+    constraint_filtered_eq(builder, yield_constr, is_hash, product, is_nonempty);
+    let is_empty = builder.sub_extension(one, is_nonempty);
+    let constraint = builder.mul_many_extension(&[is_hash, words_left, is_empty]);
+    yield_constr.constraint(builder, constraint);
+
+    // Loading rows absorb the words of the program in the same way, with the sponge state set up in
+    // the first row, so the last one computes the program's digest, which must be the one in the
+    // public inputs. There must be as many of them as the public program length; an empty program
+    // has no loading rows, and a zero digest.
+    let program_length = vars.public_inputs[PI_PROGRAM_LENGTH];
+    let is_not_loading = builder.sub_extension(one, is_loading);
+    let constraint = builder.sub_extension(words_left, program_length);
+    yield_constr.constraint_first_row(builder, constraint);
+    let constraint = builder.mul_extension(is_not_loading, program_length);
+    yield_constr.constraint_first_row(builder, constraint);
+    let constraint =
+        builder.mul_sub_extension(is_loading, local_values[col_absorb_position(0)], is_loading);
+    yield_constr.constraint_first_row(builder, constraint);
+    for &diff in &state_minus_initial {
+        let constraint = builder.mul_extension(is_loading, diff);
+        yield_constr.constraint_first_row(builder, constraint);
+    }
+    let is_last_loading = builder.mul_extension(is_loading, is_last);
+    for i in 0..NUM_HASH_OUT_ELTS {
+        let program_hash = vars.public_inputs[pi_program_hash(i)];
+        let next_state = local_values[col_sponge_next_state(i)];
+        constraint_filtered_eq(
+            builder,
+            yield_constr,
+            is_last_loading,
+            next_state,
+            program_hash,
+        );
+        let constraint = builder.mul_extension(is_not_loading, program_hash);
+        yield_constr.constraint_first_row(builder, constraint);
+    }
+
+    // Exactly one position flag is set in absorb and output rows, and none in other rows.
+    for (num_positions, col_position, phase) in [
+        (
+            SPONGE_RATE,
+            col_absorb_position as fn(usize) -> usize,
+            is_absorb,
+        ),
+        (NUM_DIGEST_WORDS, col_output_position, is_output),
+    ] {
+        let flags = (0..num_positions)
+            .map(|i| local_values[col_position(i)])
+            .collect::<Vec<_>>();
+        for &flag in &flags {
+            let constraint = builder.mul_sub_extension(flag, flag, flag);
+            yield_constr.constraint(builder, constraint);
+        }
+        let position_sum = builder.add_many_extension(&flags);
+        let constraint = builder.sub_extension(position_sum, phase);
+        yield_constr.constraint(builder, constraint);
+    }
+
+    // An absorb row writes its word to its position of the rate, leaving the rest of the sponge
+    // state unchanged, and passes the result to the permutation unit.
+    let word = local_values[COL_MEMORY_OP_VALUE];
+    for i in 0..SPONGE_WIDTH {
+        let input = local_values[col_input(i)];
+        let state = local_values[col_sponge_state(i)];
+        if i < SPONGE_RATE {
+            let flag = local_values[col_absorb_position(i)];
+            constraint_filtered_eq(builder, yield_constr, flag, input, word);
+            let filter = builder.sub_extension(is_absorb, flag);
+            constraint_filtered_eq(builder, yield_constr, filter, input, state);
+        } else {
+            constraint_filtered_eq(builder, yield_constr, is_absorb, input, state);
+        }
+    }
+
+    // The last word is the one with one word left. The sponge state is permuted after the last word,
+    // or when the rate is full.
+    let words_left_minus_one = builder.sub_extension(words_left, one);
+    let words_left_minus_one_inv = local_values[COL_HASH_WORDS_LEFT_MINUS_ONE_INV];
+    let constraint =
+        builder.mul_add_extension(words_left_minus_one, words_left_minus_one_inv, is_last);
+    let constraint = builder.sub_extension(constraint, one);
+    let constraint = builder.mul_extension(is_absorb, constraint);
+    yield_constr.constraint(builder, constraint);
+    let constraint = builder.mul_many_extension(&[is_absorb, is_last, words_left_minus_one]);
+    yield_constr.constraint(builder, constraint);
+    let is_rate_full = local_values[col_absorb_position(SPONGE_RATE - 1)];
+    let expected = builder.add_extension(is_rate_full, is_last);
+    let expected =
+        builder.arithmetic_extension(F::NEG_ONE, F::ONE, is_rate_full, is_last, expected);
+    constraint_filtered_eq(builder, yield_constr, is_absorb, permute, expected);
+
+    // Each row of `HASH`, and each loading row, passes the sponge state on to the next one. Only
+    // absorb rows change it.
+    let is_hash_or_output = builder.add_extension(is_hash, is_output);
+    let next_uses_sponge = builder.add_extension(next_is_absorb, next_is_output);
+    for i in 0..SPONGE_WIDTH {
+        let state = local_values[col_sponge_state(i)];
+        let next_state = local_values[col_sponge_next_state(i)];
+        let input = local_values[col_input(i)];
+        let output = local_values[col_output(i)];
+        let diff = builder.sub_extension(output, input);
+        let expected = builder.mul_add_extension(permute, diff, input);
+        constraint_filtered_eq(builder, yield_constr, is_absorb, next_state, expected);
+        constraint_filtered_eq(builder, yield_constr, is_hash_or_output, next_state, state);
+        transition_filtered_eq(
+            builder,
+            yield_constr,
+            next_uses_sponge,
+            col_sponge_state(i),
+            next_state,
+        );
+    }
+
+    // Absorb rows write to consecutive positions of the rate, starting with the first, and those of
+    // `HASH` read consecutive words. Output rows write the words of the digest in order, to
+    // consecutive addresses, after the last absorb row, or after the execute row if the input is
+    // empty.
+    let expected =
+        builder.add_extension(local_values[col_absorb_position(SPONGE_RATE - 1)], is_hash);
+    transition_filtered_eq(
+        builder,
+        yield_constr,
+        next_is_absorb,
+        col_absorb_position(0),
+        expected,
+    );
+    for i in 1..SPONGE_RATE {
+        transition_filtered_eq(
+            builder,
+            yield_constr,
+            next_is_absorb,
+            col_absorb_position(i),
+            local_values[col_absorb_position(i - 1)],
+        );
+    }
+    let input_address = local_values[COL_HASH_INPUT_ADDRESS];
+    let expected = builder.add_extension(input_address, is_hash_absorb);
+    transition_filtered_eq(
+        builder,
+        yield_constr,
+        next_is_hash_absorb,
+        COL_HASH_INPUT_ADDRESS,
+        expected,
+    );
+    let expected = builder.sub_extension(words_left, is_absorb);
+    transition_filtered_eq(
+        builder,
+        yield_constr,
+        next_is_absorb,
+        COL_HASH_WORDS_LEFT,
+        expected,
+    );
+
+    let expected = builder.mul_extension(is_hash_absorb, is_last);
+    let expected = builder.mul_add_extension(is_hash, is_empty, expected);
+    let constraint = builder.sub_extension(next_values[col_output_position(0)], expected);
+    yield_constr.constraint_transition(builder, constraint);
+    for i in 1..NUM_DIGEST_WORDS {
+        let constraint = builder.sub_extension(
+            next_values[col_output_position(i)],
+            local_values[col_output_position(i - 1)],
+        );
+        yield_constr.constraint_transition(builder, constraint);
+    }
+    let output_address = local_values[COL_HASH_OUTPUT_ADDRESS];
+    let expected = builder.add_extension(output_address, is_output);
+    let next_is_hash_absorb_or_output = builder.add_extension(next_is_hash_absorb, next_is_output);
+    transition_filtered_eq(
+        builder,
+        yield_constr,
+        next_is_hash_absorb_or_output,
+        COL_HASH_OUTPUT_ADDRESS,
+        expected,
+    );
+
+    // An output row splits an element of the digest canonically into u32s, and writes the low one
+    // if its position is even, or the high one if it is odd.
+    let base = F::from_canonical_u32(1 << 16);
+    let lo = builder.mul_const_add_extension(
+        base,
+        local_values[col_digest_limb(1)],
+        local_values[col_digest_limb(0)],
+    );
+    let hi = builder.mul_const_add_extension(
+        base,
+        local_values[col_digest_limb(3)],
+        local_values[col_digest_limb(2)],
+    );
+    let inverse = local_values[COL_HASH_CANONICAL_INV];
+    let element =
+        combine_u32s_check_canonical_circuit(builder, lo, hi, inverse, is_output, yield_constr);
+    let mut even_flags = Vec::with_capacity(NUM_HASH_OUT_ELTS);
+    let mut odd_flags = Vec::with_capacity(NUM_HASH_OUT_ELTS);
+    for i in 0..NUM_HASH_OUT_ELTS {
+        let even_flag = local_values[col_output_position(2 * i)];
+        let odd_flag = local_values[col_output_position(2 * i + 1)];
+        let filter = builder.add_extension(even_flag, odd_flag);
+        let digest_element = local_values[col_sponge_state(i)];
+        constraint_filtered_eq(builder, yield_constr, filter, digest_element, element);
+        even_flags.push(even_flag);
+        odd_flags.push(odd_flag);
+    }
+    let is_even = builder.add_many_extension(&even_flags);
+    let is_odd = builder.add_many_extension(&odd_flags);
+    constraint_filtered_eq(builder, yield_constr, is_even, word, lo);
+    constraint_filtered_eq(builder, yield_constr, is_odd, word, hi);
+}
@@ -0,0 +1,322 @@
+//! The instruction set.
+//!
+//! The machine has `NUM_REGISTERS` general purpose registers, each holding a u32. Each instruction
+//! is encoded as a single u32 word, laid out as
+//!
+//! ```text
+//! bits 0..8    opcode
+//! bits 8..10   rd, the destination register
+//! bits 10..12  rs1, the first source register
+//! bits 12..14  rs2, the second source register
+//! bits 14..16  unused; must be zero
+//! bits 16..32  imm, a 16-bit immediate
+//! ```
+//!
+//! The 256-bit instructions operate on 256-bit words in main memory rather than on registers. A
+//! 256-bit word occupies eight consecutive words of memory, in little-endian order, and is treated
+//! as unsigned, or as two's complement by the signed instructions. These instructions read their
+//! operands from `main[rs1 + imm..]` and `main[rs2..]`, where `rs1` and `rs2` are the values of
+//! those registers, and write their result to `main[rd..]`. Comparisons write 1 or 0.
+//!
+//! Fields which an instruction doesn't use should be zero. The program is loaded into the code
+//! segment before execution begins, and execution starts at address 0. Since memory which has never
+//! been written to is zero, and zero encodes `HALT`, running past the end of the program halts.
+//! Proofs commit to the program with its length and its digest, given by `hash_program`.
+
+use plonky2::hash::hash_types::{HashOut, RichField};
+use plonky2::hash::hashing::SPONGE_WIDTH;
+
+use crate::registers::hash::SPONGE_RATE;
+use crate::registers::state::NUM_HASH_OUT_ELTS;
+use crate::registers::{alu, core, logic, wide_alu};
+
+/// The number of general purpose registers.
+pub const NUM_REGISTERS: usize = 4;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Opcode {
+    /// Stops execution.
+    Halt = 0,
+    /// `rd = rs1 + rs2`, wrapping.
+    Add = 1,
+    /// `rd = rs1 - rs2`, wrapping.
+    Sub = 2,
+    /// `rd = rs1 * rs2`, wrapping.
+    Mul = 3,
+    /// `rd = rs1 / rs2`, rounding down, or zero if `rs2` is zero.
+    Div = 4,
+    /// `rd = rs1 & rs2`.
+    And = 5,
+    /// `rd = rs1 | rs2`.
+    Or = 6,
+    /// `rd = rs1 ^ rs2`.
+    Xor = 7,
+    /// `rd = !rs1`.
+    Not = 8,
+    /// `rd = rs1 << rs2`, or zero if `rs2` is 32 or more.
+    Shl = 9,
+    /// `rd = rs1 >> rs2`, or zero if `rs2` is 32 or more.
+    Shr = 10,
+    /// `rd = imm`.
+    LoadI = 11,
+    /// `rd = main[rs1 + imm]`.
+    Load = 12,
+    /// `main[rs1 + imm] = rs2`.
+    Store = 13,
+    /// Pushes `rs1` onto the stack.
+    Push = 14,
+    /// Pops the top of the stack into `rd`.
+    Pop = 15,
+    /// Jumps to `imm`.
+    Jump = 16,
+    /// Jumps to `imm` if `rs1` is nonzero.
+    JumpI = 17,
+    /// Calls the function at `imm`. This pushes a frame record, containing the return address and
+    /// the caller's frame pointer, and points the frame pointer just past it.
+    Call = 18,
+    /// Returns from the current function, restoring the stack and frame pointers from before the
+    /// corresponding `CALL`. Anything the callee left on the stack is discarded.
+    Ret = 19,
+    /// `rd = state[rs1 + imm]`.
+    SLoad = 20,
+    /// `state[rs1 + imm] = rs2`.
+    SStore = 21,
+    /// Hashes the `rs2` words at `main[rs1 + imm]` with Poseidon, as `hash_words` would, and
+    /// writes the digest to the eight words at `main[rd]`. Each element of the digest is written as
+    /// its low u32 followed by its high u32.
+    Hash = 22,
+    /// `a + b`, wrapping, where `a` and `b` are the 256-bit words at `main[rs1 + imm..]` and
+    /// `main[rs2..]`.
+    Add256 = 23,
+    /// `a - b`, wrapping.
+    Sub256 = 24,
+    /// `a * b`, wrapping.
+    Mul256 = 25,
+    /// `a / b`, rounding down, or zero if `b` is zero.
+    Div256 = 26,
+    /// `a % b`, or zero if `b` is zero.
+    Mod256 = 27,
+    /// `(a + b) % m`, without wrapping, or zero if `m` is zero, where `m` is the 256-bit word at
+    /// `main[rs2 + 8..]`.
+    AddMod256 = 28,
+    /// `(a * b) % m`, without wrapping, or zero if `m` is zero.
+    MulMod256 = 29,
+    /// `a / b` of signed words, rounding towards zero, or zero if `b` is zero. `-2^255 / -1` wraps
+    /// to `-2^255`.
+    SDiv256 = 30,
+    /// The remainder of `a / b` of signed words, which has the sign of `a`, or zero if `b` is zero.
+    SMod256 = 31,
+    /// `a < b`.
+    Lt256 = 32,
+    /// `a > b`.
+    Gt256 = 33,
+    /// `a < b` of signed words.
+    SLt256 = 34,
+    /// `a > b` of signed words.
+    SGt256 = 35,
+    /// `a == b`.
+    Eq256 = 36,
+    /// `a == 0`.
+    IsZero256 = 37,
+}
+
+impl Opcode {
+    pub(crate) const ALL: [Opcode; 38] = [
+        Opcode::Halt,
+        Opcode::Add,
+        Opcode::Sub,
+        Opcode::Mul,
+        Opcode::Div,
+        Opcode::And,
+        Opcode::Or,
+        Opcode::Xor,
+        Opcode::Not,
+        Opcode::Shl,
+        Opcode::Shr,
+        Opcode::LoadI,
+        Opcode::Load,
+        Opcode::Store,
+        Opcode::Push,
+        Opcode::Pop,
+        Opcode::Jump,
+        Opcode::JumpI,
+        Opcode::Call,
+        Opcode::Ret,
+        Opcode::SLoad,
+        Opcode::SStore,
+        Opcode::Hash,
+        Opcode::Add256,
+        Opcode::Sub256,
+        Opcode::Mul256,
+        Opcode::Div256,
+        Opcode::Mod256,
+        Opcode::AddMod256,
+        Opcode::MulMod256,
+        Opcode::SDiv256,
+        Opcode::SMod256,
+        Opcode::Lt256,
+        Opcode::Gt256,
+        Opcode::SLt256,
+        Opcode::SGt256,
+        Opcode::Eq256,
+        Opcode::IsZero256,
+    ];
+
+    pub(crate) fn from_u8(opcode: u8) -> Option<Self> {
+        Self::ALL.get(opcode as usize).copied()
+    }
+
+    /// The column of the flag which is set when this opcode is executed. Arithmetic and logic
+    /// opcodes use the flags of the ALU, logic unit and 256-bit ALU.
+    pub(crate) const fn flag(self) -> usize {
+        match self {
+            Opcode::Halt => core::IS_HALT,
+            Opcode::Add => alu::IS_ADD,
+            Opcode::Sub => alu::IS_SUB,
+            Opcode::Mul => alu::IS_MUL_ADD,
+            Opcode::Div => alu::IS_DIV,
+            Opcode::And => logic::IS_AND,
+            Opcode::Or => logic::IS_OR,
+            Opcode::Xor => logic::IS_XOR,
+            Opcode::Not => logic::IS_NOT,
+            Opcode::Shl => logic::IS_SHL,
+            Opcode::Shr => logic::IS_SHR,
+            Opcode::LoadI => core::IS_LOADI,
+            Opcode::Load => core::IS_LOAD,
+            Opcode::Store => core::IS_STORE,
+            Opcode::Push => core::IS_PUSH,
+            Opcode::Pop => core::IS_POP,
+            Opcode::Jump => core::IS_JUMP,
+            Opcode::JumpI => core::IS_JUMPI,
+            Opcode::Call => core::IS_CALL,
+            Opcode::Ret => core::IS_RET,
+            Opcode::SLoad => core::IS_SLOAD,
+            Opcode::SStore => core::IS_SSTORE,
+            Opcode::Hash => core::IS_HASH,
+            Opcode::Add256 => wide_alu::IS_ADD_256,
+            Opcode::Sub256 => wide_alu::IS_SUB_256,
+            Opcode::Mul256 => wide_alu::IS_MUL_256,
+            Opcode::Div256 => wide_alu::IS_DIV_256,
+            Opcode::Mod256 => wide_alu::IS_MOD_256,
+            Opcode::AddMod256 => wide_alu::IS_ADD_MOD_256,
+            Opcode::MulMod256 => wide_alu::IS_MUL_MOD_256,
+            Opcode::SDiv256 => wide_alu::IS_SDIV_256,
+            Opcode::SMod256 => wide_alu::IS_SMOD_256,
+            Opcode::Lt256 => wide_alu::IS_LT_256,
+            Opcode::Gt256 => wide_alu::IS_GT_256,
+            Opcode::SLt256 => wide_alu::IS_SLT_256,
+            Opcode::SGt256 => wide_alu::IS_SGT_256,
+            Opcode::Eq256 => wide_alu::IS_EQ_256,
+            Opcode::IsZero256 => wide_alu::IS_ISZERO_256,
+        }
+    }
+
+    /// Whether this opcode writes its result to `rd`.
+    pub(crate) const fn writes_register(self) -> bool {
+        matches!(
+            self,
+            Opcode::Add
+                | Opcode::Sub
+                | Opcode::Mul
+                | Opcode::Div
+                | Opcode::And
+                | Opcode::Or
+                | Opcode::Xor
+                | Opcode::Not
+                | Opcode::Shl
+                | Opcode::Shr
+                | Opcode::LoadI
+                | Opcode::Load
+                | Opcode::Pop
+                | Opcode::SLoad
+        )
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    pub rd: usize,
+    pub rs1: usize,
+    pub rs2: usize,
+    pub imm: u16,
+}
+
+impl Instruction {
+    pub fn new(opcode: Opcode, rd: usize, rs1: usize, rs2: usize, imm: u16) -> Self {
+        assert!(
+            rd < NUM_REGISTERS && rs1 < NUM_REGISTERS && rs2 < NUM_REGISTERS,
+            "Invalid register."
+        );
+        Self {
+            opcode,
+            rd,
+            rs1,
+            rs2,
+            imm,
+        }
+    }
+
+    pub fn encode(self) -> u32 {
+        self.opcode as u32
+            | (self.rd as u32) << 8
+            | (self.rs1 as u32) << 10
+            | (self.rs2 as u32) << 12
+            | (self.imm as u32) << 16
+    }
+
+    /// Decodes an instruction, or returns `None` if `word` is not a valid encoding.
+    pub fn decode(word: u32) -> Option<Self> {
+        if word & 0xc000 != 0 {
+            return None;
+        }
+        Some(Self {
+            opcode: Opcode::from_u8(word as u8)?,
+            rd: (word >> 8) as usize & 3,
+            rs1: (word >> 10) as usize & 3,
+            rs2: (word >> 12) as usize & 3,
+            imm: (word >> 16) as u16,
+        })
+    }
+}
+
+/// Hashes `words` as `HASH` does, with a Poseidon sponge in overwrite mode, like `hash_no_pad`,
+/// except that the sponge state starts with the number of words in its first capacity element.
+/// Inputs which differ only in trailing zeros, such as `[x]` and `[x, 0]`, thus have different
+/// digests. The empty input's digest is zero.
+pub fn hash_words<F: RichField>(words: &[F]) -> HashOut<F> {
+    let mut state = [F::ZERO; SPONGE_WIDTH];
+    state[SPONGE_RATE] = F::from_canonical_usize(words.len());
+    for chunk in words.chunks(SPONGE_RATE) {
+        state[..chunk.len()].copy_from_slice(chunk);
+        state = F::poseidon(state);
+    }
+    HashOut {
+        elements: state[..NUM_HASH_OUT_ELTS].try_into().unwrap(),
+    }
+}
+
+/// The digest of `program` which proofs of its execution commit to. This is the hash of its
+/// encoded words, as `HASH` would compute it, so the empty program's digest is zero.
+pub fn hash_program<F: RichField>(program: &[Instruction]) -> HashOut<F> {
+    let words = program
+        .iter()
+        .map(|instruction| F::from_canonical_u32(instruction.encode()))
+        .collect::<Vec<_>>();
+    hash_words(&words)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instruction::{Instruction, Opcode};
+
+    #[test]
+    fn encode_decode() {
+        for opcode in Opcode::ALL {
+            let instruction = Instruction::new(opcode, 1, 2, 3, 0xbeef);
+            assert_eq!(Instruction::decode(instruction.encode()), Some(instruction));
+        }
+        assert_eq!(Instruction::decode(0xff), None);
+        assert_eq!(Instruction::decode(0x4000), None);
+    }
+}
@@ -0,0 +1,252 @@
+//! A native interpreter for the instruction set, which serves as a reference for the trace
+//! generator.
+
+use std::cmp::Ordering;
+
+use plonky2::hash::hash_types::RichField;
+
+use crate::bigint::{
+    abs, add, cmp, div_rem, is_negative, is_zero, mul, neg, overflowing_sub, signed_cmp, truncate,
+};
+use crate::instruction::{hash_words, Instruction, Opcode, NUM_REGISTERS};
+use crate::memory::{Segment, TransactionMemory};
+use crate::state::State;
+
+/// The state of the machine while executing a program.
+pub struct Interpreter {
+    program: Vec<Instruction>,
+    pub registers: [u32; NUM_REGISTERS],
+    pub instruction_ptr: usize,
+    pub frame_ptr: usize,
+    pub stack_ptr: usize,
+    pub(crate) memory: TransactionMemory,
+    pub state: State,
+    pub halted: bool,
+    /// The number of instructions executed so far, including any final `HALT`.
+    pub num_steps: usize,
+}
+
+impl Interpreter {
+    /// Creates an interpreter which is about to execute `program`, with the registers holding
+    /// `inputs`, and the given initial state.
+    pub fn new(program: &[Instruction], inputs: [u32; NUM_REGISTERS], state: State) -> Self {
+        Self {
+            program: program.to_vec(),
+            registers: inputs,
+            instruction_ptr: 0,
+            frame_ptr: 0,
+            stack_ptr: 0,
+            memory: TransactionMemory::default(),
+            state,
+            halted: false,
+            num_steps: 0,
+        }
+    }
+
+    /// Runs `program` until it halts, and returns the final state. `HASH` uses Poseidon over `F`.
+    pub fn run<F: RichField>(
+        program: &[Instruction],
+        inputs: [u32; NUM_REGISTERS],
+        state: State,
+    ) -> Self {
+        let mut interpreter = Self::new(program, inputs, state);
+        while !interpreter.halted {
+            interpreter.step::<F>();
+        }
+        interpreter
+    }
+
+    /// Executes a single instruction. Addresses past the end of the program hold zero words, which
+    /// decode to `HALT`. `HASH` uses Poseidon over `F`.
+    pub fn step<F: RichField>(&mut self) {
+        assert!(!self.halted, "Execution has halted.");
+        let instruction = self
+            .program
+            .get(self.instruction_ptr)
+            .copied()
+            .unwrap_or_else(|| Instruction::new(Opcode::Halt, 0, 0, 0, 0));
+        let op_0 = self.registers[instruction.rs1];
+        let op_1 = self.registers[instruction.rs2];
+        let imm = instruction.imm;
+        self.num_steps += 1;
+
+        // Like any other instruction, `HALT` advances the instruction pointer.
+        let mut next_instruction_ptr = self.instruction_ptr + 1;
+        let result = match instruction.opcode {
+            Opcode::Halt => {
+                self.halted = true;
+                None
+            }
+            Opcode::Add => Some(op_0.wrapping_add(op_1)),
+            Opcode::Sub => Some(op_0.wrapping_sub(op_1)),
+            Opcode::Mul => Some(op_0.wrapping_mul(op_1)),
+            Opcode::Div => Some(op_0.checked_div(op_1).unwrap_or(0)),
+            Opcode::And => Some(op_0 & op_1),
+            Opcode::Or => Some(op_0 | op_1),
+            Opcode::Xor => Some(op_0 ^ op_1),
+            Opcode::Not => Some(!op_0),
+            Opcode::Shl => Some(op_0.checked_shl(op_1).unwrap_or(0)),
+            Opcode::Shr => Some(op_0.checked_shr(op_1).unwrap_or(0)),
+            Opcode::LoadI => Some(imm as u32),
+            Opcode::Load => Some(self.read(Segment::Main, op_0 as usize + imm as usize)),
+            Opcode::Store => {
+                self.write(Segment::Main, op_0 as usize + imm as usize, op_1);
+                None
+            }
+            Opcode::Push => {
+                self.write(Segment::Stack, self.stack_ptr, op_0);
+                self.stack_ptr += 1;
+                None
+            }
+            Opcode::Pop => {
+                self.stack_ptr = self.stack_ptr.checked_sub(1).expect("Stack underflow.");
+                Some(self.read(Segment::Stack, self.stack_ptr))
+            }
+            Opcode::Jump => {
+                next_instruction_ptr = imm as usize;
+                None
+            }
+            Opcode::JumpI => {
+                if op_0 != 0 {
+                    next_instruction_ptr = imm as usize;
+                }
+                None
+            }
+            Opcode::Call => {
+                assert!(
+                    next_instruction_ptr < 1 << 16 && self.frame_ptr < 1 << 16,
+                    "Frame record overflow."
+                );
+                let record = next_instruction_ptr as u32 | (self.frame_ptr as u32) << 16;
+                self.write(Segment::Stack, self.stack_ptr, record);
+                self.stack_ptr += 1;
+                self.frame_ptr = self.stack_ptr;
+                next_instruction_ptr = imm as usize;
+                None
+            }
+            Opcode::Ret => {
+                let record_ptr = self
+                    .frame_ptr
+                    .checked_sub(1)
+                    .expect("RET outside of a function.");
+                let record = self.read(Segment::Stack, record_ptr);
+                next_instruction_ptr = (record & 0xffff) as usize;
+                self.frame_ptr = (record >> 16) as usize;
+                self.stack_ptr = record_ptr;
+                None
+            }
+            Opcode::SLoad => Some(self.state.read(op_0 as usize + imm as usize)),
+            Opcode::SStore => {
+                self.state.write(op_0 as usize + imm as usize, op_1);
+                None
+            }
+            Opcode::Hash => {
+                let input_address = op_0 as usize + imm as usize;
+                let input = (0..op_1 as usize)
+                    .map(|i| F::from_canonical_u32(self.read(Segment::Main, input_address + i)))
+                    .collect::<Vec<_>>();
+                let digest = hash_words(&input);
+                let output_address = self.registers[instruction.rd] as usize;
+                for (i, element) in digest.elements.into_iter().enumerate() {
+                    let element = element.to_canonical_u64();
+                    self.write(Segment::Main, output_address + 2 * i, element as u32);
+                    self.write(
+                        Segment::Main,
+                        output_address + 2 * i + 1,
+                        (element >> 32) as u32,
+                    );
+                }
+                None
+            }
+            Opcode::Add256
+            | Opcode::Sub256
+            | Opcode::Mul256
+            | Opcode::Div256
+            | Opcode::Mod256
+            | Opcode::AddMod256
+            | Opcode::MulMod256
+            | Opcode::SDiv256
+            | Opcode::SMod256
+            | Opcode::Lt256
+            | Opcode::Gt256
+            | Opcode::SLt256
+            | Opcode::SGt256
+            | Opcode::Eq256
+            | Opcode::IsZero256 => {
+                let a = self.read_wide(op_0 as usize + imm as usize);
+                let b = self.read_wide(op_1 as usize);
+                let m = self.read_wide(op_1 as usize + 8);
+                let output_address = self.registers[instruction.rd] as usize;
+                let output = wide_result(instruction.opcode, a, b, m);
+                for (i, word) in output.into_iter().enumerate() {
+                    self.write(Segment::Main, output_address + i, word);
+                }
+                None
+            }
+        };
+
+        if let Some(result) = result {
+            self.registers[instruction.rd] = result;
+        }
+        self.instruction_ptr = next_instruction_ptr;
+    }
+
+    fn read(&self, segment: Segment, address: usize) -> u32 {
+        self.memory.read(0, segment, address)
+    }
+
+    fn write(&mut self, segment: Segment, address: usize, value: u32) {
+        self.memory.write(0, segment, address, value);
+    }
+
+    /// Reads the 256-bit word at `main[address..]`.
+    fn read_wide(&self, address: usize) -> [u32; 8] {
+        let mut word = [0; 8];
+        for (i, limb) in word.iter_mut().enumerate() {
+            *limb = self.read(Segment::Main, address + i);
+        }
+        word
+    }
+}
+
+/// The result of a 256-bit instruction, given its operands and, for `ADDMOD` and `MULMOD`, its
+/// modulus.
+fn wide_result(opcode: Opcode, a: [u32; 8], b: [u32; 8], m: [u32; 8]) -> [u32; 8] {
+    let from_bool = |x: bool| truncate(&[x as u32]);
+    match opcode {
+        Opcode::Add256 => truncate(&add(&a, &b)),
+        Opcode::Sub256 => truncate(&overflowing_sub(&a, &b).0),
+        Opcode::Mul256 => truncate(&mul(&a, &b)),
+        Opcode::Div256 | Opcode::Mod256 | Opcode::SDiv256 | Opcode::SMod256 if is_zero(&b) => {
+            [0; 8]
+        }
+        Opcode::Div256 => truncate(&div_rem(&a, &b).0),
+        Opcode::Mod256 => truncate(&div_rem(&a, &b).1),
+        Opcode::AddMod256 | Opcode::MulMod256 if is_zero(&m) => [0; 8],
+        Opcode::AddMod256 => truncate(&div_rem(&add(&a, &b), &m).1),
+        Opcode::MulMod256 => truncate(&div_rem(&mul(&a, &b), &m).1),
+        Opcode::SDiv256 => {
+            let quotient = div_rem(&abs(&a), &abs(&b)).0;
+            if is_negative(&a) != is_negative(&b) {
+                truncate(&neg(&quotient))
+            } else {
+                truncate(&quotient)
+            }
+        }
+        Opcode::SMod256 => {
+            let remainder = div_rem(&abs(&a), &abs(&b)).1;
+            if is_negative(&a) {
+                truncate(&neg(&remainder))
+            } else {
+                truncate(&remainder)
+            }
+        }
+        Opcode::Lt256 => from_bool(cmp(&a, &b) == Ordering::Less),
+        Opcode::Gt256 => from_bool(cmp(&a, &b) == Ordering::Greater),
+        Opcode::SLt256 => from_bool(signed_cmp(&a, &b) == Ordering::Less),
+        Opcode::SGt256 => from_bool(signed_cmp(&a, &b) == Ordering::Greater),
+        Opcode::Eq256 => from_bool(a == b),
+        Opcode::IsZero256 => from_bool(is_zero(&a)),
+        _ => unreachable!("{:?} is not a 256-bit opcode.", opcode),
+    }
+}
@@ -1,12 +1,26 @@
 // TODO: Remove these when crate is closer to being finished.
 #![allow(dead_code)]
 #![allow(unused_variables)]
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
 
 mod alu;
+mod bigint;
+mod boolean_unit;
 mod core_registers;
+mod hash_unit;
+pub mod instruction;
+pub mod interpreter;
+mod logic_unit;
 pub mod lookup;
 mod memory;
+mod memory_unit;
 mod permutation_unit;
 mod public_input_layout;
+mod range_check_unit;
+pub mod recursion;
 mod registers;
+pub mod state;
+mod state_unit;
 pub mod system_zero;
+mod wide_alu;
@@ -0,0 +1,400 @@
+use plonky2::field::extension_field::Extendable;
+use plonky2::field::field_types::{Field, PrimeField64};
+use plonky2::field::packed_field::PackedField;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::vars::StarkEvaluationTargets;
+use starky::vars::StarkEvaluationVars;
+
+use crate::alu::canonical::{
+    combine_u32s_check_canonical, combine_u32s_check_canonical_circuit, compute_canonical_inv,
+};
+use crate::public_input_layout::NUM_PUBLIC_INPUTS;
+use crate::registers::logic::*;
+use crate::registers::NUM_COLUMNS;
+
+const OPCODE_FLAGS: [usize; 6] = [IS_AND, IS_OR, IS_XOR, IS_NOT, IS_SHL, IS_SHR];
+
+pub(crate) fn generate_logic_unit<F: PrimeField64>(values: &mut [F; NUM_COLUMNS]) {
+    let is_shl = values[IS_SHL].is_one();
+    let is_shr = values[IS_SHR].is_one();
+
+    let in_0 = values[COL_LOGIC_INPUT_0].to_canonical_u64() as u32;
+    let in_1 = values[COL_LOGIC_INPUT_1].to_canonical_u64() as u32;
+
+    let output = if values[IS_AND].is_one() {
+        in_0 & in_1
+    } else if values[IS_OR].is_one() {
+        in_0 | in_1
+    } else if values[IS_XOR].is_one() {
+        in_0 ^ in_1
+    } else if values[IS_NOT].is_one() {
+        !in_0
+    } else if is_shl {
+        in_0.checked_shl(in_1).unwrap_or(0)
+    } else if is_shr {
+        in_0.checked_shr(in_1).unwrap_or(0)
+    } else {
+        return;
+    };
+    values[COL_LOGIC_OUTPUT] = F::from_canonical_u32(output);
+
+    for i in 0..32 {
+        values[col_input_0_bit(i)] = F::from_bool((in_0 >> i) & 1 != 0);
+        values[col_input_1_bit(i)] = F::from_bool((in_1 >> i) & 1 != 0);
+    }
+
+    if is_shl || is_shr {
+        let shift_low = in_1 % 32;
+        let shift_high = in_1 - shift_low;
+
+        for i in 0..4 {
+            values[col_shift_pow_partial(i)] = F::from_canonical_u64(1 << (shift_low % (4 << i)));
+        }
+        values[COL_SHIFT_IN_RANGE] = F::from_bool(shift_high == 0);
+        values[COL_SHIFT_HIGH_INV] = F::from_canonical_u32(shift_high)
+            .try_inverse()
+            .unwrap_or(F::ZERO);
+
+        let multiplier = match (shift_high == 0, is_shl) {
+            (false, _) => 0,
+            (true, true) => 1u64 << shift_low,
+            (true, false) => 1u64 << (32 - shift_low),
+        };
+        values[COL_SHIFT_MULTIPLIER] = F::from_canonical_u64(multiplier);
+
+        let product = in_0 as u64 * multiplier;
+        values[COL_SHIFT_PRODUCT_CANONICAL_INV] = compute_canonical_inv(product);
+        for i in 0..32 {
+            values[col_shift_product_lo_bit(i)] = F::from_bool((product >> i) & 1 != 0);
+            values[col_shift_product_hi_bit(i)] = F::from_bool((product >> (32 + i)) & 1 != 0);
+        }
+    }
+}
+
+/// Combines little-endian bits into a single value.
+fn combine_bits<F: Field, P: PackedField<Scalar = F>>(
+    bits: impl DoubleEndedIterator<Item = P>,
+) -> P {
+    bits.rev().fold(P::ZEROS, |acc, bit| acc * F::TWO + bit)
+}
+
+pub(crate) fn eval_logic_unit<F: Field, P: PackedField<Scalar = F>>(
+    vars: StarkEvaluationVars<F, P, NUM_COLUMNS, NUM_PUBLIC_INPUTS>,
+    yield_constr: &mut ConstraintConsumer<P>,
+) {
+    let local_values = &vars.local_values;
+
+    // Check that the operation flag values are binary.
+    for col in OPCODE_FLAGS {
+        let val = local_values[col];
+        yield_constr.constraint(val * val - val);
+    }
+
+    let is_and = local_values[IS_AND];
+    let is_or = local_values[IS_OR];
+    let is_xor = local_values[IS_XOR];
+    let is_not = local_values[IS_NOT];
+    let is_shl = local_values[IS_SHL];
+    let is_shr = local_values[IS_SHR];
+    let is_shift = is_shl + is_shr;
+    let is_logic = is_and + is_or + is_xor + is_not + is_shift;
+
+    let in_0 = local_values[COL_LOGIC_INPUT_0];
+    let in_1 = local_values[COL_LOGIC_INPUT_1];
+    let output = local_values[COL_LOGIC_OUTPUT];
+    let in_0_bits = (0..32).map(|i| local_values[col_input_0_bit(i)]);
+    let in_1_bits = (0..32).map(|i| local_values[col_input_1_bit(i)]);
+
+    // Check the bit decompositions of the inputs, which also ensures that they are u32s.
+    yield_constr.constraint(is_logic * (in_0 - combine_bits(in_0_bits.clone())));
+    yield_constr.constraint(is_logic * (in_1 - combine_bits(in_1_bits.clone())));
+
+    // Bitwise operations.
+    let bit_pairs = in_0_bits.zip(in_1_bits);
+    let and = combine_bits(bit_pairs.clone().map(|(a, b)| a * b));
+    let or = combine_bits(bit_pairs.clone().map(|(a, b)| a + b - a * b));
+    let xor = combine_bits(bit_pairs.map(|(a, b)| a + b - a * b * F::TWO));
+    yield_constr.constraint(is_and * (output - and));
+    yield_constr.constraint(is_or * (output - or));
+    yield_constr.constraint(is_xor * (output - xor));
+    yield_constr.constraint(is_not * (output + in_0 - F::from_canonical_u32(u32::MAX)));
+
+    // Compute `2^(shift % 32)` from the low five bits of the shift.
+    let shift_bits = (0..5)
+        .map(|i| local_values[col_input_1_bit(i)])
+        .collect::<Vec<_>>();
+    let mut pow = shift_bits[0] + F::ONE;
+    for i in 0..4 {
+        let partial = local_values[col_shift_pow_partial(i)];
+        let factor = shift_bits[i + 1] * F::from_canonical_u64((1 << (1 << (i + 1))) - 1) + F::ONE;
+        yield_constr.constraint(is_shift * (partial - pow * factor));
+        pow = partial;
+    }
+
+    // Check whether the shift is in range, i.e. whether `shift - shift % 32` is zero.
+    let shift_high = in_1 - combine_bits(shift_bits.into_iter());
+    let shift_high_inv = local_values[COL_SHIFT_HIGH_INV];
+    let in_range = local_values[COL_SHIFT_IN_RANGE];
+    yield_constr.constraint(is_shift * (shift_high * shift_high_inv + in_range - F::ONE));
+    yield_constr.constraint(is_shift * shift_high * in_range);
+
+    // The multiplier is `2^shift` for SHL, or `2^32 / 2^shift` for SHR, or zero if the shift is out
+    // of range.
+    let multiplier = local_values[COL_SHIFT_MULTIPLIER];
+    yield_constr.constraint(is_shl * (multiplier - in_range * pow));
+    yield_constr
+        .constraint(is_shr * (multiplier * pow - in_range * F::from_canonical_u64(1 << 32)));
+
+    // The shifted value is one half of `in_0 * multiplier`.
+    let product_lo = combine_bits((0..32).map(|i| local_values[col_shift_product_lo_bit(i)]));
+    let product_hi = combine_bits((0..32).map(|i| local_values[col_shift_product_hi_bit(i)]));
+    let product = combine_u32s_check_canonical(
+        product_lo,
+        product_hi,
+        local_values[COL_SHIFT_PRODUCT_CANONICAL_INV],
+        is_shift,
+        yield_constr,
+    );
+    yield_constr.constraint(is_shift * (in_0 * multiplier - product));
+    yield_constr.constraint(is_shl * (output - product_lo));
+    yield_constr.constraint(is_shr * (output - product_hi));
+}
+
+/// Combines little-endian bits into a single value.
+fn combine_bits_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    bits: impl DoubleEndedIterator<Item = ExtensionTarget<D>>,
+) -> ExtensionTarget<D> {
+    let zero = builder.zero_extension();
+    bits.rev().fold(zero, |acc, bit| {
+        builder.mul_const_add_extension(F::TWO, acc, bit)
+    })
+}
+
+pub(crate) fn eval_logic_unit_recursively<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    vars: StarkEvaluationTargets<D, NUM_COLUMNS, NUM_PUBLIC_INPUTS>,
+    yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    let local_values = &vars.local_values;
+    let one = builder.one_extension();
+
+    // Check that the operation flag values are binary.
+    for col in OPCODE_FLAGS {
+        let val = local_values[col];
+        let constraint = builder.mul_sub_extension(val, val, val);
+        yield_constr.constraint(builder, constraint);
+    }
+
+    let is_and = local_values[IS_AND];
+    let is_or = local_values[IS_OR];
+    let is_xor = local_values[IS_XOR];
+    let is_not = local_values[IS_NOT];
+    let is_shl = local_values[IS_SHL];
+    let is_shr = local_values[IS_SHR];
+    let is_shift = builder.add_extension(is_shl, is_shr);
+    let is_logic = builder.add_many_extension(&[is_and, is_or, is_xor, is_not, is_shift]);
+
+    let in_0 = local_values[COL_LOGIC_INPUT_0];
+    let in_1 = local_values[COL_LOGIC_INPUT_1];
+    let output = local_values[COL_LOGIC_OUTPUT];
+    let in_0_bits = (0..32)
+        .map(|i| local_values[col_input_0_bit(i)])
+        .collect::<Vec<_>>();
+    let in_1_bits = (0..32)
+        .map(|i| local_values[col_input_1_bit(i)])
+        .collect::<Vec<_>>();
+
+    // Check the bit decompositions of the inputs, which also ensures that they are u32s.
+    for (input, bits) in [(in_0, &in_0_bits), (in_1, &in_1_bits)] {
+        let combined = combine_bits_circuit(builder, bits.iter().copied());
+        let diff = builder.sub_extension(input, combined);
+        let constraint = builder.mul_extension(is_logic, diff);
+        yield_constr.constraint(builder, constraint);
+    }
+
+    // Bitwise operations.
+    let bit_pairs = in_0_bits.into_iter().zip(in_1_bits).collect::<Vec<_>>();
+    let and_bits = bit_pairs
+        .iter()
+        .map(|&(a, b)| builder.mul_extension(a, b))
+        .collect::<Vec<_>>();
+    let or_bits = bit_pairs
+        .iter()
+        .map(|&(a, b)| {
+            let sum = builder.add_extension(a, b);
+            builder.arithmetic_extension(F::NEG_ONE, F::ONE, a, b, sum)
+        })
+        .collect::<Vec<_>>();
+    let xor_bits = bit_pairs
+        .iter()
+        .map(|&(a, b)| {
+            let sum = builder.add_extension(a, b);
+            builder.arithmetic_extension(-F::TWO, F::ONE, a, b, sum)
+        })
+        .collect::<Vec<_>>();
+    for (filter, bits) in [(is_and, and_bits), (is_or, or_bits), (is_xor, xor_bits)] {
+        let combined = combine_bits_circuit(builder, bits.into_iter());
+        let diff = builder.sub_extension(output, combined);
+        let constraint = builder.mul_extension(filter, diff);
+        yield_constr.constraint(builder, constraint);
+    }
+    let u32_max = builder.constant_extension(F::Extension::from_canonical_u32(u32::MAX));
+    let not_diff = builder.add_extension(output, in_0);
+    let not_diff = builder.sub_extension(not_diff, u32_max);
+    let constraint = builder.mul_extension(is_not, not_diff);
+    yield_constr.constraint(builder, constraint);
+
+    // Compute `2^(shift % 32)` from the low five bits of the shift.
+    let shift_bits = (0..5)
+        .map(|i| local_values[col_input_1_bit(i)])
+        .collect::<Vec<_>>();
+    let mut pow = builder.add_extension(shift_bits[0], one);
+    for i in 0..4 {
+        let partial = local_values[col_shift_pow_partial(i)];
+        let factor = builder.mul_const_add_extension(
+            F::from_canonical_u64((1 << (1 << (i + 1))) - 1),
+            shift_bits[i + 1],
+            one,
+        );
+        let expected = builder.mul_extension(pow, factor);
+        let diff = builder.sub_extension(partial, expected);
+        let constraint = builder.mul_extension(is_shift, diff);
+        yield_constr.constraint(builder, constraint);
+        pow = partial;
+    }
+
+    // Check whether the shift is in range, i.e. whether `shift - shift % 32` is zero.
+    let shift_low = combine_bits_circuit(builder, shift_bits.into_iter());
+    let shift_high = builder.sub_extension(in_1, shift_low);
+    let shift_high_inv = local_values[COL_SHIFT_HIGH_INV];
+    let in_range = local_values[COL_SHIFT_IN_RANGE];
+    let constraint = builder.mul_add_extension(shift_high, shift_high_inv, in_range);
+    let constraint = builder.sub_extension(constraint, one);
+    let constraint = builder.mul_extension(is_shift, constraint);
+    yield_constr.constraint(builder, constraint);
+    let constraint = builder.mul_many_extension(&[is_shift, shift_high, in_range]);
+    yield_constr.constraint(builder, constraint);
+
+    // The multiplier is `2^shift` for SHL, or `2^32 / 2^shift` for SHR, or zero if the shift is out
+    // of range.
+    let multiplier = local_values[COL_SHIFT_MULTIPLIER];
+    let expected = builder.mul_extension(in_range, pow);
+    let diff = builder.sub_extension(multiplier, expected);
+    let constraint = builder.mul_extension(is_shl, diff);
+    yield_constr.constraint(builder, constraint);
+    let expected = builder.mul_const_extension(F::from_canonical_u64(1 << 32), in_range);
+    let diff = builder.mul_sub_extension(multiplier, pow, expected);
+    let constraint = builder.mul_extension(is_shr, diff);
+    yield_constr.constraint(builder, constraint);
+
+    // The shifted value is one half of `in_0 * multiplier`.
+    let product_lo = combine_bits_circuit(
+        builder,
+        (0..32).map(|i| local_values[col_shift_product_lo_bit(i)]),
+    );
+    let product_hi = combine_bits_circuit(
+        builder,
+        (0..32).map(|i| local_values[col_shift_product_hi_bit(i)]),
+    );
+    let product = combine_u32s_check_canonical_circuit(
+        builder,
+        product_lo,
+        product_hi,
+        local_values[COL_SHIFT_PRODUCT_CANONICAL_INV],
+        is_shift,
+        yield_constr,
+    );
+    let diff = builder.mul_sub_extension(in_0, multiplier, product);
+    let constraint = builder.mul_extension(is_shift, diff);
+    yield_constr.constraint(builder, constraint);
+    for (filter, half) in [(is_shl, product_lo), (is_shr, product_hi)] {
+        let diff = builder.sub_extension(output, half);
+        let constraint = builder.mul_extension(filter, diff);
+        yield_constr.constraint(builder, constraint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::field_types::Field;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use crate::logic_unit::{eval_logic_unit, generate_logic_unit};
+    use crate::registers::logic::*;
+    use crate::registers::NUM_COLUMNS;
+    use crate::system_zero::unit_testing::row_constraints_hold;
+
+    type F = GoldilocksField;
+
+    fn generate(opcode: usize, in_0: u32, in_1: u32) -> [F; NUM_COLUMNS] {
+        let mut values = [F::ZERO; NUM_COLUMNS];
+        values[opcode] = F::ONE;
+        values[COL_LOGIC_INPUT_0] = F::from_canonical_u32(in_0);
+        values[COL_LOGIC_INPUT_1] = F::from_canonical_u32(in_1);
+        generate_logic_unit(&mut values);
+        values
+    }
+
+    fn constraints_hold(values: &[F; NUM_COLUMNS]) -> bool {
+        row_constraints_hold(values, eval_logic_unit)
+    }
+
+    #[test]
+    fn generate_eval_consistency() {
+        type Op = fn(u32, u32) -> u32;
+        let ops: [(usize, Op); 6] = [
+            (IS_AND, |a, b| a & b),
+            (IS_OR, |a, b| a | b),
+            (IS_XOR, |a, b| a ^ b),
+            (IS_NOT, |a, _| !a),
+            (IS_SHL, |a, b| a.checked_shl(b).unwrap_or(0)),
+            (IS_SHR, |a, b| a.checked_shr(b).unwrap_or(0)),
+        ];
+        let inputs = [
+            (0, 0),
+            (0xdeadbeef, 0x12345678),
+            (u32::MAX, 0),
+            (u32::MAX, 1),
+            (u32::MAX, 31),
+            (u32::MAX, 32),
+            (0x80000001, 17),
+            (12345, u32::MAX),
+        ];
+        for (opcode, op) in ops {
+            for (in_0, in_1) in inputs {
+                let values = generate(opcode, in_0, in_1);
+                assert!(constraints_hold(&values));
+                assert_eq!(
+                    values[COL_LOGIC_OUTPUT],
+                    F::from_canonical_u32(op(in_0, in_1))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn wrong_output() {
+        for opcode in [IS_AND, IS_OR, IS_XOR, IS_NOT, IS_SHL, IS_SHR] {
+            let mut values = generate(opcode, 0xdeadbeef, 7);
+            values[COL_LOGIC_OUTPUT] += F::ONE;
+            assert!(!constraints_hold(&values));
+        }
+    }
+
+    #[test]
+    fn wrong_out_of_range_shift() {
+        // Claim that a shift by 32 is in range; the multiplier is then 2^0 = 1 for SHL.
+        let mut values = generate(IS_SHL, 5, 32);
+        values[COL_SHIFT_IN_RANGE] = F::ONE;
+        values[COL_SHIFT_HIGH_INV] = F::ZERO;
+        values[COL_SHIFT_MULTIPLIER] = F::ONE;
+        values[col_shift_product_lo_bit(0)] = F::ONE;
+        values[col_shift_product_lo_bit(2)] = F::ONE;
+        values[COL_LOGIC_OUTPUT] = F::from_canonical_u32(5);
+        assert!(!constraints_hold(&values));
+    }
+}
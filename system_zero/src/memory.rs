@@ -3,14 +3,99 @@ pub struct TransactionMemory {
     pub calls: Vec<ContractMemory>,
 }
 
+impl TransactionMemory {
+    /// Reads a word of memory. Memory which has never been written to is zero.
+    pub fn read(&self, context: usize, segment: Segment, address: usize) -> u32 {
+        self.calls
+            .get(context)
+            .map_or(0, |call| call.segment(segment).read(address))
+    }
+
+    pub fn write(&mut self, context: usize, segment: Segment, address: usize, value: u32) {
+        if context >= self.calls.len() {
+            self.calls.resize_with(context + 1, ContractMemory::default);
+        }
+        self.calls[context]
+            .segment_mut(segment)
+            .write(address, value);
+    }
+}
+
 /// A virtual memory space specific to the current contract call.
+#[derive(Default)]
 pub struct ContractMemory {
     pub code: MemorySegment,
     pub main: MemorySegment,
     pub calldata: MemorySegment,
     pub returndata: MemorySegment,
+    /// Holds values pushed by `PUSH`, and frame records pushed by `CALL`.
+    pub stack: MemorySegment,
+}
+
+impl ContractMemory {
+    pub fn segment(&self, segment: Segment) -> &MemorySegment {
+        match segment {
+            Segment::Code => &self.code,
+            Segment::Main => &self.main,
+            Segment::Calldata => &self.calldata,
+            Segment::Returndata => &self.returndata,
+            Segment::Stack => &self.stack,
+        }
+    }
+
+    pub fn segment_mut(&mut self, segment: Segment) -> &mut MemorySegment {
+        match segment {
+            Segment::Code => &mut self.code,
+            Segment::Main => &mut self.main,
+            Segment::Calldata => &mut self.calldata,
+            Segment::Returndata => &mut self.returndata,
+            Segment::Stack => &mut self.stack,
+        }
+    }
+}
+
+/// Identifies a segment of a `ContractMemory`. In the trace, segments are represented by their
+/// discriminants.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Segment {
+    Code = 0,
+    Main = 1,
+    Calldata = 2,
+    Returndata = 3,
+    Stack = 4,
+}
+
+impl Segment {
+    pub(crate) const ALL: [Segment; 5] = [
+        Segment::Code,
+        Segment::Main,
+        Segment::Calldata,
+        Segment::Returndata,
+        Segment::Stack,
+    ];
+
+    pub(crate) fn from_index(index: usize) -> Self {
+        Self::ALL
+            .get(index)
+            .copied()
+            .unwrap_or_else(|| panic!("Invalid segment {}.", index))
+    }
 }
 
+#[derive(Default)]
 pub struct MemorySegment {
-    pub content: Vec<u8>,
+    pub content: Vec<u32>,
+}
+
+impl MemorySegment {
+    pub fn read(&self, address: usize) -> u32 {
+        self.content.get(address).copied().unwrap_or(0)
+    }
+
+    pub fn write(&mut self, address: usize, value: u32) {
+        if address >= self.content.len() {
+            self.content.resize(address + 1, 0);
+        }
+        self.content[address] = value;
+    }
 }
@@ -0,0 +1,384 @@
+//! Memory unit, which checks that memory operations are consistent, i.e. that each read returns the
+//! value most recently written to the same address, or zero if the address was never written to.
+//!
+//! The sorted copy of the operations is constrained to be strictly increasing in (context, segment,
+//! address, timestamp) order, by range checking the difference in the first field which changes
+//! between consecutive operations. Since range checks only cover `[0, degree)`, gaps between
+//! consecutive contexts, segments or addresses must not exceed the trace length, so the trace
+//! generator pads the trace to at least `min_rows_for_memory` rows.
+
+use itertools::Itertools;
+use plonky2::field::extension_field::Extendable;
+use plonky2::field::field_types::{Field, PrimeField64};
+use plonky2::field::packed_field::PackedField;
+use plonky2::hash::hash_types::RichField;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::trace::TraceBuilder;
+use starky::vars::StarkEvaluationTargets;
+use starky::vars::StarkEvaluationVars;
+
+use crate::memory::{Segment, TransactionMemory};
+use crate::public_input_layout::NUM_PUBLIC_INPUTS;
+use crate::registers::core::COL_CLOCK;
+use crate::registers::memory::*;
+use crate::registers::NUM_COLUMNS;
+
+/// Sets up a dummy memory operation, which reads address 0 of the code segment in context 0. Rows
+/// which don't access memory perform this operation, since it has no effect.
+pub(crate) fn generate_dummy_memory_op<F: Field>(values: &mut [F; NUM_COLUMNS]) {
+    values[COL_MEMORY_OP_CONTEXT] = F::ZERO;
+    values[COL_MEMORY_OP_SEGMENT] = F::from_canonical_usize(Segment::Code as usize);
+    values[COL_MEMORY_OP_ADDRESS] = F::ZERO;
+    values[COL_MEMORY_OP_VALUE] = F::ZERO;
+    values[COL_MEMORY_OP_IS_READ] = F::ONE;
+}
+
+/// Fills in the values of reads, then generates the sorted copy of the memory operations along with
+/// its helper columns. The operations are performed in row order, which must match the clock.
+pub(crate) fn generate_memory<F: PrimeField64>(trace_cols: &mut [Vec<F>]) {
+    let num_rows = trace_cols[0].len();
+    let to_usize = |x: F| x.to_canonical_u64() as usize;
+
+    let mut memory = TransactionMemory::default();
+    for i in 0..num_rows {
+        let context = to_usize(trace_cols[COL_MEMORY_OP_CONTEXT][i]);
+        let segment = Segment::from_index(to_usize(trace_cols[COL_MEMORY_OP_SEGMENT][i]));
+        let address = to_usize(trace_cols[COL_MEMORY_OP_ADDRESS][i]);
+        if trace_cols[COL_MEMORY_OP_IS_READ][i].is_one() {
+            let value = memory.read(context, segment, address);
+            trace_cols[COL_MEMORY_OP_VALUE][i] = F::from_canonical_u32(value);
+        } else {
+            let value = trace_cols[COL_MEMORY_OP_VALUE][i].to_canonical_u64();
+            let value = value.try_into().expect("Memory values must be u32s.");
+            memory.write(context, segment, address, value);
+        }
+    }
+
+    let sort_key = |i: usize| {
+        [
+            COL_MEMORY_OP_CONTEXT,
+            COL_MEMORY_OP_SEGMENT,
+            COL_MEMORY_OP_ADDRESS,
+            COL_CLOCK,
+        ]
+        .map(|col| trace_cols[col][i].to_canonical_u64())
+    };
+    let sorted_rows = (0..num_rows)
+        .sorted_unstable_by_key(|&i| sort_key(i))
+        .collect_vec();
+    for (col, sorted_col) in MEMORY_PERMUTATION_COLUMN_PAIRS {
+        trace_cols[sorted_col] = sorted_rows.iter().map(|&i| trace_cols[col][i]).collect();
+    }
+
+    let first_change_cols = [
+        COL_CONTEXT_FIRST_CHANGE,
+        COL_SEGMENT_FIRST_CHANGE,
+        COL_ADDRESS_FIRST_CHANGE,
+    ];
+    for i in 0..num_rows - 1 {
+        let diffs = [
+            COL_SORTED_MEMORY_CONTEXT,
+            COL_SORTED_MEMORY_SEGMENT,
+            COL_SORTED_MEMORY_ADDRESS,
+            COL_SORTED_MEMORY_TIMESTAMP,
+        ]
+        .map(|col| trace_cols[col][i + 1] - trace_cols[col][i]);
+        let first_change = diffs
+            .iter()
+            .position(|diff| diff.is_nonzero())
+            .expect("Timestamps should be distinct.");
+        for (j, col) in first_change_cols.into_iter().enumerate() {
+            trace_cols[col][i] = F::from_bool(j == first_change);
+        }
+        trace_cols[COL_MEMORY_RANGE_CHECK][i] = diffs[first_change] - F::ONE;
+    }
+
+    // The last operation is followed by the first, which is treated as a change of context, so that
+    // a read there must return zero.
+    for (j, col) in first_change_cols.into_iter().enumerate() {
+        trace_cols[col][num_rows - 1] = F::from_bool(j == 0);
+    }
+    trace_cols[COL_MEMORY_RANGE_CHECK][num_rows - 1] = F::ZERO;
+}
+
+/// Returns the minimum trace length for which the memory operations in `trace` can be range
+/// checked, i.e. the largest gap between consecutive contexts, segments or addresses in sorted
+/// order. Gaps between timestamps are always smaller than the trace length. This accounts for the
+/// dummy operations performed by padding rows.
+pub(crate) fn min_rows_for_memory<F: PrimeField64>(trace: &TraceBuilder<F, NUM_COLUMNS>) -> usize {
+    let mut dummy_row = [F::ZERO; NUM_COLUMNS];
+    generate_dummy_memory_op(&mut dummy_row);
+    let key = |row: &[F; NUM_COLUMNS]| {
+        [
+            COL_MEMORY_OP_CONTEXT,
+            COL_MEMORY_OP_SEGMENT,
+            COL_MEMORY_OP_ADDRESS,
+        ]
+        .map(|col| row[col].to_canonical_u64())
+    };
+    (0..trace.num_rows())
+        .map(|i| key(trace.row(i)))
+        .chain([key(&dummy_row)])
+        .sorted_unstable()
+        .dedup()
+        .tuple_windows()
+        .map(|(prev, next)| {
+            let first_change = (0..3).find(|&j| prev[j] != next[j]).unwrap();
+            (next[first_change] - prev[first_change]) as usize
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+pub(crate) fn eval_memory_unit<F: Field, P: PackedField<Scalar = F>>(
+    vars: StarkEvaluationVars<F, P, NUM_COLUMNS, NUM_PUBLIC_INPUTS>,
+    yield_constr: &mut ConstraintConsumer<P>,
+) {
+    let local_values = &vars.local_values;
+    let next_values = &vars.next_values;
+
+    let is_read = local_values[COL_MEMORY_OP_IS_READ];
+    yield_constr.constraint(is_read * is_read - is_read);
+
+    let context = local_values[COL_SORTED_MEMORY_CONTEXT];
+    let segment = local_values[COL_SORTED_MEMORY_SEGMENT];
+    let address = local_values[COL_SORTED_MEMORY_ADDRESS];
+    let value = local_values[COL_SORTED_MEMORY_VALUE];
+    let timestamp = local_values[COL_SORTED_MEMORY_TIMESTAMP];
+    let next_context = next_values[COL_SORTED_MEMORY_CONTEXT];
+    let next_segment = next_values[COL_SORTED_MEMORY_SEGMENT];
+    let next_address = next_values[COL_SORTED_MEMORY_ADDRESS];
+    let next_value = next_values[COL_SORTED_MEMORY_VALUE];
+    let next_is_read = next_values[COL_SORTED_MEMORY_IS_READ];
+    let next_timestamp = next_values[COL_SORTED_MEMORY_TIMESTAMP];
+
+    // At most one of the first-change flags may be set. If none are, the address is unchanged.
+    let context_first_change = local_values[COL_CONTEXT_FIRST_CHANGE];
+    let segment_first_change = local_values[COL_SEGMENT_FIRST_CHANGE];
+    let address_first_change = local_values[COL_ADDRESS_FIRST_CHANGE];
+    for flag in [
+        context_first_change,
+        segment_first_change,
+        address_first_change,
+    ] {
+        yield_constr.constraint(flag * flag - flag);
+    }
+    let address_changed = context_first_change + segment_first_change + address_first_change;
+    let address_unchanged = P::ONES - address_changed;
+    yield_constr.constraint(address_changed * address_changed - address_changed);
+    // The last operation is followed by the first, so it must be treated as an address change.
+    yield_constr.constraint_last_row(address_unchanged);
+
+    // Fields before the first change must be unchanged.
+    yield_constr.constraint_transition((P::ONES - context_first_change) * (next_context - context));
+    yield_constr.constraint_transition(
+        (P::ONES - context_first_change - segment_first_change) * (next_segment - segment),
+    );
+    yield_constr.constraint_transition(address_unchanged * (next_address - address));
+
+    // The first field which changes must increase, which ensures that the sorted operations are
+    // strictly increasing.
+    let range_check = context_first_change * (next_context - context - F::ONE)
+        + segment_first_change * (next_segment - segment - F::ONE)
+        + address_first_change * (next_address - address - F::ONE)
+        + address_unchanged * (next_timestamp - timestamp - F::ONE);
+    yield_constr.constraint_transition(range_check - local_values[COL_MEMORY_RANGE_CHECK]);
+
+    // A read must return the previous value at the same address, or zero if this is the first
+    // operation on the address. This also covers the first row, via the last row's address change.
+    yield_constr.constraint(address_unchanged * next_is_read * (next_value - value));
+    yield_constr.constraint(address_changed * next_is_read * next_value);
+}
+
+pub(crate) fn eval_memory_unit_recursively<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    vars: StarkEvaluationTargets<D, NUM_COLUMNS, NUM_PUBLIC_INPUTS>,
+    yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    let local_values = &vars.local_values;
+    let next_values = &vars.next_values;
+    let one = builder.one_extension();
+
+    let is_read = local_values[COL_MEMORY_OP_IS_READ];
+    let constraint = builder.mul_sub_extension(is_read, is_read, is_read);
+    yield_constr.constraint(builder, constraint);
+
+    let context = local_values[COL_SORTED_MEMORY_CONTEXT];
+    let segment = local_values[COL_SORTED_MEMORY_SEGMENT];
+    let address = local_values[COL_SORTED_MEMORY_ADDRESS];
+    let value = local_values[COL_SORTED_MEMORY_VALUE];
+    let timestamp = local_values[COL_SORTED_MEMORY_TIMESTAMP];
+    let next_context = next_values[COL_SORTED_MEMORY_CONTEXT];
+    let next_segment = next_values[COL_SORTED_MEMORY_SEGMENT];
+    let next_address = next_values[COL_SORTED_MEMORY_ADDRESS];
+    let next_value = next_values[COL_SORTED_MEMORY_VALUE];
+    let next_is_read = next_values[COL_SORTED_MEMORY_IS_READ];
+    let next_timestamp = next_values[COL_SORTED_MEMORY_TIMESTAMP];
+
+    // At most one of the first-change flags may be set. If none are, the address is unchanged.
+    let context_first_change = local_values[COL_CONTEXT_FIRST_CHANGE];
+    let segment_first_change = local_values[COL_SEGMENT_FIRST_CHANGE];
+    let address_first_change = local_values[COL_ADDRESS_FIRST_CHANGE];
+    for flag in [
+        context_first_change,
+        segment_first_change,
+        address_first_change,
+    ] {
+        let constraint = builder.mul_sub_extension(flag, flag, flag);
+        yield_constr.constraint(builder, constraint);
+    }
+    let address_changed = builder.add_many_extension(&[
+        context_first_change,
+        segment_first_change,
+        address_first_change,
+    ]);
+    let address_unchanged = builder.sub_extension(one, address_changed);
+    let constraint = builder.mul_sub_extension(address_changed, address_changed, address_changed);
+    yield_constr.constraint(builder, constraint);
+    // The last operation is followed by the first, so it must be treated as an address change.
+    yield_constr.constraint_last_row(builder, address_unchanged);
+
+    // Fields before the first change must be unchanged.
+    let context_unchanged = builder.sub_extension(one, context_first_change);
+    let segment_unchanged = builder.sub_extension(context_unchanged, segment_first_change);
+    for (unchanged, local, next) in [
+        (context_unchanged, context, next_context),
+        (segment_unchanged, segment, next_segment),
+        (address_unchanged, address, next_address),
+    ] {
+        let diff = builder.sub_extension(next, local);
+        let constraint = builder.mul_extension(unchanged, diff);
+        yield_constr.constraint_transition(builder, constraint);
+    }
+
+    // The first field which changes must increase, which ensures that the sorted operations are
+    // strictly increasing.
+    let mut range_check = builder.zero_extension();
+    for (flag, local, next) in [
+        (context_first_change, context, next_context),
+        (segment_first_change, segment, next_segment),
+        (address_first_change, address, next_address),
+        (address_unchanged, timestamp, next_timestamp),
+    ] {
+        let diff = builder.sub_extension(next, local);
+        let diff_minus_one = builder.sub_extension(diff, one);
+        range_check = builder.mul_add_extension(flag, diff_minus_one, range_check);
+    }
+    let constraint = builder.sub_extension(range_check, local_values[COL_MEMORY_RANGE_CHECK]);
+    yield_constr.constraint_transition(builder, constraint);
+
+    // A read must return the previous value at the same address, or zero if this is the first
+    // operation on the address. This also covers the first row, via the last row's address change.
+    let value_diff = builder.sub_extension(next_value, value);
+    let constraint = builder.mul_many_extension(&[address_unchanged, next_is_read, value_diff]);
+    yield_constr.constraint(builder, constraint);
+    let constraint = builder.mul_many_extension(&[address_changed, next_is_read, next_value]);
+    yield_constr.constraint(builder, constraint);
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::field_types::Field;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use crate::memory::Segment;
+    use crate::memory_unit::{eval_memory_unit, generate_dummy_memory_op, generate_memory};
+    use crate::registers::core::COL_CLOCK;
+    use crate::registers::memory::*;
+    use crate::registers::NUM_COLUMNS;
+    use crate::system_zero::unit_testing;
+
+    type F = GoldilocksField;
+
+    /// A memory operation: `(context, segment, address, value)`, where `value` is `None` for reads.
+    type Op = (usize, Segment, usize, Option<u32>);
+
+    /// Generates a column-wise trace containing the given memory operations, followed by dummy
+    /// operations.
+    fn generate(ops: &[Op], num_rows: usize) -> Vec<Vec<F>> {
+        let rows = (0..num_rows)
+            .map(|i| {
+                let mut values = [F::ZERO; NUM_COLUMNS];
+                values[COL_CLOCK] = F::from_canonical_usize(i);
+                generate_dummy_memory_op(&mut values);
+                if let Some(&(context, segment, address, value)) = ops.get(i) {
+                    values[COL_MEMORY_OP_CONTEXT] = F::from_canonical_usize(context);
+                    values[COL_MEMORY_OP_SEGMENT] = F::from_canonical_usize(segment as usize);
+                    values[COL_MEMORY_OP_ADDRESS] = F::from_canonical_usize(address);
+                    values[COL_MEMORY_OP_VALUE] = F::from_canonical_u32(value.unwrap_or(0));
+                    values[COL_MEMORY_OP_IS_READ] = F::from_bool(value.is_none());
+                }
+                values
+            })
+            .collect::<Vec<_>>();
+        let mut trace_cols = (0..NUM_COLUMNS)
+            .map(|col| rows.iter().map(|row| row[col]).collect())
+            .collect::<Vec<_>>();
+        generate_memory(&mut trace_cols);
+        trace_cols
+    }
+
+    fn constraints_hold(trace_cols: &[Vec<F>]) -> bool {
+        unit_testing::constraints_hold(trace_cols, eval_memory_unit)
+    }
+
+    #[test]
+    fn generate_eval_consistency() {
+        let ops = [
+            (0, Segment::Main, 3, None),
+            (0, Segment::Main, 3, Some(7)),
+            (1, Segment::Main, 3, Some(8)),
+            (0, Segment::Main, 3, None),
+            (1, Segment::Calldata, 3, None),
+            (1, Segment::Main, 3, None),
+            (0, Segment::Main, 3, Some(9)),
+            (0, Segment::Main, 3, None),
+            (0, Segment::Code, 0, Some(10)),
+        ];
+        let trace_cols = generate(&ops, 16);
+        assert!(constraints_hold(&trace_cols));
+
+        let read_values = trace_cols[COL_MEMORY_OP_VALUE][..ops.len()].to_vec();
+        let expected = [0, 7, 8, 7, 0, 8, 9, 9, 10].map(F::from_canonical_u32);
+        assert_eq!(read_values, expected);
+        // The dummy operations read the value written by the last operation.
+        assert_eq!(trace_cols[COL_MEMORY_OP_VALUE][ops.len()], expected[8]);
+    }
+
+    #[test]
+    fn wrong_read_value() {
+        let ops = [(0, Segment::Main, 3, Some(7)), (0, Segment::Main, 3, None)];
+        let mut trace_cols = generate(&ops, 8);
+        // Change the read value, in both the original and sorted operations.
+        for col in [COL_MEMORY_OP_VALUE, COL_SORTED_MEMORY_VALUE] {
+            let i = trace_cols[col]
+                .iter()
+                .position(|&x| x == F::from_canonical_u32(7))
+                .unwrap();
+            trace_cols[col][i + 1] = F::from_canonical_u32(8);
+        }
+        assert!(!constraints_hold(&trace_cols));
+    }
+
+    #[test]
+    fn wrong_uninitialized_read() {
+        let ops = [(0, Segment::Main, 3, None)];
+        let mut trace_cols = generate(&ops, 8);
+        // The first operation on the address is the last one in sorted order.
+        trace_cols[COL_MEMORY_OP_VALUE][0] = F::ONE;
+        trace_cols[COL_SORTED_MEMORY_VALUE][7] = F::ONE;
+        assert!(!constraints_hold(&trace_cols));
+    }
+
+    #[test]
+    fn wrong_order() {
+        let ops = [(0, Segment::Main, 3, Some(7)), (0, Segment::Main, 3, None)];
+        let mut trace_cols = generate(&ops, 8);
+        // Swap the two sorted operations on address 3, which are the last two in sorted order.
+        for (_, sorted_col) in MEMORY_PERMUTATION_COLUMN_PAIRS {
+            trace_cols[sorted_col].swap(6, 7);
+        }
+        assert!(!constraints_hold(&trace_cols));
+    }
+}
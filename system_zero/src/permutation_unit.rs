@@ -254,19 +254,62 @@ pub(crate) fn eval_permutation_unit_recursively<F: RichField + Extendable<D>, co
 
 #[cfg(test)]
 mod tests {
+    use anyhow::Result;
+    use plonky2::field::extension_field::FieldExtension;
     use plonky2::field::field_types::Field;
     use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::packed_field::PackedField;
     use plonky2::hash::poseidon::Poseidon;
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
     use rand::SeedableRng;
     use rand_chacha::ChaCha8Rng;
-    use starky::constraint_consumer::ConstraintConsumer;
-    use starky::vars::StarkEvaluationVars;
-
-    use crate::permutation_unit::{eval_permutation_unit, generate_permutation_unit, SPONGE_WIDTH};
+    use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+    use starky::stark::Stark;
+    use starky::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
+    use starky::vars::{StarkEvaluationTargets, StarkEvaluationVars};
+
+    use crate::permutation_unit::{
+        eval_permutation_unit, eval_permutation_unit_recursively, generate_permutation_unit,
+        SPONGE_WIDTH,
+    };
     use crate::public_input_layout::NUM_PUBLIC_INPUTS;
     use crate::registers::permutation::{col_input, col_output};
     use crate::registers::NUM_COLUMNS;
 
+    /// A STARK with only the constraints of the permutation unit.
+    #[derive(Copy, Clone)]
+    struct PermutationStark;
+
+    impl Stark<GoldilocksField, 2> for PermutationStark {
+        const COLUMNS: usize = NUM_COLUMNS;
+        const PUBLIC_INPUTS: usize = NUM_PUBLIC_INPUTS;
+
+        fn eval_packed_generic<FE, P, const D2: usize>(
+            &self,
+            vars: StarkEvaluationVars<FE, P, NUM_COLUMNS, NUM_PUBLIC_INPUTS>,
+            yield_constr: &mut ConstraintConsumer<P>,
+        ) where
+            FE: FieldExtension<D2, BaseField = GoldilocksField>,
+            P: PackedField<Scalar = FE>,
+        {
+            eval_permutation_unit::<GoldilocksField, FE, P, D2>(vars, yield_constr);
+        }
+
+        fn eval_ext_recursively(
+            &self,
+            builder: &mut CircuitBuilder<GoldilocksField, 2>,
+            vars: StarkEvaluationTargets<2, NUM_COLUMNS, NUM_PUBLIC_INPUTS>,
+            yield_constr: &mut RecursiveConstraintConsumer<GoldilocksField, 2>,
+        ) {
+            eval_permutation_unit_recursively(builder, vars, yield_constr);
+        }
+
+        fn constraint_degree(&self) -> usize {
+            3
+        }
+    }
+
     #[test]
     fn generate_eval_consistency() {
         const D: usize = 1;
@@ -278,6 +321,9 @@ mod tests {
         let vars = StarkEvaluationVars {
             local_values: &values,
             next_values: &[F::default(); NUM_COLUMNS],
+            window_values: &[values, [F::default(); NUM_COLUMNS]],
+            local_preprocessed_values: &[],
+            next_preprocessed_values: &[],
             public_inputs: &[F::default(); NUM_PUBLIC_INPUTS],
         };
 
@@ -288,7 +334,7 @@ mod tests {
             GoldilocksField::ONE,
         );
         eval_permutation_unit(vars, &mut constrant_consumer);
-        for &acc in &constrant_consumer.constraint_accs {
+        for acc in constrant_consumer.accumulators() {
             assert_eq!(acc, GoldilocksField::ZERO);
         }
     }
@@ -319,6 +365,17 @@ mod tests {
         assert_eq!(target, result);
     }
 
-    // TODO(JN): test degree
-    // TODO(JN): test `eval_permutation_unit_recursively`
+    #[test]
+    fn degree() -> Result<()> {
+        test_stark_low_degree(PermutationStark)
+    }
+
+    #[test]
+    fn circuit() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = GoldilocksField;
+
+        test_stark_circuit_constraints::<F, C, PermutationStark, D>(PermutationStark)
+    }
 }
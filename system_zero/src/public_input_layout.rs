@@ -1,7 +1,37 @@
+use crate::instruction::NUM_REGISTERS;
+use crate::registers::state::NUM_HASH_OUT_ELTS;
+
 /// The previous state root, before these transactions were executed.
-const PI_OLD_STATE_ROOT: usize = 0;
+pub(crate) const fn pi_old_state_root(i: usize) -> usize {
+    debug_assert!(i < NUM_HASH_OUT_ELTS);
+    i
+}
 
 /// The updated state root, after these transactions were executed.
-const PI_NEW_STATE_ROOT: usize = PI_OLD_STATE_ROOT + 1;
+pub(crate) const fn pi_new_state_root(i: usize) -> usize {
+    debug_assert!(i < NUM_HASH_OUT_ELTS);
+    pi_old_state_root(NUM_HASH_OUT_ELTS - 1) + 1 + i
+}
+
+/// The initial values of the general purpose registers, which serve as the program's inputs.
+pub(crate) const fn pi_input_register(i: usize) -> usize {
+    debug_assert!(i < NUM_REGISTERS);
+    pi_new_state_root(NUM_HASH_OUT_ELTS - 1) + 1 + i
+}
+
+/// The final values of the general purpose registers, which serve as the program's outputs.
+pub(crate) const fn pi_output_register(i: usize) -> usize {
+    debug_assert!(i < NUM_REGISTERS);
+    pi_input_register(NUM_REGISTERS - 1) + 1 + i
+}
+
+/// The digest of the program, as computed by `hash_program`.
+pub(crate) const fn pi_program_hash(i: usize) -> usize {
+    debug_assert!(i < NUM_HASH_OUT_ELTS);
+    pi_output_register(NUM_REGISTERS - 1) + 1 + i
+}
+
+/// The number of words in the program.
+pub(crate) const PI_PROGRAM_LENGTH: usize = pi_program_hash(NUM_HASH_OUT_ELTS - 1) + 1;
 
-pub(crate) const NUM_PUBLIC_INPUTS: usize = PI_NEW_STATE_ROOT + 1;
+pub(crate) const NUM_PUBLIC_INPUTS: usize = PI_PROGRAM_LENGTH + 1;
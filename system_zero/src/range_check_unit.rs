@@ -0,0 +1,63 @@
+use plonky2::field::extension_field::Extendable;
+use plonky2::field::field_types::{Field, PrimeField64};
+use plonky2::field::packed_field::PackedField;
+use plonky2::hash::hash_types::RichField;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::vars::StarkEvaluationTargets;
+use starky::vars::StarkEvaluationVars;
+
+use crate::public_input_layout::NUM_PUBLIC_INPUTS;
+use crate::registers::range_check_16::{col_rc_16_byte, col_rc_16_input, NUM_RANGE_CHECKS};
+use crate::registers::NUM_COLUMNS;
+
+/// Splits the inputs of the 16-bit range checks into bytes. This must run before the lookup
+/// columns are generated.
+pub(crate) fn generate_range_checks<F: PrimeField64>(trace_cols: &mut [Vec<F>]) {
+    for i in 0..NUM_RANGE_CHECKS {
+        let (lo, hi): (Vec<F>, Vec<F>) = trace_cols[col_rc_16_input(i)]
+            .iter()
+            .map(|x| {
+                let x = x.to_canonical_u64();
+                debug_assert!(x < 1 << 16, "Range checked value {} is not 16 bits.", x);
+                (
+                    F::from_canonical_u64(x & 0xff),
+                    F::from_canonical_u64(x >> 8),
+                )
+            })
+            .unzip();
+        trace_cols[col_rc_16_byte(i, 0)] = lo;
+        trace_cols[col_rc_16_byte(i, 1)] = hi;
+    }
+}
+
+pub(crate) fn eval_range_checks<F: Field, P: PackedField<Scalar = F>>(
+    vars: StarkEvaluationVars<F, P, NUM_COLUMNS, NUM_PUBLIC_INPUTS>,
+    yield_constr: &mut ConstraintConsumer<P>,
+) {
+    // Each input must be the sum of its bytes, which are checked to be in `[0, 2^8)` by lookups.
+    let base = F::from_canonical_u64(1 << 8);
+    for i in 0..NUM_RANGE_CHECKS {
+        let input = vars.local_values[col_rc_16_input(i)];
+        let lo = vars.local_values[col_rc_16_byte(i, 0)];
+        let hi = vars.local_values[col_rc_16_byte(i, 1)];
+        yield_constr.constraint(lo + hi * base - input);
+    }
+}
+
+pub(crate) fn eval_range_checks_recursively<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    vars: StarkEvaluationTargets<D, NUM_COLUMNS, NUM_PUBLIC_INPUTS>,
+    yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    // Each input must be the sum of its bytes, which are checked to be in `[0, 2^8)` by lookups.
+    let base = F::from_canonical_u64(1 << 8);
+    for i in 0..NUM_RANGE_CHECKS {
+        let input = vars.local_values[col_rc_16_input(i)];
+        let lo = vars.local_values[col_rc_16_byte(i, 0)];
+        let hi = vars.local_values[col_rc_16_byte(i, 1)];
+        let sum = builder.mul_const_add_extension(base, hi, lo);
+        let constraint = builder.sub_extension(sum, input);
+        yield_constr.constraint(builder, constraint);
+    }
+}
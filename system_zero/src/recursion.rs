@@ -0,0 +1,283 @@
+use anyhow::{ensure, Result};
+use plonky2::field::extension_field::Extendable;
+use plonky2::hash::hash_types::{HashOutTarget, RichField};
+use plonky2::iop::target::Target;
+use plonky2::iop::witness::{PartialWitness, Witness};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::{CircuitConfig, CircuitData, VerifierCircuitTarget};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
+use plonky2::plonk::proof::{ProofWithPublicInputs, ProofWithPublicInputsTarget};
+use starky::config::StarkConfig;
+use starky::proof::{StarkProofWithPublicInputs, StarkProofWithPublicInputsTarget};
+use starky::recursive_verifier::{
+    add_virtual_stark_proof_with_pis, recursively_verify_stark_proof,
+    set_stark_proof_with_pis_target,
+};
+use starky::stark::Stark;
+
+use crate::instruction::NUM_REGISTERS;
+use crate::public_input_layout::{
+    pi_new_state_root, pi_old_state_root, pi_output_register, pi_program_hash, PI_PROGRAM_LENGTH,
+};
+use crate::registers::state::NUM_HASH_OUT_ELTS;
+use crate::system_zero::SystemZero;
+
+/// A plonky2 circuit which verifies a system zero STARK proof with a trace of `2^degree_bits`
+/// rows. Its public inputs are those of the STARK proof, so the state roots and register values
+/// can be read with the same methods of `SystemZero`. Executions can be given a common trace length
+/// with `SystemZero::new`, so that their proofs can all be wrapped by one `BlockCircuit`.
+pub struct BlockCircuit<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> {
+    pub data: CircuitData<F, C, D>,
+    stark_proof: StarkProofWithPublicInputsTarget<D>,
+    degree_bits: usize,
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> BlockCircuit<F, C, D>
+where
+    C::Hasher: AlgebraicHasher<F>,
+    [(); <SystemZero<F, D> as Stark<F, D>>::COLUMNS]:,
+    [(); <SystemZero<F, D> as Stark<F, D>>::PUBLIC_INPUTS]:,
+    [(); C::Hasher::HASH_SIZE]:,
+{
+    pub fn new(
+        system: SystemZero<F, D>,
+        stark_config: &StarkConfig,
+        degree_bits: usize,
+        config: &CircuitConfig,
+    ) -> Self {
+        let mut builder = CircuitBuilder::<F, D>::new(config.clone());
+        let stark_proof =
+            add_virtual_stark_proof_with_pis(&mut builder, system, stark_config, degree_bits);
+        builder.register_public_inputs(&stark_proof.public_inputs);
+        recursively_verify_stark_proof::<F, C, SystemZero<F, D>, D>(
+            &mut builder,
+            system,
+            stark_proof.clone(),
+            stark_config,
+        );
+
+        Self {
+            data: builder.build::<C>(),
+            stark_proof,
+            degree_bits,
+        }
+    }
+
+    /// Wraps a STARK proof of a system zero execution in a plonky2 proof.
+    pub fn prove(
+        &self,
+        stark_proof: &StarkProofWithPublicInputs<F, C, D>,
+        stark_config: &StarkConfig,
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        ensure!(
+            stark_proof.proof.recover_degree_bits(stark_config) == self.degree_bits,
+            "STARK proof has a different trace length than the circuit expects."
+        );
+        let mut pw = PartialWitness::new();
+        set_stark_proof_with_pis_target(&mut pw, &self.stark_proof, stark_proof);
+        self.data.prove(pw)
+    }
+}
+
+/// A plonky2 circuit which verifies two proofs of consecutive executions of the same program,
+/// generated by the same inner circuit, and checks that the new state root of the first is the old
+/// state root of the second. Its public inputs have the same layout as those of the inner proofs,
+/// so they can be read with the same methods of `SystemZero`: they hold the old state root and
+/// input registers of the first execution, the new state root and output registers of the second,
+/// and the digest and length of the program both executions ran.
+///
+/// The inner circuit may be a `BlockCircuit`, or another `AggregationCircuit`, so `2^n` blocks can
+/// be aggregated with a tree of `n` aggregation circuits.
+pub struct AggregationCircuit<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+> {
+    pub data: CircuitData<F, C, D>,
+    first: ProofWithPublicInputsTarget<D>,
+    second: ProofWithPublicInputsTarget<D>,
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
+    AggregationCircuit<F, C, D>
+where
+    C::Hasher: AlgebraicHasher<F>,
+    [(); C::Hasher::HASH_SIZE]:,
+{
+    pub fn new(inner: &CircuitData<F, C, D>, config: &CircuitConfig) -> Self {
+        let mut builder = CircuitBuilder::<F, D>::new(config.clone());
+        // The inner circuit is fixed, so its verifier data is a constant of this circuit.
+        let inner_verifier_data = VerifierCircuitTarget {
+            constants_sigmas_cap: builder
+                .constant_merkle_cap(&inner.verifier_only.constants_sigmas_cap),
+        };
+        let first = builder.add_virtual_proof_with_pis(&inner.common);
+        let second = builder.add_virtual_proof_with_pis(&inner.common);
+
+        builder.connect_hashes(
+            new_state_root_target(&first.public_inputs),
+            old_state_root_target(&second.public_inputs),
+        );
+        builder.connect_hashes(
+            program_hash_target(&first.public_inputs),
+            program_hash_target(&second.public_inputs),
+        );
+        builder.connect(
+            first.public_inputs[PI_PROGRAM_LENGTH],
+            second.public_inputs[PI_PROGRAM_LENGTH],
+        );
+
+        let mut public_inputs = first.public_inputs.clone();
+        for i in 0..NUM_HASH_OUT_ELTS {
+            public_inputs[pi_new_state_root(i)] = second.public_inputs[pi_new_state_root(i)];
+        }
+        for i in 0..NUM_REGISTERS {
+            public_inputs[pi_output_register(i)] = second.public_inputs[pi_output_register(i)];
+        }
+        builder.register_public_inputs(&public_inputs);
+
+        builder.verify_proof(first.clone(), &inner_verifier_data, &inner.common);
+        builder.verify_proof(second.clone(), &inner_verifier_data, &inner.common);
+
+        Self {
+            data: builder.build::<C>(),
+            first,
+            second,
+        }
+    }
+
+    /// Aggregates proofs of two consecutive executions of the same program, where `second` starts
+    /// from the state `first` ends with.
+    pub fn prove(
+        &self,
+        first: &ProofWithPublicInputs<F, C, D>,
+        second: &ProofWithPublicInputs<F, C, D>,
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        ensure!(
+            SystemZero::<F, D>::new_state_root(&first.public_inputs)
+                == SystemZero::<F, D>::old_state_root(&second.public_inputs),
+            "Executions are not consecutive."
+        );
+        ensure!(
+            SystemZero::<F, D>::program_hash(&first.public_inputs)
+                == SystemZero::<F, D>::program_hash(&second.public_inputs)
+                && first.public_inputs[PI_PROGRAM_LENGTH]
+                    == second.public_inputs[PI_PROGRAM_LENGTH],
+            "Executions are of different programs."
+        );
+        let mut pw = PartialWitness::new();
+        pw.set_proof_with_pis_target(&self.first, first);
+        pw.set_proof_with_pis_target(&self.second, second);
+        self.data.prove(pw)
+    }
+}
+
+fn old_state_root_target(public_inputs: &[Target]) -> HashOutTarget {
+    HashOutTarget {
+        elements: [0, 1, 2, 3].map(|i| public_inputs[pi_old_state_root(i)]),
+    }
+}
+
+fn new_state_root_target(public_inputs: &[Target]) -> HashOutTarget {
+    HashOutTarget {
+        elements: [0, 1, 2, 3].map(|i| public_inputs[pi_new_state_root(i)]),
+    }
+}
+
+fn program_hash_target(public_inputs: &[Target]) -> HashOutTarget {
+    HashOutTarget {
+        elements: [0, 1, 2, 3].map(|i| public_inputs[pi_program_hash(i)]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use anyhow::Result;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::{PartialWitness, Witness};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+    use starky::config::StarkConfig;
+
+    use crate::instruction::Opcode::*;
+    use crate::instruction::{Instruction, NUM_REGISTERS};
+    use crate::recursion::{AggregationCircuit, BlockCircuit};
+    use crate::state::State;
+    use crate::system_zero::SystemZero;
+
+    type F = GoldilocksField;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+    type S = SystemZero<F, D>;
+
+    #[test]
+    fn aggregate_blocks() -> Result<()> {
+        let system = S::default();
+        let stark_config = StarkConfig::standard_fast_config();
+        let config = CircuitConfig::standard_recursion_config();
+
+        // Each block increments the value stored at address 1.
+        let program = [
+            Instruction::new(SLoad, 0, 3, 0, 1),
+            Instruction::new(LoadI, 1, 0, 0, 1),
+            Instruction::new(Add, 0, 0, 1, 0),
+            Instruction::new(SStore, 0, 3, 0, 1),
+        ];
+        let initial_state = State::default();
+        let first = system.prove_program::<C>(
+            &program,
+            [0; NUM_REGISTERS],
+            &initial_state,
+            &stark_config,
+        )?;
+        let mut state = initial_state.clone();
+        state.write(1, 1);
+        let second =
+            system.prove_program::<C>(&program, [0; NUM_REGISTERS], &state, &stark_config)?;
+        let mut final_state = state.clone();
+        final_state.write(1, 2);
+
+        // A different program, which overwrites the value rather than incrementing it.
+        let other_program = [
+            Instruction::new(LoadI, 0, 0, 0, 7),
+            Instruction::new(SStore, 0, 3, 0, 1),
+        ];
+        let other =
+            system.prove_program::<C>(&other_program, [0; NUM_REGISTERS], &state, &stark_config)?;
+
+        let degree_bits = first.proof.recover_degree_bits(&stark_config);
+        let block_circuit =
+            BlockCircuit::<F, C, D>::new(system, &stark_config, degree_bits, &config);
+        let first = block_circuit.prove(&first, &stark_config)?;
+        let second = block_circuit.prove(&second, &stark_config)?;
+        block_circuit.data.verify(first.clone())?;
+        block_circuit.data.verify(second.clone())?;
+        let other = block_circuit.prove(&other, &stark_config)?;
+
+        let aggregation_circuit = AggregationCircuit::new(&block_circuit.data, &config);
+        // Blocks must be aggregated in order.
+        assert!(aggregation_circuit.prove(&second, &first).is_err());
+        let aggregate = aggregation_circuit.prove(&first, &second)?;
+        assert_eq!(
+            S::old_state_root(&aggregate.public_inputs),
+            initial_state.root()
+        );
+        assert_eq!(
+            S::new_state_root(&aggregate.public_inputs),
+            final_state.root()
+        );
+        assert!(S::is_proof_of_program(&aggregate.public_inputs, &program));
+
+        // Blocks of different programs can't be aggregated, even if their state roots match.
+        assert!(aggregation_circuit.prove(&first, &other).is_err());
+        let mut pw = PartialWitness::new();
+        pw.set_proof_with_pis_target(&aggregation_circuit.first, &first);
+        pw.set_proof_with_pis_target(&aggregation_circuit.second, &other);
+        let result = catch_unwind(AssertUnwindSafe(|| aggregation_circuit.data.prove(pw)));
+        assert!(!matches!(result, Ok(Ok(_))));
+
+        aggregation_circuit.data.verify(aggregate)
+    }
+}
@@ -55,7 +55,10 @@ pub(crate) const COL_MUL_ADD_ADDEND: usize = shared_col(2);
 
 /// The inverse of `u32::MAX - result_hi`, where `output_hi` is the high 32-bits of the result.
 /// See https://hackmd.io/NC-yRmmtRQSvToTHb96e8Q#Checking-element-validity
-pub(crate) const COL_MUL_ADD_RESULT_CANONICAL_INV: usize = shared_col(3);
+///
+/// This column is not shared, since it must be zero whenever `IS_MUL_ADD` is not set; see
+/// `combine_u32s_check_canonical`.
+pub(crate) const COL_MUL_ADD_RESULT_CANONICAL_INV: usize = START_SHARED_COLS + NUM_SHARED_COLS;
 
 /// The first 16-bit chunk of the output, based on little-endian ordering.
 pub(crate) const COL_MUL_ADD_OUTPUT_0: usize = super::range_check_16::col_rc_16_input(0);
@@ -66,4 +69,28 @@ pub(crate) const COL_MUL_ADD_OUTPUT_2: usize = super::range_check_16::col_rc_16_
 /// The fourth 16-bit chunk of the output, based on little-endian ordering.
 pub(crate) const COL_MUL_ADD_OUTPUT_3: usize = super::range_check_16::col_rc_16_input(3);
 
-pub(super) const END: usize = super::START_ALU + NUM_SHARED_COLS;
+/// The value to be divided; treated as an unsigned u32.
+pub(crate) const COL_DIV_INPUT_DIVIDEND: usize = shared_col(0);
+/// The value to divide by; treated as an unsigned u32.
+pub(crate) const COL_DIV_INPUT_DIVISOR: usize = shared_col(1);
+/// The inverse of the divisor, or zero if the divisor is zero.
+pub(crate) const COL_DIV_INVDIVISOR: usize = shared_col(2);
+/// A flag which is 1 if the divisor is zero, and 0 otherwise.
+pub(crate) const COL_DIV_DIVISOR_IS_ZERO: usize = shared_col(3);
+
+/// The first 16-bit chunk of the quotient, based on little-endian ordering.
+pub(crate) const COL_DIV_OUTPUT_QUOT_0: usize = super::range_check_16::col_rc_16_input(0);
+/// The second 16-bit chunk of the quotient, based on little-endian ordering.
+pub(crate) const COL_DIV_OUTPUT_QUOT_1: usize = super::range_check_16::col_rc_16_input(1);
+/// The first 16-bit chunk of the remainder, based on little-endian ordering.
+pub(crate) const COL_DIV_OUTPUT_REM_0: usize = super::range_check_16::col_rc_16_input(2);
+/// The second 16-bit chunk of the remainder, based on little-endian ordering.
+pub(crate) const COL_DIV_OUTPUT_REM_1: usize = super::range_check_16::col_rc_16_input(3);
+
+/// The first 16-bit chunk of `divisor - remainder - 1`, which is range checked to show that the
+/// remainder is less than the divisor. Unused if the divisor is zero.
+pub(crate) const COL_DIV_RANGE_CHECKED_TMP_0: usize = super::range_check_16::col_rc_16_input(4);
+/// The second 16-bit chunk of `divisor - remainder - 1`.
+pub(crate) const COL_DIV_RANGE_CHECKED_TMP_1: usize = super::range_check_16::col_rc_16_input(5);
+
+pub(super) const END: usize = COL_MUL_ADD_RESULT_CANONICAL_INV + 1;
@@ -1,6 +1,6 @@
 //! Boolean unit. Contains columns whose values must be 0 or 1.
 
-const NUM_BITS: usize = 128;
+pub(crate) const NUM_BITS: usize = 128;
 
 pub const fn col_bit(index: usize) -> usize {
     debug_assert!(index < NUM_BITS);
@@ -1,20 +1,122 @@
 //! Core registers.
+//!
+//! Execution proceeds in phases. First, the program is written to the code segment, one word per
+//! row, and hashed, so that it matches the digest in the public inputs; see the hash unit. Then
+//! each instruction takes two rows: a fetch row, which reads the instruction word from the code
+//! segment, and an execute row, which decodes and executes it. State accesses are followed by rows
+//! which hash their Merkle paths; see the state unit. Likewise, `HASH` is followed by rows which
+//! absorb its input and write its digest; see the hash unit. 256-bit instructions are followed by
+//! rows which read their operands, compute their result and write it; see the 256-bit ALU.
+//! After a `HALT` instruction has been executed, all remaining rows are halted rows, which do
+//! nothing.
+
+use crate::instruction::NUM_REGISTERS;
 
 /// A cycle counter. Starts at 0; increments by 1.
 pub(crate) const COL_CLOCK: usize = super::START_CORE;
 
-/// A column which contains the values `[0, ... 2^16 - 1]`, potentially with duplicates. Used for
-/// 16-bit range checks.
+/// A column which contains the values `[0, ... 2^8 - 1]`, potentially with duplicates. Used for
+/// 16-bit range checks, which look up each byte of the value being checked.
 ///
-/// For ease of verification, we enforce that it must begin with 0 and end with `2^16 - 1`, and each
+/// For ease of verification, we enforce that it must begin with 0 and end with `2^8 - 1`, and each
 /// delta must be either 0 or 1.
-pub(crate) const COL_RANGE_16: usize = COL_CLOCK + 1;
+pub(crate) const COL_RANGE_8: usize = COL_CLOCK + 1;
 
 /// Pointer to the current instruction.
-pub(crate) const COL_INSTRUCTION_PTR: usize = COL_RANGE_16 + 1;
+pub(crate) const COL_INSTRUCTION_PTR: usize = COL_RANGE_8 + 1;
 /// Pointer to the base of the current call's stack frame.
 pub(crate) const COL_FRAME_PTR: usize = COL_INSTRUCTION_PTR + 1;
 /// Pointer to the tip of the current call's stack frame.
 pub(crate) const COL_STACK_PTR: usize = COL_FRAME_PTR + 1;
 
-pub(super) const END: usize = COL_STACK_PTR + 1;
+/// The general purpose registers.
+pub(crate) const fn col_register(i: usize) -> usize {
+    debug_assert!(i < NUM_REGISTERS);
+    COL_STACK_PTR + 1 + i
+}
+
+/// 1 if this row writes a word of the program to the code segment, and 0 otherwise.
+pub(crate) const IS_LOADING: usize = col_register(NUM_REGISTERS - 1) + 1;
+/// 1 if this row fetches an instruction, and 0 otherwise.
+pub(crate) const IS_FETCH: usize = IS_LOADING + 1;
+/// 1 if execution has halted, and 0 otherwise.
+pub(crate) const IS_HALTED: usize = IS_FETCH + 1;
+/// 1 if this row hashes a level of the old Merkle path of a state access, and 0 otherwise.
+pub(crate) const IS_OLD_PATH: usize = IS_HALTED + 1;
+/// 1 if this row hashes a level of the new Merkle path of a state access, and 0 otherwise.
+pub(crate) const IS_NEW_PATH: usize = IS_OLD_PATH + 1;
+/// 1 if this row absorbs a word of the input of `HASH`, and 0 otherwise.
+pub(crate) const IS_HASH_ABSORB: usize = IS_NEW_PATH + 1;
+/// 1 if this row writes a word of the digest of `HASH`, and 0 otherwise.
+pub(crate) const IS_HASH_OUTPUT: usize = IS_HASH_ABSORB + 1;
+/// 1 if this row reads a word of the operands of a 256-bit instruction, and 0 otherwise.
+pub(crate) const IS_WIDE_READ: usize = IS_HASH_OUTPUT + 1;
+/// 1 if this row checks a limb of the relation of a 256-bit instruction, and 0 otherwise.
+pub(crate) const IS_WIDE_COMPUTE: usize = IS_WIDE_READ + 1;
+/// 1 if this row writes a word of the result of a 256-bit instruction, and 0 otherwise.
+pub(crate) const IS_WIDE_WRITE: usize = IS_WIDE_COMPUTE + 1;
+
+/// The instruction word being executed. Zero in rows other than execute rows.
+pub(crate) const COL_INSTRUCTION_WORD: usize = IS_WIDE_WRITE + 1;
+
+// Flags for the opcodes which aren't handled by other units; see `Opcode::flag`.
+pub(crate) const IS_HALT: usize = COL_INSTRUCTION_WORD + 1;
+pub(crate) const IS_LOADI: usize = IS_HALT + 1;
+pub(crate) const IS_LOAD: usize = IS_LOADI + 1;
+pub(crate) const IS_STORE: usize = IS_LOAD + 1;
+pub(crate) const IS_PUSH: usize = IS_STORE + 1;
+pub(crate) const IS_POP: usize = IS_PUSH + 1;
+pub(crate) const IS_JUMP: usize = IS_POP + 1;
+pub(crate) const IS_JUMPI: usize = IS_JUMP + 1;
+pub(crate) const IS_CALL: usize = IS_JUMPI + 1;
+pub(crate) const IS_RET: usize = IS_CALL + 1;
+pub(crate) const IS_SLOAD: usize = IS_RET + 1;
+pub(crate) const IS_SSTORE: usize = IS_SLOAD + 1;
+pub(crate) const IS_HASH: usize = IS_SSTORE + 1;
+
+/// One-hot encoding of the instruction's `rd` field.
+pub(crate) const fn col_rd_flag(i: usize) -> usize {
+    debug_assert!(i < NUM_REGISTERS);
+    IS_HASH + 1 + i
+}
+
+/// One-hot encoding of the instruction's `rs1` field.
+pub(crate) const fn col_rs1_flag(i: usize) -> usize {
+    debug_assert!(i < NUM_REGISTERS);
+    col_rd_flag(NUM_REGISTERS - 1) + 1 + i
+}
+
+/// One-hot encoding of the instruction's `rs2` field.
+pub(crate) const fn col_rs2_flag(i: usize) -> usize {
+    debug_assert!(i < NUM_REGISTERS);
+    col_rs1_flag(NUM_REGISTERS - 1) + 1 + i
+}
+
+/// The instruction's immediate. Since this must be range checked, it is an alias for a range check
+/// input, not a column owned by the core registers.
+pub(crate) const COL_IMMEDIATE: usize = super::range_check_16::col_rc_16_input(6);
+
+/// The value of register `rs1`.
+pub(crate) const COL_OPERAND_0: usize = col_rs2_flag(NUM_REGISTERS - 1) + 1;
+/// The value of register `rs2`.
+pub(crate) const COL_OPERAND_1: usize = COL_OPERAND_0 + 1;
+/// The value written to register `rd`, for instructions which write a register.
+pub(crate) const COL_RESULT: usize = COL_OPERAND_1 + 1;
+
+/// 1 if this row writes to register `i`, and 0 otherwise.
+pub(crate) const fn col_register_write(i: usize) -> usize {
+    debug_assert!(i < NUM_REGISTERS);
+    COL_RESULT + 1 + i
+}
+
+/// The inverse of the condition of `JUMPI`, or zero if it is zero.
+pub(crate) const COL_JUMPI_CONDITION_INV: usize = col_register_write(NUM_REGISTERS - 1) + 1;
+/// 1 if this row executes a `JUMPI` whose condition is nonzero, and 0 otherwise.
+pub(crate) const COL_JUMPI_TAKEN: usize = COL_JUMPI_CONDITION_INV + 1;
+
+/// `RET` splits the frame record it pops into the return address and the caller's frame pointer,
+/// which are range checked to make the split unique. These are aliases for range check inputs.
+pub(crate) const COL_RET_INSTRUCTION_PTR: usize = super::range_check_16::col_rc_16_input(0);
+pub(crate) const COL_RET_FRAME_PTR: usize = super::range_check_16::col_rc_16_input(1);
+
+pub(super) const END: usize = COL_JUMPI_TAKEN + 1;
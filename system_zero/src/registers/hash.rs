@@ -0,0 +1,82 @@
+//! Hash unit.
+//!
+//! `HASH` runs a Poseidon sponge in overwrite mode over words of main memory, as `hash_words` does.
+//! Its execute row sets the sponge state to zero, except for the first capacity element, which holds
+//! the length of the input so that inputs differing only in trailing zeros have different digests.
+//! It is followed by an absorb row for each input word. An absorb row reads its word and writes it
+//! to the next position of the sponge's rate, and permutes the sponge state if the rate is full or
+//! the input is exhausted, using the permutation unit. These are followed by an output row for each
+//! of the eight words of the digest, which writes the low or high half of an element of the digest
+//! to memory.
+//!
+//! Loading rows use the same sponge to hash the program as it is written to the code segment, with
+//! the sponge state set up in the first row. The sponge state after the last loading row holds the
+//! program's digest.
+
+use plonky2::hash::hashing::SPONGE_WIDTH;
+
+use super::state::NUM_HASH_OUT_ELTS;
+
+/// The number of elements of the sponge state which input is absorbed into, as in plonky2's
+/// sponge.
+pub(crate) const SPONGE_RATE: usize = 8;
+
+/// The number of words of memory a digest is written to.
+pub(crate) const NUM_DIGEST_WORDS: usize = 2 * NUM_HASH_OUT_ELTS;
+
+/// The sponge state before this row. In the execute row of `HASH`, this is zero except for the
+/// length of the input in the first capacity element.
+pub(crate) const fn col_sponge_state(i: usize) -> usize {
+    debug_assert!(i < SPONGE_WIDTH);
+    super::START_HASH + i
+}
+
+/// The sponge state after this row. In absorb rows, this is the permutation unit's input, or its
+/// output if the sponge state is permuted; in other rows of `HASH`, it is the sponge state before
+/// this row.
+pub(crate) const fn col_sponge_next_state(i: usize) -> usize {
+    debug_assert!(i < SPONGE_WIDTH);
+    col_sponge_state(SPONGE_WIDTH - 1) + 1 + i
+}
+
+/// One-hot encoding of the position in the sponge's rate which an absorb row writes to.
+pub(crate) const fn col_absorb_position(i: usize) -> usize {
+    debug_assert!(i < SPONGE_RATE);
+    col_sponge_next_state(SPONGE_WIDTH - 1) + 1 + i
+}
+
+/// One-hot encoding of the word of the digest which an output row writes.
+pub(crate) const fn col_output_position(i: usize) -> usize {
+    debug_assert!(i < NUM_DIGEST_WORDS);
+    col_absorb_position(SPONGE_RATE - 1) + 1 + i
+}
+
+/// The address of the next input word.
+pub(crate) const COL_HASH_INPUT_ADDRESS: usize = col_output_position(NUM_DIGEST_WORDS - 1) + 1;
+/// The number of input words left to absorb, including this row's. In the execute row of `HASH`,
+/// this is the length of the input.
+pub(crate) const COL_HASH_WORDS_LEFT: usize = COL_HASH_INPUT_ADDRESS + 1;
+/// The inverse of the length of the input, or zero if it is zero. Only used in execute rows.
+pub(crate) const COL_HASH_LENGTH_INV: usize = COL_HASH_WORDS_LEFT + 1;
+/// 1 if the input is nonempty, and 0 otherwise. Only used in execute rows.
+pub(crate) const COL_HASH_IS_NONEMPTY: usize = COL_HASH_LENGTH_INV + 1;
+/// The inverse of `words_left - 1`, or zero if it is zero.
+pub(crate) const COL_HASH_WORDS_LEFT_MINUS_ONE_INV: usize = COL_HASH_IS_NONEMPTY + 1;
+/// 1 if this row absorbs the last input word, and 0 otherwise.
+pub(crate) const COL_HASH_IS_LAST_WORD: usize = COL_HASH_WORDS_LEFT_MINUS_ONE_INV + 1;
+/// 1 if this row permutes the sponge state, i.e. if it fills the rate or absorbs the last word, and
+/// 0 otherwise.
+pub(crate) const COL_HASH_PERMUTE: usize = COL_HASH_IS_LAST_WORD + 1;
+/// The address of the next word of the digest.
+pub(crate) const COL_HASH_OUTPUT_ADDRESS: usize = COL_HASH_PERMUTE + 1;
+/// The helper value of the check that the digest element being written is split canonically.
+pub(crate) const COL_HASH_CANONICAL_INV: usize = COL_HASH_OUTPUT_ADDRESS + 1;
+
+/// The digest element being written, as four u16 limbs in little-endian order. Since these must be
+/// range checked, they are aliases for range check inputs.
+pub(crate) const fn col_digest_limb(i: usize) -> usize {
+    debug_assert!(i < 4);
+    super::range_check_16::col_rc_16_input(i)
+}
+
+pub(super) const END: usize = COL_HASH_CANONICAL_INV + 1;
@@ -1,3 +1,68 @@
-//! Logic unit.
+//! Logic unit, which performs bitwise operations and shifts on u32 words.
+//!
+//! Both inputs are decomposed into bits using the boolean unit. Shifts are computed by multiplying
+//! the first input by `2^shift` (for SHL) or `2^(32 - shift)` (for SHR), and splitting the 64-bit
+//! product into two u32 halves, whose bits also live in the boolean unit.
 
-pub(super) const END: usize = super::START_LOGIC;
+use super::boolean::col_bit;
+
+pub(crate) const IS_AND: usize = super::START_LOGIC;
+pub(crate) const IS_OR: usize = IS_AND + 1;
+pub(crate) const IS_XOR: usize = IS_OR + 1;
+pub(crate) const IS_NOT: usize = IS_XOR + 1;
+pub(crate) const IS_SHL: usize = IS_NOT + 1;
+pub(crate) const IS_SHR: usize = IS_SHL + 1;
+
+/// The first input; treated as an unsigned u32. This is the value being shifted in shifts.
+pub(crate) const COL_LOGIC_INPUT_0: usize = IS_SHR + 1;
+/// The second input; treated as an unsigned u32. This is the shift amount in shifts, and is
+/// ignored by NOT.
+pub(crate) const COL_LOGIC_INPUT_1: usize = COL_LOGIC_INPUT_0 + 1;
+pub(crate) const COL_LOGIC_OUTPUT: usize = COL_LOGIC_INPUT_1 + 1;
+
+/// The `i`th bit of the first input, in little-endian order.
+pub(crate) const fn col_input_0_bit(i: usize) -> usize {
+    debug_assert!(i < 32);
+    col_bit(i)
+}
+
+/// The `i`th bit of the second input, in little-endian order.
+pub(crate) const fn col_input_1_bit(i: usize) -> usize {
+    debug_assert!(i < 32);
+    col_bit(32 + i)
+}
+
+/// The `i`th bit of the low half of the shift product, in little-endian order.
+pub(crate) const fn col_shift_product_lo_bit(i: usize) -> usize {
+    debug_assert!(i < 32);
+    col_bit(64 + i)
+}
+
+/// The `i`th bit of the high half of the shift product, in little-endian order.
+pub(crate) const fn col_shift_product_hi_bit(i: usize) -> usize {
+    debug_assert!(i < 32);
+    col_bit(96 + i)
+}
+
+/// Partial products used to compute `2^(shift % 32)` from the low five bits of the shift. The
+/// `i`th partial product covers bits `0..i + 2`, so the last one is `2^(shift % 32)`.
+pub(crate) const fn col_shift_pow_partial(i: usize) -> usize {
+    debug_assert!(i < 4);
+    COL_LOGIC_OUTPUT + 1 + i
+}
+
+/// `2^(shift % 32)`.
+pub(crate) const COL_SHIFT_POW: usize = col_shift_pow_partial(3);
+
+/// A flag which is 1 if the shift is less than 32, and 0 otherwise.
+pub(crate) const COL_SHIFT_IN_RANGE: usize = COL_SHIFT_POW + 1;
+/// The inverse of `shift - shift % 32`, or zero if that is zero.
+pub(crate) const COL_SHIFT_HIGH_INV: usize = COL_SHIFT_IN_RANGE + 1;
+/// The value the first input is multiplied by: `2^shift` for SHL, `2^(32 - shift)` for SHR, or zero
+/// if the shift is 32 or more.
+pub(crate) const COL_SHIFT_MULTIPLIER: usize = COL_SHIFT_HIGH_INV + 1;
+/// An advice value used to check that the two halves of the shift product represent a canonical
+/// field element. Must be zero in rows which don't perform a shift.
+pub(crate) const COL_SHIFT_PRODUCT_CANONICAL_INV: usize = COL_SHIFT_MULTIPLIER + 1;
+
+pub(super) const END: usize = COL_SHIFT_PRODUCT_CANONICAL_INV + 1;
@@ -4,13 +4,13 @@
 const START_UNIT: usize = super::START_LOOKUP;
 
 pub(crate) const NUM_LOOKUPS: usize =
-    super::range_check_16::NUM_RANGE_CHECKS + super::range_check_degree::NUM_RANGE_CHECKS;
+    super::range_check_16::NUM_BYTES + super::range_check_degree::NUM_RANGE_CHECKS;
 
 pub(crate) const fn col_input(i: usize) -> usize {
-    if i < super::range_check_16::NUM_RANGE_CHECKS {
-        super::range_check_16::col_rc_16_input(i)
+    if i < super::range_check_16::NUM_BYTES {
+        super::range_check_16::col_rc_16_byte(i / 2, i % 2)
     } else {
-        super::range_check_degree::col_rc_degree_input(i - super::range_check_16::NUM_RANGE_CHECKS)
+        super::range_check_degree::col_rc_degree_input(i - super::range_check_16::NUM_BYTES)
     }
 }
 
@@ -21,8 +21,8 @@ pub(crate) const fn col_permuted_input(i: usize) -> usize {
 }
 
 pub(crate) const fn col_table(i: usize) -> usize {
-    if i < super::range_check_16::NUM_RANGE_CHECKS {
-        super::core::COL_RANGE_16
+    if i < super::range_check_16::NUM_BYTES {
+        super::core::COL_RANGE_8
     } else {
         super::core::COL_CLOCK
     }
@@ -1,3 +1,50 @@
 //! Memory unit.
+//!
+//! Each row performs one memory operation, at the timestamp given by the clock. The operations are
+//! also listed in a sorted copy, ordered by context, segment, address and then timestamp, which is
+//! constrained to be a permutation of the original operations. In sorted order, consecutive
+//! operations on the same address are adjacent, so it is easy to check that each read returns the
+//! most recently written value.
 
-pub(super) const END: usize = super::START_MEMORY;
+/// The context (i.e. contract call) of this row's memory operation.
+pub(crate) const COL_MEMORY_OP_CONTEXT: usize = super::START_MEMORY;
+/// The segment of this row's memory operation; see `memory::Segment`.
+pub(crate) const COL_MEMORY_OP_SEGMENT: usize = COL_MEMORY_OP_CONTEXT + 1;
+/// The address of this row's memory operation, within its segment.
+pub(crate) const COL_MEMORY_OP_ADDRESS: usize = COL_MEMORY_OP_SEGMENT + 1;
+/// The value read or written by this row's memory operation.
+pub(crate) const COL_MEMORY_OP_VALUE: usize = COL_MEMORY_OP_ADDRESS + 1;
+/// 1 if this row's memory operation is a read, or 0 if it is a write.
+pub(crate) const COL_MEMORY_OP_IS_READ: usize = COL_MEMORY_OP_VALUE + 1;
+
+pub(crate) const COL_SORTED_MEMORY_CONTEXT: usize = COL_MEMORY_OP_IS_READ + 1;
+pub(crate) const COL_SORTED_MEMORY_SEGMENT: usize = COL_SORTED_MEMORY_CONTEXT + 1;
+pub(crate) const COL_SORTED_MEMORY_ADDRESS: usize = COL_SORTED_MEMORY_SEGMENT + 1;
+pub(crate) const COL_SORTED_MEMORY_VALUE: usize = COL_SORTED_MEMORY_ADDRESS + 1;
+pub(crate) const COL_SORTED_MEMORY_IS_READ: usize = COL_SORTED_MEMORY_VALUE + 1;
+pub(crate) const COL_SORTED_MEMORY_TIMESTAMP: usize = COL_SORTED_MEMORY_IS_READ + 1;
+
+/// 1 if the context changes between this sorted operation and the next one, and 0 otherwise.
+pub(crate) const COL_CONTEXT_FIRST_CHANGE: usize = COL_SORTED_MEMORY_TIMESTAMP + 1;
+/// 1 if the segment changes, but not the context, between this sorted operation and the next one.
+pub(crate) const COL_SEGMENT_FIRST_CHANGE: usize = COL_CONTEXT_FIRST_CHANGE + 1;
+/// 1 if the address changes, but not the context or segment, between this sorted operation and the
+/// next one.
+pub(crate) const COL_ADDRESS_FIRST_CHANGE: usize = COL_SEGMENT_FIRST_CHANGE + 1;
+
+/// The difference between this sorted operation and the next one in the first field that changes,
+/// minus one. It is range checked to ensure that the sorted operations are strictly increasing.
+pub(crate) const COL_MEMORY_RANGE_CHECK: usize = super::range_check_degree::col_rc_degree_input(0);
+
+/// Pairs of columns which must be permutations of one another, as a single permutation argument.
+/// The original operations use the clock as their timestamp.
+pub(crate) const MEMORY_PERMUTATION_COLUMN_PAIRS: [(usize, usize); 6] = [
+    (COL_MEMORY_OP_CONTEXT, COL_SORTED_MEMORY_CONTEXT),
+    (COL_MEMORY_OP_SEGMENT, COL_SORTED_MEMORY_SEGMENT),
+    (COL_MEMORY_OP_ADDRESS, COL_SORTED_MEMORY_ADDRESS),
+    (COL_MEMORY_OP_VALUE, COL_SORTED_MEMORY_VALUE),
+    (COL_MEMORY_OP_IS_READ, COL_SORTED_MEMORY_IS_READ),
+    (super::core::COL_CLOCK, COL_SORTED_MEMORY_TIMESTAMP),
+];
+
+pub(super) const END: usize = COL_ADDRESS_FIRST_CHANGE + 1;
@@ -1,6 +1,8 @@
-//! Range check unit which checks that values are in `[0, 2^16)`.
+//! Range check unit which checks that values are in `[0, 2^16)`. Each value is split into two
+//! bytes, which are looked up in an 8-bit table. This way the table has only `2^8` entries, so
+//! short executions don't need to pad their traces to `2^16` rows.
 
-pub(crate) const NUM_RANGE_CHECKS: usize = 5;
+pub(crate) const NUM_RANGE_CHECKS: usize = 7;
 
 /// The input of the `i`th range check, i.e. the value being range checked.
 pub(crate) const fn col_rc_16_input(i: usize) -> usize {
@@ -8,4 +10,14 @@ pub(crate) const fn col_rc_16_input(i: usize) -> usize {
     super::START_RANGE_CHECK_16 + i
 }
 
-pub(super) const END: usize = super::START_RANGE_CHECK_16 + NUM_RANGE_CHECKS;
+/// The number of bytes which are looked up in the 8-bit table.
+pub(crate) const NUM_BYTES: usize = 2 * NUM_RANGE_CHECKS;
+
+/// The `j`th byte of the input of the `i`th range check, in little-endian order.
+pub(crate) const fn col_rc_16_byte(i: usize, j: usize) -> usize {
+    debug_assert!(i < NUM_RANGE_CHECKS);
+    debug_assert!(j < 2);
+    super::START_RANGE_CHECK_16 + NUM_RANGE_CHECKS + 2 * i + j
+}
+
+pub(super) const END: usize = super::START_RANGE_CHECK_16 + NUM_RANGE_CHECKS + NUM_BYTES;
@@ -0,0 +1,56 @@
+//! State unit, which authenticates accesses to the state against a Poseidon Merkle tree.
+//!
+//! A state access is executed in one row, which sets up the leaf hashes of the old and new values
+//! of the accessed slot. It is followed by a pair of rows for each level of the tree: one hashing
+//! the old Merkle path, and one hashing the new Merkle path with the same sibling. Both use the
+//! permutation unit. After the last pair, the old path's root is checked against the current state
+//! root, which is then replaced by the new path's root.
+
+/// The number of field elements in a `HashOut`.
+pub(crate) const NUM_HASH_OUT_ELTS: usize = 4;
+
+/// The current state root. Starts as the old state root, and ends as the new state root.
+pub(crate) const fn col_state_root(i: usize) -> usize {
+    debug_assert!(i < NUM_HASH_OUT_ELTS);
+    super::START_STATE + i
+}
+
+/// The hash of the current node on the old Merkle path. In the execute row of a state access, this
+/// is the hash of the old leaf, whose first element is the old value and whose others are zero.
+pub(crate) const fn col_old_path_digest(i: usize) -> usize {
+    debug_assert!(i < NUM_HASH_OUT_ELTS);
+    col_state_root(NUM_HASH_OUT_ELTS - 1) + 1 + i
+}
+
+/// The hash of the current node on the new Merkle path. In the execute row of a state access, this
+/// is the hash of the new leaf, whose first element is the new value and whose others are zero.
+pub(crate) const fn col_new_path_digest(i: usize) -> usize {
+    debug_assert!(i < NUM_HASH_OUT_ELTS);
+    col_old_path_digest(NUM_HASH_OUT_ELTS - 1) + 1 + i
+}
+
+pub(crate) const COL_STATE_OLD_VALUE: usize = col_old_path_digest(0);
+pub(crate) const COL_STATE_NEW_VALUE: usize = col_new_path_digest(0);
+
+/// The hash of the current node's sibling.
+pub(crate) const fn col_path_sibling(i: usize) -> usize {
+    debug_assert!(i < NUM_HASH_OUT_ELTS);
+    col_new_path_digest(NUM_HASH_OUT_ELTS - 1) + 1 + i
+}
+
+/// The index of the current node within its level. In the execute row of a state access, this is
+/// the accessed slot.
+pub(crate) const COL_PATH_INDEX: usize = col_path_sibling(NUM_HASH_OUT_ELTS - 1) + 1;
+/// The lowest bit of the index, which is 1 if the current node is a right child.
+pub(crate) const COL_PATH_INDEX_BIT: usize = COL_PATH_INDEX + 1;
+/// The number of levels left to hash, including the current one.
+pub(crate) const COL_PATH_LEVELS_LEFT: usize = COL_PATH_INDEX_BIT + 1;
+/// The inverse of `levels_left - 1`, or zero if it is zero.
+pub(crate) const COL_PATH_LEVELS_LEFT_MINUS_ONE_INV: usize = COL_PATH_LEVELS_LEFT + 1;
+/// 1 if this is the last level, and 0 otherwise.
+pub(crate) const COL_PATH_IS_LAST: usize = COL_PATH_LEVELS_LEFT_MINUS_ONE_INV + 1;
+/// 1 if this row hashes the last level of the new Merkle path, which replaces the state root, and 0
+/// otherwise.
+pub(crate) const COL_STATE_ROOT_UPDATE: usize = COL_PATH_IS_LAST + 1;
+
+pub(super) const END: usize = COL_STATE_ROOT_UPDATE + 1;
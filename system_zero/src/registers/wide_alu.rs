@@ -0,0 +1,202 @@
+//! 256-bit ALU.
+//!
+//! The 256-bit instructions operate on 256-bit words in main memory, each stored as eight u32 words
+//! in little-endian order. Every operation is reduced to the relation
+//!
+//! ```text
+//! x * y + z = q * n + r + 2^256 h
+//! ```
+//!
+//! between 256-bit values `x`, `y`, `z`, `n`, `r` and `h`, and a 512-bit `q`, where each value is
+//! either an operand, a constant, or a witness; see `wide_alu` for how each operation is mapped onto
+//! it. Signed operations add terms which reinterpret some of the values as two's complement.
+//!
+//! The execute row of a 256-bit instruction is followed by a read row for each word of its
+//! operands, which also sets up the values of the relation. Next come `NUM_COMPUTE_ROWS` compute
+//! rows, the `k`th of which checks the `k`th u16 limb of the relation, carrying into the next one.
+//! The compute rows also range check the witnesses, and check that the remainder of a division is
+//! less than the divisor. Last come a write row for each word of the result.
+//!
+//! The flags of the unit are its own columns, since other units use the boolean unit's bits in
+//! execute rows. The exception is the high bits of carries, which only matter in compute rows. The
+//! limbs which must be range checked are aliases for range check inputs.
+
+use super::boolean::col_bit;
+use super::range_check_16::col_rc_16_input;
+
+/// The number of u32 words in a 256-bit word.
+pub(crate) const NUM_WIDE_WORDS: usize = 8;
+/// The number of u16 limbs in a 256-bit word.
+pub(crate) const NUM_WIDE_LIMBS: usize = 2 * NUM_WIDE_WORDS;
+/// The most operands a 256-bit instruction reads.
+pub(crate) const MAX_WIDE_OPERANDS: usize = 3;
+/// The number of 256-bit operations.
+pub(crate) const NUM_WIDE_OPS: usize = 15;
+/// The number of read positions, one per word of each operand.
+pub(crate) const NUM_READ_POSITIONS: usize = MAX_WIDE_OPERANDS * NUM_WIDE_WORDS;
+/// The number of compute rows, one per u16 limb of the 768-bit products `x * y` and `q * n`.
+pub(crate) const NUM_COMPUTE_ROWS: usize = 3 * NUM_WIDE_LIMBS;
+/// The number of u16 limbs of `q`.
+pub(crate) const NUM_QUOTIENT_LIMBS: usize = 2 * NUM_WIDE_LIMBS;
+
+// The values of the relation, other than `q`; see `col_relation_limb`.
+pub(crate) const REL_X: usize = 0;
+pub(crate) const REL_Y: usize = 1;
+pub(crate) const REL_Z: usize = 2;
+pub(crate) const REL_N: usize = 3;
+pub(crate) const REL_R: usize = 4;
+pub(crate) const REL_H: usize = 5;
+pub(crate) const NUM_RELATION_VALUES: usize = 6;
+
+// Flags for the 256-bit opcodes, which are only set in execute rows; see `Opcode::flag`.
+pub(crate) const IS_ADD_256: usize = super::START_WIDE_ALU;
+pub(crate) const IS_SUB_256: usize = IS_ADD_256 + 1;
+pub(crate) const IS_MUL_256: usize = IS_SUB_256 + 1;
+pub(crate) const IS_DIV_256: usize = IS_MUL_256 + 1;
+pub(crate) const IS_MOD_256: usize = IS_DIV_256 + 1;
+pub(crate) const IS_ADD_MOD_256: usize = IS_MOD_256 + 1;
+pub(crate) const IS_MUL_MOD_256: usize = IS_ADD_MOD_256 + 1;
+pub(crate) const IS_SDIV_256: usize = IS_MUL_MOD_256 + 1;
+pub(crate) const IS_SMOD_256: usize = IS_SDIV_256 + 1;
+pub(crate) const IS_LT_256: usize = IS_SMOD_256 + 1;
+pub(crate) const IS_GT_256: usize = IS_LT_256 + 1;
+pub(crate) const IS_SLT_256: usize = IS_GT_256 + 1;
+pub(crate) const IS_SGT_256: usize = IS_SLT_256 + 1;
+pub(crate) const IS_EQ_256: usize = IS_SGT_256 + 1;
+pub(crate) const IS_ISZERO_256: usize = IS_EQ_256 + 1;
+
+/// The `i`th u16 limb of a value of the relation, in little-endian order. Set in the first read row
+/// of an operation, and unchanged until its last write row.
+pub(crate) const fn col_relation_limb(value: usize, i: usize) -> usize {
+    debug_assert!(value < NUM_RELATION_VALUES);
+    debug_assert!(i < NUM_WIDE_LIMBS);
+    IS_ISZERO_256 + 1 + value * NUM_WIDE_LIMBS + i
+}
+
+/// The `i`th u16 limb of the result, in little-endian order. Set like the values of the relation.
+pub(crate) const fn col_result_limb(i: usize) -> usize {
+    debug_assert!(i < NUM_WIDE_LIMBS);
+    col_relation_limb(NUM_RELATION_VALUES - 1, NUM_WIDE_LIMBS - 1) + 1 + i
+}
+
+/// In the compute row for limb `k`, the `i`th element of this window is limb `k - i` of `y`, or zero
+/// if there is no such limb.
+pub(crate) const fn col_y_window(i: usize) -> usize {
+    debug_assert!(i < NUM_WIDE_LIMBS);
+    col_result_limb(NUM_WIDE_LIMBS - 1) + 1 + i
+}
+
+/// In the compute row for limb `k`, the `i`th element of this window is limb `k - i` of `q`, or zero
+/// if there is no such limb. Each limb of `q` enters the window in its own compute row, where it is
+/// range checked, so the first element is an alias for a range check input.
+pub(crate) const fn col_q_window(i: usize) -> usize {
+    debug_assert!(i < NUM_WIDE_LIMBS);
+    if i == 0 {
+        col_rc_16_input(4)
+    } else {
+        col_y_window(NUM_WIDE_LIMBS - 1) + i
+    }
+}
+
+/// The address of the first operand, read from `main[rs1 + imm..]`.
+pub(crate) const COL_WIDE_ADDRESS_0: usize = col_q_window(NUM_WIDE_LIMBS - 1) + 1;
+/// The address of the second operand, read from `main[rs2..]`. The third operand, if any, follows
+/// it.
+pub(crate) const COL_WIDE_ADDRESS_1: usize = COL_WIDE_ADDRESS_0 + 1;
+/// The address the result is written to, `main[rd..]`.
+pub(crate) const COL_WIDE_OUTPUT_ADDRESS: usize = COL_WIDE_ADDRESS_1 + 1;
+/// The address of this row's memory operation in read and write rows, and zero in other rows.
+pub(crate) const COL_WIDE_MEMORY_ADDRESS: usize = COL_WIDE_OUTPUT_ADDRESS + 1;
+/// The carry into this compute row's limb of the relation.
+pub(crate) const COL_WIDE_CARRY_IN: usize = COL_WIDE_MEMORY_ADDRESS + 1;
+/// The carry into this compute row's limb of the bound check, which checks that the remainder of a
+/// division is less than the divisor. Carries of the bound check may be negative.
+pub(crate) const COL_WIDE_BOUND_CARRY_IN: usize = COL_WIDE_CARRY_IN + 1;
+/// The sum of the limbs of the divisor: `y` for divisions and `n` for modular operations. Zero for
+/// other operations.
+pub(crate) const COL_WIDE_DIVISOR_SUM: usize = COL_WIDE_BOUND_CARRY_IN + 1;
+/// The inverse of the divisor sum, or zero if it is zero.
+pub(crate) const COL_WIDE_DIVISOR_SUM_INV: usize = COL_WIDE_DIVISOR_SUM + 1;
+/// The inverse of the sum of the limbs of `x`, or zero if it is zero.
+pub(crate) const COL_WIDE_X_SUM_INV: usize = COL_WIDE_DIVISOR_SUM_INV + 1;
+
+/// 1 if this row belongs to the `i`th operation of `WIDE_OPS` and is a read, compute or write row,
+/// and 0 otherwise.
+pub(crate) const fn col_wide_op(i: usize) -> usize {
+    debug_assert!(i < NUM_WIDE_OPS);
+    COL_WIDE_X_SUM_INV + 1 + i
+}
+
+/// One-hot encoding of the word a read row reads: the `i`th word of operand `r / 8`, where `i` is
+/// `r % 8`.
+pub(crate) const fn col_read_position(r: usize) -> usize {
+    debug_assert!(r < NUM_READ_POSITIONS);
+    col_wide_op(NUM_WIDE_OPS - 1) + 1 + r
+}
+
+/// One-hot encoding of the limb a compute row checks.
+pub(crate) const fn col_compute_position(k: usize) -> usize {
+    debug_assert!(k < NUM_COMPUTE_ROWS);
+    col_read_position(NUM_READ_POSITIONS - 1) + 1 + k
+}
+
+/// One-hot encoding of the word of the result a write row writes.
+pub(crate) const fn col_write_position(j: usize) -> usize {
+    debug_assert!(j < NUM_WIDE_WORDS);
+    col_compute_position(NUM_COMPUTE_ROWS - 1) + 1 + j
+}
+
+/// 1 if this row reads the last word of the operands, and 0 otherwise.
+pub(crate) const COL_WIDE_LAST_READ: usize = col_write_position(NUM_WIDE_WORDS - 1) + 1;
+/// 1 if the divisor is zero, and 0 otherwise. Always 1 for operations without a divisor.
+pub(crate) const COL_WIDE_DIVISOR_IS_ZERO: usize = COL_WIDE_LAST_READ + 1;
+/// 1 if `x` is zero, and 0 otherwise.
+pub(crate) const COL_WIDE_X_IS_ZERO: usize = COL_WIDE_DIVISOR_IS_ZERO + 1;
+/// 1 if the quotient of a signed division is negative, and 0 otherwise.
+pub(crate) const COL_WIDE_QUOTIENT_IS_NEG: usize = COL_WIDE_X_IS_ZERO + 1;
+// The top bits of `y`, `z` and `r`.
+pub(crate) const COL_WIDE_Y_SIGN: usize = COL_WIDE_QUOTIENT_IS_NEG + 1;
+pub(crate) const COL_WIDE_Z_SIGN: usize = COL_WIDE_Y_SIGN + 1;
+pub(crate) const COL_WIDE_R_SIGN: usize = COL_WIDE_Z_SIGN + 1;
+// 1 if the operation is signed and `y`, `z` or `r` is negative, and 0 otherwise.
+pub(crate) const COL_WIDE_Y_IS_NEG: usize = COL_WIDE_R_SIGN + 1;
+pub(crate) const COL_WIDE_Z_IS_NEG: usize = COL_WIDE_Y_IS_NEG + 1;
+pub(crate) const COL_WIDE_R_IS_NEG: usize = COL_WIDE_Z_IS_NEG + 1;
+
+pub(super) const END: usize = COL_WIDE_R_IS_NEG + 1;
+
+/// The number of bits in the high part of a carry; see `COL_WIDE_CARRY_OUT_LO`.
+pub(crate) const NUM_CARRY_HI_BITS: usize = 7;
+
+/// The bits of the high part of the carry out of this compute row's limb of the relation.
+pub(crate) const fn col_carry_out_hi_bit(i: usize) -> usize {
+    debug_assert!(i < NUM_CARRY_HI_BITS);
+    col_bit(i)
+}
+
+/// The low u16 of the carry out of this compute row's limb of the relation. The carry is
+/// `lo + 2^16 hi - 2^22`, where the bits of `hi` are given by `col_carry_out_hi_bit`.
+pub(crate) const COL_WIDE_CARRY_OUT_LO: usize = col_rc_16_input(5);
+
+/// The low and high u16s of the word a read row reads. These are range checked.
+pub(crate) const COL_WIDE_WORD_LO: usize = col_rc_16_input(0);
+pub(crate) const COL_WIDE_WORD_HI: usize = col_rc_16_input(1);
+
+/// In the compute rows for the first `NUM_WIDE_LIMBS` limbs, the current limbs of `x`, `z`, `r` and
+/// `h`, which are range checked.
+pub(crate) const fn col_witness_limb(i: usize) -> usize {
+    debug_assert!(i < 4);
+    col_rc_16_input(i)
+}
+
+/// In the compute rows for limbs `NUM_WIDE_LIMBS..2 NUM_WIDE_LIMBS`, a limb of the difference checked
+/// by the bound check, and the carry out of it plus `2^15`. These are range checked.
+pub(crate) const COL_WIDE_BOUND_LIMB: usize = col_rc_16_input(0);
+pub(crate) const COL_WIDE_BOUND_CARRY_OUT: usize = col_rc_16_input(1);
+
+/// In the compute row after the bound check, `2 (t - 2^15 s)` for the top limb `t` and sign bit `s`
+/// of `y`, `z` and `r`, which are range checked to show that each `s` is the top bit of its value.
+pub(crate) const fn col_sign_check(i: usize) -> usize {
+    debug_assert!(i < 3);
+    col_rc_16_input(i)
+}
@@ -0,0 +1,90 @@
+use plonky2::hash::hash_types::{HashOut, RichField};
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::plonk::config::Hasher;
+
+/// The height of the state tree.
+pub const STATE_TREE_HEIGHT: usize = 8;
+
+/// The number of state slots, each of which is a leaf of the state tree.
+pub const NUM_STATE_SLOTS: usize = 1 << STATE_TREE_HEIGHT;
+
+/// The persistent state, which is accessed by `SLOAD` and `SSTORE`. It is committed to by the root
+/// of a Poseidon Merkle tree, whose leaves hash the value of each slot.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct State {
+    slots: Vec<u32>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            slots: vec![0; NUM_STATE_SLOTS],
+        }
+    }
+}
+
+impl State {
+    pub fn read(&self, slot: usize) -> u32 {
+        assert!(slot < NUM_STATE_SLOTS, "Invalid state slot {}.", slot);
+        self.slots[slot]
+    }
+
+    pub fn write(&mut self, slot: usize, value: u32) {
+        assert!(slot < NUM_STATE_SLOTS, "Invalid state slot {}.", slot);
+        self.slots[slot] = value;
+    }
+
+    /// The root of the state tree. This agrees with the cap of a plonky2 `MerkleTree` with cap
+    /// height 0 and a single-element leaf per slot.
+    pub fn root<F: RichField>(&self) -> HashOut<F> {
+        self.node(STATE_TREE_HEIGHT, 0)
+    }
+
+    /// The hash of the node at position `index` within layer `layer` of the tree, where the leaves
+    /// are layer 0.
+    pub(crate) fn node<F: RichField>(&self, layer: usize, index: usize) -> HashOut<F> {
+        if layer == 0 {
+            let value = F::from_canonical_u32(self.slots[index]);
+            HashOut {
+                elements: [value, F::ZERO, F::ZERO, F::ZERO],
+            }
+        } else {
+            let left = self.node(layer - 1, 2 * index);
+            let right = self.node(layer - 1, 2 * index + 1);
+            PoseidonHash::two_to_one(left, right)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::field_types::Field;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::hash::hash_types::HashOut;
+    use plonky2::hash::merkle_tree::MerkleTree;
+    use plonky2::hash::poseidon::PoseidonHash;
+
+    use crate::state::{State, NUM_STATE_SLOTS, STATE_TREE_HEIGHT};
+
+    type F = GoldilocksField;
+
+    #[test]
+    fn state_root_matches_merkle_tree() {
+        let mut state = State::default();
+        state.write(3, 7);
+        state.write(NUM_STATE_SLOTS - 1, u32::MAX);
+
+        let leaves = (0..NUM_STATE_SLOTS)
+            .map(|slot| vec![F::from_canonical_u32(state.read(slot))])
+            .collect();
+        let tree = MerkleTree::<F, PoseidonHash>::new(leaves, 0);
+        let root: HashOut<F> = state.root();
+        assert_eq!(tree.cap.0, vec![root]);
+
+        let proof = tree.prove(3);
+        for (layer, &sibling) in proof.siblings.iter().enumerate() {
+            assert_eq!(state.node::<F>(layer, (3 >> layer) ^ 1), sibling);
+        }
+        assert_eq!(proof.siblings.len(), STATE_TREE_HEIGHT);
+    }
+}
@@ -0,0 +1,417 @@
+use plonky2::field::extension_field::Extendable;
+use plonky2::field::field_types::{Field, PrimeField64};
+use plonky2::field::packed_field::PackedField;
+use plonky2::hash::hash_types::{HashOut, RichField};
+use plonky2::hash::hashing::SPONGE_WIDTH;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::vars::StarkEvaluationTargets;
+use starky::vars::StarkEvaluationVars;
+
+use crate::public_input_layout::{pi_new_state_root, pi_old_state_root, NUM_PUBLIC_INPUTS};
+use crate::registers::core::{IS_NEW_PATH, IS_OLD_PATH, IS_SLOAD, IS_SSTORE};
+use crate::registers::permutation::{col_input, col_output};
+use crate::registers::state::*;
+use crate::registers::NUM_COLUMNS;
+use crate::state::{State, STATE_TREE_HEIGHT};
+
+/// The columns which are carried from the execute row of a state access to its first old path row,
+/// besides the path digests.
+const ACCESS_CARRIED_COLS: [usize; 2] = [COL_PATH_INDEX, COL_PATH_LEVELS_LEFT];
+
+pub(crate) fn generate_first_row_state_unit<F: RichField>(
+    first_values: &mut [F; NUM_COLUMNS],
+    state: &State,
+) {
+    let root: HashOut<F> = state.root();
+    for i in 0..NUM_HASH_OUT_ELTS {
+        first_values[col_state_root(i)] = root.elements[i];
+    }
+}
+
+/// Sets up the execute row of an access to `slot`, which writes `new_value` if it is given, and
+/// returns the old value.
+pub(crate) fn generate_state_access<F: PrimeField64>(
+    values: &mut [F; NUM_COLUMNS],
+    state: &mut State,
+    slot: F,
+    new_value: Option<F>,
+) -> F {
+    let slot_usize = slot.to_canonical_u64() as usize;
+    let old_value = F::from_canonical_u32(state.read(slot_usize));
+    let new_value = new_value.unwrap_or(old_value);
+    state.write(slot_usize, new_value.to_canonical_u64() as u32);
+
+    for i in 0..NUM_HASH_OUT_ELTS {
+        values[col_old_path_digest(i)] = F::ZERO;
+        values[col_new_path_digest(i)] = F::ZERO;
+    }
+    values[COL_STATE_OLD_VALUE] = old_value;
+    values[COL_STATE_NEW_VALUE] = new_value;
+    values[COL_PATH_INDEX] = slot;
+    values[COL_PATH_LEVELS_LEFT] = F::from_canonical_usize(STATE_TREE_HEIGHT);
+    old_value
+}
+
+/// Generates a row which hashes a level of the old or new Merkle path, given the path columns
+/// carried over from the previous row. `state` may already hold the new value, since the path's
+/// siblings don't depend on it.
+pub(crate) fn generate_state_path_row<F: RichField>(values: &mut [F; NUM_COLUMNS], state: &State) {
+    let index = values[COL_PATH_INDEX].to_canonical_u64() as usize;
+    let levels_left = values[COL_PATH_LEVELS_LEFT].to_canonical_u64() as usize;
+
+    let col_digest: fn(usize) -> usize = if values[IS_OLD_PATH].is_one() {
+        let sibling: HashOut<F> = state.node(STATE_TREE_HEIGHT - levels_left, index ^ 1);
+        for i in 0..NUM_HASH_OUT_ELTS {
+            values[col_path_sibling(i)] = sibling.elements[i];
+        }
+        values[COL_PATH_INDEX_BIT] = F::from_bool(index & 1 == 1);
+        col_old_path_digest
+    } else {
+        let levels_left_minus_one = values[COL_PATH_LEVELS_LEFT] - F::ONE;
+        let is_last = levels_left_minus_one.is_zero();
+        values[COL_PATH_LEVELS_LEFT_MINUS_ONE_INV] =
+            levels_left_minus_one.try_inverse().unwrap_or(F::ZERO);
+        values[COL_PATH_IS_LAST] = F::from_bool(is_last);
+        values[COL_STATE_ROOT_UPDATE] = F::from_bool(is_last);
+        col_new_path_digest
+    };
+
+    let is_right = values[COL_PATH_INDEX_BIT].is_one();
+    for i in 0..NUM_HASH_OUT_ELTS {
+        let (left, right) = if is_right {
+            (values[col_path_sibling(i)], values[col_digest(i)])
+        } else {
+            (values[col_digest(i)], values[col_path_sibling(i)])
+        };
+        values[col_input(i)] = left;
+        values[col_input(NUM_HASH_OUT_ELTS + i)] = right;
+    }
+    for i in 2 * NUM_HASH_OUT_ELTS..SPONGE_WIDTH {
+        values[col_input(i)] = F::ZERO;
+    }
+}
+
+/// Generates the state columns of the next row, given that the permutation unit has been generated
+/// for the current row.
+pub(crate) fn generate_next_row_state_unit<F: PrimeField64>(
+    local_values: &[F; NUM_COLUMNS],
+    next_values: &mut [F; NUM_COLUMNS],
+) {
+    // The following mirrors the transition constraints in `eval_state_unit`.
+    let root_update = local_values[COL_STATE_ROOT_UPDATE];
+    for i in 0..NUM_HASH_OUT_ELTS {
+        let root = local_values[col_state_root(i)];
+        next_values[col_state_root(i)] = root + root_update * (local_values[col_output(i)] - root);
+    }
+
+    let carry = |next_values: &mut [F; NUM_COLUMNS], col: usize| {
+        next_values[col] = local_values[col];
+    };
+    if (local_values[IS_SLOAD] + local_values[IS_SSTORE]).is_one() {
+        for i in 0..NUM_HASH_OUT_ELTS {
+            carry(next_values, col_old_path_digest(i));
+            carry(next_values, col_new_path_digest(i));
+        }
+        for col in ACCESS_CARRIED_COLS {
+            carry(next_values, col);
+        }
+    }
+    if local_values[IS_OLD_PATH].is_one() {
+        for i in 0..NUM_HASH_OUT_ELTS {
+            next_values[col_old_path_digest(i)] = local_values[col_output(i)];
+            carry(next_values, col_new_path_digest(i));
+            carry(next_values, col_path_sibling(i));
+        }
+        for col in ACCESS_CARRIED_COLS {
+            carry(next_values, col);
+        }
+        carry(next_values, COL_PATH_INDEX_BIT);
+    }
+    if local_values[IS_NEW_PATH].is_one() {
+        for i in 0..NUM_HASH_OUT_ELTS {
+            carry(next_values, col_old_path_digest(i));
+            next_values[col_new_path_digest(i)] = local_values[col_output(i)];
+        }
+        let index = local_values[COL_PATH_INDEX] - local_values[COL_PATH_INDEX_BIT];
+        next_values[COL_PATH_INDEX] = index / F::TWO;
+        next_values[COL_PATH_LEVELS_LEFT] = local_values[COL_PATH_LEVELS_LEFT] - F::ONE;
+    }
+}
+
+pub(crate) fn eval_state_unit<F: Field, P: PackedField<Scalar = F>>(
+    vars: StarkEvaluationVars<F, P, NUM_COLUMNS, NUM_PUBLIC_INPUTS>,
+    yield_constr: &mut ConstraintConsumer<P>,
+) {
+    let local_values = &vars.local_values;
+    let next_values = &vars.next_values;
+
+    let is_access = local_values[IS_SLOAD] + local_values[IS_SSTORE];
+    let is_old_path = local_values[IS_OLD_PATH];
+    let is_new_path = local_values[IS_NEW_PATH];
+    let index = local_values[COL_PATH_INDEX];
+    let index_bit = local_values[COL_PATH_INDEX_BIT];
+    let levels_left = local_values[COL_PATH_LEVELS_LEFT];
+    let is_last = local_values[COL_PATH_IS_LAST];
+    let root_update = local_values[COL_STATE_ROOT_UPDATE];
+
+    // The state root starts and ends as the old and new state roots. It is only changed after the
+    // last level of a new path.
+    for i in 0..NUM_HASH_OUT_ELTS {
+        let root = local_values[col_state_root(i)];
+        let output = local_values[col_output(i)];
+        yield_constr.constraint_first_row(root - vars.public_inputs[pi_old_state_root(i)]);
+        yield_constr.constraint_last_row(root - vars.public_inputs[pi_new_state_root(i)]);
+        yield_constr.constraint_transition(
+            next_values[col_state_root(i)] - root - root_update * (output - root),
+        );
+    }
+
+    // A state access sets up the leaf hashes, and the index and number of levels of its paths.
+    for i in 1..NUM_HASH_OUT_ELTS {
+        yield_constr.constraint(is_access * local_values[col_old_path_digest(i)]);
+        yield_constr.constraint(is_access * local_values[col_new_path_digest(i)]);
+    }
+    yield_constr.constraint(is_access * (levels_left - F::from_canonical_usize(STATE_TREE_HEIGHT)));
+    for i in 0..NUM_HASH_OUT_ELTS {
+        for col in [col_old_path_digest(i), col_new_path_digest(i)] {
+            yield_constr.constraint_transition(is_access * (next_values[col] - local_values[col]));
+        }
+    }
+    for col in ACCESS_CARRIED_COLS {
+        yield_constr.constraint_transition(is_access * (next_values[col] - local_values[col]));
+    }
+
+    // Path rows hash the current node with its sibling, in an order given by the index's lowest
+    // bit.
+    yield_constr.constraint(is_old_path * (index_bit * index_bit - index_bit));
+    for (is_path, col_digest) in [
+        (is_old_path, col_old_path_digest as fn(usize) -> usize),
+        (is_new_path, col_new_path_digest),
+    ] {
+        for i in 0..NUM_HASH_OUT_ELTS {
+            let digest = local_values[col_digest(i)];
+            let sibling = local_values[col_path_sibling(i)];
+            let left = local_values[col_input(i)];
+            let right = local_values[col_input(NUM_HASH_OUT_ELTS + i)];
+            yield_constr.constraint(is_path * (left - digest - index_bit * (sibling - digest)));
+            yield_constr.constraint(is_path * (right - sibling - index_bit * (digest - sibling)));
+        }
+        for i in 2 * NUM_HASH_OUT_ELTS..SPONGE_WIDTH {
+            yield_constr.constraint(is_path * local_values[col_input(i)]);
+        }
+    }
+
+    // An old path row passes its output, sibling and index on to the following new path row.
+    for i in 0..NUM_HASH_OUT_ELTS {
+        let output = local_values[col_output(i)];
+        let next_old_digest = next_values[col_old_path_digest(i)];
+        yield_constr.constraint_transition(is_old_path * (next_old_digest - output));
+        for col in [col_new_path_digest(i), col_path_sibling(i)] {
+            yield_constr
+                .constraint_transition(is_old_path * (next_values[col] - local_values[col]));
+        }
+    }
+    for col in [COL_PATH_INDEX, COL_PATH_INDEX_BIT, COL_PATH_LEVELS_LEFT] {
+        yield_constr.constraint_transition(is_old_path * (next_values[col] - local_values[col]));
+    }
+
+    // A new path row passes its output on, and moves up a level.
+    for i in 0..NUM_HASH_OUT_ELTS {
+        let output = local_values[col_output(i)];
+        let old_digest = local_values[col_old_path_digest(i)];
+        let next_new_digest = next_values[col_new_path_digest(i)];
+        let next_old_digest = next_values[col_old_path_digest(i)];
+        yield_constr.constraint_transition(is_new_path * (next_new_digest - output));
+        yield_constr.constraint_transition(is_new_path * (next_old_digest - old_digest));
+    }
+    let next_index = next_values[COL_PATH_INDEX];
+    yield_constr.constraint_transition(is_new_path * (next_index * F::TWO + index_bit - index));
+    let next_levels_left = next_values[COL_PATH_LEVELS_LEFT];
+    yield_constr.constraint_transition(is_new_path * (next_levels_left - levels_left + F::ONE));
+
+    // The last level is the one with one level left. There, the old path must lead to the current
+    // state root, which is replaced by the new path's root. The index must have no more bits, so
+    // that the slot is in range.
+    let levels_left_minus_one = levels_left - F::ONE;
+    let levels_left_minus_one_inv = local_values[COL_PATH_LEVELS_LEFT_MINUS_ONE_INV];
+    yield_constr.constraint(
+        is_new_path * (levels_left_minus_one * levels_left_minus_one_inv + is_last - F::ONE),
+    );
+    yield_constr.constraint(is_new_path * is_last * levels_left_minus_one);
+    yield_constr.constraint(root_update - is_new_path * is_last);
+    yield_constr.constraint(root_update * (index - index_bit));
+    for i in 0..NUM_HASH_OUT_ELTS {
+        let old_digest = local_values[col_old_path_digest(i)];
+        yield_constr.constraint(root_update * (old_digest - local_values[col_state_root(i)]));
+    }
+}
+
+pub(crate) fn eval_state_unit_recursively<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    vars: StarkEvaluationTargets<D, NUM_COLUMNS, NUM_PUBLIC_INPUTS>,
+    yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    let local_values = &vars.local_values;
+    let next_values = &vars.next_values;
+    let one = builder.one_extension();
+
+    let is_access = builder.add_extension(local_values[IS_SLOAD], local_values[IS_SSTORE]);
+    let is_old_path = local_values[IS_OLD_PATH];
+    let is_new_path = local_values[IS_NEW_PATH];
+    let index = local_values[COL_PATH_INDEX];
+    let index_bit = local_values[COL_PATH_INDEX_BIT];
+    let levels_left = local_values[COL_PATH_LEVELS_LEFT];
+    let is_last = local_values[COL_PATH_IS_LAST];
+    let root_update = local_values[COL_STATE_ROOT_UPDATE];
+
+    // Constrains `filter * (next_values[col] - x)` over transitions.
+    let transition_filtered_eq = |builder: &mut CircuitBuilder<F, D>,
+                                  yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+                                  filter: ExtensionTarget<D>,
+                                  col: usize,
+                                  x: ExtensionTarget<D>| {
+        let diff = builder.sub_extension(next_values[col], x);
+        let constraint = builder.mul_extension(filter, diff);
+        yield_constr.constraint_transition(builder, constraint);
+    };
+
+    // The state root starts and ends as the old and new state roots. It is only changed after the
+    // last level of a new path.
+    for i in 0..NUM_HASH_OUT_ELTS {
+        let root = local_values[col_state_root(i)];
+        let output = local_values[col_output(i)];
+        let constraint = builder.sub_extension(root, vars.public_inputs[pi_old_state_root(i)]);
+        yield_constr.constraint_first_row(builder, constraint);
+        let constraint = builder.sub_extension(root, vars.public_inputs[pi_new_state_root(i)]);
+        yield_constr.constraint_last_row(builder, constraint);
+        let diff = builder.sub_extension(output, root);
+        let expected = builder.mul_add_extension(root_update, diff, root);
+        let constraint = builder.sub_extension(next_values[col_state_root(i)], expected);
+        yield_constr.constraint_transition(builder, constraint);
+    }
+
+    // A state access sets up the leaf hashes, and the index and number of levels of its paths.
+    for i in 1..NUM_HASH_OUT_ELTS {
+        for col in [col_old_path_digest(i), col_new_path_digest(i)] {
+            let constraint = builder.mul_extension(is_access, local_values[col]);
+            yield_constr.constraint(builder, constraint);
+        }
+    }
+    let height = builder.constant_extension(F::Extension::from_canonical_usize(STATE_TREE_HEIGHT));
+    let diff = builder.sub_extension(levels_left, height);
+    let constraint = builder.mul_extension(is_access, diff);
+    yield_constr.constraint(builder, constraint);
+    for i in 0..NUM_HASH_OUT_ELTS {
+        for col in [col_old_path_digest(i), col_new_path_digest(i)] {
+            transition_filtered_eq(builder, yield_constr, is_access, col, local_values[col]);
+        }
+    }
+    for col in ACCESS_CARRIED_COLS {
+        transition_filtered_eq(builder, yield_constr, is_access, col, local_values[col]);
+    }
+
+    // Path rows hash the current node with its sibling, in an order given by the index's lowest
+    // bit.
+    let constraint = builder.mul_sub_extension(index_bit, index_bit, index_bit);
+    let constraint = builder.mul_extension(is_old_path, constraint);
+    yield_constr.constraint(builder, constraint);
+    for (is_path, col_digest) in [
+        (is_old_path, col_old_path_digest as fn(usize) -> usize),
+        (is_new_path, col_new_path_digest),
+    ] {
+        for i in 0..NUM_HASH_OUT_ELTS {
+            let digest = local_values[col_digest(i)];
+            let sibling = local_values[col_path_sibling(i)];
+            let left = local_values[col_input(i)];
+            let right = local_values[col_input(NUM_HASH_OUT_ELTS + i)];
+            for (input, first, second) in [(left, digest, sibling), (right, sibling, digest)] {
+                let diff = builder.sub_extension(second, first);
+                let expected = builder.mul_add_extension(index_bit, diff, first);
+                let diff = builder.sub_extension(input, expected);
+                let constraint = builder.mul_extension(is_path, diff);
+                yield_constr.constraint(builder, constraint);
+            }
+        }
+        for i in 2 * NUM_HASH_OUT_ELTS..SPONGE_WIDTH {
+            let constraint = builder.mul_extension(is_path, local_values[col_input(i)]);
+            yield_constr.constraint(builder, constraint);
+        }
+    }
+
+    // An old path row passes its output, sibling and index on to the following new path row.
+    for i in 0..NUM_HASH_OUT_ELTS {
+        let output = local_values[col_output(i)];
+        transition_filtered_eq(
+            builder,
+            yield_constr,
+            is_old_path,
+            col_old_path_digest(i),
+            output,
+        );
+        for col in [col_new_path_digest(i), col_path_sibling(i)] {
+            transition_filtered_eq(builder, yield_constr, is_old_path, col, local_values[col]);
+        }
+    }
+    for col in [COL_PATH_INDEX, COL_PATH_INDEX_BIT, COL_PATH_LEVELS_LEFT] {
+        transition_filtered_eq(builder, yield_constr, is_old_path, col, local_values[col]);
+    }
+
+    // A new path row passes its output on, and moves up a level.
+    for i in 0..NUM_HASH_OUT_ELTS {
+        let output = local_values[col_output(i)];
+        let old_digest = local_values[col_old_path_digest(i)];
+        transition_filtered_eq(
+            builder,
+            yield_constr,
+            is_new_path,
+            col_new_path_digest(i),
+            output,
+        );
+        transition_filtered_eq(
+            builder,
+            yield_constr,
+            is_new_path,
+            col_old_path_digest(i),
+            old_digest,
+        );
+    }
+    let next_index = next_values[COL_PATH_INDEX];
+    let constraint = builder.mul_const_add_extension(F::TWO, next_index, index_bit);
+    let constraint = builder.sub_extension(constraint, index);
+    let constraint = builder.mul_extension(is_new_path, constraint);
+    yield_constr.constraint_transition(builder, constraint);
+    let levels_left_minus_one = builder.sub_extension(levels_left, one);
+    transition_filtered_eq(
+        builder,
+        yield_constr,
+        is_new_path,
+        COL_PATH_LEVELS_LEFT,
+        levels_left_minus_one,
+    );
+
+    // The last level is the one with one level left. There, the old path must lead to the current
+    // state root, which is replaced by the new path's root. The index must have no more bits, so
+    // that the slot is in range.
+    let levels_left_minus_one_inv = local_values[COL_PATH_LEVELS_LEFT_MINUS_ONE_INV];
+    let constraint =
+        builder.mul_add_extension(levels_left_minus_one, levels_left_minus_one_inv, is_last);
+    let constraint = builder.sub_extension(constraint, one);
+    let constraint = builder.mul_extension(is_new_path, constraint);
+    yield_constr.constraint(builder, constraint);
+    let constraint = builder.mul_many_extension(&[is_new_path, is_last, levels_left_minus_one]);
+    yield_constr.constraint(builder, constraint);
+    let constraint =
+        builder.arithmetic_extension(F::NEG_ONE, F::ONE, is_new_path, is_last, root_update);
+    yield_constr.constraint(builder, constraint);
+    let diff = builder.sub_extension(index, index_bit);
+    let constraint = builder.mul_extension(root_update, diff);
+    yield_constr.constraint(builder, constraint);
+    for i in 0..NUM_HASH_OUT_ELTS {
+        let old_digest = local_values[col_old_path_digest(i)];
+        let diff = builder.sub_extension(old_digest, local_values[col_state_root(i)]);
+        let constraint = builder.mul_extension(root_update, diff);
+        yield_constr.constraint(builder, constraint);
+    }
+}
@@ -1,117 +1,325 @@
 use std::marker::PhantomData;
 
+use anyhow::Result;
 use plonky2::field::extension_field::{Extendable, FieldExtension};
 use plonky2::field::packed_field::PackedField;
 use plonky2::field::polynomial::PolynomialValues;
-use plonky2::hash::hash_types::RichField;
+use plonky2::hash::hash_types::{HashOut, RichField};
 use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::{GenericConfig, Hasher};
 use plonky2::timed;
 use plonky2::util::timing::TimingTree;
-use plonky2::util::transpose;
+use starky::config::StarkConfig;
 use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
 use starky::permutation::PermutationPair;
+use starky::proof::StarkProofWithPublicInputs;
+use starky::prover::prove;
 use starky::stark::Stark;
+use starky::trace::{TraceBuilder, TracePadding};
 use starky::vars::StarkEvaluationTargets;
 use starky::vars::StarkEvaluationVars;
 
-use crate::alu::{eval_alu, eval_alu_recursively, generate_alu};
+use crate::alu::{eval_alu, eval_alu_recursively};
+use crate::boolean_unit::{eval_boolean_unit, eval_boolean_unit_recursively};
 use crate::core_registers::{
-    eval_core_registers, eval_core_registers_recursively, generate_first_row_core_registers,
-    generate_next_row_core_registers,
+    eval_core_registers, eval_core_registers_recursively, generate_core_row,
+    generate_first_row_core_registers, generate_next_row_core_registers,
 };
+use crate::hash_unit::{
+    eval_hash_unit, eval_hash_unit_recursively, generate_first_row_hash_unit,
+    generate_next_row_hash_unit,
+};
+use crate::instruction::{hash_program, Instruction, NUM_REGISTERS};
+use crate::logic_unit::{eval_logic_unit, eval_logic_unit_recursively};
 use crate::lookup::{eval_lookups, eval_lookups_recursively, generate_lookups};
 use crate::memory::TransactionMemory;
+use crate::memory_unit::{
+    eval_memory_unit, eval_memory_unit_recursively, generate_memory, min_rows_for_memory,
+};
 use crate::permutation_unit::{
     eval_permutation_unit, eval_permutation_unit_recursively, generate_permutation_unit,
 };
-use crate::public_input_layout::NUM_PUBLIC_INPUTS;
-use crate::registers::{lookup, NUM_COLUMNS};
+use crate::public_input_layout::{
+    pi_input_register, pi_new_state_root, pi_old_state_root, pi_output_register, pi_program_hash,
+    NUM_PUBLIC_INPUTS, PI_PROGRAM_LENGTH,
+};
+use crate::range_check_unit::{
+    eval_range_checks, eval_range_checks_recursively, generate_range_checks,
+};
+use crate::registers::core::{col_register, IS_HALTED};
+use crate::registers::state::{col_state_root, NUM_HASH_OUT_ELTS};
+use crate::registers::{lookup, memory, NUM_COLUMNS};
+use crate::state::State;
+use crate::state_unit::{
+    eval_state_unit, eval_state_unit_recursively, generate_first_row_state_unit,
+    generate_next_row_state_unit,
+};
+use crate::wide_alu::{eval_wide_alu, eval_wide_alu_recursively, generate_next_row_wide_alu};
 
-/// We require at least 2^16 rows as it helps support efficient 16-bit range checks.
-const MIN_TRACE_ROWS: usize = 1 << 16;
+/// We require at least 2^8 rows, so that the trace can hold the 8-bit table used in 16-bit range
+/// checks.
+const MIN_TRACE_ROWS: usize = 1 << 8;
 
 #[derive(Copy, Clone)]
 pub struct SystemZero<F: RichField + Extendable<D>, const D: usize> {
+    /// The minimum length of generated traces. Traces are otherwise as short as the execution
+    /// allows, so this can be used to give executions of different lengths the same trace length,
+    /// e.g. so that their proofs can be verified by the same recursive circuit.
+    min_trace_rows: usize,
     _phantom: PhantomData<F>,
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> SystemZero<F, D> {
-    /// Generate the rows of the trace. Note that this does not generate the permuted columns used
-    /// in our lookup arguments, as those are computed after transposing to column-wise form.
-    fn generate_trace_rows(&self) -> Vec<[F; NUM_COLUMNS]> {
-        let memory = TransactionMemory::default();
+    /// Creates a `SystemZero` whose traces have at least `min_trace_rows` rows, which must be a
+    /// power of two.
+    pub fn new(min_trace_rows: usize) -> Self {
+        assert!(
+            min_trace_rows.is_power_of_two(),
+            "Minimum rows must be a power of two."
+        );
+        Self {
+            min_trace_rows: min_trace_rows.max(MIN_TRACE_ROWS),
+            _phantom: PhantomData,
+        }
+    }
 
-        let mut row = [F::ZERO; NUM_COLUMNS];
-        generate_first_row_core_registers(&mut row);
-        generate_alu(&mut row);
-        generate_permutation_unit(&mut row);
+    /// The number of rows of the trace of `program`, which must halt, starting with the registers
+    /// holding `inputs`, and the given initial state. This is the number of cycles of the
+    /// execution, padded to a power of two, and to at least the minimum trace length.
+    pub fn trace_rows(
+        &self,
+        program: &[Instruction],
+        inputs: [u32; NUM_REGISTERS],
+        state: &State,
+    ) -> usize {
+        let mut trace = self.generate_trace_rows(program, inputs, state);
+        trace.pad();
+        trace.num_rows()
+    }
 
-        let mut trace = Vec::with_capacity(MIN_TRACE_ROWS);
+    /// Generate the rows of the trace, by running `program` until it halts, starting with the
+    /// registers holding `inputs`, and the given initial state. Note that this does not
+    /// generate the values of memory reads, the sorted memory operations, or the permuted columns
+    /// used in our lookup arguments, as those are computed after transposing to column-wise form.
+    fn generate_trace_rows(
+        &self,
+        program: &[Instruction],
+        inputs: [u32; NUM_REGISTERS],
+        state: &State,
+    ) -> TraceBuilder<F, NUM_COLUMNS> {
+        let mut memory = TransactionMemory::default();
+        let mut state = state.clone();
 
-        loop {
-            let mut next_row = [F::ZERO; NUM_COLUMNS];
-            generate_next_row_core_registers(&row, &mut next_row);
-            generate_alu(&mut next_row);
-            generate_permutation_unit(&mut next_row);
+        let mut trace = TraceBuilder::with_capacity(self.min_trace_rows);
+        // Once execution has halted, padding rows only advance the clock and the 8-bit table.
+        trace.set_padding(TracePadding::Generated(Box::new(|prev_row| {
+            let mut row = *prev_row;
+            generate_next_row_core_registers(prev_row, &mut row);
+            row
+        })));
 
-            trace.push(row);
-            row = next_row;
+        let mut row = [F::ZERO; NUM_COLUMNS];
+        generate_first_row_core_registers(&mut row, program, inputs);
+        generate_first_row_state_unit(&mut row, &state);
+        generate_first_row_hash_unit(&mut row, program.len());
+        loop {
+            generate_core_row(&mut row, program, &mut memory, &mut state);
+            generate_permutation_unit(&mut row);
+            trace.push_row(row);
 
-            // TODO: Replace with proper termination condition.
-            if trace.len() == (1 << 16) - 1 {
+            // We stop after the first halted row; the rest are added as padding.
+            if row[IS_HALTED].is_one() {
                 break;
             }
+
+            let mut next_row = [F::ZERO; NUM_COLUMNS];
+            generate_next_row_core_registers(&row, &mut next_row);
+            generate_next_row_state_unit(&row, &mut next_row);
+            generate_next_row_hash_unit(&row, &mut next_row);
+            generate_next_row_wide_alu(&row, &mut next_row);
+            row = next_row;
         }
 
-        trace.push(row);
+        // The trace must also be long enough to range check the gaps between memory addresses.
+        let min_rows = min_rows_for_memory(&trace).next_power_of_two();
+        trace.set_min_rows(self.min_trace_rows.max(min_rows));
         trace
     }
 
-    fn generate_trace(&self) -> Vec<PolynomialValues<F>> {
+    /// Generates a trace of `program`, which must halt, starting with the registers holding
+    /// `inputs`, and the given initial state.
+    pub fn generate_trace(
+        &self,
+        program: &[Instruction],
+        inputs: [u32; NUM_REGISTERS],
+        state: &State,
+    ) -> Vec<PolynomialValues<F>> {
         let mut timing = TimingTree::new("generate trace", log::Level::Debug);
 
         // Generate the witness, except for permuted columns in the lookup argument.
         let trace_rows = timed!(
             &mut timing,
             "generate trace rows",
-            self.generate_trace_rows()
+            self.generate_trace_rows(program, inputs, state)
         );
 
-        // Transpose from row-wise to column-wise.
-        let trace_row_vecs: Vec<_> = timed!(
-            &mut timing,
-            "convert to Vecs",
-            trace_rows.into_iter().map(|row| row.to_vec()).collect()
+        let trace_polys = self.finalize_trace(trace_rows, &mut timing);
+        timing.print();
+        trace_polys
+    }
+
+    /// Converts the trace rows to column-wise form, and generates the columns which depend on the
+    /// whole trace: the values of memory reads, the sorted memory operations, the bytes of range
+    /// checked values, and the permuted columns used in our lookup arguments.
+    fn finalize_trace(
+        &self,
+        trace_rows: TraceBuilder<F, NUM_COLUMNS>,
+        timing: &mut TimingTree,
+    ) -> Vec<PolynomialValues<F>> {
+        // Pad and transpose from row-wise to column-wise.
+        let mut trace_col_vecs = timed!(timing, "transpose", trace_rows.into_columns());
+
+        // Fill in the values of memory reads, and generate the sorted memory operations.
+        timed!(
+            timing,
+            "generate memory",
+            generate_memory(&mut trace_col_vecs)
+        );
+
+        // Split range checked values into bytes, which are then looked up.
+        timed!(
+            timing,
+            "generate range checks",
+            generate_range_checks(&mut trace_col_vecs)
         );
-        let mut trace_col_vecs: Vec<Vec<F>> =
-            timed!(&mut timing, "transpose", transpose(&trace_row_vecs));
 
         // Generate permuted columns in the lookup argument.
         timed!(
-            &mut timing,
+            timing,
             "generate lookup columns",
             generate_lookups(&mut trace_col_vecs)
         );
 
-        let trace_polys = timed!(
-            &mut timing,
+        timed!(
+            timing,
             "convert to PolynomialValues",
             trace_col_vecs
                 .into_iter()
-                .map(|column| PolynomialValues::new(column))
+                .map(PolynomialValues::new)
                 .collect()
-        );
+        )
+    }
 
+    /// Runs `program`, which must halt, starting with the registers holding `inputs`, and the given
+    /// initial state, and proves its execution. The proof's public inputs contain `inputs`, the
+    /// final register values, the old and new state roots, and the program's digest and length,
+    /// which can be read with `program_inputs`, `program_outputs`, `old_state_root`,
+    /// `new_state_root`, `program_hash` and `program_length`. A verifier must check the latter two
+    /// against the program it expects, e.g. with `is_proof_of_program`.
+    pub fn prove_program<C>(
+        &self,
+        program: &[Instruction],
+        inputs: [u32; NUM_REGISTERS],
+        state: &State,
+        config: &StarkConfig,
+    ) -> Result<StarkProofWithPublicInputs<F, C, D>>
+    where
+        C: GenericConfig<D, F = F>,
+        [(); <Self as Stark<F, D>>::COLUMNS]:,
+        [(); <Self as Stark<F, D>>::PUBLIC_INPUTS]:,
+        [(); C::Hasher::HASH_SIZE]:,
+    {
+        let mut timing = TimingTree::new("prove program", log::Level::Debug);
+        let trace = self.generate_trace(program, inputs, state);
+        let public_inputs = Self::public_inputs(program, &trace, inputs, state);
+        let proof = prove::<F, C, Self, D>(*self, config, trace, public_inputs, &mut timing)?;
         timing.print();
-        trace_polys
+        Ok(proof)
+    }
+
+    /// The public inputs of a trace of `program` generated with the given inputs and initial state.
+    fn public_inputs(
+        program: &[Instruction],
+        trace: &[PolynomialValues<F>],
+        inputs: [u32; NUM_REGISTERS],
+        state: &State,
+    ) -> [F; NUM_PUBLIC_INPUTS] {
+        let last = |col: usize| *trace[col].values.last().expect("Trace is empty.");
+
+        let mut public_inputs = [F::ZERO; NUM_PUBLIC_INPUTS];
+        for i in 0..NUM_REGISTERS {
+            public_inputs[pi_input_register(i)] = F::from_canonical_u32(inputs[i]);
+            public_inputs[pi_output_register(i)] = last(col_register(i));
+        }
+        let old_state_root: HashOut<F> = state.root();
+        for i in 0..NUM_HASH_OUT_ELTS {
+            public_inputs[pi_old_state_root(i)] = old_state_root.elements[i];
+            public_inputs[pi_new_state_root(i)] = last(col_state_root(i));
+        }
+        let program_hash = hash_program(program);
+        for i in 0..NUM_HASH_OUT_ELTS {
+            public_inputs[pi_program_hash(i)] = program_hash.elements[i];
+        }
+        public_inputs[PI_PROGRAM_LENGTH] = F::from_canonical_usize(program.len());
+        public_inputs
+    }
+
+    /// Reads the program's inputs from a proof's public inputs.
+    pub fn program_inputs(public_inputs: &[F]) -> [u32; NUM_REGISTERS] {
+        Self::read_registers(public_inputs, pi_input_register)
+    }
+
+    /// Reads the program's outputs, i.e. the final register values, from a proof's public inputs.
+    pub fn program_outputs(public_inputs: &[F]) -> [u32; NUM_REGISTERS] {
+        Self::read_registers(public_inputs, pi_output_register)
+    }
+
+    /// Reads the state root before the program's execution from a proof's public inputs.
+    pub fn old_state_root(public_inputs: &[F]) -> HashOut<F> {
+        HashOut {
+            elements: [0, 1, 2, 3].map(|i| public_inputs[pi_old_state_root(i)]),
+        }
+    }
+
+    /// Reads the state root after the program's execution from a proof's public inputs.
+    pub fn new_state_root(public_inputs: &[F]) -> HashOut<F> {
+        HashOut {
+            elements: [0, 1, 2, 3].map(|i| public_inputs[pi_new_state_root(i)]),
+        }
+    }
+
+    /// Reads the digest of the executed program from a proof's public inputs.
+    pub fn program_hash(public_inputs: &[F]) -> HashOut<F> {
+        HashOut {
+            elements: [0, 1, 2, 3].map(|i| public_inputs[pi_program_hash(i)]),
+        }
+    }
+
+    /// Reads the length of the executed program from a proof's public inputs.
+    pub fn program_length(public_inputs: &[F]) -> usize {
+        public_inputs[PI_PROGRAM_LENGTH].to_canonical_u64() as usize
+    }
+
+    /// Whether a proof's public inputs commit to `program`.
+    pub fn is_proof_of_program(public_inputs: &[F], program: &[Instruction]) -> bool {
+        Self::program_length(public_inputs) == program.len()
+            && Self::program_hash(public_inputs) == hash_program(program)
+    }
+
+    fn read_registers(
+        public_inputs: &[F],
+        pi_register: fn(usize) -> usize,
+    ) -> [u32; NUM_REGISTERS] {
+        [0, 1, 2, 3].map(|i| {
+            let value = public_inputs[pi_register(i)].to_canonical_u64();
+            value.try_into().expect("Register value is not a u32.")
+        })
     }
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> Default for SystemZero<F, D> {
     fn default() -> Self {
-        Self {
-            _phantom: PhantomData,
-        }
+        Self::new(MIN_TRACE_ROWS)
     }
 }
 
@@ -128,8 +336,15 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for SystemZero<F,
         P: PackedField<Scalar = FE>,
     {
         eval_core_registers(vars, yield_constr);
+        eval_state_unit(vars, yield_constr);
+        eval_hash_unit(vars, yield_constr);
+        eval_wide_alu(vars, yield_constr);
         eval_alu(vars, yield_constr);
+        eval_boolean_unit(vars, yield_constr);
+        eval_logic_unit(vars, yield_constr);
+        eval_memory_unit(vars, yield_constr);
         eval_permutation_unit::<F, FE, P, D2>(vars, yield_constr);
+        eval_range_checks(vars, yield_constr);
         eval_lookups(vars, yield_constr);
         // TODO: Other units
     }
@@ -141,8 +356,15 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for SystemZero<F,
         yield_constr: &mut RecursiveConstraintConsumer<F, D>,
     ) {
         eval_core_registers_recursively(builder, vars, yield_constr);
+        eval_state_unit_recursively(builder, vars, yield_constr);
+        eval_hash_unit_recursively(builder, vars, yield_constr);
+        eval_wide_alu_recursively(builder, vars, yield_constr);
         eval_alu_recursively(builder, vars, yield_constr);
+        eval_boolean_unit_recursively(builder, vars, yield_constr);
+        eval_logic_unit_recursively(builder, vars, yield_constr);
+        eval_memory_unit_recursively(builder, vars, yield_constr);
         eval_permutation_unit_recursively(builder, vars, yield_constr);
+        eval_range_checks_recursively(builder, vars, yield_constr);
         eval_lookups_recursively(builder, vars, yield_constr);
         // TODO: Other units
     }
@@ -165,58 +387,662 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for SystemZero<F,
             ));
         }
 
-        // TODO: Add permutation pairs for memory.
+        pairs.push(PermutationPair {
+            column_pairs: memory::MEMORY_PERMUTATION_COLUMN_PAIRS.to_vec(),
+        });
 
         pairs
     }
 }
 
+/// Helpers for testing a unit's constraints on a hand-made trace, without proving.
+#[cfg(test)]
+pub(crate) mod unit_testing {
+    use plonky2::field::field_types::Field;
+    use starky::constraint_consumer::ConstraintConsumer;
+    use starky::vars::StarkEvaluationVars;
+
+    use crate::public_input_layout::NUM_PUBLIC_INPUTS;
+    use crate::registers::NUM_COLUMNS;
+
+    pub(crate) type UnitVars<'a, F> = StarkEvaluationVars<'a, F, F, NUM_COLUMNS, NUM_PUBLIC_INPUTS>;
+
+    /// Evaluates `eval` on each row of a column-wise trace, together with the next row, wrapping
+    /// around, and returns whether all of its constraints hold.
+    pub(crate) fn constraints_hold<F: Field>(
+        trace_cols: &[Vec<F>],
+        eval: impl Fn(UnitVars<F>, &mut ConstraintConsumer<F>),
+    ) -> bool {
+        let num_rows = trace_cols[0].len();
+        let row = |i: usize| -> [F; NUM_COLUMNS] {
+            let i = i % num_rows;
+            trace_cols
+                .iter()
+                .map(|col| col[i])
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap()
+        };
+        (0..num_rows).all(|i| {
+            let vars = StarkEvaluationVars {
+                local_values: &row(i),
+                next_values: &row(i + 1),
+                window_values: &[],
+                local_preprocessed_values: &[],
+                next_preprocessed_values: &[],
+                public_inputs: &[F::ZERO; NUM_PUBLIC_INPUTS],
+            };
+            let mut consumer = ConstraintConsumer::new(
+                vec![F::rand()],
+                F::from_bool(i != num_rows - 1),
+                F::from_bool(i == 0),
+                F::from_bool(i == num_rows - 1),
+            );
+            eval(vars, &mut consumer);
+            consumer.accumulators()[0] == F::ZERO
+        })
+    }
+
+    /// Like `constraints_hold`, for a trace with the single row `values`.
+    pub(crate) fn row_constraints_hold<F: Field>(
+        values: &[F; NUM_COLUMNS],
+        eval: impl Fn(UnitVars<F>, &mut ConstraintConsumer<F>),
+    ) -> bool {
+        let trace_cols: Vec<_> = values.iter().map(|&x| vec![x]).collect();
+        constraints_hold(&trace_cols, eval)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
-    use log::Level;
-    use plonky2::field::field_types::Field;
+    use plonky2::field::field_types::{Field, PrimeField64};
     use plonky2::field::goldilocks_field::GoldilocksField;
     use plonky2::plonk::config::PoseidonGoldilocksConfig;
-    use plonky2::util::timing::TimingTree;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
     use starky::config::StarkConfig;
-    use starky::prover::prove;
-    use starky::stark::Stark;
-    use starky::stark_testing::test_stark_low_degree;
+    use starky::constraint_checker::check_constraints;
+    use starky::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
     use starky::verifier::verify_stark_proof;
 
+    use crate::instruction::Opcode::*;
+    use crate::instruction::{hash_program, hash_words, Instruction, Opcode, NUM_REGISTERS};
+    use crate::interpreter::Interpreter;
+    use crate::memory::Segment;
+    use crate::public_input_layout::{
+        pi_new_state_root, pi_old_state_root, pi_output_register, pi_program_hash,
+        PI_PROGRAM_LENGTH,
+    };
+    use crate::registers::core::{
+        col_register, COL_FRAME_PTR, COL_INSTRUCTION_PTR, COL_STACK_PTR, IS_HASH_OUTPUT,
+        IS_WIDE_WRITE,
+    };
+    use crate::registers::memory::COL_MEMORY_OP_VALUE;
+    use crate::registers::state::COL_STATE_OLD_VALUE;
+    use crate::state::State;
     use crate::system_zero::SystemZero;
 
+    type F = GoldilocksField;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+    type S = SystemZero<F, D>;
+
+    fn instr(opcode: Opcode, rd: usize, rs1: usize, rs2: usize, imm: u16) -> Instruction {
+        Instruction::new(opcode, rd, rs1, rs2, imm)
+    }
+
+    /// Proves and verifies an execution of `program` with an empty initial state, checks that its
+    /// outputs match those of the interpreter, and returns them.
+    fn run_program(
+        program: &[Instruction],
+        inputs: [u32; NUM_REGISTERS],
+    ) -> Result<[u32; NUM_REGISTERS]> {
+        let (outputs, _) = run_program_with_state(program, inputs, State::default())?;
+        Ok(outputs)
+    }
+
+    /// Like `run_program`, but also checks the state roots against those of the interpreter, and
+    /// returns the final state.
+    fn run_program_with_state(
+        program: &[Instruction],
+        inputs: [u32; NUM_REGISTERS],
+        state: State,
+    ) -> Result<([u32; NUM_REGISTERS], State)> {
+        init_logger();
+
+        let system = S::default();
+        let config = StarkConfig::standard_fast_config();
+        let proof = system.prove_program::<C>(program, inputs, &state, &config)?;
+        let outputs = S::program_outputs(&proof.public_inputs);
+        let interpreter = Interpreter::run::<F>(program, inputs, state.clone());
+        assert_eq!(S::program_inputs(&proof.public_inputs), inputs);
+        assert!(S::is_proof_of_program(&proof.public_inputs, program));
+        assert_eq!(outputs, interpreter.registers);
+        assert_eq!(S::old_state_root(&proof.public_inputs), state.root());
+        assert_eq!(
+            S::new_state_root(&proof.public_inputs),
+            interpreter.state.root()
+        );
+
+        verify_stark_proof(system, proof, &config)?;
+        Ok((outputs, interpreter.state))
+    }
+
     #[test]
     fn run() -> Result<()> {
-        init_logger();
+        assert_eq!(run_program(&[], [0; NUM_REGISTERS])?, [0; NUM_REGISTERS]);
+        Ok(())
+    }
+
+    #[test]
+    fn run_with_division() -> Result<()> {
+        let program = [
+            instr(LoadI, 1, 0, 0, 3),
+            instr(Not, 0, 2, 0, 0),
+            instr(Div, 2, 0, 1, 0),
+            instr(LoadI, 1, 0, 0, 0),
+            instr(Div, 3, 0, 1, 0),
+            instr(LoadI, 0, 0, 0, 17),
+            instr(LoadI, 1, 0, 0, 5),
+            instr(Div, 1, 0, 1, 0),
+        ];
+        assert_eq!(
+            run_program(&program, [0; NUM_REGISTERS])?,
+            [17, 3, u32::MAX / 3, 0]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn run_with_logic() -> Result<()> {
+        let program = [
+            instr(LoadI, 0, 0, 0, 0xbeef),
+            instr(LoadI, 1, 0, 0, 16),
+            instr(Shl, 0, 0, 1, 0),
+            instr(LoadI, 2, 0, 0, 0xdead),
+            instr(Or, 0, 0, 2, 0),
+            instr(Shr, 1, 0, 1, 0),
+            instr(LoadI, 2, 0, 0, 0x0f0f),
+            instr(Xor, 3, 0, 2, 0),
+            instr(And, 2, 0, 2, 0),
+            instr(Not, 2, 2, 0, 0),
+            instr(Sub, 3, 3, 1, 0),
+            instr(LoadI, 1, 0, 0, 32),
+            instr(Shl, 1, 0, 1, 0),
+        ];
+        assert_eq!(
+            run_program(&program, [0; NUM_REGISTERS])?,
+            [0xbeefdead, 0, 0xfffff1f2, 0xbeefd1a2 - 0xbeef]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn run_with_memory() -> Result<()> {
+        let program = [
+            instr(LoadI, 0, 0, 0, 100),
+            instr(LoadI, 1, 0, 0, 7),
+            instr(Store, 0, 0, 1, 1),
+            instr(LoadI, 1, 0, 0, 9),
+            instr(Push, 0, 1, 0, 0),
+            instr(Load, 2, 0, 0, 1),
+            instr(Load, 1, 0, 0, 2),
+            instr(Pop, 3, 0, 0, 0),
+        ];
+        assert_eq!(run_program(&program, [0; NUM_REGISTERS])?, [100, 0, 7, 9]);
+        Ok(())
+    }
+
+    #[test]
+    fn run_with_control_flow() -> Result<()> {
+        // Sums 1 to 10, by calling a function which adds the counter to the sum.
+        let program = [
+            instr(LoadI, 0, 0, 0, 10),
+            instr(LoadI, 1, 0, 0, 0),
+            instr(LoadI, 3, 0, 0, 1),
+            instr(Call, 0, 0, 0, 8),
+            instr(Sub, 0, 0, 3, 0),
+            instr(JumpI, 0, 0, 0, 3),
+            instr(Jump, 0, 0, 0, 11),
+            instr(Halt, 0, 0, 0, 0),
+            // The function pushes a value, which is discarded on return.
+            instr(Push, 0, 0, 0, 0),
+            instr(Add, 1, 1, 0, 0),
+            instr(Ret, 0, 0, 0, 0),
+            instr(LoadI, 2, 0, 0, 42),
+        ];
+        assert_eq!(run_program(&program, [0; NUM_REGISTERS])?, [0, 55, 42, 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn run_with_inputs() -> Result<()> {
+        // Computes the sum and the product of the first two inputs.
+        let program = [instr(Add, 2, 0, 1, 0), instr(Mul, 3, 0, 1, 0)];
+        assert_eq!(run_program(&program, [6, 7, 0, 9])?, [6, 7, 13, 42]);
+        Ok(())
+    }
+
+    #[test]
+    fn trace_length() {
+        let state = State::default();
+        let inputs = [0; NUM_REGISTERS];
+        let program = [instr(LoadI, 0, 0, 0, 1)];
+        assert_eq!(S::default().trace_rows(&program, inputs, &state), 1 << 8);
+        assert_eq!(
+            S::new(1 << 10).trace_rows(&program, inputs, &state),
+            1 << 10
+        );
+
+        // A gap of 999 between memory addresses requires 1024 rows to range check.
+        let program = [instr(Store, 0, 0, 0, 1), instr(Store, 0, 0, 0, 1000)];
+        assert_eq!(S::default().trace_rows(&program, inputs, &state), 1 << 10);
 
-        type F = GoldilocksField;
-        type C = PoseidonGoldilocksConfig;
-        const D: usize = 2;
+        // Long executions need long traces.
+        let program = [
+            instr(LoadI, 0, 0, 0, 1000),
+            instr(LoadI, 1, 0, 0, 1),
+            instr(Sub, 0, 0, 1, 0),
+            instr(JumpI, 0, 0, 0, 2),
+        ];
+        assert_eq!(S::default().trace_rows(&program, inputs, &state), 1 << 12);
+    }
+
+    #[test]
+    fn wrong_outputs() -> Result<()> {
+        let program = [instr(Add, 2, 0, 1, 0)];
+        let system = S::default();
+        let config = StarkConfig::standard_fast_config();
+        let mut proof =
+            system.prove_program::<C>(&program, [6, 7, 0, 0], &State::default(), &config)?;
+        proof.public_inputs[pi_output_register(2)] = F::from_canonical_u32(14);
+        assert!(verify_stark_proof(system, proof, &config).is_err());
+        Ok(())
+    }
 
-        type S = SystemZero<F, D>;
+    #[test]
+    fn wrong_program() -> Result<()> {
+        let program = [instr(LoadI, 0, 0, 0, 1), instr(LoadI, 1, 0, 0, 2)];
+        let other_program = [instr(LoadI, 0, 0, 0, 1), instr(LoadI, 1, 0, 0, 3)];
         let system = S::default();
-        let public_inputs = [F::ZERO; S::PUBLIC_INPUTS];
         let config = StarkConfig::standard_fast_config();
-        let mut timing = TimingTree::new("prove", Level::Debug);
-        let trace = system.generate_trace();
-        let proof = prove::<F, C, S, D>(system, &config, trace, public_inputs, &mut timing)?;
+        let state = State::default();
+        let inputs = [0; NUM_REGISTERS];
+        let proof = system.prove_program::<C>(&program, inputs, &state, &config)?;
+        assert!(S::is_proof_of_program(&proof.public_inputs, &program));
+        assert!(!S::is_proof_of_program(
+            &proof.public_inputs,
+            &other_program
+        ));
+
+        // A proof of one program doesn't verify as a proof of another.
+        let claim_program = |public_inputs: &mut [F], program: &[Instruction]| {
+            let program_hash = hash_program::<F>(program);
+            for i in 0..program_hash.elements.len() {
+                public_inputs[pi_program_hash(i)] = program_hash.elements[i];
+            }
+            public_inputs[PI_PROGRAM_LENGTH] = F::from_canonical_usize(program.len());
+        };
+        let mut wrong_proof = proof.clone();
+        claim_program(&mut wrong_proof.public_inputs, &other_program);
+        assert!(verify_stark_proof(system, wrong_proof, &config).is_err());
 
-        verify_stark_proof(system, proof, &config)
+        // Nor as a proof of a prefix of the program, or of the empty program.
+        let trace = system.generate_trace(&program, inputs, &state);
+        let public_inputs = S::public_inputs(&program, &trace, inputs, &state);
+        assert!(check_constraints::<F, S, D>(&system, &trace, public_inputs).is_empty());
+        for prefix in [&program[..1], &program[..0]] {
+            let mut wrong_public_inputs = public_inputs;
+            claim_program(&mut wrong_public_inputs, prefix);
+            assert!(!check_constraints::<F, S, D>(&system, &trace, wrong_public_inputs).is_empty());
+        }
+
+        // Loading a word other than the program's fails.
+        let mut wrong_trace = trace.clone();
+        let row = wrong_trace[COL_MEMORY_OP_VALUE]
+            .values
+            .iter()
+            .position(|&x| x == F::from_canonical_u32(program[1].encode()))
+            .unwrap();
+        wrong_trace[COL_MEMORY_OP_VALUE].values[row] =
+            F::from_canonical_u32(other_program[1].encode());
+        assert!(!check_constraints::<F, S, D>(&system, &wrong_trace, public_inputs).is_empty());
+        Ok(())
     }
 
+    /// Checks that the trace generator agrees with the interpreter on random programs.
     #[test]
-    fn degree() -> Result<()> {
-        type F = GoldilocksField;
-        type C = PoseidonGoldilocksConfig;
-        const D: usize = 2;
+    fn interpreter_matches_trace() {
+        const OPCODES: [Opcode; 13] = [
+            Add, Sub, Mul, Div, And, Or, Xor, Not, Shl, Shr, LoadI, Push, Pop,
+        ];
+
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let system = S::default();
+        for _ in 0..4 {
+            // Straight line programs always halt. We avoid memory instructions with arbitrary
+            // addresses, and popping an empty stack.
+            let mut program = vec![];
+            let mut stack_size = 0;
+            while program.len() < 100 {
+                let opcode = OPCODES[rng.gen_range(0..OPCODES.len())];
+                match opcode {
+                    Push => stack_size += 1,
+                    Pop if stack_size == 0 => continue,
+                    Pop => stack_size -= 1,
+                    _ => {}
+                }
+                let [rd, rs1, rs2] = [(); 3].map(|_| rng.gen_range(0..NUM_REGISTERS));
+                program.push(instr(opcode, rd, rs1, rs2, rng.gen()));
+            }
+            let inputs = rng.gen();
+
+            let trace = system.generate_trace(&program, inputs, &State::default());
+            let last = |col: usize| trace[col].values.last().unwrap().to_canonical_u64() as usize;
+            let interpreter = Interpreter::run::<F>(&program, inputs, State::default());
+            assert_eq!(
+                interpreter.registers,
+                [0, 1, 2, 3].map(|i| last(col_register(i)) as u32)
+            );
+            assert_eq!(interpreter.instruction_ptr, last(COL_INSTRUCTION_PTR));
+            assert_eq!(interpreter.frame_ptr, last(COL_FRAME_PTR));
+            assert_eq!(interpreter.stack_ptr, last(COL_STACK_PTR));
+        }
+    }
+
+    #[test]
+    fn run_with_state() -> Result<()> {
+        let mut state = State::default();
+        state.write(5, 40);
+        let program = [
+            instr(SLoad, 0, 3, 0, 5),
+            instr(LoadI, 1, 0, 0, 2),
+            instr(Add, 0, 0, 1, 0),
+            instr(SStore, 0, 3, 0, 255),
+            instr(SLoad, 2, 3, 0, 255),
+        ];
+        let (outputs, new_state) = run_program_with_state(&program, [0; NUM_REGISTERS], state)?;
+        assert_eq!(outputs, [42, 2, 42, 0]);
+        assert_eq!(new_state.read(5), 40);
+        assert_eq!(new_state.read(255), 42);
+        Ok(())
+    }
+
+    #[test]
+    fn wrong_state() {
+        let mut state = State::default();
+        state.write(1, 40);
+        let program = [instr(SLoad, 0, 0, 0, 1), instr(SStore, 0, 0, 0, 2)];
+        let system = S::default();
+        let inputs = [0; NUM_REGISTERS];
+        let trace = system.generate_trace(&program, inputs, &state);
+        let public_inputs = S::public_inputs(&program, &trace, inputs, &state);
+        assert!(check_constraints::<F, S, D>(&system, &trace, public_inputs).is_empty());
+
+        // Claim a different old state root.
+        let mut wrong_public_inputs = public_inputs;
+        wrong_public_inputs[pi_old_state_root(0)] += F::ONE;
+        assert!(!check_constraints::<F, S, D>(&system, &trace, wrong_public_inputs).is_empty());
+
+        // Claim a different new state root.
+        let mut wrong_public_inputs = public_inputs;
+        wrong_public_inputs[pi_new_state_root(3)] += F::ONE;
+        assert!(!check_constraints::<F, S, D>(&system, &trace, wrong_public_inputs).is_empty());
+
+        // Load a different value, without changing the state.
+        let mut wrong_trace = trace;
+        let row = wrong_trace[COL_STATE_OLD_VALUE]
+            .values
+            .iter()
+            .position(|&x| x == F::from_canonical_u32(40))
+            .unwrap();
+        wrong_trace[COL_STATE_OLD_VALUE].values[row] = F::from_canonical_u32(41);
+        assert!(!check_constraints::<F, S, D>(&system, &wrong_trace, public_inputs).is_empty());
+    }
+
+    #[test]
+    fn run_with_hash() -> Result<()> {
+        let program = [
+            // Write 0, ..., 8 to main[100..109].
+            instr(LoadI, 1, 0, 0, 9),
+            instr(LoadI, 3, 0, 0, 1),
+            instr(Store, 0, 0, 0, 100),
+            instr(Add, 0, 0, 3, 0),
+            instr(Sub, 2, 1, 0, 0),
+            instr(JumpI, 0, 2, 0, 2),
+            // Hash them, which takes two permutations, and write the digest to main[200..208].
+            instr(LoadI, 0, 0, 0, 90),
+            instr(LoadI, 1, 0, 0, 200),
+            instr(LoadI, 2, 0, 0, 9),
+            instr(Hash, 1, 0, 2, 10),
+            // Hash the empty input, overwriting the second half of the digest with zeros.
+            instr(LoadI, 1, 0, 0, 204),
+            instr(LoadI, 2, 0, 0, 0),
+            instr(Hash, 1, 0, 2, 0),
+            instr(Load, 0, 3, 0, 199),
+            instr(Load, 1, 3, 0, 200),
+            instr(Load, 2, 3, 0, 201),
+            instr(Load, 3, 3, 0, 203),
+        ];
+        let input = (0..9).map(F::from_canonical_u32).collect::<Vec<_>>();
+        let digest = hash_words(&input).elements;
+        let [element_0, element_1] = [0, 1].map(|i| digest[i].to_canonical_u64());
+        assert_eq!(
+            run_program(&program, [0; NUM_REGISTERS])?,
+            [
+                element_0 as u32,
+                (element_0 >> 32) as u32,
+                element_1 as u32,
+                0
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn run_with_hash_trailing_zeros() -> Result<()> {
+        // Hash `[7]` and `[7, 0]`, and read the first element of each digest.
+        let program = [
+            instr(LoadI, 0, 0, 0, 7),
+            instr(Store, 0, 3, 0, 100),
+            instr(LoadI, 1, 0, 0, 1),
+            instr(LoadI, 2, 0, 0, 200),
+            instr(Hash, 2, 3, 1, 100),
+            instr(LoadI, 1, 0, 0, 2),
+            instr(LoadI, 2, 0, 0, 208),
+            instr(Hash, 2, 3, 1, 100),
+            instr(Load, 0, 3, 0, 200),
+            instr(Load, 1, 3, 0, 201),
+            instr(Load, 2, 3, 0, 208),
+            instr(Load, 3, 3, 0, 209),
+        ];
+        let x = F::from_canonical_u32(7);
+        let digest = hash_words(&[x]).elements;
+        let padded_digest = hash_words(&[x, F::ZERO]).elements;
+        assert_ne!(digest, padded_digest);
+        let [element, padded_element] = [digest[0], padded_digest[0]].map(|x| x.to_canonical_u64());
+        assert_eq!(
+            run_program(&program, [0; NUM_REGISTERS])?,
+            [
+                element as u32,
+                (element >> 32) as u32,
+                padded_element as u32,
+                (padded_element >> 32) as u32
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn wrong_hash() {
+        let program = [instr(LoadI, 0, 0, 0, 7), instr(Hash, 0, 1, 0, 0)];
+        let system = S::default();
+        let state = State::default();
+        let mut trace = system.generate_trace(&program, [0; NUM_REGISTERS], &state);
+        let public_inputs = S::public_inputs(&program, &trace, [0; NUM_REGISTERS], &state);
+        assert!(check_constraints::<F, S, D>(&system, &trace, public_inputs).is_empty());
+
+        // Write a different word of the digest.
+        let row = trace[IS_HASH_OUTPUT]
+            .values
+            .iter()
+            .position(|x| x.is_one())
+            .unwrap();
+        trace[COL_MEMORY_OP_VALUE].values[row] += F::ONE;
+        assert!(!check_constraints::<F, S, D>(&system, &trace, public_inputs).is_empty());
+    }
+
+    #[test]
+    fn run_with_wide_alu() -> Result<()> {
+        // 256-bit words: 3 at 0, -5 at 8, 7 at 16, -2^255 at 24, -1 at 32 and 0 at 40 and 48.
+        const B: u16 = 0;
+        const A: u16 = 8;
+        const MODULUS: u16 = 16;
+        const MIN: u16 = 24;
+        const NEG_ONE: u16 = 32;
+        const ZERO: u16 = 40;
+        let mut program = vec![
+            instr(LoadI, 1, 0, 0, 3),
+            instr(Store, 0, 0, 1, B),
+            instr(LoadI, 1, 0, 0, 4),
+            instr(Not, 1, 1, 0, 0),
+            instr(Store, 0, 0, 1, A),
+            instr(LoadI, 1, 0, 0, 7),
+            instr(Store, 0, 0, 1, MODULUS),
+            instr(LoadI, 1, 0, 0, 1),
+            instr(LoadI, 2, 0, 0, 31),
+            instr(Shl, 1, 1, 2, 0),
+            instr(Store, 0, 0, 1, MIN + 7),
+            instr(Not, 1, 0, 0, 0),
+        ];
+        program.extend((A + 1..A + 8).map(|i| instr(Store, 0, 0, 1, i)));
+        program.extend((NEG_ONE..NEG_ONE + 8).map(|i| instr(Store, 0, 0, 1, i)));
 
-        type S = SystemZero<F, D>;
+        let small = |x: u32| [x, 0, 0, 0, 0, 0, 0, 0];
+        let negative = |x: u32| {
+            let mut word = [u32::MAX; 8];
+            word[0] = x.wrapping_neg();
+            word
+        };
+        let mut min = [0; 8];
+        min[7] = 1 << 31;
+        // Each operation reads `main[a..]` and `main[b..]`, and the modulus from `main[b + 8..]`.
+        let cases = [
+            (Add256, A, B, negative(2)),
+            (Sub256, B, A, small(8)),
+            (Mul256, A, B, negative(15)),
+            (Div256, MODULUS, B, small(2)),
+            (Div256, A, ZERO, small(0)),
+            (Mod256, MODULUS, B, small(1)),
+            (Mod256, A, ZERO, small(0)),
+            (AddMod256, A, A, small(1)),
+            (AddMod256, A, ZERO, small(0)),
+            (MulMod256, A, A, small(2)),
+            (SDiv256, A, B, negative(1)),
+            (SDiv256, MIN, NEG_ONE, min),
+            (SDiv256, B, ZERO, small(0)),
+            (SMod256, A, B, negative(2)),
+            (SMod256, MODULUS, A, small(2)),
+            (Lt256, B, A, small(1)),
+            (Gt256, B, A, small(0)),
+            (SLt256, A, B, small(1)),
+            (SGt256, A, B, small(0)),
+            (SLt256, MIN, NEG_ONE, small(1)),
+            (Eq256, A, A, small(1)),
+            (Eq256, A, B, small(0)),
+            (IsZero256, ZERO, 0, small(1)),
+            (IsZero256, NEG_ONE, 0, small(0)),
+        ];
+        let output_address = |i: usize| 100 + 8 * i as u16;
+        for (i, &(opcode, a, b, _)) in cases.iter().enumerate() {
+            program.push(instr(LoadI, 2, 0, 0, b));
+            program.push(instr(LoadI, 3, 0, 0, output_address(i)));
+            program.push(instr(opcode, 3, 0, 2, a));
+        }
+        program.extend([
+            instr(Load, 1, 0, 0, output_address(0)),
+            instr(Load, 2, 0, 0, output_address(11) + 7),
+            instr(Load, 3, 0, 0, output_address(13) + 3),
+        ]);
+
+        assert_eq!(
+            run_program(&program, [0; NUM_REGISTERS])?,
+            [0, u32::MAX - 1, 1 << 31, u32::MAX]
+        );
+        let interpreter = Interpreter::run::<F>(&program, [0; NUM_REGISTERS], State::default());
+        for (i, &(opcode, _, _, expected)) in cases.iter().enumerate() {
+            let address = output_address(i) as usize;
+            let result = [0, 1, 2, 3, 4, 5, 6, 7]
+                .map(|j| interpreter.memory.read(0, Segment::Main, address + j));
+            assert_eq!(
+                result, expected,
+                "Wrong result for case {} ({:?}).",
+                i, opcode
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn wrong_wide_result() {
+        let program = [
+            instr(LoadI, 0, 0, 0, 5),
+            instr(Store, 0, 1, 0, 0),
+            instr(LoadI, 3, 0, 0, 50),
+            instr(Add256, 3, 1, 1, 0),
+        ];
+        let system = S::default();
+        let state = State::default();
+        let mut trace = system.generate_trace(&program, [0; NUM_REGISTERS], &state);
+        let public_inputs = S::public_inputs(&program, &trace, [0; NUM_REGISTERS], &state);
+        assert!(check_constraints::<F, S, D>(&system, &trace, public_inputs).is_empty());
+
+        // Write a different word of the sum.
+        let row = trace[IS_WIDE_WRITE]
+            .values
+            .iter()
+            .position(|x| x.is_one())
+            .unwrap();
+        assert_eq!(
+            trace[COL_MEMORY_OP_VALUE].values[row],
+            F::from_canonical_u32(10)
+        );
+        trace[COL_MEMORY_OP_VALUE].values[row] += F::ONE;
+        assert!(!check_constraints::<F, S, D>(&system, &trace, public_inputs).is_empty());
+    }
+
+    #[test]
+    fn wrong_register() {
+        let program = [instr(LoadI, 0, 0, 0, 5), instr(Add, 1, 0, 0, 0)];
+        let system = S::default();
+        let state = State::default();
+        let mut trace = system.generate_trace(&program, [0; NUM_REGISTERS], &state);
+        let public_inputs = S::public_inputs(&program, &trace, [0; NUM_REGISTERS], &state);
+        assert!(check_constraints::<F, S, D>(&system, &trace, public_inputs).is_empty());
+
+        // Change r0 after the `LOADI` has executed.
+        let row = trace[col_register(0)]
+            .values
+            .iter()
+            .position(|&x| x == F::from_canonical_u32(5))
+            .unwrap();
+        trace[col_register(0)].values[row] = F::from_canonical_u32(6);
+        assert!(!check_constraints::<F, S, D>(&system, &trace, public_inputs).is_empty());
+    }
+
+    #[test]
+    fn degree() -> Result<()> {
         let system = S::default();
         test_stark_low_degree(system)
     }
 
+    #[test]
+    fn circuit() -> Result<()> {
+        let system = S::default();
+        test_stark_circuit_constraints::<F, C, S, D>(system)
+    }
+
     fn init_logger() {
         let _ = env_logger::builder().format_timestamp(None).try_init();
     }
@@ -0,0 +1,1564 @@
+use plonky2::field::extension_field::Extendable;
+use plonky2::field::field_types::{Field, PrimeField64};
+use plonky2::field::packed_field::PackedField;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::vars::StarkEvaluationTargets;
+use starky::vars::StarkEvaluationVars;
+
+use crate::bigint::{
+    abs, add, div_rem, is_negative, is_zero, mul, neg, overflowing_sub, signed_cmp, sub, truncate,
+};
+use crate::instruction::Opcode;
+use crate::memory::{Segment, TransactionMemory};
+use crate::public_input_layout::NUM_PUBLIC_INPUTS;
+use crate::registers::core::{IS_WIDE_COMPUTE, IS_WIDE_READ, IS_WIDE_WRITE};
+use crate::registers::memory::COL_MEMORY_OP_VALUE;
+use crate::registers::wide_alu::*;
+use crate::registers::NUM_COLUMNS;
+
+/// Where an operation takes a value of the relation from.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Source {
+    /// The given operand, which is read from memory.
+    Operand(usize),
+    One,
+    Zero,
+    /// A witness, which is range checked.
+    Witness,
+}
+
+/// What an operation writes as its result.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Output {
+    /// The given value of the relation.
+    Value(usize),
+    /// The given value of the relation, or zero if the divisor is zero.
+    ValueUnlessDivisorIsZero(usize),
+    /// `h`, which is 1 if `r < z` for operations with `x + z = r + 2^256 h`, and 0 otherwise.
+    Borrow,
+    /// 1 if `x` is zero, and 0 otherwise.
+    XIsZero,
+}
+
+/// Which value of the relation, if any, is a divisor.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Divisor {
+    None,
+    /// `y` divides `r`, with quotient `x` and remainder `z`. If `y` is zero, `x` is zero.
+    Y,
+    /// `n` divides `x * y + z`, with quotient `q` and remainder `r`. If `n` is zero, `q` is
+    /// unused, and otherwise `h` is zero.
+    N,
+}
+
+/// How an operation is mapped onto the relation `x * y + z = q * n + r + 2^256 h`.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct WideOp {
+    pub(crate) opcode: Opcode,
+    /// The number of operands read from memory.
+    num_operands: usize,
+    /// The sources of `x`, `y`, `z`, `n`, `r` and `h`.
+    sources: [Source; NUM_RELATION_VALUES],
+    output: Output,
+    /// Unless it is zero, the remainder must be less than the divisor.
+    divisor: Divisor,
+    /// Whether `y`, `z` and `r` are treated as two's complement, as is `x` for divisions.
+    signed: bool,
+}
+
+const fn wide_op(
+    opcode: Opcode,
+    num_operands: usize,
+    sources: [Source; NUM_RELATION_VALUES],
+    output: Output,
+    divisor: Divisor,
+    signed: bool,
+) -> WideOp {
+    WideOp {
+        opcode,
+        num_operands,
+        sources,
+        output,
+        divisor,
+        signed,
+    }
+}
+
+const A: Source = Source::Operand(0);
+const B: Source = Source::Operand(1);
+const M: Source = Source::Operand(2);
+const ONE: Source = Source::One;
+const ZERO: Source = Source::Zero;
+const WITNESS: Source = Source::Witness;
+
+/// The operations of the unit. The `i`th operation uses the flag `col_wide_op(i)`.
+///
+/// Comparisons compute `a - b` as `x` in `x + b = a + 2^256 h`, so `h` is the borrow, and `x` is
+/// zero iff `a = b`. For signed comparisons, the sign terms of the relation make `h` the borrow of
+/// the signed subtraction instead.
+pub(crate) const WIDE_OPS: [WideOp; NUM_WIDE_OPS] = [
+    wide_op(
+        Opcode::Add256,
+        2,
+        [A, ONE, B, ZERO, WITNESS, WITNESS],
+        Output::Value(REL_R),
+        Divisor::None,
+        false,
+    ),
+    wide_op(
+        Opcode::Sub256,
+        2,
+        [WITNESS, ONE, B, ZERO, A, WITNESS],
+        Output::Value(REL_X),
+        Divisor::None,
+        false,
+    ),
+    wide_op(
+        Opcode::Mul256,
+        2,
+        [A, B, ZERO, ZERO, WITNESS, WITNESS],
+        Output::Value(REL_R),
+        Divisor::None,
+        false,
+    ),
+    wide_op(
+        Opcode::Div256,
+        2,
+        [WITNESS, B, WITNESS, ZERO, A, ZERO],
+        Output::Value(REL_X),
+        Divisor::Y,
+        false,
+    ),
+    wide_op(
+        Opcode::Mod256,
+        2,
+        [WITNESS, B, WITNESS, ZERO, A, ZERO],
+        Output::ValueUnlessDivisorIsZero(REL_Z),
+        Divisor::Y,
+        false,
+    ),
+    wide_op(
+        Opcode::AddMod256,
+        3,
+        [A, ONE, B, M, WITNESS, WITNESS],
+        Output::ValueUnlessDivisorIsZero(REL_R),
+        Divisor::N,
+        false,
+    ),
+    wide_op(
+        Opcode::MulMod256,
+        3,
+        [A, B, ZERO, M, WITNESS, WITNESS],
+        Output::ValueUnlessDivisorIsZero(REL_R),
+        Divisor::N,
+        false,
+    ),
+    wide_op(
+        Opcode::SDiv256,
+        2,
+        [WITNESS, B, WITNESS, ZERO, A, ZERO],
+        Output::Value(REL_X),
+        Divisor::Y,
+        true,
+    ),
+    wide_op(
+        Opcode::SMod256,
+        2,
+        [WITNESS, B, WITNESS, ZERO, A, ZERO],
+        Output::ValueUnlessDivisorIsZero(REL_Z),
+        Divisor::Y,
+        true,
+    ),
+    wide_op(
+        Opcode::Lt256,
+        2,
+        [WITNESS, ONE, B, ZERO, A, WITNESS],
+        Output::Borrow,
+        Divisor::None,
+        false,
+    ),
+    wide_op(
+        Opcode::Gt256,
+        2,
+        [WITNESS, ONE, A, ZERO, B, WITNESS],
+        Output::Borrow,
+        Divisor::None,
+        false,
+    ),
+    wide_op(
+        Opcode::SLt256,
+        2,
+        [WITNESS, ONE, B, ZERO, A, WITNESS],
+        Output::Borrow,
+        Divisor::None,
+        true,
+    ),
+    wide_op(
+        Opcode::SGt256,
+        2,
+        [WITNESS, ONE, A, ZERO, B, WITNESS],
+        Output::Borrow,
+        Divisor::None,
+        true,
+    ),
+    wide_op(
+        Opcode::Eq256,
+        2,
+        [WITNESS, ONE, B, ZERO, A, WITNESS],
+        Output::XIsZero,
+        Divisor::None,
+        false,
+    ),
+    wide_op(
+        Opcode::IsZero256,
+        1,
+        [A, ONE, ZERO, ZERO, WITNESS, WITNESS],
+        Output::XIsZero,
+        Divisor::None,
+        false,
+    ),
+];
+
+/// The compute row whose carry in from the bound check must be zero, unless the divisor is zero.
+const BOUND_CHECK_END: usize = 2 * NUM_WIDE_LIMBS;
+/// The compute row which checks that the sign bits are the top bits of their values.
+const SIGN_CHECK_ROW: usize = BOUND_CHECK_END + 1;
+
+/// The carries of the relation are offset by this to make them nonnegative.
+const CARRY_OFFSET: u64 = 1 << 22;
+/// The carries of the bound check are offset by this to make them nonnegative.
+const BOUND_CARRY_OFFSET: u64 = 1 << 15;
+
+/// The columns which are set in the first read row of an operation, and are unchanged until its last
+/// write row.
+fn carried_columns() -> impl Iterator<Item = usize> {
+    let limbs = (0..NUM_RELATION_VALUES)
+        .flat_map(|value| (0..NUM_WIDE_LIMBS).map(move |i| col_relation_limb(value, i)));
+    let result = (0..NUM_WIDE_LIMBS).map(col_result_limb);
+    limbs.chain(result).chain([
+        COL_WIDE_DIVISOR_IS_ZERO,
+        COL_WIDE_X_IS_ZERO,
+        COL_WIDE_QUOTIENT_IS_NEG,
+        COL_WIDE_Y_SIGN,
+        COL_WIDE_Z_SIGN,
+        COL_WIDE_R_SIGN,
+        COL_WIDE_Y_IS_NEG,
+        COL_WIDE_Z_IS_NEG,
+        COL_WIDE_R_IS_NEG,
+        COL_WIDE_DIVISOR_SUM,
+        COL_WIDE_DIVISOR_SUM_INV,
+        COL_WIDE_X_SUM_INV,
+    ])
+}
+
+/// The columns of the unit which must be binary.
+fn flag_columns() -> impl Iterator<Item = usize> {
+    let ops = (0..NUM_WIDE_OPS).map(col_wide_op);
+    let read_positions = (0..NUM_READ_POSITIONS).map(col_read_position);
+    let compute_positions = (0..NUM_COMPUTE_ROWS).map(col_compute_position);
+    let write_positions = (0..NUM_WIDE_WORDS).map(col_write_position);
+    ops.chain(read_positions)
+        .chain(compute_positions)
+        .chain(write_positions)
+        .chain(COL_WIDE_LAST_READ..=COL_WIDE_R_IS_NEG)
+}
+
+/// The `i`th u16 limb of a 256-bit word.
+fn u16_limb(word: &[u32], i: usize) -> u64 {
+    (word.get(i / 2).copied().unwrap_or(0) >> (16 * (i % 2))) as u64 & 0xffff
+}
+
+/// Reads the 256-bit word at `main[address..]`.
+fn read_wide(memory: &TransactionMemory, address: usize) -> [u32; NUM_WIDE_WORDS] {
+    let mut word = [0; NUM_WIDE_WORDS];
+    for (i, limb) in word.iter_mut().enumerate() {
+        *limb = memory.read(0, Segment::Main, address + i);
+    }
+    word
+}
+
+/// Reads a value of the relation from its limbs.
+fn relation_value<F: PrimeField64>(
+    values: &[F; NUM_COLUMNS],
+    value: usize,
+) -> [u32; NUM_WIDE_WORDS] {
+    let limb = |i: usize| values[col_relation_limb(value, i)].to_canonical_u64() as u32;
+    let mut word = [0; NUM_WIDE_WORDS];
+    for (i, x) in word.iter_mut().enumerate() {
+        *x = limb(2 * i) | limb(2 * i + 1) << 16;
+    }
+    word
+}
+
+/// Interprets a field element as a signed integer.
+fn to_signed<F: PrimeField64>(x: F) -> i64 {
+    let x = x.to_canonical_u64();
+    if x < F::ORDER / 2 {
+        x as i64
+    } else {
+        -((F::ORDER - x) as i64)
+    }
+}
+
+/// The index of the operation a read, compute or write row belongs to.
+fn active_op<F: Field>(values: &[F; NUM_COLUMNS]) -> usize {
+    (0..NUM_WIDE_OPS)
+        .position(|i| values[col_wide_op(i)].is_one())
+        .expect("No operation flag is set.")
+}
+
+/// The values of the relation for an operation with operands `a`, `b` and `m`, as `x`, `y`, `z`,
+/// `n`, `r` and `h`, along with `q`, and whether the quotient of a signed division is negative.
+fn relation_witness(
+    opcode: Opcode,
+    a: [u32; NUM_WIDE_WORDS],
+    b: [u32; NUM_WIDE_WORDS],
+    m: [u32; NUM_WIDE_WORDS],
+) -> (
+    [[u32; NUM_WIDE_WORDS]; NUM_RELATION_VALUES],
+    [u32; 2 * NUM_WIDE_WORDS],
+    bool,
+) {
+    let one = truncate(&[1]);
+    let zero = [0; NUM_WIDE_WORDS];
+    let from_bool = |x: bool| truncate(&[x as u32]);
+    // Splits a value into its low and high 256 bits.
+    let split = |x: Vec<u32>| (truncate(&x), truncate(&x[NUM_WIDE_WORDS..]));
+    let no_quotient = [0; 2 * NUM_WIDE_WORDS];
+
+    match opcode {
+        Opcode::Add256 => {
+            let (r, h) = split(add(&a, &b));
+            ([a, one, b, zero, r, h], no_quotient, false)
+        }
+        Opcode::Sub256 | Opcode::Lt256 | Opcode::Eq256 => {
+            let (x, borrow) = overflowing_sub(&a, &b);
+            let h = from_bool(borrow);
+            ([truncate(&x), one, b, zero, a, h], no_quotient, false)
+        }
+        Opcode::Gt256 => {
+            let (x, borrow) = overflowing_sub(&b, &a);
+            let h = from_bool(borrow);
+            ([truncate(&x), one, a, zero, b, h], no_quotient, false)
+        }
+        Opcode::SLt256 | Opcode::SGt256 => {
+            let (a, b) = if opcode == Opcode::SLt256 {
+                (a, b)
+            } else {
+                (b, a)
+            };
+            let x = truncate(&overflowing_sub(&a, &b).0);
+            let h = from_bool(signed_cmp(&a, &b).is_lt());
+            ([x, one, b, zero, a, h], no_quotient, false)
+        }
+        Opcode::Mul256 => {
+            let (r, h) = split(mul(&a, &b));
+            ([a, b, zero, zero, r, h], no_quotient, false)
+        }
+        Opcode::Div256 | Opcode::Mod256 => {
+            let (x, z) = if is_zero(&b) {
+                (zero, a)
+            } else {
+                let (x, z) = div_rem(&a, &b);
+                (truncate(&x), truncate(&z))
+            };
+            ([x, b, z, zero, a, zero], no_quotient, false)
+        }
+        Opcode::SDiv256 | Opcode::SMod256 => {
+            let (x, z, quotient_is_neg) = if is_zero(&b) {
+                (zero, a, false)
+            } else {
+                let (x, z) = div_rem(&abs(&a), &abs(&b));
+                let quotient_is_neg = is_negative(&a) != is_negative(&b) && !is_zero(&x);
+                let x = if quotient_is_neg { neg(&x) } else { x };
+                let z = if is_negative(&a) { neg(&z) } else { z };
+                (truncate(&x), truncate(&z), quotient_is_neg)
+            };
+            ([x, b, z, zero, a, zero], no_quotient, quotient_is_neg)
+        }
+        Opcode::AddMod256 | Opcode::MulMod256 => {
+            let (y, z, t) = if opcode == Opcode::AddMod256 {
+                (one, b, add(&a, &b))
+            } else {
+                (b, zero, mul(&a, &b))
+            };
+            if is_zero(&m) {
+                let (r, h) = split(t);
+                ([a, y, z, m, r, h], no_quotient, false)
+            } else {
+                let (q, r) = div_rem(&t, &m);
+                ([a, y, z, m, truncate(&r), zero], truncate(&q), false)
+            }
+        }
+        Opcode::IsZero256 => ([a, one, zero, zero, a, zero], no_quotient, false),
+        _ => unreachable!("{:?} is not a 256-bit opcode.", opcode),
+    }
+}
+
+/// Sets up the execute row of a 256-bit instruction, which reads its operands from `address_0` and
+/// `address_1`, and writes its result to `output_address`.
+pub(crate) fn generate_wide_op<F: Field>(
+    values: &mut [F; NUM_COLUMNS],
+    address_0: F,
+    address_1: F,
+    output_address: F,
+) {
+    values[COL_WIDE_ADDRESS_0] = address_0;
+    values[COL_WIDE_ADDRESS_1] = address_1;
+    values[COL_WIDE_OUTPUT_ADDRESS] = output_address;
+}
+
+/// Sets up the values of the relation, and the columns derived from them, in the first read row of
+/// an operation.
+fn generate_relation<F: PrimeField64>(values: &mut [F; NUM_COLUMNS], memory: &TransactionMemory) {
+    let op = WIDE_OPS[active_op(values)];
+    let address_1 = values[COL_WIDE_ADDRESS_1].to_canonical_u64() as usize;
+    let a = read_wide(
+        memory,
+        values[COL_WIDE_ADDRESS_0].to_canonical_u64() as usize,
+    );
+    let b = read_wide(memory, address_1);
+    let m = read_wide(memory, address_1 + NUM_WIDE_WORDS);
+    let (relation, _, quotient_is_neg) = relation_witness(op.opcode, a, b, m);
+
+    for (value, word) in relation.iter().enumerate() {
+        for i in 0..NUM_WIDE_LIMBS {
+            values[col_relation_limb(value, i)] = F::from_canonical_u64(u16_limb(word, i));
+        }
+    }
+
+    let sum =
+        |word: &[u32]| F::from_canonical_u64((0..NUM_WIDE_LIMBS).map(|i| u16_limb(word, i)).sum());
+    let divisor_sum = match op.divisor {
+        Divisor::None => F::ZERO,
+        Divisor::Y => sum(&relation[REL_Y]),
+        Divisor::N => sum(&relation[REL_N]),
+    };
+    let divisor_is_zero = divisor_sum.is_zero();
+    values[COL_WIDE_DIVISOR_SUM] = divisor_sum;
+    values[COL_WIDE_DIVISOR_SUM_INV] = divisor_sum.try_inverse().unwrap_or(F::ZERO);
+    values[COL_WIDE_DIVISOR_IS_ZERO] = F::from_bool(divisor_is_zero);
+    let x_sum = sum(&relation[REL_X]);
+    values[COL_WIDE_X_SUM_INV] = x_sum.try_inverse().unwrap_or(F::ZERO);
+    values[COL_WIDE_X_IS_ZERO] = F::from_bool(x_sum.is_zero());
+
+    values[COL_WIDE_QUOTIENT_IS_NEG] = F::from_bool(quotient_is_neg);
+    for (value, col_sign, col_is_neg) in [
+        (REL_Y, COL_WIDE_Y_SIGN, COL_WIDE_Y_IS_NEG),
+        (REL_Z, COL_WIDE_Z_SIGN, COL_WIDE_Z_IS_NEG),
+        (REL_R, COL_WIDE_R_SIGN, COL_WIDE_R_IS_NEG),
+    ] {
+        let sign = is_negative(&relation[value]);
+        values[col_sign] = F::from_bool(sign);
+        values[col_is_neg] = F::from_bool(sign && op.signed);
+    }
+
+    let result = match op.output {
+        Output::Value(value) => relation[value],
+        Output::ValueUnlessDivisorIsZero(_) if divisor_is_zero => [0; NUM_WIDE_WORDS],
+        Output::ValueUnlessDivisorIsZero(value) => relation[value],
+        Output::Borrow => relation[REL_H],
+        Output::XIsZero => truncate(&[x_sum.is_zero() as u32]),
+    };
+    for i in 0..NUM_WIDE_LIMBS {
+        values[col_result_limb(i)] = F::from_canonical_u64(u16_limb(&result, i));
+    }
+}
+
+/// Generates a read row, given the columns carried over from the previous row, and returns the
+/// address it reads. The first read row of an operation also sets up the values of the relation.
+pub(crate) fn generate_wide_read_row<F: PrimeField64>(
+    values: &mut [F; NUM_COLUMNS],
+    memory: &TransactionMemory,
+) -> F {
+    let position = (0..NUM_READ_POSITIONS)
+        .position(|r| values[col_read_position(r)].is_one())
+        .expect("No read position is set.");
+    if position == 0 {
+        generate_relation(values, memory);
+    }
+    let op = WIDE_OPS[active_op(values)];
+    values[COL_WIDE_LAST_READ] = F::from_bool(position == NUM_WIDE_WORDS * op.num_operands - 1);
+
+    let address = if position < NUM_WIDE_WORDS {
+        values[COL_WIDE_ADDRESS_0] + F::from_canonical_usize(position)
+    } else {
+        values[COL_WIDE_ADDRESS_1] + F::from_canonical_usize(position - NUM_WIDE_WORDS)
+    };
+    values[COL_WIDE_MEMORY_ADDRESS] = address;
+    let word = memory.read(0, Segment::Main, address.to_canonical_u64() as usize);
+    values[COL_WIDE_WORD_LO] = F::from_canonical_u32(word & 0xffff);
+    values[COL_WIDE_WORD_HI] = F::from_canonical_u32(word >> 16);
+    address
+}
+
+/// Generates a compute row, given the columns carried over from the previous row.
+pub(crate) fn generate_wide_compute_row<F: PrimeField64>(values: &mut [F; NUM_COLUMNS]) {
+    let k = (0..NUM_COMPUTE_ROWS)
+        .position(|k| values[col_compute_position(k)].is_one())
+        .expect("No compute position is set.");
+    let op = WIDE_OPS[active_op(values)];
+    let limb = |values: &[F; NUM_COLUMNS], value: usize, i: usize| {
+        to_signed(values[col_relation_limb(value, i)])
+    };
+    let bit = |values: &[F; NUM_COLUMNS], col: usize| to_signed(values[col]);
+
+    // `q` is whatever makes the relation hold, or zero if `n` is zero.
+    let x = relation_value(values, REL_X);
+    let n = relation_value(values, REL_N);
+    let quotient = if is_zero(&n) {
+        vec![0]
+    } else {
+        let r = relation_value(values, REL_R);
+        let h = relation_value(values, REL_H);
+        let t = add(
+            &mul(&x, &relation_value(values, REL_Y)),
+            &relation_value(values, REL_Z),
+        );
+        let t = sub(&t, &[r, h].concat());
+        div_rem(&t, &n).0
+    };
+    if k < NUM_WIDE_LIMBS {
+        values[col_y_window(0)] = values[col_relation_limb(REL_Y, k)];
+    }
+    values[col_q_window(0)] = F::from_canonical_u64(u16_limb(&quotient, k));
+
+    // The carry out of this limb of the relation.
+    let mut total = to_signed(values[COL_WIDE_CARRY_IN]) as i128;
+    for i in 0..NUM_WIDE_LIMBS {
+        total += (limb(values, REL_X, i) * to_signed(values[col_y_window(i)])) as i128;
+        total -= (limb(values, REL_N, i) * to_signed(values[col_q_window(i)])) as i128;
+    }
+    let quotient_is_neg = bit(values, COL_WIDE_QUOTIENT_IS_NEG);
+    let y_is_neg = bit(values, COL_WIDE_Y_IS_NEG);
+    let z_is_neg = bit(values, COL_WIDE_Z_IS_NEG);
+    let r_is_neg = bit(values, COL_WIDE_R_IS_NEG);
+    if k < NUM_WIDE_LIMBS {
+        total += (limb(values, REL_Z, k) - limb(values, REL_R, k)) as i128;
+    } else if k < 2 * NUM_WIDE_LIMBS {
+        let i = k - NUM_WIDE_LIMBS;
+        total -= (limb(values, REL_H, i)
+            + quotient_is_neg * limb(values, REL_Y, i)
+            + y_is_neg * limb(values, REL_X, i)) as i128;
+    }
+    if k == NUM_WIDE_LIMBS {
+        total -= (z_is_neg - r_is_neg) as i128;
+    }
+    if k == 2 * NUM_WIDE_LIMBS {
+        total += (quotient_is_neg * y_is_neg) as i128;
+    }
+    debug_assert_eq!(total % (1 << 16), 0, "Relation does not hold.");
+    let carry_out = (total >> 16) as i64 + CARRY_OFFSET as i64;
+    debug_assert!((0..1 << (16 + NUM_CARRY_HI_BITS)).contains(&carry_out));
+    values[COL_WIDE_CARRY_OUT_LO] = F::from_canonical_u64(carry_out as u64 & 0xffff);
+    for i in 0..NUM_CARRY_HI_BITS {
+        values[col_carry_out_hi_bit(i)] = F::from_bool((carry_out >> (16 + i)) & 1 != 0);
+    }
+
+    if k < NUM_WIDE_LIMBS {
+        for (i, value) in [REL_X, REL_Z, REL_R, REL_H].into_iter().enumerate() {
+            values[col_witness_limb(i)] = values[col_relation_limb(value, k)];
+        }
+    } else if k < 2 * NUM_WIDE_LIMBS {
+        // The bound check computes `|divisor| - |remainder| - 1`, which is nonnegative iff the
+        // remainder is less than the divisor.
+        let j = k - NUM_WIDE_LIMBS;
+        let (divisor, remainder) = match op.divisor {
+            Divisor::None => (0, 0),
+            Divisor::Y => (limb(values, REL_Y, j), limb(values, REL_Z, j)),
+            Divisor::N => (limb(values, REL_N, j), limb(values, REL_R, j)),
+        };
+        let total = divisor - remainder - 2 * y_is_neg * limb(values, REL_Y, j)
+            + 2 * z_is_neg * limb(values, REL_Z, j)
+            - (j == 0) as i64
+            + to_signed(values[COL_WIDE_BOUND_CARRY_IN]);
+        values[COL_WIDE_BOUND_LIMB] = F::from_canonical_u64(total.rem_euclid(1 << 16) as u64);
+        values[COL_WIDE_BOUND_CARRY_OUT] =
+            F::from_canonical_u64((total.div_euclid(1 << 16) + BOUND_CARRY_OFFSET as i64) as u64);
+    } else if k == SIGN_CHECK_ROW {
+        for (i, (value, col_sign)) in [
+            (REL_Y, COL_WIDE_Y_SIGN),
+            (REL_Z, COL_WIDE_Z_SIGN),
+            (REL_R, COL_WIDE_R_SIGN),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let top = limb(values, value, NUM_WIDE_LIMBS - 1) - (bit(values, col_sign) << 15);
+            values[col_sign_check(i)] = F::from_canonical_u64(2 * top as u64);
+        }
+    }
+}
+
+/// Generates a write row, given the columns carried over from the previous row, and returns the
+/// address it writes to and the word it writes.
+pub(crate) fn generate_wide_write_row<F: Field>(values: &mut [F; NUM_COLUMNS]) -> (F, F) {
+    let position = (0..NUM_WIDE_WORDS)
+        .position(|j| values[col_write_position(j)].is_one())
+        .expect("No write position is set.");
+    let address = values[COL_WIDE_OUTPUT_ADDRESS] + F::from_canonical_usize(position);
+    values[COL_WIDE_MEMORY_ADDRESS] = address;
+    let base = F::from_canonical_u64(1 << 16);
+    let word =
+        values[col_result_limb(2 * position)] + values[col_result_limb(2 * position + 1)] * base;
+    (address, word)
+}
+
+/// The carry out of a compute row's limb of the relation.
+fn carry_out<F: Field, P: PackedField<Scalar = F>>(values: &[P; NUM_COLUMNS]) -> P {
+    let hi = (0..NUM_CARRY_HI_BITS).rev().fold(P::ZEROS, |acc, i| {
+        acc * F::TWO + values[col_carry_out_hi_bit(i)]
+    });
+    values[COL_WIDE_CARRY_OUT_LO] + hi * F::from_canonical_u64(1 << 16)
+        - F::from_canonical_u64(CARRY_OFFSET)
+}
+
+/// Generates the columns of the 256-bit ALU in the next row, given that its phase flags have been
+/// generated.
+pub(crate) fn generate_next_row_wide_alu<F: Field>(
+    local_values: &[F; NUM_COLUMNS],
+    next_values: &mut [F; NUM_COLUMNS],
+) {
+    // The following mirrors the transition constraints in `eval_wide_alu`.
+    let is_wide = WIDE_OPS
+        .iter()
+        .map(|op| local_values[op.opcode.flag()])
+        .sum::<F>();
+    let last_read = local_values[COL_WIDE_LAST_READ];
+    next_values[col_read_position(0)] = is_wide;
+    for r in 1..NUM_READ_POSITIONS {
+        let previous = local_values[col_read_position(r - 1)];
+        next_values[col_read_position(r)] = if r % NUM_WIDE_WORDS == 0 {
+            previous * (F::ONE - last_read)
+        } else {
+            previous
+        };
+    }
+    next_values[col_compute_position(0)] = last_read;
+    for k in 1..NUM_COMPUTE_ROWS {
+        next_values[col_compute_position(k)] = local_values[col_compute_position(k - 1)];
+    }
+    next_values[col_write_position(0)] = local_values[col_compute_position(NUM_COMPUTE_ROWS - 1)];
+    for j in 1..NUM_WIDE_WORDS {
+        next_values[col_write_position(j)] = local_values[col_write_position(j - 1)];
+    }
+
+    let next_in_op =
+        next_values[IS_WIDE_READ] + next_values[IS_WIDE_COMPUTE] + next_values[IS_WIDE_WRITE];
+    if next_in_op.is_one() {
+        for (i, op) in WIDE_OPS.iter().enumerate() {
+            next_values[col_wide_op(i)] =
+                local_values[col_wide_op(i)] + local_values[op.opcode.flag()];
+        }
+        for col in [
+            COL_WIDE_ADDRESS_0,
+            COL_WIDE_ADDRESS_1,
+            COL_WIDE_OUTPUT_ADDRESS,
+        ] {
+            next_values[col] = local_values[col];
+        }
+        if next_values[col_read_position(0)].is_zero() {
+            for col in carried_columns() {
+                next_values[col] = local_values[col];
+            }
+        }
+    }
+
+    if next_values[IS_WIDE_COMPUTE].is_one() && next_values[col_compute_position(0)].is_zero() {
+        for i in 1..NUM_WIDE_LIMBS {
+            next_values[col_y_window(i)] = local_values[col_y_window(i - 1)];
+            next_values[col_q_window(i)] = local_values[col_q_window(i - 1)];
+        }
+        next_values[COL_WIDE_CARRY_IN] = carry_out(local_values);
+    }
+    if (NUM_WIDE_LIMBS + 1..=BOUND_CHECK_END).any(|k| next_values[col_compute_position(k)].is_one())
+    {
+        next_values[COL_WIDE_BOUND_CARRY_IN] =
+            local_values[COL_WIDE_BOUND_CARRY_OUT] - F::from_canonical_u64(BOUND_CARRY_OFFSET);
+    }
+}
+
+/// The indices of the operations which satisfy `predicate`.
+fn ops_where(predicate: impl Fn(&WideOp) -> bool) -> Vec<usize> {
+    (0..NUM_WIDE_OPS)
+        .filter(|&i| predicate(&WIDE_OPS[i]))
+        .collect()
+}
+
+pub(crate) fn eval_wide_alu<F: Field, P: PackedField<Scalar = F>>(
+    vars: StarkEvaluationVars<F, P, NUM_COLUMNS, NUM_PUBLIC_INPUTS>,
+    yield_constr: &mut ConstraintConsumer<P>,
+) {
+    let local_values = &vars.local_values;
+    let next_values = &vars.next_values;
+    let base = F::from_canonical_u64(1 << 16);
+    // The sum of the flags of the given operations.
+    let op_sum = |ops: Vec<usize>| {
+        ops.into_iter()
+            .map(|i| local_values[col_wide_op(i)])
+            .fold(P::ZEROS, |acc, x| acc + x)
+    };
+    let rel = |value: usize, i: usize| local_values[col_relation_limb(value, i)];
+    let read_position = |r: usize| local_values[col_read_position(r)];
+    let compute_position = |k: usize| local_values[col_compute_position(k)];
+    let write_position = |j: usize| local_values[col_write_position(j)];
+    let limb_sum = |value: usize| {
+        (0..NUM_WIDE_LIMBS)
+            .map(|i| rel(value, i))
+            .fold(P::ZEROS, |acc, x| acc + x)
+    };
+
+    let is_read = local_values[IS_WIDE_READ];
+    let is_compute = local_values[IS_WIDE_COMPUTE];
+    let is_write = local_values[IS_WIDE_WRITE];
+    let in_op = is_read + is_compute + is_write;
+    let next_is_compute = next_values[IS_WIDE_COMPUTE];
+    let next_in_op = next_values[IS_WIDE_READ] + next_is_compute + next_values[IS_WIDE_WRITE];
+    let last_read = local_values[COL_WIDE_LAST_READ];
+    let divisor_is_zero = local_values[COL_WIDE_DIVISOR_IS_ZERO];
+    let x_is_zero = local_values[COL_WIDE_X_IS_ZERO];
+    let quotient_is_neg = local_values[COL_WIDE_QUOTIENT_IS_NEG];
+    let y_is_neg = local_values[COL_WIDE_Y_IS_NEG];
+    let z_is_neg = local_values[COL_WIDE_Z_IS_NEG];
+    let r_is_neg = local_values[COL_WIDE_R_IS_NEG];
+    let is_div = op_sum(ops_where(|op| op.divisor == Divisor::Y));
+    let is_mod = op_sum(ops_where(|op| op.divisor == Divisor::N));
+    let is_signed = op_sum(ops_where(|op| op.signed));
+    let is_signed_div = op_sum(ops_where(|op| op.signed && op.divisor == Divisor::Y));
+
+    // The execute row of a 256-bit instruction is followed by its read rows, one per word of its
+    // operands, then its compute rows, then its write rows. Each of these rows has a flag for the
+    // operation, and a position.
+    let mut is_wide = P::ZEROS;
+    for op in WIDE_OPS {
+        let flag = local_values[op.opcode.flag()];
+        yield_constr.constraint(flag * flag - flag);
+        is_wide += flag;
+    }
+    for col in flag_columns() {
+        let flag = local_values[col];
+        yield_constr.constraint(flag * flag - flag);
+    }
+    let read_position_sum = (0..NUM_READ_POSITIONS)
+        .map(read_position)
+        .fold(P::ZEROS, |acc, x| acc + x);
+    yield_constr.constraint(read_position_sum - is_read);
+    let compute_position_sum = (0..NUM_COMPUTE_ROWS)
+        .map(compute_position)
+        .fold(P::ZEROS, |acc, x| acc + x);
+    yield_constr.constraint(compute_position_sum - is_compute);
+    let write_position_sum = (0..NUM_WIDE_WORDS)
+        .map(write_position)
+        .fold(P::ZEROS, |acc, x| acc + x);
+    yield_constr.constraint(write_position_sum - is_write);
+    yield_constr.constraint(op_sum((0..NUM_WIDE_OPS).collect()) - in_op);
+    let expected_last_read = WIDE_OPS
+        .iter()
+        .enumerate()
+        .map(|(i, op)| {
+            local_values[col_wide_op(i)] * read_position(NUM_WIDE_WORDS * op.num_operands - 1)
+        })
+        .fold(P::ZEROS, |acc, x| acc + x);
+    yield_constr.constraint(last_read - expected_last_read);
+
+    yield_constr.constraint_transition(next_values[col_read_position(0)] - is_wide);
+    for r in 1..NUM_READ_POSITIONS {
+        let previous = read_position(r - 1);
+        let expected = if r % NUM_WIDE_WORDS == 0 {
+            previous - previous * last_read
+        } else {
+            previous
+        };
+        yield_constr.constraint_transition(next_values[col_read_position(r)] - expected);
+    }
+    yield_constr.constraint_transition(next_values[col_compute_position(0)] - last_read);
+    for k in 1..NUM_COMPUTE_ROWS {
+        yield_constr
+            .constraint_transition(next_values[col_compute_position(k)] - compute_position(k - 1));
+    }
+    yield_constr.constraint_transition(
+        next_values[col_write_position(0)] - compute_position(NUM_COMPUTE_ROWS - 1),
+    );
+    for j in 1..NUM_WIDE_WORDS {
+        yield_constr
+            .constraint_transition(next_values[col_write_position(j)] - write_position(j - 1));
+    }
+
+    // The operation and its addresses are set by the execute row, and the values of the relation by
+    // the first read row. They are unchanged until the last write row.
+    for (i, op) in WIDE_OPS.iter().enumerate() {
+        let flag = local_values[col_wide_op(i)];
+        let next_flag = next_values[col_wide_op(i)];
+        yield_constr.constraint_transition(
+            next_in_op * (next_flag - flag - local_values[op.opcode.flag()]),
+        );
+    }
+    for col in [
+        COL_WIDE_ADDRESS_0,
+        COL_WIDE_ADDRESS_1,
+        COL_WIDE_OUTPUT_ADDRESS,
+    ] {
+        yield_constr.constraint_transition(next_in_op * (next_values[col] - local_values[col]));
+    }
+    let next_continues = next_in_op - next_values[col_read_position(0)];
+    for col in carried_columns() {
+        yield_constr.constraint_transition(next_continues * (next_values[col] - local_values[col]));
+    }
+
+    // Read rows read consecutive words of the operands, and write rows write consecutive words of
+    // the result.
+    let address_0 = local_values[COL_WIDE_ADDRESS_0];
+    let address_1 = local_values[COL_WIDE_ADDRESS_1];
+    let output_address = local_values[COL_WIDE_OUTPUT_ADDRESS];
+    let mut expected_address = P::ZEROS;
+    for r in 0..NUM_READ_POSITIONS {
+        let address = if r < NUM_WIDE_WORDS {
+            address_0 + F::from_canonical_usize(r)
+        } else {
+            address_1 + F::from_canonical_usize(r - NUM_WIDE_WORDS)
+        };
+        expected_address += read_position(r) * address;
+    }
+    for j in 0..NUM_WIDE_WORDS {
+        expected_address += write_position(j) * (output_address + F::from_canonical_usize(j));
+    }
+    yield_constr.constraint(local_values[COL_WIDE_MEMORY_ADDRESS] - expected_address);
+    let mem_value = local_values[COL_MEMORY_OP_VALUE];
+    let word_limbs = [
+        local_values[COL_WIDE_WORD_LO],
+        local_values[COL_WIDE_WORD_HI],
+    ];
+    yield_constr.constraint(is_read * (mem_value - word_limbs[0] - word_limbs[1] * base));
+
+    // Each value of the relation is an operand, which is taken from the words read, a constant, or
+    // a witness, which is range checked below.
+    for value in 0..NUM_RELATION_VALUES {
+        let operand_ops = (0..MAX_WIDE_OPERANDS)
+            .map(|m| ops_where(|op| op.sources[value] == Source::Operand(m)))
+            .collect::<Vec<_>>();
+        if operand_ops.iter().all(|ops| ops.is_empty()) {
+            continue;
+        }
+        let operand_filters = operand_ops.into_iter().map(op_sum).collect::<Vec<_>>();
+        for i in 0..NUM_WIDE_LIMBS {
+            let filter = (0..MAX_WIDE_OPERANDS)
+                .map(|m| operand_filters[m] * read_position(NUM_WIDE_WORDS * m + i / 2))
+                .fold(P::ZEROS, |acc, x| acc + x);
+            yield_constr.constraint(filter * (rel(value, i) - word_limbs[i % 2]));
+        }
+    }
+    for value in 0..NUM_RELATION_VALUES {
+        let one_ops = ops_where(|op| op.sources[value] == Source::One);
+        let zero_ops = ops_where(|op| op.sources[value] == Source::Zero);
+        if one_ops.is_empty() && zero_ops.is_empty() {
+            continue;
+        }
+        let is_one = op_sum(one_ops);
+        let is_zero = op_sum(zero_ops);
+        for i in 0..NUM_WIDE_LIMBS {
+            let limb = rel(value, i);
+            let one_limb = if i == 0 { P::ONES } else { P::ZEROS };
+            yield_constr.constraint(is_one * (limb - one_limb) + is_zero * limb);
+        }
+    }
+    // Modular operations don't overflow unless the modulus is zero, and division by zero gives
+    // zero. Only signed divisions have a sign for the quotient.
+    for i in 0..NUM_WIDE_LIMBS {
+        yield_constr.constraint(is_mod * (P::ONES - divisor_is_zero) * rel(REL_H, i));
+        yield_constr.constraint(is_div * divisor_is_zero * rel(REL_X, i));
+    }
+    yield_constr.constraint(quotient_is_neg * (P::ONES - is_signed_div));
+
+    // Check whether the divisor and `x` are zero, which their limb sums, being sums of u16s, show.
+    let divisor_sum = local_values[COL_WIDE_DIVISOR_SUM];
+    let divisor_sum_inv = local_values[COL_WIDE_DIVISOR_SUM_INV];
+    yield_constr.constraint(divisor_sum - is_div * limb_sum(REL_Y) - is_mod * limb_sum(REL_N));
+    yield_constr.constraint(in_op * (divisor_sum * divisor_sum_inv + divisor_is_zero - F::ONE));
+    yield_constr.constraint(divisor_sum * divisor_is_zero);
+    let x_sum = limb_sum(REL_X);
+    let x_sum_inv = local_values[COL_WIDE_X_SUM_INV];
+    yield_constr.constraint(in_op * (x_sum * x_sum_inv + x_is_zero - F::ONE));
+    yield_constr.constraint(x_sum * x_is_zero);
+
+    // Signed operations treat `y`, `z` and `r` as negative if their sign bits are set. The remainder
+    // of a signed division has the sign of the dividend, unless it is zero.
+    let y_sign = local_values[COL_WIDE_Y_SIGN];
+    let z_sign = local_values[COL_WIDE_Z_SIGN];
+    let r_sign = local_values[COL_WIDE_R_SIGN];
+    yield_constr.constraint(y_is_neg - is_signed * y_sign);
+    yield_constr.constraint(z_is_neg - is_signed * z_sign);
+    yield_constr.constraint(r_is_neg - is_signed * r_sign);
+    yield_constr.constraint(is_signed_div * limb_sum(REL_Z) * (z_sign - r_sign));
+
+    // The result is taken from the relation, and written by the write rows.
+    for i in 0..NUM_WIDE_LIMBS {
+        let expected = WIDE_OPS
+            .iter()
+            .enumerate()
+            .map(|(o, op)| {
+                let flag = local_values[col_wide_op(o)];
+                match op.output {
+                    Output::Value(value) => flag * rel(value, i),
+                    Output::ValueUnlessDivisorIsZero(value) => {
+                        flag * (P::ONES - divisor_is_zero) * rel(value, i)
+                    }
+                    Output::Borrow => flag * rel(REL_H, i),
+                    Output::XIsZero if i == 0 => flag * x_is_zero,
+                    Output::XIsZero => P::ZEROS,
+                }
+            })
+            .fold(P::ZEROS, |acc, x| acc + x);
+        yield_constr.constraint(in_op * local_values[col_result_limb(i)] - expected);
+    }
+    let written = (0..NUM_WIDE_WORDS)
+        .map(|j| {
+            let word = local_values[col_result_limb(2 * j)]
+                + local_values[col_result_limb(2 * j + 1)] * base;
+            write_position(j) * (mem_value - word)
+        })
+        .fold(P::ZEROS, |acc, x| acc + x);
+    yield_constr.constraint(written);
+
+    // The windows start out empty. In the compute row for limb `k`, limb `k` of `y`, if any, enters
+    // the window of `y`, and limb `k` of `q` enters the window of `q`, which range checks it. `q`
+    // has no limbs past `NUM_QUOTIENT_LIMBS`.
+    let expected_y_limb = (0..NUM_WIDE_LIMBS)
+        .map(|k| compute_position(k) * rel(REL_Y, k))
+        .fold(P::ZEROS, |acc, x| acc + x);
+    yield_constr.constraint(is_compute * local_values[col_y_window(0)] - expected_y_limb);
+    let past_quotient = (NUM_QUOTIENT_LIMBS..NUM_COMPUTE_ROWS)
+        .map(compute_position)
+        .fold(P::ZEROS, |acc, x| acc + x);
+    yield_constr.constraint(past_quotient * local_values[col_q_window(0)]);
+    let first_compute = compute_position(0);
+    let next_shifts = next_is_compute - next_values[col_compute_position(0)];
+    for i in 1..NUM_WIDE_LIMBS {
+        for col_window in [col_y_window, col_q_window] {
+            yield_constr.constraint(first_compute * local_values[col_window(i)]);
+            yield_constr.constraint_transition(
+                next_shifts * (next_values[col_window(i)] - local_values[col_window(i - 1)]),
+            );
+        }
+    }
+
+    // Each compute row checks a limb of
+    // `x y + z - q n - r - 2^256 (h + ux y + uy x + uz - ur) + 2^512 ux uy = 0`, which is the
+    // relation with `x`, `y`, `z` and `r` reinterpreted as signed where needed.
+    let mut limb = P::ZEROS;
+    for i in 0..NUM_WIDE_LIMBS {
+        limb += rel(REL_X, i) * local_values[col_y_window(i)];
+        limb -= rel(REL_N, i) * local_values[col_q_window(i)];
+    }
+    let carry_in = local_values[COL_WIDE_CARRY_IN];
+    let carry_out = carry_out(local_values);
+    let mut constraint = is_compute * (limb + carry_in - carry_out * base);
+    for k in 0..NUM_WIDE_LIMBS {
+        constraint += compute_position(k) * (rel(REL_Z, k) - rel(REL_R, k));
+        constraint -= compute_position(NUM_WIDE_LIMBS + k)
+            * (rel(REL_H, k) + quotient_is_neg * rel(REL_Y, k) + y_is_neg * rel(REL_X, k));
+    }
+    constraint -= compute_position(NUM_WIDE_LIMBS) * (z_is_neg - r_is_neg);
+    constraint += compute_position(2 * NUM_WIDE_LIMBS) * quotient_is_neg * y_is_neg;
+    yield_constr.constraint(constraint);
+    yield_constr.constraint(first_compute * carry_in);
+    yield_constr.constraint_transition(next_shifts * (next_values[COL_WIDE_CARRY_IN] - carry_out));
+    yield_constr.constraint(compute_position(NUM_COMPUTE_ROWS - 1) * carry_out);
+
+    // The compute rows for the low limbs range check the witnesses.
+    for (i, value) in [REL_X, REL_Z, REL_R, REL_H].into_iter().enumerate() {
+        let witness_limb = local_values[col_witness_limb(i)];
+        let constraint = (0..NUM_WIDE_LIMBS)
+            .map(|k| compute_position(k) * (witness_limb - rel(value, k)))
+            .fold(P::ZEROS, |acc, x| acc + x);
+        yield_constr.constraint(constraint);
+    }
+
+    // The compute rows for the middle limbs compute `|divisor| - |remainder| - 1` limb by limb, with
+    // range checked limbs, where `|v| = v - 2 uv v + 2^256 uv`. It is nonnegative iff the last carry
+    // is `uz - uy`.
+    let bound_limb = local_values[COL_WIDE_BOUND_LIMB];
+    let bound_carry_in = local_values[COL_WIDE_BOUND_CARRY_IN];
+    let bound_carry_out =
+        local_values[COL_WIDE_BOUND_CARRY_OUT] - F::from_canonical_u64(BOUND_CARRY_OFFSET);
+    let mut is_bound_check = P::ZEROS;
+    let mut constraint = P::ZEROS;
+    for j in 0..NUM_WIDE_LIMBS {
+        let flag = compute_position(NUM_WIDE_LIMBS + j);
+        let y = rel(REL_Y, j);
+        let z = rel(REL_Z, j);
+        let difference = is_div * (y - z) + is_mod * (rel(REL_N, j) - rel(REL_R, j))
+            - y_is_neg * y * F::TWO
+            + z_is_neg * z * F::TWO;
+        constraint += flag * difference;
+        is_bound_check += flag;
+    }
+    constraint -= compute_position(NUM_WIDE_LIMBS);
+    constraint += is_bound_check * (bound_carry_in - bound_limb - bound_carry_out * base);
+    yield_constr.constraint(constraint);
+    yield_constr.constraint(compute_position(NUM_WIDE_LIMBS) * bound_carry_in);
+    let next_continues_bound_check = (NUM_WIDE_LIMBS + 1..=BOUND_CHECK_END)
+        .map(|k| next_values[col_compute_position(k)])
+        .fold(P::ZEROS, |acc, x| acc + x);
+    yield_constr.constraint_transition(
+        next_continues_bound_check * (next_values[COL_WIDE_BOUND_CARRY_IN] - bound_carry_out),
+    );
+    yield_constr.constraint(
+        compute_position(BOUND_CHECK_END)
+            * (P::ONES - divisor_is_zero)
+            * (bound_carry_in + y_is_neg - z_is_neg),
+    );
+
+    // The next compute row checks that the sign bits are the top bits of their values.
+    let is_sign_check = compute_position(SIGN_CHECK_ROW);
+    for (i, (value, col_sign)) in [
+        (REL_Y, COL_WIDE_Y_SIGN),
+        (REL_Z, COL_WIDE_Z_SIGN),
+        (REL_R, COL_WIDE_R_SIGN),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let top = rel(value, NUM_WIDE_LIMBS - 1)
+            - local_values[col_sign] * F::from_canonical_u64(1 << 15);
+        yield_constr.constraint(is_sign_check * (local_values[col_sign_check(i)] - top * F::TWO));
+    }
+}
+
+pub(crate) fn eval_wide_alu_recursively<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    vars: StarkEvaluationTargets<D, NUM_COLUMNS, NUM_PUBLIC_INPUTS>,
+    yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    let local_values = &vars.local_values;
+    let next_values = &vars.next_values;
+    let zero = builder.zero_extension();
+    let one = builder.one_extension();
+    let base = F::from_canonical_u64(1 << 16);
+    // The sum of the flags of the given operations.
+    let op_sum = |builder: &mut CircuitBuilder<F, D>, ops: Vec<usize>| {
+        let flags = ops
+            .into_iter()
+            .map(|i| local_values[col_wide_op(i)])
+            .collect::<Vec<_>>();
+        builder.add_many_extension(&flags)
+    };
+    let rel = |value: usize, i: usize| local_values[col_relation_limb(value, i)];
+    let read_position = |r: usize| local_values[col_read_position(r)];
+    let compute_position = |k: usize| local_values[col_compute_position(k)];
+    let write_position = |j: usize| local_values[col_write_position(j)];
+    let limb_sum = |builder: &mut CircuitBuilder<F, D>, value: usize| {
+        let limbs = (0..NUM_WIDE_LIMBS)
+            .map(|i| rel(value, i))
+            .collect::<Vec<_>>();
+        builder.add_many_extension(&limbs)
+    };
+    // Constrains `filter * (x - y)`.
+    let constraint_filtered_eq = |builder: &mut CircuitBuilder<F, D>,
+                                  yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+                                  filter: ExtensionTarget<D>,
+                                  x: ExtensionTarget<D>,
+                                  y: ExtensionTarget<D>| {
+        let diff = builder.sub_extension(x, y);
+        let constraint = builder.mul_extension(filter, diff);
+        yield_constr.constraint(builder, constraint);
+    };
+    // Constrains `filter * (next_values[col] - x)` over transitions.
+    let transition_filtered_eq = |builder: &mut CircuitBuilder<F, D>,
+                                  yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+                                  filter: ExtensionTarget<D>,
+                                  col: usize,
+                                  x: ExtensionTarget<D>| {
+        let diff = builder.sub_extension(next_values[col], x);
+        let constraint = builder.mul_extension(filter, diff);
+        yield_constr.constraint_transition(builder, constraint);
+    };
+
+    let is_read = local_values[IS_WIDE_READ];
+    let is_compute = local_values[IS_WIDE_COMPUTE];
+    let is_write = local_values[IS_WIDE_WRITE];
+    let in_op = builder.add_many_extension(&[is_read, is_compute, is_write]);
+    let next_is_compute = next_values[IS_WIDE_COMPUTE];
+    let next_in_op = builder.add_many_extension(&[
+        next_values[IS_WIDE_READ],
+        next_is_compute,
+        next_values[IS_WIDE_WRITE],
+    ]);
+    let last_read = local_values[COL_WIDE_LAST_READ];
+    let divisor_is_zero = local_values[COL_WIDE_DIVISOR_IS_ZERO];
+    let divisor_is_nonzero = builder.sub_extension(one, divisor_is_zero);
+    let x_is_zero = local_values[COL_WIDE_X_IS_ZERO];
+    let quotient_is_neg = local_values[COL_WIDE_QUOTIENT_IS_NEG];
+    let y_is_neg = local_values[COL_WIDE_Y_IS_NEG];
+    let z_is_neg = local_values[COL_WIDE_Z_IS_NEG];
+    let r_is_neg = local_values[COL_WIDE_R_IS_NEG];
+    let is_div = op_sum(builder, ops_where(|op| op.divisor == Divisor::Y));
+    let is_mod = op_sum(builder, ops_where(|op| op.divisor == Divisor::N));
+    let is_signed = op_sum(builder, ops_where(|op| op.signed));
+    let is_signed_div = op_sum(
+        builder,
+        ops_where(|op| op.signed && op.divisor == Divisor::Y),
+    );
+
+    // The execute row of a 256-bit instruction is followed by its read rows, one per word of its
+    // operands, then its compute rows, then its write rows. Each of these rows has a flag for the
+    // operation, and a position.
+    let exec_flags = WIDE_OPS.map(|op| local_values[op.opcode.flag()]);
+    for flag in exec_flags {
+        let constraint = builder.mul_sub_extension(flag, flag, flag);
+        yield_constr.constraint(builder, constraint);
+    }
+    let is_wide = builder.add_many_extension(&exec_flags);
+    for col in flag_columns() {
+        let flag = local_values[col];
+        let constraint = builder.mul_sub_extension(flag, flag, flag);
+        yield_constr.constraint(builder, constraint);
+    }
+    for (num_positions, col_position, phase) in [
+        (
+            NUM_READ_POSITIONS,
+            col_read_position as fn(usize) -> usize,
+            is_read,
+        ),
+        (NUM_COMPUTE_ROWS, col_compute_position, is_compute),
+        (NUM_WIDE_WORDS, col_write_position, is_write),
+    ] {
+        let flags = (0..num_positions)
+            .map(|i| local_values[col_position(i)])
+            .collect::<Vec<_>>();
+        let position_sum = builder.add_many_extension(&flags);
+        let constraint = builder.sub_extension(position_sum, phase);
+        yield_constr.constraint(builder, constraint);
+    }
+    let op_flag_sum = op_sum(builder, (0..NUM_WIDE_OPS).collect());
+    let constraint = builder.sub_extension(op_flag_sum, in_op);
+    yield_constr.constraint(builder, constraint);
+    let pairs = WIDE_OPS
+        .iter()
+        .enumerate()
+        .map(|(i, op)| {
+            (
+                local_values[col_wide_op(i)],
+                read_position(NUM_WIDE_WORDS * op.num_operands - 1),
+            )
+        })
+        .collect();
+    let expected_last_read = builder.inner_product_extension(F::ONE, zero, pairs);
+    let constraint = builder.sub_extension(last_read, expected_last_read);
+    yield_constr.constraint(builder, constraint);
+
+    let constraint = builder.sub_extension(next_values[col_read_position(0)], is_wide);
+    yield_constr.constraint_transition(builder, constraint);
+    for r in 1..NUM_READ_POSITIONS {
+        let previous = read_position(r - 1);
+        let expected = if r % NUM_WIDE_WORDS == 0 {
+            builder.arithmetic_extension(F::NEG_ONE, F::ONE, previous, last_read, previous)
+        } else {
+            previous
+        };
+        let constraint = builder.sub_extension(next_values[col_read_position(r)], expected);
+        yield_constr.constraint_transition(builder, constraint);
+    }
+    let constraint = builder.sub_extension(next_values[col_compute_position(0)], last_read);
+    yield_constr.constraint_transition(builder, constraint);
+    for k in 1..NUM_COMPUTE_ROWS {
+        let constraint = builder.sub_extension(
+            next_values[col_compute_position(k)],
+            compute_position(k - 1),
+        );
+        yield_constr.constraint_transition(builder, constraint);
+    }
+    let constraint = builder.sub_extension(
+        next_values[col_write_position(0)],
+        compute_position(NUM_COMPUTE_ROWS - 1),
+    );
+    yield_constr.constraint_transition(builder, constraint);
+    for j in 1..NUM_WIDE_WORDS {
+        let constraint =
+            builder.sub_extension(next_values[col_write_position(j)], write_position(j - 1));
+        yield_constr.constraint_transition(builder, constraint);
+    }
+
+    // The operation and its addresses are set by the execute row, and the values of the relation by
+    // the first read row. They are unchanged until the last write row.
+    for (i, op) in WIDE_OPS.iter().enumerate() {
+        let expected =
+            builder.add_extension(local_values[col_wide_op(i)], local_values[op.opcode.flag()]);
+        transition_filtered_eq(builder, yield_constr, next_in_op, col_wide_op(i), expected);
+    }
+    for col in [
+        COL_WIDE_ADDRESS_0,
+        COL_WIDE_ADDRESS_1,
+        COL_WIDE_OUTPUT_ADDRESS,
+    ] {
+        transition_filtered_eq(builder, yield_constr, next_in_op, col, local_values[col]);
+    }
+    let next_continues = builder.sub_extension(next_in_op, next_values[col_read_position(0)]);
+    for col in carried_columns() {
+        transition_filtered_eq(
+            builder,
+            yield_constr,
+            next_continues,
+            col,
+            local_values[col],
+        );
+    }
+
+    // Read rows read consecutive words of the operands, and write rows write consecutive words of
+    // the result.
+    let address_0 = local_values[COL_WIDE_ADDRESS_0];
+    let address_1 = local_values[COL_WIDE_ADDRESS_1];
+    let output_address = local_values[COL_WIDE_OUTPUT_ADDRESS];
+    let mut pairs = Vec::with_capacity(NUM_READ_POSITIONS + NUM_WIDE_WORDS);
+    for r in 0..NUM_READ_POSITIONS {
+        let address = if r < NUM_WIDE_WORDS {
+            builder.add_const_extension(address_0, F::from_canonical_usize(r))
+        } else {
+            builder.add_const_extension(address_1, F::from_canonical_usize(r - NUM_WIDE_WORDS))
+        };
+        pairs.push((read_position(r), address));
+    }
+    for j in 0..NUM_WIDE_WORDS {
+        let address = builder.add_const_extension(output_address, F::from_canonical_usize(j));
+        pairs.push((write_position(j), address));
+    }
+    let expected_address = builder.inner_product_extension(F::ONE, zero, pairs);
+    let constraint = builder.sub_extension(local_values[COL_WIDE_MEMORY_ADDRESS], expected_address);
+    yield_constr.constraint(builder, constraint);
+    let mem_value = local_values[COL_MEMORY_OP_VALUE];
+    let word_limbs = [
+        local_values[COL_WIDE_WORD_LO],
+        local_values[COL_WIDE_WORD_HI],
+    ];
+    let word = builder.mul_const_add_extension(base, word_limbs[1], word_limbs[0]);
+    constraint_filtered_eq(builder, yield_constr, is_read, mem_value, word);
+
+    // Each value of the relation is an operand, which is taken from the words read, a constant, or
+    // a witness, which is range checked below.
+    for value in 0..NUM_RELATION_VALUES {
+        let operand_ops = (0..MAX_WIDE_OPERANDS)
+            .map(|m| ops_where(|op| op.sources[value] == Source::Operand(m)))
+            .collect::<Vec<_>>();
+        if operand_ops.iter().all(|ops| ops.is_empty()) {
+            continue;
+        }
+        let operand_filters = operand_ops
+            .into_iter()
+            .map(|ops| op_sum(builder, ops))
+            .collect::<Vec<_>>();
+        for i in 0..NUM_WIDE_LIMBS {
+            let pairs = (0..MAX_WIDE_OPERANDS)
+                .map(|m| {
+                    (
+                        operand_filters[m],
+                        read_position(NUM_WIDE_WORDS * m + i / 2),
+                    )
+                })
+                .collect();
+            let filter = builder.inner_product_extension(F::ONE, zero, pairs);
+            constraint_filtered_eq(
+                builder,
+                yield_constr,
+                filter,
+                rel(value, i),
+                word_limbs[i % 2],
+            );
+        }
+    }
+    for value in 0..NUM_RELATION_VALUES {
+        let one_ops = ops_where(|op| op.sources[value] == Source::One);
+        let zero_ops = ops_where(|op| op.sources[value] == Source::Zero);
+        if one_ops.is_empty() && zero_ops.is_empty() {
+            continue;
+        }
+        let is_one = op_sum(builder, one_ops);
+        let is_zero = op_sum(builder, zero_ops);
+        for i in 0..NUM_WIDE_LIMBS {
+            let limb = rel(value, i);
+            let one_limb = if i == 0 { one } else { zero };
+            let diff = builder.sub_extension(limb, one_limb);
+            let constraint = builder.mul_extension(is_zero, limb);
+            let constraint = builder.mul_add_extension(is_one, diff, constraint);
+            yield_constr.constraint(builder, constraint);
+        }
+    }
+    // Modular operations don't overflow unless the modulus is zero, and division by zero gives
+    // zero. Only signed divisions have a sign for the quotient.
+    for i in 0..NUM_WIDE_LIMBS {
+        let constraint = builder.mul_many_extension(&[is_mod, divisor_is_nonzero, rel(REL_H, i)]);
+        yield_constr.constraint(builder, constraint);
+        let constraint = builder.mul_many_extension(&[is_div, divisor_is_zero, rel(REL_X, i)]);
+        yield_constr.constraint(builder, constraint);
+    }
+    let constraint = builder.arithmetic_extension(
+        F::NEG_ONE,
+        F::ONE,
+        quotient_is_neg,
+        is_signed_div,
+        quotient_is_neg,
+    );
+    yield_constr.constraint(builder, constraint);
+
+    // Check whether the divisor and `x` are zero, which their limb sums, being sums of u16s, show.
+    let divisor_sum = local_values[COL_WIDE_DIVISOR_SUM];
+    let divisor_sum_inv = local_values[COL_WIDE_DIVISOR_SUM_INV];
+    let y_sum = limb_sum(builder, REL_Y);
+    let n_sum = limb_sum(builder, REL_N);
+    let expected = builder.mul_extension(is_div, y_sum);
+    let expected = builder.mul_add_extension(is_mod, n_sum, expected);
+    let constraint = builder.sub_extension(divisor_sum, expected);
+    yield_constr.constraint(builder, constraint);
+    let x_sum = limb_sum(builder, REL_X);
+    let x_sum_inv = local_values[COL_WIDE_X_SUM_INV];
+    for (sum, inv, is_zero) in [
+        (divisor_sum, divisor_sum_inv, divisor_is_zero),
+        (x_sum, x_sum_inv, x_is_zero),
+    ] {
+        let product = builder.mul_add_extension(sum, inv, is_zero);
+        constraint_filtered_eq(builder, yield_constr, in_op, product, one);
+        let constraint = builder.mul_extension(sum, is_zero);
+        yield_constr.constraint(builder, constraint);
+    }
+
+    // Signed operations treat `y`, `z` and `r` as negative if their sign bits are set. The remainder
+    // of a signed division has the sign of the dividend, unless it is zero.
+    let y_sign = local_values[COL_WIDE_Y_SIGN];
+    let z_sign = local_values[COL_WIDE_Z_SIGN];
+    let r_sign = local_values[COL_WIDE_R_SIGN];
+    for (is_neg, sign) in [(y_is_neg, y_sign), (z_is_neg, z_sign), (r_is_neg, r_sign)] {
+        let constraint = builder.arithmetic_extension(F::NEG_ONE, F::ONE, is_signed, sign, is_neg);
+        yield_constr.constraint(builder, constraint);
+    }
+    let z_sum = limb_sum(builder, REL_Z);
+    let sign_diff = builder.sub_extension(z_sign, r_sign);
+    let constraint = builder.mul_many_extension(&[is_signed_div, z_sum, sign_diff]);
+    yield_constr.constraint(builder, constraint);
+
+    // The result is taken from the relation, and written by the write rows.
+    for i in 0..NUM_WIDE_LIMBS {
+        let mut expected = zero;
+        for (o, op) in WIDE_OPS.iter().enumerate() {
+            let flag = local_values[col_wide_op(o)];
+            let term = match op.output {
+                Output::Value(value) => builder.mul_extension(flag, rel(value, i)),
+                Output::ValueUnlessDivisorIsZero(value) => {
+                    builder.mul_many_extension(&[flag, divisor_is_nonzero, rel(value, i)])
+                }
+                Output::Borrow => builder.mul_extension(flag, rel(REL_H, i)),
+                Output::XIsZero if i == 0 => builder.mul_extension(flag, x_is_zero),
+                Output::XIsZero => zero,
+            };
+            expected = builder.add_extension(expected, term);
+        }
+        let constraint =
+            builder.mul_sub_extension(in_op, local_values[col_result_limb(i)], expected);
+        yield_constr.constraint(builder, constraint);
+    }
+    let mut written = zero;
+    for j in 0..NUM_WIDE_WORDS {
+        let word = builder.mul_const_add_extension(
+            base,
+            local_values[col_result_limb(2 * j + 1)],
+            local_values[col_result_limb(2 * j)],
+        );
+        let diff = builder.sub_extension(mem_value, word);
+        written = builder.mul_add_extension(write_position(j), diff, written);
+    }
+    yield_constr.constraint(builder, written);
+
+    // The windows start out empty. In the compute row for limb `k`, limb `k` of `y`, if any, enters
+    // the window of `y`, and limb `k` of `q` enters the window of `q`, which range checks it. `q`
+    // has no limbs past `NUM_QUOTIENT_LIMBS`.
+    let pairs = (0..NUM_WIDE_LIMBS)
+        .map(|k| (compute_position(k), rel(REL_Y, k)))
+        .collect();
+    let expected_y_limb = builder.inner_product_extension(F::ONE, zero, pairs);
+    let constraint =
+        builder.mul_sub_extension(is_compute, local_values[col_y_window(0)], expected_y_limb);
+    yield_constr.constraint(builder, constraint);
+    let past_quotient = (NUM_QUOTIENT_LIMBS..NUM_COMPUTE_ROWS)
+        .map(compute_position)
+        .collect::<Vec<_>>();
+    let past_quotient = builder.add_many_extension(&past_quotient);
+    let constraint = builder.mul_extension(past_quotient, local_values[col_q_window(0)]);
+    yield_constr.constraint(builder, constraint);
+    let first_compute = compute_position(0);
+    let next_shifts = builder.sub_extension(next_is_compute, next_values[col_compute_position(0)]);
+    for i in 1..NUM_WIDE_LIMBS {
+        for col_window in [col_y_window, col_q_window] {
+            let constraint = builder.mul_extension(first_compute, local_values[col_window(i)]);
+            yield_constr.constraint(builder, constraint);
+            transition_filtered_eq(
+                builder,
+                yield_constr,
+                next_shifts,
+                col_window(i),
+                local_values[col_window(i - 1)],
+            );
+        }
+    }
+
+    // Each compute row checks a limb of
+    // `x y + z - q n - r - 2^256 (h + ux y + uy x + uz - ur) + 2^512 ux uy = 0`, which is the
+    // relation with `x`, `y`, `z` and `r` reinterpreted as signed where needed.
+    let mut limb = zero;
+    for i in 0..NUM_WIDE_LIMBS {
+        limb = builder.mul_add_extension(rel(REL_X, i), local_values[col_y_window(i)], limb);
+        limb = builder.arithmetic_extension(
+            F::NEG_ONE,
+            F::ONE,
+            rel(REL_N, i),
+            local_values[col_q_window(i)],
+            limb,
+        );
+    }
+    let carry_in = local_values[COL_WIDE_CARRY_IN];
+    let carry_out = carry_out_circuit(builder, local_values);
+    let limb = builder.add_extension(limb, carry_in);
+    let limb = builder.mul_const_add_extension(-base, carry_out, limb);
+    let mut constraint = builder.mul_extension(is_compute, limb);
+    for k in 0..NUM_WIDE_LIMBS {
+        let diff = builder.sub_extension(rel(REL_Z, k), rel(REL_R, k));
+        constraint = builder.mul_add_extension(compute_position(k), diff, constraint);
+        let high = builder.mul_add_extension(quotient_is_neg, rel(REL_Y, k), rel(REL_H, k));
+        let high = builder.mul_add_extension(y_is_neg, rel(REL_X, k), high);
+        constraint = builder.arithmetic_extension(
+            F::NEG_ONE,
+            F::ONE,
+            compute_position(NUM_WIDE_LIMBS + k),
+            high,
+            constraint,
+        );
+    }
+    let sign_terms = builder.sub_extension(z_is_neg, r_is_neg);
+    constraint = builder.arithmetic_extension(
+        F::NEG_ONE,
+        F::ONE,
+        compute_position(NUM_WIDE_LIMBS),
+        sign_terms,
+        constraint,
+    );
+    let product = builder.mul_extension(quotient_is_neg, y_is_neg);
+    constraint =
+        builder.mul_add_extension(compute_position(2 * NUM_WIDE_LIMBS), product, constraint);
+    yield_constr.constraint(builder, constraint);
+    let constraint = builder.mul_extension(first_compute, carry_in);
+    yield_constr.constraint(builder, constraint);
+    transition_filtered_eq(
+        builder,
+        yield_constr,
+        next_shifts,
+        COL_WIDE_CARRY_IN,
+        carry_out,
+    );
+    let constraint = builder.mul_extension(compute_position(NUM_COMPUTE_ROWS - 1), carry_out);
+    yield_constr.constraint(builder, constraint);
+
+    // The compute rows for the low limbs range check the witnesses.
+    for (i, value) in [REL_X, REL_Z, REL_R, REL_H].into_iter().enumerate() {
+        let witness_limb = local_values[col_witness_limb(i)];
+        let mut constraint = zero;
+        for k in 0..NUM_WIDE_LIMBS {
+            let diff = builder.sub_extension(witness_limb, rel(value, k));
+            constraint = builder.mul_add_extension(compute_position(k), diff, constraint);
+        }
+        yield_constr.constraint(builder, constraint);
+    }
+
+    // The compute rows for the middle limbs compute `|divisor| - |remainder| - 1` limb by limb, with
+    // range checked limbs, where `|v| = v - 2 uv v + 2^256 uv`. It is nonnegative iff the last carry
+    // is `uz - uy`.
+    let bound_limb = local_values[COL_WIDE_BOUND_LIMB];
+    let bound_carry_in = local_values[COL_WIDE_BOUND_CARRY_IN];
+    let bound_carry_out = builder.add_const_extension(
+        local_values[COL_WIDE_BOUND_CARRY_OUT],
+        -F::from_canonical_u64(BOUND_CARRY_OFFSET),
+    );
+    let mut constraint = zero;
+    for j in 0..NUM_WIDE_LIMBS {
+        let flag = compute_position(NUM_WIDE_LIMBS + j);
+        let y = rel(REL_Y, j);
+        let z = rel(REL_Z, j);
+        let y_diff = builder.sub_extension(y, z);
+        let n_diff = builder.sub_extension(rel(REL_N, j), rel(REL_R, j));
+        let difference = builder.mul_extension(is_div, y_diff);
+        let difference = builder.mul_add_extension(is_mod, n_diff, difference);
+        let difference = builder.arithmetic_extension(-F::TWO, F::ONE, y_is_neg, y, difference);
+        let difference = builder.arithmetic_extension(F::TWO, F::ONE, z_is_neg, z, difference);
+        constraint = builder.mul_add_extension(flag, difference, constraint);
+    }
+    let bound_check_flags = (0..NUM_WIDE_LIMBS)
+        .map(|j| compute_position(NUM_WIDE_LIMBS + j))
+        .collect::<Vec<_>>();
+    let is_bound_check = builder.add_many_extension(&bound_check_flags);
+    let constraint = builder.sub_extension(constraint, compute_position(NUM_WIDE_LIMBS));
+    let carried = builder.sub_extension(bound_carry_in, bound_limb);
+    let carried = builder.mul_const_add_extension(-base, bound_carry_out, carried);
+    let constraint = builder.mul_add_extension(is_bound_check, carried, constraint);
+    yield_constr.constraint(builder, constraint);
+    let constraint = builder.mul_extension(compute_position(NUM_WIDE_LIMBS), bound_carry_in);
+    yield_constr.constraint(builder, constraint);
+    let next_bound_flags = (NUM_WIDE_LIMBS + 1..=BOUND_CHECK_END)
+        .map(|k| next_values[col_compute_position(k)])
+        .collect::<Vec<_>>();
+    let next_continues_bound_check = builder.add_many_extension(&next_bound_flags);
+    transition_filtered_eq(
+        builder,
+        yield_constr,
+        next_continues_bound_check,
+        COL_WIDE_BOUND_CARRY_IN,
+        bound_carry_out,
+    );
+    let last_carry = builder.add_extension(bound_carry_in, y_is_neg);
+    let last_carry = builder.sub_extension(last_carry, z_is_neg);
+    let constraint = builder.mul_many_extension(&[
+        compute_position(BOUND_CHECK_END),
+        divisor_is_nonzero,
+        last_carry,
+    ]);
+    yield_constr.constraint(builder, constraint);
+
+    // The next compute row checks that the sign bits are the top bits of their values.
+    let is_sign_check = compute_position(SIGN_CHECK_ROW);
+    for (i, (value, col_sign)) in [
+        (REL_Y, COL_WIDE_Y_SIGN),
+        (REL_Z, COL_WIDE_Z_SIGN),
+        (REL_R, COL_WIDE_R_SIGN),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let top = builder.mul_const_add_extension(
+            -F::from_canonical_u64(1 << 15),
+            local_values[col_sign],
+            rel(value, NUM_WIDE_LIMBS - 1),
+        );
+        let expected = builder.mul_const_extension(F::TWO, top);
+        constraint_filtered_eq(
+            builder,
+            yield_constr,
+            is_sign_check,
+            local_values[col_sign_check(i)],
+            expected,
+        );
+    }
+}
+
+/// The carry out of a compute row's limb of the relation.
+fn carry_out_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    values: &[ExtensionTarget<D>; NUM_COLUMNS],
+) -> ExtensionTarget<D> {
+    let mut hi = builder.zero_extension();
+    for i in (0..NUM_CARRY_HI_BITS).rev() {
+        hi = builder.mul_const_add_extension(F::TWO, hi, values[col_carry_out_hi_bit(i)]);
+    }
+    let carry = builder.mul_const_add_extension(
+        F::from_canonical_u64(1 << 16),
+        hi,
+        values[COL_WIDE_CARRY_OUT_LO],
+    );
+    builder.add_const_extension(carry, -F::from_canonical_u64(CARRY_OFFSET))
+}